@@ -0,0 +1,8 @@
+// Fuzzes password generation with an arbitrary master password, distinguish key and preset,
+// separated by NUL bytes.
+// 以 NUL 字节分隔的任意主密码、区分密钥与预设对密码生成进行模糊测试。
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| aegixpass::core::fuzz_generate(data));
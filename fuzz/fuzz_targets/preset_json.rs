@@ -0,0 +1,7 @@
+// Fuzzes the preset JSON parser, its resource limits and validation.
+// 对预设 JSON 解析器及其资源限制与校验进行模糊测试。
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| aegixpass::core::fuzz_preset_json(data));
@@ -0,0 +1,108 @@
+// --- Security question answers ---
+// --- 安全问题答案 ---
+// Derives the answer to a site's security question from the master password, the site's
+// distinguish key and the question itself, so recovery questions get answers nobody can look up
+// and nobody has to store. The question is normalized first, so "Mother's maiden name?" and
+// "mother's  maiden name" give the same answer, and it becomes part of the label of the `answer`
+// purpose key, so every question of every site gets an independent answer. Answers are
+// pronounceable nonsense words, easy to read out to a support agent over the phone.
+// 从主密码、站点的区分密钥与问题本身派生站点安全问题的答案，使找回问题的答案既无法被查到，也无需保存。问题会先被规范化，
+// 因此 "Mother's maiden name?" 与 "mother's  maiden name" 得到相同的答案；规范化后的问题成为 `answer` 用途密钥标签的一部分，
+// 使每个站点的每个问题都得到相互独立的答案。答案由可读的无意义单词组成，便于在电话中念给客服人员。
+
+use crate::core::{create_rng_from_seed, derive_purpose_key, AegixPassError, Preset, Purpose, SeedOptions, ANSWER_LABEL_PREFIX};
+use crate::username::{pick, USERNAME_CONSONANTS, USERNAME_VOWELS};
+
+/// Words of an answer. / 答案的单词数。
+pub const ANSWER_WORDS: usize = 3;
+/// Consonant-vowel syllables of each word. / 每个单词的辅音-元音音节数。
+pub const ANSWER_SYLLABLES: usize = 3;
+
+/// How many bits of the derivation an answer keeps. / 答案保留了派生结果的多少比特。
+pub fn answer_entropy_bits() -> f64 {
+    (ANSWER_WORDS * ANSWER_SYLLABLES) as f64 * ((USERNAME_CONSONANTS.len() * USERNAME_VOWELS.len()) as f64).log2()
+}
+
+/// Lowercases the question and joins its runs of letters and digits with single spaces, so
+/// punctuation, case and spacing do not change the answer.
+// 将问题转换为小写，并用单个空格连接其中连续的字母与数字，使标点、大小写与空白不会改变答案。
+pub fn normalize_question(question: &str) -> String {
+    question.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// Derives the answer to a security question of a site. / 为站点的安全问题派生答案。
+pub fn derive_answer(password_source: &str, distinguish_key: &str, question: &str, preset: &Preset) -> Result<String, AegixPassError> {
+    derive_answer_with(password_source, distinguish_key, question, preset, &SeedOptions::default())
+}
+
+/// [`derive_answer`] with extra seed inputs such as a keyfile. The `answer` purpose key, with the
+/// label `answer:{normalized question}`, seeds the preset's RNG, which draws every letter with
+/// the same unbiased range sampling as passwords.
+// 带有额外种子输入（例如密钥文件）的 [`derive_answer`]。以标签 `answer:{规范化后的问题}` 派生的 `answer` 用途密钥作为
+// 预设 RNG 的种子，每个字母都使用与密码相同的无偏范围抽样抽取。
+pub fn derive_answer_with(
+    password_source: &str,
+    distinguish_key: &str,
+    question: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+) -> Result<String, AegixPassError> {
+    let question = normalize_question(question);
+    if question.is_empty() {
+        return Err(AegixPassError::EmptyQuestion);
+    }
+    let label = format!("{}{}", ANSWER_LABEL_PREFIX, question);
+    let key = derive_purpose_key(password_source, distinguish_key, preset, Purpose::Answer, &label, 32, options)?;
+    let seed: [u8; 32] = key.try_into().map_err(|key: Vec<u8>| AegixPassError::InvalidKeyLength(key.len(), 32))?;
+    let mut rng = create_rng_from_seed(seed, &preset.rng_algorithm)?;
+
+    let mut words = Vec::with_capacity(ANSWER_WORDS);
+    for _ in 0..ANSWER_WORDS {
+        let mut word = String::with_capacity(2 * ANSWER_SYLLABLES);
+        for _ in 0..ANSWER_SYLLABLES {
+            word.push(pick(&mut *rng, USERNAME_CONSONANTS));
+            word.push(pick(&mut *rng, USERNAME_VOWELS));
+        }
+        words.push(word);
+    }
+    Ok(words.join(" "))
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::load_purpose_preset;
+
+    #[test]
+    fn test_normalize_question() {
+        assert_eq!(normalize_question("  What is your Mother's maiden name? "), "what is your mother s maiden name");
+        assert_eq!(normalize_question("Erste Straße, in der du gewohnt hast"), "erste straße in der du gewohnt hast");
+        assert_eq!(normalize_question("?!  "), "");
+    }
+
+    #[test]
+    fn test_answer() {
+        let preset = load_purpose_preset(2);
+        let answer = derive_answer("MySecretPassword123!", "example.com", "Mother's maiden name?", &preset).unwrap();
+        // The question is part of the label, so this also pins the normalization.
+        // 问题是标签的一部分，因此这里也固定了规范化的结果。
+        assert_eq!(answer, "manedo vejofo nebimi");
+        assert_eq!(answer, derive_answer("MySecretPassword123!", "example.com", "mother's   MAIDEN name", &preset).unwrap());
+        let words: Vec<&str> = answer.split(' ').collect();
+        assert_eq!(words.len(), ANSWER_WORDS);
+        for word in words {
+            assert_eq!(word.len(), 2 * ANSWER_SYLLABLES);
+            for (i, c) in word.chars().enumerate() {
+                assert!(if i % 2 == 0 { USERNAME_CONSONANTS } else { USERNAME_VOWELS }.contains(c), "{}", answer);
+            }
+        }
+
+        assert_ne!(answer, derive_answer("MySecretPassword123!", "example.com", "Name of your first pet?", &preset).unwrap());
+        assert_ne!(answer, derive_answer("MySecretPassword123!", "example.org", "Mother's maiden name?", &preset).unwrap());
+        assert_ne!(answer, derive_answer("AnotherPassword!", "example.com", "Mother's maiden name?", &preset).unwrap());
+        assert_eq!(derive_answer("MySecretPassword123!", "example.com", " ? ", &preset), Err(AegixPassError::EmptyQuestion));
+        assert_eq!(derive_answer("", "example.com", "Mother's maiden name?", &preset), Err(AegixPassError::InputEmpty));
+    }
+}
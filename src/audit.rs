@@ -0,0 +1,277 @@
+// --- Configuration audit ---
+// --- 配置审计 ---
+// An offline security checkup of the local presets and site list. Each preset is checked for
+// short or small-alphabet passwords, fast hashes, weakened or unmaintained algorithms and
+// custom platformIds shared between presets; the findings are weighted into a score out of 100.
+// Nothing is derived and nothing leaves the machine.
+// 对本地预设与站点列表进行离线安全检查。逐个检查预设的密码是否过短或字符集过小、是否使用快速哈希、
+// 是否使用被削弱或已不再维护的算法，以及预设之间是否共用自定义 platformId；所有发现按权重折算为百分制得分。
+// 审计不进行任何派生，也不会有任何数据离开本机。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::core::{AegixPassError, HashAlgorithm, Preset, RngAlgorithm, ARGON2_ITERATIONS, ARGON2_MEMORY_KIB};
+use crate::integration::IntegrationError;
+use crate::registry;
+use crate::settings::{Settings, SETTINGS_FILE};
+use crate::sites::SitesFile;
+
+/// Entropy below which a derived password is reported as weak. / 低于该熵值的派生密码会被报告为弱密码。
+pub const WEAK_ENTROPY_BITS: f64 = 64.0;
+/// Entropy below which a derived password is reported as merely adequate.
+// 低于该熵值的派生密码会被报告为仅够用。
+pub const GOOD_ENTROPY_BITS: f64 = 80.0;
+
+const SITES_FILE: &str = "sites.json";
+
+/// How much a finding matters. / 发现项的严重程度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    /// A short human-readable label. / 简短的可读标签。
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// Points deducted from the score of 100. / 从 100 分中扣除的分数。
+    fn penalty(&self) -> u32 {
+        match self {
+            Severity::Info => 2,
+            Severity::Warning => 10,
+            Severity::Critical => 25,
+        }
+    }
+}
+
+/// One problem found in a file. / 在某个文件中发现的一个问题。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// The file name within the audited directory. / 被审计目录中的文件名。
+    pub file: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// The findings of an audit, most severe first. / 审计的发现项，按严重程度从高到低排列。
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditReport {
+    /// The files that were checked. / 已检查的文件。
+    pub files: Vec<String>,
+    pub findings: Vec<Finding>,
+}
+
+impl AuditReport {
+    /// 100 minus the penalty of every finding, never below 0. / 100 减去所有发现项的扣分，最低为 0。
+    pub fn score(&self) -> u32 {
+        let penalty: u32 = self.findings.iter().map(|f| f.severity.penalty()).sum();
+        100u32.saturating_sub(penalty)
+    }
+}
+
+/// Audits every preset and the site list in `dir`.
+// 审计 `dir` 中的所有预设及站点列表。
+pub fn audit_dir(dir: &Path) -> Result<AuditReport, AegixPassError> {
+    let io_error = |e: std::io::Error| IntegrationError::Audit(format!("{}: {}", dir.display(), e));
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .map_err(io_error)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter_map(|path| path.file_name().and_then(|n| n.to_str()).map(str::to_string))
+        .filter(|name| name.ends_with(".json"))
+        .collect();
+    names.sort();
+
+    let mut findings = Vec::new();
+    let mut presets = Vec::new();
+    for name in &names {
+        let path = dir.join(name);
+        if name == SITES_FILE {
+            match SitesFile::load(&path) {
+                Ok(sites) => findings.extend(audit_sites(name, &sites)),
+                Err(e) => findings.push(finding(name, Severity::Critical, e.to_string())),
+            }
+            continue;
+        }
+        if name == SETTINGS_FILE {
+            if let Err(e) = Settings::load(&path) {
+                findings.push(finding(name, Severity::Critical, e.to_string()));
+            }
+            continue;
+        }
+        let json = std::fs::read_to_string(&path).map_err(io_error)?;
+        match Preset::from_json(&json).and_then(|preset| preset.validate().map(|()| preset)) {
+            Ok(preset) => presets.push((name.clone(), preset)),
+            Err(e) => findings.push(finding(name, Severity::Critical, format!("cannot be used: {}", e))),
+        }
+    }
+    findings.extend(audit_presets(&presets));
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.file.cmp(&b.file)));
+    Ok(AuditReport { files: names, findings })
+}
+
+/// Checks each preset on its own, then for custom platformIds shared between presets. The
+/// official presets share the well-known platformId by design and are not reported.
+// 先逐个检查预设，再检查预设之间共用的自定义 platformId。官方预设按设计共用公认的 platformId，因此不会被报告。
+pub fn audit_presets(presets: &[(String, Preset)]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (file, preset) in presets {
+        findings.extend(audit_preset(file, preset));
+    }
+    let mut by_platform_id: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (file, preset) in presets.iter().filter(|(_, preset)| !registry::is_known_platform_id(&preset.platform_id)) {
+        by_platform_id.entry(preset.platform_id.as_str()).or_default().push(file);
+    }
+    for (platform_id, files) in by_platform_id.into_iter().filter(|(_, files)| files.len() > 1) {
+        findings.push(finding(
+            files[0],
+            Severity::Warning,
+            format!(
+                "shares the custom platformId '{}' with {}; give each preset its own platformId to keep their passwords separated",
+                platform_id,
+                files[1..].join(", ")
+            ),
+        ));
+    }
+    findings
+}
+
+/// Checks one preset. / 检查单个预设。
+pub fn audit_preset(file: &str, preset: &Preset) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut push = |severity, message: String| findings.push(finding(file, severity, message));
+
+    let bits = preset.entropy_bits();
+    if bits < WEAK_ENTROPY_BITS {
+        push(
+            Severity::Critical,
+            format!("passwords carry only about {:.0} bits of entropy; raise `length` or add charsets to reach {:.0}", bits, GOOD_ENTROPY_BITS),
+        );
+    } else if bits < GOOD_ENTROPY_BITS {
+        push(
+            Severity::Warning,
+            format!("passwords carry about {:.0} bits of entropy; {:.0} or more is recommended", bits, GOOD_ENTROPY_BITS),
+        );
+    }
+
+    match &preset.hash_algorithm {
+        HashAlgorithm::Sha256 | HashAlgorithm::Blake3 | HashAlgorithm::Sha3_256 | HashAlgorithm::Blake2b => push(
+            Severity::Warning,
+            format!(
+                "'{}' is a fast hash: anyone holding one derived password can guess the master password at GPU speed; prefer argon2id",
+                preset.hash_algorithm.name()
+            ),
+        ),
+        HashAlgorithm::Argon2id => {
+            let settings = preset.argon2.unwrap_or_default();
+            if settings.memory_kib < ARGON2_MEMORY_KIB || settings.iterations < ARGON2_ITERATIONS {
+                push(
+                    Severity::Info,
+                    format!(
+                        "Argon2 runs with {} KiB and {} iteration(s), below the standard {} KiB and {}",
+                        settings.memory_kib, settings.iterations, ARGON2_MEMORY_KIB, ARGON2_ITERATIONS
+                    ),
+                );
+            }
+        }
+        HashAlgorithm::Scrypt => {}
+        HashAlgorithm::Custom(name) => {
+            push(Severity::Info, format!("the registered hash '{}' cannot be audited", name));
+        }
+    }
+
+    match &preset.rng_algorithm {
+        RngAlgorithm::ChaCha20 => {}
+        RngAlgorithm::Hc128 => push(
+            Severity::Warning,
+            "the hc128 RNG is no longer maintained upstream; prefer chaCha20 for new presets".to_string(),
+        ),
+        RngAlgorithm::Xoshiro256StarStar => push(
+            Severity::Info,
+            "xoshiro256StarStar is not a cryptographic RNG; prefer chaCha20 unless the device cannot afford it".to_string(),
+        ),
+        RngAlgorithm::Custom(name) => {
+            push(Severity::Info, format!("the registered RNG '{}' cannot be audited", name));
+        }
+    }
+
+    if let Some(warning) = preset.platform_id_warning() {
+        push(Severity::Info, warning);
+    }
+    if let Some(warning) = preset.deprecation_warning() {
+        push(Severity::Warning, warning);
+    }
+    findings
+}
+
+/// Checks the site list. / 检查站点列表。
+pub fn audit_sites(file: &str, sites: &SitesFile) -> Vec<Finding> {
+    let pending = sites.sites.iter().filter(|site| !site.migrated).count();
+    if pending == 0 {
+        return Vec::new();
+    }
+    vec![finding(
+        file,
+        Severity::Info,
+        format!("{} of {} site(s) still use passwords stored in the old manager; see `aegixpass migrate-report`", pending, sites.sites.len()),
+    )]
+}
+
+fn finding(file: &str, severity: Severity, message: String) -> Finding {
+    Finding { file: file.to_string(), severity, message }
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(hash: &str, rng: &str, length: usize, platform_id: &str) -> Preset {
+        Preset::from_json(&format!(
+            r#"{{"name": "T", "version": 1, "hashAlgorithm": "{}", "rngAlgorithm": "{}", "shuffleAlgorithm": "fisherYates",
+                "length": {}, "platformId": "{}", "charsets": ["0123456789", "abcdefghijklmnopqrstuvwxyz"]}}"#,
+            hash, rng, length, platform_id
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_audit_presets() {
+        // 20 characters from 36 give about 103 bits, and argon2id/chaCha20 raise nothing.
+        // 从 36 个字符中取 20 个约为 103 比特，argon2id/chaCha20 不会产生任何发现项。
+        let strong = preset("argon2id", "chaCha20", 20, "aegixpass.takuron.com");
+        assert!(audit_preset("strong.json", &strong).is_empty());
+
+        let weak = preset("sha256", "hc128", 8, "aegixpass.takuron.com");
+        let severities: Vec<Severity> = audit_preset("weak.json", &weak).iter().map(|f| f.severity).collect();
+        assert_eq!(severities, vec![Severity::Critical, Severity::Warning, Severity::Warning]);
+        let light = preset("argon2id", "xoshiro256StarStar", 20, "aegixpass.takuron.com");
+        assert_eq!(audit_preset("light.json", &light)[0].severity, Severity::Info);
+
+        // The official platformId may be shared; a custom one may not.
+        // 官方 platformId 可以共用；自定义的不可以。
+        let presets = [("a.json".to_string(), strong), ("b.json".to_string(), weak)];
+        assert_eq!(audit_presets(&presets).len(), 3);
+        let custom = [
+            ("c.json".to_string(), preset("argon2id", "chaCha20", 20, "work.example")),
+            ("d.json".to_string(), preset("argon2id", "chaCha20", 24, "work.example")),
+        ];
+        let findings = audit_presets(&custom);
+        let severities: Vec<Severity> = findings.iter().map(|f| f.severity).collect();
+        assert_eq!(severities, vec![Severity::Info, Severity::Info, Severity::Warning]);
+        assert!(findings[2].file == "c.json" && findings[2].message.contains("d.json"));
+
+        let report = AuditReport { files: Vec::new(), findings: audit_presets(&presets) };
+        assert_eq!(report.score(), 100 - 25 - 10 - 10);
+    }
+}
@@ -0,0 +1,238 @@
+// --- Configuration backup ---
+// --- 配置备份 ---
+// Bundles the non-secret configuration in the config directory (presets, `sites.json` and any
+// other state kept there as JSON) into one file, so moving to a new machine is a single command
+// and both machines derive the same passwords. No master password or derived secret is ever
+// part of it, and neither is the managed store's `store.json`, which holds stored passwords; the
+// optional passphrase encryption only hides which sites and presets you use.
+// 将配置目录中的非机密配置（预设、`sites.json` 以及其中以 JSON 保存的其它状态）打包为一个文件，
+// 使迁移到新机器只需一条命令，且两台机器派生出相同的密码。其中从不包含主密码或任何派生出的秘密，
+// 也不包含保存着托管密码的 `store.json`；可选的口令加密只是为了隐藏你使用了哪些站点与预设。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+use crate::store::STORE_FILE;
+use crate::write_guard;
+
+const BACKUP_FORMAT: &str = "aegixpass-backup";
+const ENCRYPTED_FORMAT: &str = "aegixpass-backup-encrypted";
+const BACKUP_VERSION: u32 = 1;
+/// PBKDF2-HMAC-SHA256 iterations for passphrase-encrypted backups.
+// 口令加密备份所用的 PBKDF2-HMAC-SHA256 迭代次数。
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Most iterations a backup file may ask for, so a crafted header cannot stall a restore.
+// 备份文件可要求的最大迭代次数，以免构造的文件头让恢复长时间卡住。
+const MAX_PBKDF2_ITERATIONS: u32 = 10 * PBKDF2_ITERATIONS;
+
+/// The configuration files of one config directory, by file name.
+// 一个配置目录中的配置文件，以文件名为键。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    format: String,
+    pub version: u32,
+    /// Unix time the backup was created. / 创建备份时的 Unix 时间。
+    pub created: u64,
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedBackup {
+    format: String,
+    version: u32,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl Backup {
+    /// Collects every `.json` file directly inside `dir` except the managed store's.
+    // 收集 `dir` 目录下（不含子目录）除托管存储文件外的所有 `.json` 文件。
+    pub fn collect(dir: &Path, created: u64) -> Result<Self, AegixPassError> {
+        let backup_error = |e: std::io::Error| IntegrationError::Backup(format!("{}: {}", dir.display(), e));
+        let mut files = BTreeMap::new();
+        for entry in std::fs::read_dir(dir).map_err(backup_error)? {
+            let path = entry.map_err(backup_error)?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if path.is_file() && name.ends_with(".json") && name != STORE_FILE {
+                files.insert(name.to_string(), std::fs::read_to_string(&path).map_err(backup_error)?);
+            }
+        }
+        Ok(Self { format: BACKUP_FORMAT.to_string(), version: BACKUP_VERSION, created, files })
+    }
+
+    /// Serializes the backup, encrypting it when a passphrase is given.
+    // 序列化备份；给出口令时对其加密。
+    pub fn to_json(&self, passphrase: Option<&str>) -> Result<String, AegixPassError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| IntegrationError::Backup(e.to_string()))?;
+        let Some(passphrase) = passphrase else {
+            return Ok(json + "\n");
+        };
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rand::rng().fill_bytes(&mut salt);
+        rand::rng().fill_bytes(&mut nonce);
+        let key = passphrase_key(passphrase, &salt, PBKDF2_ITERATIONS);
+        let ciphertext = ChaCha20Poly1305::new(&key.into())
+            .encrypt(Nonce::from_slice(&nonce), json.as_bytes())
+            .map_err(|e| IntegrationError::Backup(e.to_string()))?;
+        let encrypted = EncryptedBackup {
+            format: ENCRYPTED_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            iterations: PBKDF2_ITERATIONS,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        serde_json::to_string_pretty(&encrypted)
+            .map(|json| json + "\n")
+            .map_err(|e| IntegrationError::Backup(e.to_string()).into())
+    }
+
+    /// Whether a backup file is passphrase-encrypted. / 备份文件是否经过口令加密。
+    pub fn is_encrypted(json: &str) -> bool {
+        serde_json::from_str::<EncryptedBackup>(json).is_ok_and(|b| b.format == ENCRYPTED_FORMAT)
+    }
+
+    /// Parses a backup file, decrypting it with the passphrase if it is encrypted.
+    // 解析备份文件；若已加密，则用口令解密。
+    pub fn from_json(json: &str, passphrase: Option<&str>) -> Result<Self, AegixPassError> {
+        let invalid = |e: &dyn std::fmt::Display| IntegrationError::Backup(format!("not a valid backup: {}", e));
+        let plain = if Self::is_encrypted(json) {
+            let encrypted: EncryptedBackup = serde_json::from_str(json).map_err(|e| invalid(&e))?;
+            let passphrase = passphrase
+                .ok_or_else(|| IntegrationError::Backup("the backup is encrypted; a passphrase is required".to_string()))?;
+            let salt = BASE64.decode(&encrypted.salt).map_err(|e| invalid(&e))?;
+            let nonce = BASE64.decode(&encrypted.nonce).map_err(|e| invalid(&e))?;
+            let ciphertext = BASE64.decode(&encrypted.ciphertext).map_err(|e| invalid(&e))?;
+            if nonce.len() != 12 || encrypted.iterations == 0 {
+                return Err(invalid(&"malformed encryption header").into());
+            }
+            if encrypted.iterations > MAX_PBKDF2_ITERATIONS {
+                return Err(invalid(&format!("{} iterations exceed the maximum of {}", encrypted.iterations, MAX_PBKDF2_ITERATIONS)).into());
+            }
+            let key = passphrase_key(passphrase, &salt, encrypted.iterations);
+            let plaintext = ChaCha20Poly1305::new(&key.into())
+                .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+                .map_err(|_| IntegrationError::Backup("wrong passphrase or corrupted backup".to_string()))?;
+            String::from_utf8(plaintext).map_err(|e| invalid(&e))?
+        } else {
+            json.to_string()
+        };
+        let backup: Self = serde_json::from_str(&plain).map_err(|e| invalid(&e))?;
+        if backup.format != BACKUP_FORMAT || backup.version != BACKUP_VERSION {
+            return Err(invalid(&format!("unsupported format '{}' version {}", backup.format, backup.version)).into());
+        }
+        // File names come from the backup, so they must not point outside the directory.
+        // 文件名来自备份文件，因此不能指向目录之外。
+        if let Some(name) = backup.files.keys().find(|name| !is_plain_json_name(name)) {
+            return Err(invalid(&format!("unsafe file name '{}'", name)).into());
+        }
+        Ok(backup)
+    }
+
+    /// Writes the files into `dir`. Existing files with different contents are only replaced
+    /// when `force` is set; returns the names written.
+    // 将文件写入 `dir`。内容不同的已有文件仅在设置 `force` 时才会被替换；返回写入的文件名。
+    pub fn restore(&self, dir: &Path, force: bool) -> Result<Vec<String>, AegixPassError> {
+        let backup_error = |name: &str, e: std::io::Error| IntegrationError::Backup(format!("{}: {}", dir.join(name).display(), e));
+        let conflicts: Vec<&str> = self
+            .files
+            .iter()
+            .filter(|(name, contents)| std::fs::read_to_string(dir.join(name)).is_ok_and(|current| &current != *contents))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !conflicts.is_empty() && !force {
+            return Err(IntegrationError::Backup(format!(
+                "{} would be overwritten with different contents; rerun with --force to replace",
+                conflicts.join(", ")
+            )).into());
+        }
+        write_guard::create_dir_all(dir).map_err(|e| backup_error("", e))?;
+        let mut written = Vec::new();
+        for (name, contents) in &self.files {
+            let path = dir.join(name);
+            let _lock = write_guard::lock(&path).map_err(|e| backup_error(name, e))?;
+            write_guard::write(&path, contents).map_err(|e| backup_error(name, e))?;
+            written.push(name.clone());
+        }
+        Ok(written)
+    }
+}
+
+/// A bare `*.json` file name without any path component. / 不含任何路径成分的 `*.json` 文件名。
+fn is_plain_json_name(name: &str) -> bool {
+    name.ends_with(".json") && !name.starts_with('.') && !name.contains(['/', '\\', ':'])
+}
+
+fn passphrase_key(passphrase: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Backup {
+        let files = BTreeMap::from([
+            ("default.json".to_string(), "{\"version\": 1}\n".to_string()),
+            ("sites.json".to_string(), "{\"version\": 1, \"sites\": []}\n".to_string()),
+        ]);
+        Backup { format: BACKUP_FORMAT.to_string(), version: BACKUP_VERSION, created: 1_700_000_000, files }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let backup = sample();
+        let plain = backup.to_json(None).unwrap();
+        assert!(!Backup::is_encrypted(&plain));
+        assert_eq!(Backup::from_json(&plain, None).unwrap(), backup);
+
+        let encrypted = backup.to_json(Some("correct horse")).unwrap();
+        assert!(Backup::is_encrypted(&encrypted) && !encrypted.contains("sites.json"));
+        assert_eq!(Backup::from_json(&encrypted, Some("correct horse")).unwrap(), backup);
+        assert!(Backup::from_json(&encrypted, Some("wrong")).is_err());
+        assert!(Backup::from_json(&encrypted, None).is_err());
+    }
+
+    #[test]
+    fn test_unsafe_names_are_rejected() {
+        let mut backup = sample();
+        backup.files.insert("../evil.json".to_string(), String::new());
+        assert!(Backup::from_json(&backup.to_json(None).unwrap(), None).is_err());
+    }
+
+    #[test]
+    fn test_store_is_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("sites.json"), "{}").unwrap();
+        std::fs::write(dir.path().join(STORE_FILE), "{}").unwrap();
+        assert_eq!(Backup::collect(dir.path(), 0).unwrap().files.keys().collect::<Vec<_>>(), ["sites.json"]);
+    }
+
+    #[test]
+    fn test_excessive_iterations_are_rejected() {
+        let encrypted = sample().to_json(Some("correct horse")).unwrap();
+        let mut header: serde_json::Value = serde_json::from_str(&encrypted).unwrap();
+        header["iterations"] = (MAX_PBKDF2_ITERATIONS + 1).into();
+        let error = Backup::from_json(&header.to_string(), Some("correct horse")).unwrap_err();
+        assert!(error.to_string().contains("exceed the maximum"));
+    }
+}
@@ -0,0 +1,172 @@
+// --- Charset tester ---
+// --- 字符集测试 ---
+// Describes a candidate charset string before it goes into a preset: how many characters it
+// really has (code points, as derivation counts them, versus what a reader sees), its size in
+// bytes, repeated and ambiguous characters, and what kinds of characters it is made of.
+// 在候选字符集字符串写入预设之前对其进行描述：它实际包含多少个字符（派生所计的码位数，以及读者看到的字素数）、
+// 其字节大小、重复与易混淆的字符，以及它由哪些类别的字符组成。
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::core::AMBIGUOUS_CHARS;
+
+/// Kinds of characters a charset is made of. / 字符集所包含的字符类别。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    Digit,
+    Lowercase,
+    Uppercase,
+    /// ASCII punctuation and symbols. / ASCII 标点与符号。
+    Symbol,
+    Space,
+    /// Letters and digits outside ASCII, which some sites reject. / ASCII 之外的字母与数字，部分网站不接受。
+    NonAscii,
+    /// Combining marks, control and format characters: invisible or attached to their neighbour.
+    // 组合标记、控制字符与格式字符：不可见或依附于相邻字符。
+    Invisible,
+}
+
+impl Category {
+    /// The category's name. / 类别名称。
+    pub fn label(&self) -> &'static str {
+        match self {
+            Category::Digit => "digits",
+            Category::Lowercase => "lowercase",
+            Category::Uppercase => "uppercase",
+            Category::Symbol => "symbols",
+            Category::Space => "whitespace",
+            Category::NonAscii => "non-ASCII",
+            Category::Invisible => "invisible or combining",
+        }
+    }
+
+    fn of(c: char) -> Self {
+        match c {
+            '0'..='9' => Category::Digit,
+            'a'..='z' => Category::Lowercase,
+            'A'..='Z' => Category::Uppercase,
+            c if c.is_ascii_punctuation() => Category::Symbol,
+            c if c.is_whitespace() => Category::Space,
+            c if c.is_control() || is_format_or_mark(c) => Category::Invisible,
+            _ => Category::NonAscii,
+        }
+    }
+}
+
+/// What [`analyze`] found in a charset string. / [`analyze`] 在字符集字符串中发现的内容。
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharsetReport {
+    /// Code points: what derivation indexes and counts. / 码位数：派生进行索引与计数的单位。
+    pub chars: usize,
+    /// User-perceived characters. / 用户感知的字符数（字素簇）。
+    pub graphemes: usize,
+    pub utf8_bytes: usize,
+    pub utf16_units: usize,
+    /// Characters listed more than once, with their count; they are drawn that much more often.
+    // 出现多于一次的字符及其次数；它们被抽中的概率会相应提高。
+    pub duplicates: Vec<(char, usize)>,
+    /// Characters of [`AMBIGUOUS_CHARS`] present. / 出现的 [`AMBIGUOUS_CHARS`] 中的字符。
+    pub ambiguous: Vec<char>,
+    /// Distinct characters per category. / 各类别中不同字符的数量。
+    pub categories: Vec<(Category, usize)>,
+    /// Bits of entropy one character drawn from the charset adds. / 从该字符集中抽取的一个字符所贡献的熵（比特）。
+    pub bits_per_char: f64,
+}
+
+impl CharsetReport {
+    /// Problems worth fixing before using the charset. / 使用该字符集之前值得修正的问题。
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.chars == 0 {
+            warnings.push("the charset is empty".to_string());
+        }
+        if !self.duplicates.is_empty() {
+            warnings.push("repeated characters are drawn more often than the others; list each once".to_string());
+        }
+        if self.graphemes != self.chars {
+            warnings.push(format!(
+                "{} code points form {} visible characters; derivation picks code points and splits them unless the preset sets graphemes",
+                self.chars, self.graphemes
+            ));
+        }
+        for (category, _) in &self.categories {
+            match category {
+                Category::Space => warnings.push("whitespace is easily lost when a password is pasted".to_string()),
+                Category::Invisible => warnings.push("invisible characters cannot be retyped".to_string()),
+                Category::NonAscii => warnings.push("some sites reject non-ASCII characters".to_string()),
+                _ => {}
+            }
+        }
+        if !self.ambiguous.is_empty() {
+            warnings.push("ambiguous characters are hard to retype; consider excludeAmbiguous".to_string());
+        }
+        warnings
+    }
+}
+
+/// Analyzes a candidate charset string. / 分析候选字符集字符串。
+pub fn analyze(chars: &str) -> CharsetReport {
+    let mut counts: Vec<(char, usize)> = Vec::new();
+    for c in chars.chars() {
+        match counts.iter_mut().find(|(seen, _)| *seen == c) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((c, 1)),
+        }
+    }
+    let mut categories: Vec<(Category, usize)> = Vec::new();
+    for &(c, _) in &counts {
+        let category = Category::of(c);
+        match categories.iter_mut().find(|(seen, _)| *seen == category) {
+            Some((_, count)) => *count += 1,
+            None => categories.push((category, 1)),
+        }
+    }
+    categories.sort();
+    CharsetReport {
+        chars: chars.chars().count(),
+        graphemes: chars.graphemes(true).count(),
+        utf8_bytes: chars.len(),
+        utf16_units: chars.encode_utf16().count(),
+        duplicates: counts.iter().copied().filter(|&(_, count)| count > 1).collect(),
+        ambiguous: counts.iter().map(|&(c, _)| c).filter(|&c| AMBIGUOUS_CHARS.contains(c)).collect(),
+        categories,
+        bits_per_char: if counts.is_empty() { 0.0 } else { (counts.len() as f64).log2() },
+    }
+}
+
+/// Combining marks and the format characters people paste by accident (joiners, marks, BOM).
+// 组合标记，以及人们容易误粘贴的格式字符（连接符、方向标记、BOM）。
+fn is_format_or_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE00}'..='\u{FE0F}' | '\u{FE20}'..='\u{FE2F}' | '\u{00AD}' | '\u{200B}'..='\u{200F}'
+        | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}')
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze() {
+        let report = analyze("abcA1!a0O");
+        assert_eq!((report.chars, report.graphemes, report.utf8_bytes), (9, 9, 9));
+        assert_eq!(report.duplicates, vec![('a', 2)]);
+        assert_eq!(report.ambiguous, vec!['1', '0', 'O']);
+        assert_eq!(
+            report.categories,
+            vec![(Category::Digit, 2), (Category::Lowercase, 3), (Category::Uppercase, 2), (Category::Symbol, 1)]
+        );
+        assert_eq!(report.bits_per_char, 3.0);
+
+        // "é" written as e + combining acute: two code points, one visible character.
+        // 以 e 加组合锐音符书写的 "é"：两个码位，一个可见字符。
+        let report = analyze("e\u{301}ß");
+        assert_eq!((report.chars, report.graphemes, report.utf8_bytes, report.utf16_units), (3, 2, 5, 3));
+        assert!(report.categories.contains(&(Category::Invisible, 1)));
+        assert_eq!(report.warnings().len(), 3);
+        assert!(analyze("abc").warnings().is_empty());
+    }
+}
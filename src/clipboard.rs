@@ -0,0 +1,58 @@
+// --- Clipboard ---
+// --- 剪贴板 ---
+// Copies text to the system clipboard through the platform's own command-line tool: pbcopy on
+// macOS, clip.exe on Windows, and wl-copy (Wayland) or xclip (X11) elsewhere.
+// 通过平台自带的命令行工具将文本复制到系统剪贴板：macOS 上为 pbcopy，Windows 上为 clip.exe，
+// 其它系统上为 wl-copy（Wayland）或 xclip（X11）。
+// Integration tests replace the clipboard with a file (see `set_fake`).
+// 集成测试会用一个文件代替剪贴板（参见 `set_fake`）。
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+
+/// The file standing in for the clipboard, if any. / 代替剪贴板的文件（若有）。
+static FAKE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Makes [`copy`] write to `path` instead of the system clipboard, or restores it with `None`.
+/// Meant for tests, which must not touch the user's clipboard.
+// 使 [`copy`] 写入 `path` 而不是系统剪贴板；传入 `None` 则恢复。供测试使用，测试不得改动用户的剪贴板。
+pub fn set_fake(path: Option<PathBuf>) {
+    *FAKE.lock().unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+/// Copies `text` to the clipboard. / 将 `text` 复制到剪贴板。
+pub fn copy(text: &str) -> Result<(), AegixPassError> {
+    if let Some(path) = FAKE.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return std::fs::write(path, text).map_err(|e| IntegrationError::Clipboard(format!("{}: {}", path.display(), e)).into());
+    }
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "macos") {
+        ("pbcopy", &[])
+    } else if cfg!(windows) {
+        ("clip", &[])
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wl-copy", &[])
+    } else {
+        ("xclip", &["-selection", "clipboard"])
+    };
+    let clipboard_error = |e: &dyn std::fmt::Display| IntegrationError::Clipboard(format!("{}: {}", program, e));
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| clipboard_error(&e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).map_err(|e| clipboard_error(&e))?;
+    }
+    let status = child.wait().map_err(|e| clipboard_error(&e))?;
+    if !status.success() {
+        return Err(clipboard_error(&status).into());
+    }
+    Ok(())
+}
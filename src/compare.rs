@@ -0,0 +1,126 @@
+// --- Preset comparison ---
+// --- 预设比较 ---
+// Answers "why does this preset give me a different password?". Every top-level field on which
+// two presets differ is swapped into the first preset on its own, and the result is derived
+// against fixed synthetic inputs: a field whose swap changes the output is a cause of the
+// divergence, the others (such as the name) are cosmetic.
+// 回答“为什么这个预设生成的密码不一样？”。两个预设中每个不同的顶层字段都会被单独替换进第一个预设，
+// 并用固定的合成输入进行派生：替换后输出发生变化的字段就是差异的原因，其余字段（例如名称）只是表面差异。
+
+use serde_json::Value;
+
+use crate::core::{aegixpass_generator, AegixPassError, Preset};
+
+/// Synthetic (master password, distinguish key) pairs derived with both presets.
+// 使用两个预设分别派生的合成（主密码，区分密钥）组合。
+const TEST_VECTORS: [(&str, &str); 2] = [("AegixPass why-different", "example.com"), ("correct horse battery staple", "mail.example.org")];
+
+/// One top-level field on which two presets differ.
+// 两个预设之间存在差异的一个顶层字段。
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDifference {
+    pub field: String,
+    /// The field in the first preset as JSON, or `None` when absent. / 第一个预设中该字段的 JSON，缺失时为 `None`。
+    pub left: Option<String>,
+    /// The field in the second preset as JSON, or `None` when absent. / 第二个预设中该字段的 JSON，缺失时为 `None`。
+    pub right: Option<String>,
+    /// Whether taking just this field from the second preset changes the derived passwords.
+    // 仅从第二个预设取用该字段是否会改变派生密码。
+    pub changes_output: bool,
+}
+
+/// The result of comparing two presets. / 比较两个预设的结果。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub differences: Vec<FieldDifference>,
+    /// Whether the two presets derive different passwords. / 两个预设派生的密码是否不同。
+    pub outputs_differ: bool,
+}
+
+impl Comparison {
+    /// True when the outputs differ although no single field explains it, i.e. only a
+    /// combination of fields does (e.g. `length` together with `charsets`).
+    // 当输出不同却没有任何单个字段能解释时为真，即只有字段组合才会导致差异（例如 `length` 与 `charsets` 一同变化）。
+    pub fn combination_only(&self) -> bool {
+        self.outputs_differ && !self.differences.iter().any(|d| d.changes_output)
+    }
+}
+
+/// Compares two preset JSON documents field by field.
+// 逐字段比较两个预设 JSON 文档。
+pub fn why_different(left_json: &str, right_json: &str) -> Result<Comparison, AegixPassError> {
+    let parse = |json: &str| -> Result<serde_json::Map<String, Value>, AegixPassError> {
+        match serde_json::from_str(json).map_err(|e| AegixPassError::PresetParseError(e.to_string()))? {
+            Value::Object(map) => Ok(map),
+            _ => Err(AegixPassError::PresetParseError("a preset must be a JSON object".to_string())),
+        }
+    };
+    let left = parse(left_json)?;
+    let right = parse(right_json)?;
+    let baseline = outputs(&left)?;
+
+    let mut fields: Vec<&String> = left.keys().chain(right.keys()).collect();
+    fields.sort();
+    fields.dedup();
+    let mut differences = Vec::new();
+    for field in fields {
+        let (l, r) = (left.get(field), right.get(field));
+        if l == r {
+            continue;
+        }
+        let mut hybrid = left.clone();
+        match r {
+            Some(value) => hybrid.insert(field.clone(), value.clone()),
+            None => hybrid.remove(field),
+        };
+        // A hybrid that fails to derive (e.g. too short for its charsets) also changes the output.
+        // 无法派生的混合预设（例如长度不足以容纳其字符集）同样视为改变了输出。
+        let changes_output = outputs(&hybrid).map_or(true, |o| o != baseline);
+        differences.push(FieldDifference {
+            field: field.clone(),
+            left: l.map(Value::to_string),
+            right: r.map(Value::to_string),
+            changes_output,
+        });
+    }
+    let outputs_differ = outputs(&right)? != baseline;
+    Ok(Comparison { differences, outputs_differ })
+}
+
+/// Derives the test vectors with a preset given as a JSON object.
+// 使用以 JSON 对象形式给出的预设派生测试向量。
+fn outputs(preset: &serde_json::Map<String, Value>) -> Result<Vec<String>, AegixPassError> {
+    let preset = Preset::from_json(&Value::Object(preset.clone()).to_string())?;
+    TEST_VECTORS
+        .iter()
+        .map(|(password_source, distinguish_key)| aegixpass_generator(password_source, distinguish_key, &preset))
+        .collect()
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESET: &str = r#"{
+      "name": "A", "version": 1, "hashAlgorithm": "sha256", "rngAlgorithm": "chaCha20",
+      "shuffleAlgorithm": "fisherYates", "length": 16, "platformId": "aegixpass.takuron.com",
+      "charsets": ["0123456789", "abcdefghijklmnopqrstuvwxyz"]
+    }"#;
+
+    #[test]
+    fn test_why_different() {
+        let other = PRESET.replace(r#""name": "A""#, r#""name": "B""#).replace(r#""length": 16"#, r#""length": 20"#);
+        let comparison = why_different(PRESET, &other).unwrap();
+        assert!(comparison.outputs_differ && !comparison.combination_only());
+        let summary: Vec<(&str, bool)> = comparison.differences.iter().map(|d| (d.field.as_str(), d.changes_output)).collect();
+        assert_eq!(summary, vec![("length", true), ("name", false)]);
+
+        let cosmetic = PRESET.replace(r#""name": "A""#, r#""name": "B", "official": true"#);
+        let comparison = why_different(PRESET, &cosmetic).unwrap();
+        assert!(!comparison.outputs_differ);
+        assert_eq!(comparison.differences.len(), 2);
+        assert_eq!(comparison.differences[1].left, None);
+    }
+}
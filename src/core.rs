@@ -2,7 +2,7 @@
 // --- 导入依赖 ---
 // Serde library for serializing and deserializing Rust data structures to and from JSON.
 // Serde 库，用于在 Rust 数据结构和 JSON 格式之间进行序列化和反序列化。
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 // SHA-2 hashing library, a widely used standard hash function.
 // SHA-2 哈希算法库，一个广泛使用的标准哈希函数。
 use sha2::{Digest, Sha256};
@@ -12,45 +12,137 @@ use rand::prelude::*;
 // ChaCha20 is a high-performance, deterministic random number generator (RNG) that can be created from a seed.
 // ChaCha20 是一个高性能的、可从种子（seed）创建的确定性随机数生成器 (RNG)。
 use rand_chacha::ChaCha20Rng;
+#[cfg(feature = "hc128")]
 use rand_hc::Hc128Rng;
+#[cfg(feature = "xoshiro")]
+use rand_xoshiro::Xoshiro256StarStar;
+#[cfg(feature = "sha3")]
 use sha3::Sha3_256;
+#[cfg(feature = "blake2")]
+use blake2::{digest::consts::U32, Blake2b};
 // thiserror library to easily derive the standard Error trait for custom error types.
 // thiserror 库，可以方便地为自定义错误类型派生标准的 Error trait。
 use thiserror::Error;
+#[cfg(feature = "argon2")]
 use argon2::{Algorithm as Argon2Algorithm , Argon2, Params, Version as Argon2Version};
+#[cfg(feature = "scrypt")]
 use scrypt::{scrypt, Params as ScryptParams};
+// HKDF (RFC 5869) is used to expand the master seed into labelled key material.
+// HKDF (RFC 5869) 用于将主种子扩展为带标签的密钥材料。
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+// Grapheme clusters for presets with `graphemes` on. / 用于开启 `graphemes` 的预设的字素簇切分。
+use unicode_segmentation::UnicodeSegmentation;
+// NFC for presets with `normalizeInputs` on. / 用于开启 `normalizeInputs` 的预设的 NFC 规范化。
+use unicode_normalization::UnicodeNormalization;
+// Global tables of hash, RNG and post-processing algorithms.
+// 哈希、RNG 与后处理算法的全局注册表。
+use crate::integration::IntegrationError;
+use crate::kdf_cache;
+use crate::passphrase::PassphraseSettings;
+use crate::recovery::{MAX_RECOVERY_CODES, MAX_RECOVERY_DIGITS, MIN_RECOVERY_DIGITS};
+use crate::registry::{self, HashFunction, RngFactory};
+use crate::rotation::RotationPolicy;
 
 // --- 1. Define aegixPass JSON data structures and related enums ---
 // --- 1. 定义 aegixPass 的 JSON 数据结构和相关枚举 ---
 
 /// Defines the hash algorithm used for password generation.
 // 定义密码生成所使用的哈希算法。
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum HashAlgorithm {
     Sha256,
     Blake3,
     Sha3_256,
+    /// BLAKE2b with a 256-bit output. / 输出为 256 位的 BLAKE2b。
+    Blake2b,
     Argon2id,
     Scrypt,
+    /// An algorithm added through [`registry::register_hash`]. / 通过 [`registry::register_hash`] 添加的算法。
+    #[serde(untagged)]
+    Custom(String),
+}
+
+impl HashAlgorithm {
+    /// The algorithms built into AegixPass, some behind features. / AegixPass 内置的算法，部分需要启用特性。
+    pub const BUILT_IN: [HashAlgorithm; 6] = [
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Blake3,
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Blake2b,
+        HashAlgorithm::Argon2id,
+        HashAlgorithm::Scrypt,
+    ];
+
+    /// The name of the algorithm as written in preset files.
+    // 算法在预设文件中的名称。
+    pub fn name(&self) -> &str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Sha3_256 => "sha3_256",
+            HashAlgorithm::Blake2b => "blake2b",
+            HashAlgorithm::Argon2id => "argon2id",
+            HashAlgorithm::Scrypt => "scrypt",
+            HashAlgorithm::Custom(name) => name,
+        }
+    }
 }
 
 /// Defines the deterministic random number generator (RNG) algorithm used for password generation.
 // 定义密码生成所使用的确定性随机数生成器 (RNG) 算法。
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum RngAlgorithm {
     ChaCha20,
-    Hc128
+    Hc128,
+    /// A small, fast generator for embedded targets; not a cryptographic RNG, so chaCha20 stays the default.
+    // 适用于嵌入式目标的小巧快速生成器；它不是密码学 RNG，因此默认仍为 chaCha20。
+    Xoshiro256StarStar,
+    /// An algorithm added through [`registry::register_rng`]. / 通过 [`registry::register_rng`] 添加的算法。
+    #[serde(untagged)]
+    Custom(String),
+}
+
+impl RngAlgorithm {
+    /// The algorithms built into AegixPass, some behind features. / AegixPass 内置的算法，部分需要启用特性。
+    pub const BUILT_IN: [RngAlgorithm; 3] = [RngAlgorithm::ChaCha20, RngAlgorithm::Hc128, RngAlgorithm::Xoshiro256StarStar];
+
+    /// The name of the algorithm as written in preset files.
+    // 算法在预设文件中的名称。
+    pub fn name(&self) -> &str {
+        match self {
+            RngAlgorithm::ChaCha20 => "chaCha20",
+            RngAlgorithm::Hc128 => "hc128",
+            RngAlgorithm::Xoshiro256StarStar => "xoshiro256StarStar",
+            RngAlgorithm::Custom(name) => name,
+        }
+    }
 }
 
 /// Defines the algorithm used for shuffling the password characters.
 // 定义密码洗牌所使用的算法。
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum ShuffleAlgorithm {
     FisherYates, // Fisher-Yates is the algorithm used by the standard library's `slice::shuffle`.
     // Fisher-Yates 是标准库 `slice::shuffle` 使用的算法。
+    /// Sattolo's variant, which only produces single-cycle permutations: no character stays
+    /// where stages C and D put it.
+    // Sattolo 变体，只产生单循环排列：没有任何字符停留在阶段 C 与 D 放置的位置上。
+    Sattolo,
+}
+
+impl ShuffleAlgorithm {
+    /// The name of the algorithm as written in preset files.
+    // 算法在预设文件中的名称。
+    pub fn name(&self) -> &str {
+        match self {
+            ShuffleAlgorithm::FisherYates => "fisherYates",
+            ShuffleAlgorithm::Sattolo => "sattolo",
+        }
+    }
 }
 
 /// Defines all possible errors that can occur, using thiserror for more user-friendly error messages.
@@ -63,19 +155,227 @@ pub enum AegixPassError {
     LengthTooShort(usize, usize),
     #[error("All charset groups must contain at least one character.")]
     EmptyCharset,
+    #[error("The preset's outputPolicy does not allow {0}.")]
+    OutputNotAllowed(&'static str),
+    #[error("excludeChars removes every character of charset group {0}.")]
+    ExcludeCharsEmptiesGroup(usize),
+    #[error("Every charset group's min must be at least 1.")]
+    CharsetMinZero,
+    #[error("Password length ({0}) is shorter than the {1} characters the charset minimums require.")]
+    LengthBelowMinimums(usize, usize),
+    #[error("'{0}' is not a valid deprecatedAfter date.")]
+    InvalidDeprecationDate(String),
+    #[error("noRepeat needs charset groups without shared characters, but '{0}' appears in more than one.")]
+    NoRepeatOverlap(char),
+    #[error("Charset group {0} needs {1} different characters for noRepeat, but has only {2}.")]
+    NoRepeatGroupTooSmall(usize, usize, usize),
+    #[error("noRepeat needs {0} different characters, but the charsets offer only {1}.")]
+    NoRepeatTooFewChars(usize, usize),
+    #[error("Could not rearrange the password to satisfy {0} within {1} swaps.")]
+    LayoutUnsatisfiable(&'static str, usize),
+    #[error("No arrangement of the password satisfies {0}.")]
+    LayoutImpossible(&'static str),
+    #[error("noAdjacentGroup needs at least two charset groups.")]
+    NoAdjacentGroupSingleGroup,
+    #[error("{0} cannot always be met: {1}.")]
+    PositionUnsatisfiable(&'static str, String),
+    #[error("A PIN must have between {MIN_PIN_DIGITS} and {MAX_PIN_DIGITS} digits, not {0}.")]
+    InvalidPinLength(usize),
+    #[error("Invalid pattern: {0}.")]
+    InvalidPattern(String),
+    #[error("With graphemes on, the charsets and pattern cannot contain U+{0:04X}, which is reserved for placeholders.")]
+    GraphemePlaceholderUsed(u32),
+    #[error("Every charset group's weight must be at least 1.")]
+    CharsetWeightZero,
+    #[error("Charset group {0} has a max of {1}, below its min of {2}.")]
+    CharsetMaxBelowMin(usize, usize, usize),
+    #[error("Password length ({0}) exceeds the {1} characters the charset maximums allow.")]
+    LengthAboveMaximums(usize, usize),
+    #[error("The preset must define at least one charset group.")]
+    NoCharsets,
+    #[error("Password length ({0}) exceeds the supported maximum of {1}.")]
+    LengthTooLong(usize, usize),
+    #[error("The combined charsets contain {0} characters; at most {1} are supported.")]
+    CharsetTooLarge(usize, usize),
+    #[error("The preset's {field} ({value}) exceeds the configured limit of {limit}.")]
+    PresetLimitExceeded { field: &'static str, value: usize, limit: usize },
+    #[error("The sandbox's {what} limit of {limit} was exceeded ({value}).")]
+    SandboxLimitExceeded { what: &'static str, value: u64, limit: u64 },
+    #[error("The sandbox refused the preset: {0}.")]
+    SandboxRejected(String),
+    #[error("The sandbox's memory budget is in use ({in_flight_kib} of {limit_kib} KiB).")]
+    SandboxBusy { in_flight_kib: u64, limit_kib: u64 },
     #[error("Failed to parse the preset JSON: {0}")]
     PresetParseError(String),
     #[error("The number of charset groups ({0}) is too large; this algorithm supports a maximum of {1} groups.")]
     TooManyCharsetGroups(usize, usize),
+    #[error("The preset's seedBytes ({0}) is not supported; it must be 32, 64 or 128.")]
+    InvalidSeedBytes(usize),
+    #[error("Preset version {0} is not supported; this program supports versions 1 and 2.")]
+    UnsupportedVersion(u32),
+    #[error("'{epoch}' is not a valid epoch for the preset's {} rotation.", .policy.name())]
+    InvalidRotationEpoch { epoch: String, policy: RotationPolicy },
+    #[error("A rotation epoch was given, but the preset has no rotation policy.")]
+    RotationEpochUnused,
     #[error("Argon2 hashing failed: {0}")]
     Argon2Error(String),
+    #[error("The preset requires an Argon2 secret, but none was given.")]
+    Argon2SecretMissing,
+    #[error("An Argon2 secret was given, but the preset does not use keyed Argon2id.")]
+    Argon2SecretUnused,
+    #[error("Invalid salt: {0}.")]
+    InvalidSalt(String),
     #[error("Scrypt hashing failed: {0}")] // <-- 新增 Scrypt 错误
     ScryptError(String),
+    #[error("Invalid key length ({0} bytes); derived keys must be between 1 and {1} bytes.")]
+    InvalidKeyLength(usize, usize),
+    #[error("'{0}' is reserved for a key AegixPass derives itself.")]
+    ReservedKeyLabel(String),
+    #[error("bcrypt hashing failed: {0}")]
+    BcryptError(String),
+    #[error("crypt(3) hashing failed: {0}")]
+    CryptError(String),
+    #[error("Certificate generation failed: {0}")]
+    CertificateError(String),
+    #[error("The preset needs {required_kib} KiB of memory for Argon2, but only {available_kib} KiB are available.")]
+    InsufficientMemory { required_kib: u64, available_kib: u64, suggestions: String },
+    #[error("The preset's fingerprint is {actual}, but {expected} was pinned.")]
+    FingerprintMismatch { expected: String, actual: String },
+    #[error("The preset has no \"passphrase\" settings.")]
+    PassphraseNotConfigured,
+    #[error("Invalid passphrase settings: {0}.")]
+    InvalidPassphraseSettings(String),
+    #[error("Invalid wordlist: {0}.")]
+    WordlistError(String),
+    #[error("The wordlist's SHA-256 is {actual}, but the preset records {expected}.")]
+    WordlistChecksumMismatch { expected: String, actual: String },
+    #[error("The security question must contain at least one letter or digit.")]
+    EmptyQuestion,
+    #[error("Cannot derive {0} recovery codes of {1} digits.")]
+    InvalidRecoveryCodes(usize, usize),
+    #[error("Self-test failed for {0}: this build derives different passwords than expected.")]
+    SelfTestFailed(String),
+    #[error("Unknown algorithm '{0}'; it is neither built in nor registered.")]
+    UnknownAlgorithm(String),
+    #[error("Algorithm registration failed: {0}")]
+    RegistryError(String),
+    #[error("The preset requests the '{name}' algorithm, but this build was compiled without it.")]
+    AlgorithmUnavailable { name: &'static str, feature: &'static str },
+    #[error("A batch worker thread panicked: {0}")]
+    BatchWorkerPanicked(String),
+    #[error("Internal error: {0}.")]
+    Internal(&'static str),
+    /// A failure outside the derivation, e.g. of the clipboard or Vault. / 派生之外的错误，例如剪贴板或 Vault 的错误。
+    #[error(transparent)]
+    Integration(#[from] IntegrationError),
+}
+
+impl AegixPassError {
+    /// A concrete suggestion for fixing the error, shown by the command line under the message.
+    // 修复该错误的具体建议，命令行会将其显示在错误信息下方。
+    pub fn hint(&self) -> Option<String> {
+        use AegixPassError::*;
+        Some(match self {
+            InputEmpty => "pass a non-empty master password and distinguish key; use '-' to be prompted for the master password".to_string(),
+            LengthTooShort(length, groups) => format!(
+                "length {} < {} charset groups: increase `length` to at least {} or remove {} charset group(s)",
+                length, groups, groups, groups - length
+            ),
+            EmptyCharset => "remove the empty string from `charsets` or add characters to it".to_string(),
+            OutputNotAllowed(_) => "copy the value with --clip, or ask the preset's maintainer to relax its `outputPolicy`".to_string(),
+            CharsetMinZero => "set `min` to 1 or more, or write the group as a plain string".to_string(),
+            LengthBelowMinimums(length, required) => format!(
+                "increase `length` to at least {}, or lower the groups' `min` by {} in total",
+                required,
+                required - length
+            ),
+            InvalidDeprecationDate(_) => "write the date like 2026-12-31".to_string(),
+            ExcludeCharsEmptiesGroup(group) => format!("keep some of group {}'s characters out of excludeChars, or remove the group", group),
+            NoRepeatOverlap(c) => format!("remove '{}' from all charset groups but one", c),
+            NoRepeatGroupTooSmall(group, _, distinct) => {
+                format!("lower group {}'s `min` to at most {}, or add characters to it", group, distinct)
+            }
+            NoRepeatTooFewChars(_, available) => {
+                format!("lower `length` to at most {}, add characters, or raise the groups' `max`", available)
+            }
+            LayoutUnsatisfiable(rule, _) | LayoutImpossible(rule) => format!("add characters to the charsets, or turn {} off", rule),
+            NoAdjacentGroupSingleGroup => "add a second charset group, or turn noAdjacentGroup off".to_string(),
+            PositionUnsatisfiable(rule, _) => {
+                format!("add a charset group made only of such characters, raise its `min`, or turn {} off", rule)
+            }
+            InvalidPattern(_) => "use placeholders C c V v A a 9 X, other characters as literals, and \\ before a literal placeholder letter".to_string(),
+            GraphemePlaceholderUsed(_) => "remove private-use characters from U+F0000 upward, or turn graphemes off".to_string(),
+            InvalidPinLength(_) => format!("set `pin` (or --pin) to a number from {} to {}", MIN_PIN_DIGITS, MAX_PIN_DIGITS),
+            CharsetWeightZero => "set `weight` to 1 or more, or cap the group with `max` instead".to_string(),
+            CharsetMaxBelowMin(group, max, min) => {
+                format!("raise group {}'s `max` to at least {}, or lower its `min` to at most {}", group, min, max)
+            }
+            LengthAboveMaximums(length, allowed) => format!(
+                "lower `length` to at most {}, raise the groups' `max` by {} in total, or add a group without one",
+                allowed,
+                length - allowed
+            ),
+            NoCharsets => "add at least one charset group to `charsets`, e.g. \"0123456789\"".to_string(),
+            LengthTooLong(_, max) => format!("lower `length` to at most {}", max),
+            CharsetTooLarge(_, max) => format!("shorten `charsets` to at most {} characters in total", max),
+            PresetLimitExceeded { field, limit, .. } => format!(
+                "lower the preset's {} to at most {}; library users who trust the preset can raise `PresetLimits`",
+                field, limit
+            ),
+            SandboxLimitExceeded { .. } => "use a cheaper preset, or raise `SandboxLimits` if the server can afford it".to_string(),
+            SandboxRejected(_) => "sandboxed presets may only use built-in algorithms and no postProcessors".to_string(),
+            SandboxBusy { .. } => "retry once other derivations have finished".to_string(),
+            PresetParseError(_) => "compare the preset with default.json; every field except `official`, `postProcessors`, `argon2` and `seedBytes` is required".to_string(),
+            TooManyCharsetGroups(_, max) if *max < MAX_SEED_BYTES / 4 => format!(
+                "merge charset groups so that there are at most {}, or set \"seedBytes\" to 64 or 128 for 16 or 32 groups; this changes every derived password",
+                max
+            ),
+            TooManyCharsetGroups(_, max) => format!("merge charset groups so that there are at most {}", max),
+            Argon2SecretMissing => "pass the secret with --argon2-secret-file".to_string(),
+            Argon2SecretUnused => "use a preset with \"argon2\": {..., \"keyed\": true}, or drop --argon2-secret-file".to_string(),
+            InvalidSalt(_) => format!(
+                "write `salt` as {} to {} hex digits for an argon2id or scrypt preset, or remove it to derive the salt from platformId",
+                MIN_SALT_BYTES * 2,
+                MAX_SALT_BYTES * 2
+            ),
+            InvalidSeedBytes(_) => "set \"seedBytes\" to 32, 64 or 128, or remove it to use 32".to_string(),
+            InvalidRotationEpoch { policy, .. } => format!("write the epoch like {}", policy.example()),
+            RotationEpochUnused => "add \"rotation\": \"quarterly\" (or monthly, yearly) to the preset, or drop --epoch".to_string(),
+            UnsupportedVersion(_) => "set \"version\" to 1, or to 2 for new presets; the version changes every derived password".to_string(),
+            InvalidKeyLength(_, max) => format!("choose a key length between 1 and {} bytes", max),
+            ReservedKeyLabel(_) => "pick another --key-label, such as the default 'key'".to_string(),
+            InsufficientMemory { suggestions, .. } => format!(
+                "use the lowMemory preset, or set \"argon2\" in the preset to a scaled-down profile ({}); this changes every derived password",
+                suggestions
+            ),
+            FingerprintMismatch { .. } => "the preset changed since it was pinned; confirm the change with its publisher before updating --config-fingerprint".to_string(),
+            PassphraseNotConfigured => "add a \"passphrase\" block with \"wordlist\" and \"wordlistSha256\" to the preset".to_string(),
+            InvalidPassphraseSettings(_) => "fix the preset's \"passphrase\" block".to_string(),
+            WordlistError(_) => "use a file with one unique word per line and at least 1024 words".to_string(),
+            WordlistChecksumMismatch { .. } => "restore the original wordlist; recording the new checksum changes every passphrase".to_string(),
+            EmptyQuestion => "pass the question as the site asks it, e.g. \"mother's maiden name\"".to_string(),
+            InvalidRecoveryCodes(..) => format!(
+                "ask for 1 to {} codes of {} to {} digits",
+                MAX_RECOVERY_CODES,
+                MIN_RECOVERY_DIGITS,
+                MAX_RECOVERY_DIGITS
+            ),
+            SelfTestFailed(_) => "do not use this build; reinstall it from a trusted source and report the failure".to_string(),
+            BatchWorkerPanicked(_) | Internal(_) => "this is a bug in AegixPass; please report it along with the preset".to_string(),
+            UnknownAlgorithm(name) => format!(
+                "check the spelling of '{}'; built-in hash algorithms are sha256, blake3, sha3_256, blake2b, argon2id and scrypt, and RNGs are chaCha20, hc128 and xoshiro256StarStar",
+                name
+            ),
+            AlgorithmUnavailable { feature, .. } => format!("rebuild with `cargo build --features {}`", feature),
+            Integration(error) => return error.hint(),
+            Argon2Error(_) | ScryptError(_) | BcryptError(_) | CryptError(_) | CertificateError(_) | RegistryError(_) => return None,
+        })
+    }
 }
 
 /// Defines the complete structure for an AegixPass password generation preset.
 // 定义 AegixPass 密码生成预设的完整结构体。
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 pub struct Preset {
     pub name: String,
     pub version: u32,
@@ -88,7 +388,852 @@ pub struct Preset {
     pub length: usize,
     #[serde(rename = "platformId")]
     pub platform_id: String,
-    pub charsets: Vec<String>,
+    /// Charset groups, each written as a string or as `{"chars": "...", "min": 2}`.
+    // 字符集分组，每组写作字符串，或写作 `{"chars": "...", "min": 2}`。
+    pub charsets: Vec<Charset>,
+    /// Names of registered post-processors applied, in order, to the finished password.
+    // 按顺序应用于最终密码的已注册后处理器名称。
+    #[serde(rename = "postProcessors", default)]
+    pub post_processors: Vec<String>,
+    /// Marks a preset distributed as official; its `platformId` is expected to be well known.
+    // 标记以官方形式分发的预设；其 `platformId` 应当是公认的值。
+    #[serde(default)]
+    pub official: bool,
+    /// Argon2id cost parameters; when absent, the standard 19 MiB / 2 iterations are used.
+    // Argon2id 的成本参数；省略时使用标准的 19 MiB / 2 次迭代。
+    #[serde(default)]
+    pub argon2: Option<Argon2Settings>,
+    /// Bytes of seed material carved into 4-byte chunks in stage C, one per charset group.
+    /// 64 or 128 expand the master seed with HKDF-SHA256, allowing 16 or 32 groups; when
+    /// absent, the 32-byte master seed is used directly.
+    // 阶段 C 中被切分为 4 字节块（每个字符集分组一块）的种子字节数。设为 64 或 128 时通过 HKDF-SHA256
+    // 扩展主种子，可支持 16 或 32 个分组；省略时直接使用 32 字节的主种子。
+    #[serde(rename = "seedBytes", default)]
+    pub seed_bytes: Option<usize>,
+    /// Date-based rotation: the current epoch (see [`RotationPolicy`]) is mixed into the seed,
+    /// so every password changes when a new period begins.
+    // 基于日期的轮换：当前周期（参见 [`RotationPolicy`]）被混入种子，使所有密码在新周期开始时更换。
+    #[serde(default)]
+    pub rotation: Option<RotationPolicy>,
+    /// Where derived secrets may be delivered; enforced by the CLI. / 派生秘密可被送往何处；由命令行程序强制执行。
+    #[serde(rename = "outputPolicy", default)]
+    pub output_policy: OutputPolicy,
+    /// Strips [`AMBIGUOUS_CHARS`] from every charset before generation, for passwords that are
+    /// retyped on TVs and consoles. Equivalent to removing them from the charsets by hand.
+    // 生成之前从所有字符集中去除 [`AMBIGUOUS_CHARS`]，适用于需要在电视和游戏机上重新输入的密码。等同于手动从字符集中删去它们。
+    #[serde(rename = "excludeAmbiguous", default)]
+    pub exclude_ambiguous: bool,
+    /// Characters stripped from every charset before generation, for sites that forbid a few,
+    /// such as quotes or backslashes. Equivalent to removing them from the charsets by hand.
+    // 生成之前从所有字符集中去除的字符，适用于禁止个别字符（如引号或反斜杠）的网站。等同于手动从字符集中删去它们。
+    #[serde(rename = "excludeChars", default)]
+    pub exclude_chars: Option<String>,
+    /// No character appears twice in a password, for portals that enforce this rule.
+    // 密码中的任何字符都不会出现两次，适用于强制执行此规则的网站。
+    #[serde(rename = "noRepeat", default)]
+    pub no_repeat: bool,
+    /// No three adjacent characters run up or down by one code point, like `abc`, `123` or
+    /// `cba`, for password policies that reject such runs.
+    // 不会有三个相邻字符按码位依次加一或减一（如 `abc`、`123` 或 `cba`），适用于拒绝此类序列的密码策略。
+    #[serde(rename = "noSequential", default)]
+    pub no_sequential: bool,
+    /// No two adjacent characters come from the same charset group, for policies that want
+    /// visibly mixed passwords. Each group is capped at half the length, rounded up.
+    // 任何两个相邻字符都不来自同一字符集分组，适用于要求密码明显混合的策略。每个分组的数量上限为长度的一半（向上取整）。
+    #[serde(rename = "noAdjacentGroup", default)]
+    pub no_adjacent_group: bool,
+    /// A `YYYY-MM-DD` date after which the CLI warns that the preset is deprecated, so an
+    /// organization can move its users to a stronger preset over time.
+    // 一个 `YYYY-MM-DD` 日期，过了该日期后命令行程序会警告预设已弃用，使组织可以逐步将用户迁移到更强的预设。
+    #[serde(rename = "deprecatedAfter", default)]
+    pub deprecated_after: Option<String>,
+    /// The name of the preset to move to, shown in the deprecation warning.
+    // 应迁移到的预设名称，显示在弃用警告中。
+    #[serde(rename = "replacedBy", default)]
+    pub replaced_by: Option<String>,
+    /// Words, separator and wordlist of the passphrase output (see [`crate::passphrase`]).
+    // 口令短语输出的单词数、分隔符与词表（参见 [`crate::passphrase`]）。
+    #[serde(default)]
+    pub passphrase: Option<PassphraseSettings>,
+    /// Salts slow hashes with SHA-256 of `"{SALT_DOMAIN_TAG}:{platformId}"` instead of the bare
+    /// `platformId` (see [`SALT_DOMAIN_TAG`]).
+    // 慢哈希的盐改用 `"{SALT_DOMAIN_TAG}:{platformId}"` 的 SHA-256，而不是裸 `platformId` 的 SHA-256（参见 [`SALT_DOMAIN_TAG`]）。
+    #[serde(rename = "saltDomainTag", default)]
+    pub salt_domain_tag: bool,
+    /// A fixed salt for `argon2id` and `scrypt`, as hex, used byte for byte instead of the salt
+    /// derived from `platformId`, to match implementations that salt with fixed random bytes.
+    // `argon2id` 与 `scrypt` 的固定盐（十六进制），逐字节使用而不是由 `platformId` 派生的盐，以便与使用固定随机字节作盐的实现互通。
+    #[serde(default)]
+    pub salt: Option<String>,
+    /// Derives a PIN of this many digits instead of a password, for bank cards and phone unlock
+    /// codes: the charsets become [`PIN_DIGITS`] alone and the length this count.
+    // 派生具有该位数的 PIN 而非密码，适用于银行卡与手机解锁码：字符集只剩下 [`PIN_DIGITS`]，长度即为该位数。
+    #[serde(default)]
+    pub pin: Option<usize>,
+    /// Derives passwords of an exact format, such as `Cvcv-9999-XXXX`, instead of drawing
+    /// `length` characters from the charsets (see [`PATTERN_CLASSES`]).
+    // 派生具有精确格式（例如 `Cvcv-9999-XXXX`）的密码，而不是从字符集中抽取 `length` 个字符（参见 [`PATTERN_CLASSES`]）。
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Treats each grapheme cluster of the charsets (an emoji with its modifiers, a letter with its
+    /// combining marks) as one character, so none is ever split. Presets whose characters are all
+    /// single code points derive the same passwords either way.
+    // 将字符集中的每个字素簇（带修饰符的 emoji、带组合标记的字母）视为一个字符，使其永远不会被拆开。
+    // 所有字符都是单个码位的预设，无论是否开启都派生出相同的密码。
+    #[serde(default)]
+    pub graphemes: bool,
+    /// Normalizes the master password and distinguish key to Unicode NFC before hashing, so "café"
+    /// typed as one precomposed `é` (as on Linux and Windows) or as `e` plus a combining accent (as
+    /// macOS may produce) derives the same password. Off by default, since it changes the passwords
+    /// of inputs that are not already NFC.
+    // 哈希之前将主密码与区分密钥规范化为 Unicode NFC，使以单个预组合 `é` 输入（如 Linux 与 Windows）或以 `e` 加组合重音符输入
+    // （macOS 可能如此）的 "café" 派生出相同的密码。默认关闭，因为它会改变本不是 NFC 的输入的密码。
+    #[serde(rename = "normalizeInputs", default)]
+    pub normalize_inputs: bool,
+    /// The kind of character the password starts with, for sites that reject a leading symbol.
+    // 密码开头字符的种类，适用于拒绝以符号开头的网站。
+    #[serde(rename = "firstChar", default)]
+    pub first_char: Option<CharClass>,
+    /// The kind of character the password ends with. / 密码结尾字符的种类。
+    #[serde(rename = "lastChar", default)]
+    pub last_char: Option<CharClass>,
+}
+
+/// A kind of character that `firstChar` and `lastChar` can require, judged by Unicode
+/// properties. A grapheme cluster of several code points counts as a symbol.
+// `firstChar` 与 `lastChar` 可以要求的字符种类，按 Unicode 属性判断。由多个码位组成的字素簇计为符号。
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CharClass {
+    Letter,
+    Upper,
+    Lower,
+    Digit,
+    /// A letter or a digit. / 字母或数字。
+    Alnum,
+    /// Anything but a letter or a digit. / 字母与数字以外的任何字符。
+    Symbol,
+}
+
+impl CharClass {
+    /// The name of the class as written in preset files. / 类别在预设文件中的名称。
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharClass::Letter => "letter",
+            CharClass::Upper => "upper",
+            CharClass::Lower => "lower",
+            CharClass::Digit => "digit",
+            CharClass::Alnum => "alnum",
+            CharClass::Symbol => "symbol",
+        }
+    }
+
+    /// Whether `c` belongs to the class. / `c` 是否属于该类别。
+    pub fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Letter => c.is_alphabetic(),
+            CharClass::Upper => c.is_uppercase(),
+            CharClass::Lower => c.is_lowercase(),
+            CharClass::Digit => c.is_numeric(),
+            CharClass::Alnum => c.is_alphanumeric(),
+            CharClass::Symbol => !c.is_alphanumeric(),
+        }
+    }
+}
+
+/// Destinations a preset forbids for its derived secrets, so high-value credentials are not
+/// printed or exported by accident. The derived values themselves do not depend on it.
+// 预设禁止其派生秘密送往的目的地，避免高价值凭据被意外打印或导出。派生出的值本身与它无关。
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct OutputPolicy {
+    /// Only copying to the clipboard is allowed. / 只允许复制到剪贴板。
+    #[serde(rename = "clipboardOnly", default)]
+    pub clipboard_only: bool,
+    /// Never print a secret to standard output. / 绝不将秘密打印到标准输出。
+    #[serde(rename = "noStdout", default)]
+    pub no_stdout: bool,
+    /// Never hand a secret to another store: Vault, age-encrypted files or dotenv files.
+    // 绝不将秘密交给其它存储：Vault、age 加密文件或 dotenv 文件。
+    #[serde(rename = "noExport", default)]
+    pub no_export: bool,
+}
+
+/// Where a derived secret is delivered. / 派生秘密被送往的目的地。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
+    Stdout,
+    Clipboard,
+    /// Another store, such as Vault or an encrypted file. / 其它存储，例如 Vault 或加密文件。
+    Export,
+}
+
+impl Destination {
+    /// A short human-readable name. / 简短的可读名称。
+    pub fn name(&self) -> &'static str {
+        match self {
+            Destination::Stdout => "printing to standard output",
+            Destination::Clipboard => "copying to the clipboard",
+            Destination::Export => "exporting",
+        }
+    }
+}
+
+impl OutputPolicy {
+    /// Checks that a secret may be delivered to `destination`. / 检查秘密是否可以送往 `destination`。
+    pub fn check(&self, destination: Destination) -> Result<(), AegixPassError> {
+        let allowed = match destination {
+            Destination::Clipboard => true,
+            Destination::Stdout => !self.clipboard_only && !self.no_stdout,
+            Destination::Export => !self.clipboard_only && !self.no_export,
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(AegixPassError::OutputNotAllowed(destination.name()))
+        }
+    }
+}
+
+/// One charset group: its characters, how many of them every password contains at least and,
+/// optionally, at most, and how often stage D draws from it. A group with the default minimum
+/// of 1 and neither a maximum nor a weight is written (and hashed) as a plain string, so presets
+/// that never use these fields derive exactly what they always did.
+// 一个字符集分组：其字符，每个密码至少（和可选的至多）包含其中多少个字符，以及阶段 D 从中抽取的频率。
+// 最小值为默认的 1 且既没有最大值也没有权重的分组以纯字符串形式书写（及参与哈希），因此从未使用这些字段的预设派生结果与以往完全相同。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Charset {
+    pub chars: String,
+    pub min: usize,
+    /// For sites that cap e.g. the number of symbols. / 用于限制例如符号数量的网站。
+    pub max: Option<usize>,
+    /// Relative weight of the group when stage D fills the remaining length, e.g. 60, 30 and
+    /// 10. Groups without one weigh as many as they have characters, which is what uniform
+    /// sampling over all characters gives them.
+    // 阶段 D 填充剩余长度时该分组的相对权重，例如 60、30 和 10。未设置权重的分组，其权重等于其字符数，
+    // 与在所有字符上均匀抽样时的效果相同。
+    pub weight: Option<u16>,
+}
+
+impl Charset {
+    /// The characters of the group. / 该分组的字符。
+    pub fn as_str(&self) -> &str {
+        &self.chars
+    }
+}
+
+impl From<&str> for Charset {
+    fn from(chars: &str) -> Self {
+        Self { chars: chars.to_string(), min: 1, max: None, weight: None }
+    }
+}
+
+impl From<String> for Charset {
+    fn from(chars: String) -> Self {
+        Self { chars, min: 1, max: None, weight: None }
+    }
+}
+
+/// The two ways a charset group can be written. / 字符集分组的两种写法。
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum CharsetRepr {
+    Plain(String),
+    Structured {
+        chars: String,
+        #[serde(default = "default_min")]
+        min: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        weight: Option<u16>,
+    },
+}
+
+fn default_min() -> usize {
+    1
+}
+
+impl Serialize for Charset {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.min, self.max, self.weight) {
+            (1, None, None) => CharsetRepr::Plain(self.chars.clone()),
+            (min, max, weight) => CharsetRepr::Structured { chars: self.chars.clone(), min, max, weight },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Charset {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (chars, min, max, weight) = match CharsetRepr::deserialize(deserializer)? {
+            CharsetRepr::Plain(chars) => (chars, 1, None, None),
+            CharsetRepr::Structured { chars, min, max, weight } => (chars, min, max, weight),
+        };
+        let chars = expand_charset_alias(chars).map_err(serde::de::Error::custom)?;
+        Ok(Self { chars, min, max, weight })
+    }
+}
+
+/// Replaces a charset alias (see [`CHARSET_ALIASES`]) with its characters, so presets derive
+/// exactly as if the characters were written out. An entry of `@` followed only by lowercase
+/// letters and hyphens is taken for an alias, and an unknown one is an error rather than a
+/// literal charset, so a typo cannot silently change the passwords.
+// 将字符集别名（参见 [`CHARSET_ALIASES`]）替换为其字符，使预设的派生结果与逐个写出字符时完全相同。
+// 由 `@` 加上仅由小写字母与连字符组成的条目被视为别名；未知的别名会报错，而不是被当作字面字符集，因此拼写错误不会悄悄改变密码。
+fn expand_charset_alias(chars: String) -> Result<String, String> {
+    let is_alias = chars.strip_prefix('@').is_some_and(|name| !name.is_empty() && name.bytes().all(|b| b.is_ascii_lowercase() || b == b'-'));
+    if !is_alias {
+        return Ok(chars);
+    }
+    match CHARSET_ALIASES.iter().find(|(alias, _)| *alias == chars) {
+        Some((_, expansion)) => Ok(expansion.to_string()),
+        None => {
+            let known: Vec<&str> = CHARSET_ALIASES.iter().map(|(alias, _)| *alias).collect();
+            Err(format!("unknown charset alias '{}' (known: {})", chars, known.join(", ")))
+        }
+    }
+}
+
+/// Decodes hex digits of either case into bytes, or `None` when `hex` is not valid hex.
+// 将大小写均可的十六进制数字解码为字节；`hex` 不是有效的十六进制时返回 `None`。
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
+
+/// Argon2id cost parameters of a preset. Changing them changes every derived password.
+// 预设的 Argon2id 成本参数。修改它们会改变所有派生密码。
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub struct Argon2Settings {
+    /// Memory cost in KiB. / 内存成本，单位 KiB。
+    #[serde(rename = "memoryKib")]
+    pub memory_kib: u32,
+    /// Number of passes over the memory. / 遍历内存的次数。
+    pub iterations: u32,
+    /// Requires an Argon2 secret (the "pepper" input of RFC 9106, see [`SeedOptions::argon2_secret`]);
+    /// derivations without one are refused rather than silently producing other passwords.
+    // 要求提供 Argon2 密钥（RFC 9106 中的 "pepper" 输入，参见 [`SeedOptions::argon2_secret`]）；
+    // 未提供时拒绝派生，而不是悄悄生成不同的密码。
+    #[serde(default)]
+    pub keyed: bool,
+}
+
+impl Default for Argon2Settings {
+    fn default() -> Self {
+        Self { memory_kib: ARGON2_MEMORY_KIB, iterations: ARGON2_ITERATIONS, keyed: false }
+    }
+}
+
+/// Largest supported password length. Random indices are drawn as `u32`, so every position
+/// must be addressable by one.
+// 支持的最大密码长度。随机索引以 `u32` 抽取，因此每个位置都必须能用它寻址。
+pub const MAX_PASSWORD_LENGTH: usize = u32::MAX as usize;
+/// Largest supported number of characters across all charsets, for the same reason.
+// 出于同样的原因，所有字符集合计支持的最大字符数。
+pub const MAX_CHARSET_CHARS: usize = u32::MAX as usize;
+
+/// Largest `seedBytes` a preset may ask for. / 预设可请求的最大 `seedBytes`。
+pub const MAX_SEED_BYTES: usize = 128;
+
+/// The built-in scrypt parameters, the community's baseline for interactive logins: N = 2^15, r = 8, p = 1.
+// 内置的 scrypt 参数，即 scrypt 社区推荐的“交互式”登录安全基准：N = 2^15，r = 8，p = 1。
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+/// Memory one scrypt run takes with those parameters: 128 · r · N bytes, i.e. 32 MiB.
+// 使用上述参数时一次 scrypt 运行所需的内存：128 · r · N 字节，即 32 MiB。
+pub(crate) const SCRYPT_MEMORY_KIB: u64 = 128 * SCRYPT_R as u64 * (1 << SCRYPT_LOG_N) / 1024;
+
+/// Preset versions this build derives with. Version 2 routes every output through a purpose
+/// subkey (see [`Purpose`]); version 1 uses the master seed directly.
+// 本程序支持派生的预设版本。版本 2 让每种输出都经过对应用途的子密钥（参见 [`Purpose`]）；版本 1 直接使用主种子。
+pub const SUPPORTED_VERSIONS: [u32; 2] = [1, 2];
+/// Characters easily confused with one another, dropped by `excludeAmbiguous`.
+// 容易彼此混淆的字符，由 `excludeAmbiguous` 去除。
+pub const AMBIGUOUS_CHARS: &str = "0Oo1lI|";
+/// The only charset of a PIN preset. / PIN 预设唯一的字符集。
+pub const PIN_DIGITS: &str = "0123456789";
+/// Names a charset entry may use instead of spelling the characters out, e.g. `"@digits"`, and
+/// what each expands to. `@symbols` is the symbol set of `default.json`, `@symbols-safe` that of
+/// the `setup` tiers, which leaves out brackets.
+// 字符集条目可以用来代替逐个写出字符的名称（例如 `"@digits"`）及其展开结果。`@symbols` 是 `default.json` 的符号集，
+// `@symbols-safe` 是 `setup` 档位的符号集，其中不含括号。
+pub const CHARSET_ALIASES: [(&str, &str); 5] = [
+    ("@digits", "0123456789"),
+    ("@lower", "abcdefghijklmnopqrstuvwxyz"),
+    ("@upper", "ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+    ("@symbols", "!@#$%^&*()_+-="),
+    ("@symbols-safe", "!@#$%^&*_+-="),
+];
+/// Fewest digits a PIN may have. / PIN 最少可包含的位数。
+pub const MIN_PIN_DIGITS: usize = 4;
+/// Most digits a PIN may have. / PIN 最多可包含的位数。
+pub const MAX_PIN_DIGITS: usize = 12;
+
+/// Prefix of the hash input; the preset version follows it. / 哈希输入的前缀，其后紧跟预设版本。
+pub const HASH_INPUT_PREFIX: &str = "AegixPass_V";
+/// HKDF `info` of the stage C seed bytes when `seedBytes` is not 32. / `seedBytes` 不为 32 时阶段 C 种子字节的 HKDF `info`。
+pub const SEED_INFO: &str = "AegixPass_Seed";
+/// HKDF `info` prefix of purpose subkeys, followed by [`Purpose::name`]. / 用途子密钥的 HKDF `info` 前缀，其后为 [`Purpose::name`]。
+pub const SUBKEY_INFO: &str = "AegixPass_Subkey:";
+/// HKDF `info` prefix of labelled outputs, followed by the label. / 带标签输出的 HKDF `info` 前缀，其后为标签。
+pub const OUTPUT_INFO: &str = "AegixPass_Output:";
+/// HKDF `info` prefix of labelled key material, followed by the label. / 带标签密钥材料的 HKDF `info` 前缀，其后为标签。
+pub const KEY_INFO: &str = "AegixPass_Key:";
+/// HKDF `info` prefix of the username, answer and recovery code keys of version 1 presets,
+/// followed by the label. Version 1 has no purpose subkeys, so these keys would otherwise share
+/// the master seed and `info` of caller-chosen key labels.
+// 版本 1 预设的用户名、答案与恢复码密钥的 HKDF `info` 前缀，其后为标签。版本 1 没有用途子密钥，
+// 否则这些密钥会与调用者自选标签的密钥共用主种子与 `info`。
+pub const PURPOSE_KEY_INFO: &str = "AegixPass_PurposeKey:";
+/// Labels of keys AegixPass derives itself, which [`aegixpass_derive_key`] refuses along with any
+/// label starting with [`ANSWER_LABEL_PREFIX`], so a caller-chosen label never reproduces one.
+// AegixPass 自己派生的密钥所用的标签。[`aegixpass_derive_key`] 拒绝这些标签以及任何以 [`ANSWER_LABEL_PREFIX`]
+// 开头的标签，使调用者自选的标签永远不会重现这些密钥。
+pub const RESERVED_KEY_LABELS: [&str; 9] = [
+    "totp",
+    "passphrase",
+    "username",
+    "recovery",
+    "store",
+    "x509-ed25519",
+    "openpgp-ed25519",
+    "openpgp-cv25519",
+    "verifier-salt",
+];
+/// Label prefix of security question answers, followed by the normalized question.
+// 安全问题答案的标签前缀，其后为规范化后的问题。
+pub const ANSWER_LABEL_PREFIX: &str = "answer:";
+/// HMAC message prefix that mixes a keyfile into the master seed. / 将密钥文件混入主种子的 HMAC 消息前缀。
+pub const KEYFILE_TAG: &str = "AegixPass_Keyfile:";
+/// Domain tag prefixed to the `platformId` before it salts a slow hash, for presets that set
+/// `saltDomainTag`, so the salt never equals SHA-256 of the bare identifier other tools may use.
+// 设置了 `saltDomainTag` 的预设在以 `platformId` 作为慢哈希的盐之前为其添加的域标签，
+// 使盐永远不会等于其它工具可能使用的裸标识符的 SHA-256。
+pub const SALT_DOMAIN_TAG: &str = "AegixPass-salt-v2";
+/// The shortest explicit `salt`, in bytes (Argon2's minimum). / 显式 `salt` 的最短长度，单位字节（Argon2 的下限）。
+pub const MIN_SALT_BYTES: usize = 8;
+/// The longest explicit `salt`, in bytes. / 显式 `salt` 的最大长度，单位字节。
+pub const MAX_SALT_BYTES: usize = 64;
+
+/// Argon2id memory cost in KiB (19 MiB). / Argon2id 的内存成本，单位 KiB (19 MiB)。
+pub const ARGON2_MEMORY_KIB: u32 = 19456;
+/// Argon2id iteration count. / Argon2id 的迭代次数。
+pub const ARGON2_ITERATIONS: u32 = 2;
+
+impl Preset {
+    /// Parses a preset from JSON and checks it against the default [`PresetLimits`].
+    // 从 JSON 解析预设，并按默认的 [`PresetLimits`] 进行检查。
+    pub fn from_json(json: &str) -> Result<Self, AegixPassError> {
+        Self::from_json_with_limits(json, &PresetLimits::default())
+    }
+
+    /// Parses a preset from JSON and checks it against the given limits.
+    // 从 JSON 解析预设，并按给定的限制进行检查。
+    pub fn from_json_with_limits(json: &str, limits: &PresetLimits) -> Result<Self, AegixPassError> {
+        let preset: Preset = serde_json::from_str(json)
+            .map_err(|e| AegixPassError::PresetParseError(e.to_string()))?;
+        limits.check(&preset)?;
+        Ok(preset)
+    }
+
+    /// Returns a warning when the `platformId` is not in the registry of known-good values.
+    /// Every derived password depends on it, so a quietly altered value changes them all.
+    // 当 `platformId` 不在可信值注册表中时返回警告。所有派生密码都依赖于它，因此被悄悄修改的值会改变全部密码。
+    pub fn platform_id_warning(&self) -> Option<String> {
+        if registry::is_known_platform_id(&self.platform_id) {
+            return None;
+        }
+        Some(if self.official {
+            format!(
+                "preset '{}' is marked official but uses the unrecognized platformId '{}'; it may have been tampered with, and every password it derives will differ from the official preset's.",
+                self.name, self.platform_id
+            )
+        } else {
+            format!(
+                "platformId '{}' is not a recognized value; passwords derived with it differ from those of the official presets.",
+                self.platform_id
+            )
+        })
+    }
+
+    /// The preset derivation actually runs with: with `excludeAmbiguous` or `excludeChars`, the
+    /// charsets without [`AMBIGUOUS_CHARS`] or those characters and the field cleared, so both
+    /// spellings derive the same passwords.
+    /// With `noAdjacentGroup`, every group's `max` lowered to half the length, rounded up, so
+    /// an arrangement without adjacent characters of one group always exists.
+    // 派生实际使用的预设：设置 `excludeAmbiguous` 或 `excludeChars` 时，为去除了 [`AMBIGUOUS_CHARS`] 或这些字符的字符集且清除了该字段，
+    // 因此两种写法派生出相同的密码。设置 `noAdjacentGroup` 时，每个分组的 `max` 降低到长度的一半（向上取整），
+    // 从而总存在没有同组字符相邻的排列。
+    pub fn resolved(&self) -> std::borrow::Cow<'_, Preset> {
+        if !self.exclude_ambiguous && self.exclude_chars.is_none() && !self.no_adjacent_group && self.pin.is_none() {
+            return std::borrow::Cow::Borrowed(self);
+        }
+        let mut preset = self.clone();
+        // A PIN keeps only the rules that make sense for digits. / PIN 只保留对数字有意义的规则。
+        if let Some(digits) = preset.pin {
+            preset.charsets = vec![PIN_DIGITS.into()];
+            preset.length = digits;
+            preset.exclude_ambiguous = false;
+            preset.exclude_chars = None;
+            preset.no_repeat = false;
+            preset.no_adjacent_group = false;
+            preset.first_char = None;
+            preset.last_char = None;
+            preset.post_processors.clear();
+        }
+        if preset.exclude_ambiguous || preset.exclude_chars.is_some() {
+            let mut excluded = preset.exclude_chars.take().unwrap_or_default();
+            if preset.exclude_ambiguous {
+                excluded.push_str(AMBIGUOUS_CHARS);
+            }
+            preset.exclude_ambiguous = false;
+            for charset in &mut preset.charsets {
+                if preset.graphemes {
+                    // Only clusters that are an excluded character on their own go. / 只删去本身就是被排除字符的字素簇。
+                    charset.chars = charset.chars.graphemes(true).filter(|g| !(g.chars().count() == 1 && excluded.contains(*g))).collect();
+                } else {
+                    charset.chars.retain(|c| !excluded.contains(c));
+                }
+            }
+        }
+        // A single group is left alone, so validation can name the actual problem.
+        // 单个分组保持不变，使验证能够指出真正的问题。
+        if preset.no_adjacent_group && preset.charsets.len() > 1 {
+            let half = preset.length.div_ceil(2);
+            for charset in &mut preset.charsets {
+                charset.max = Some(charset.max.map_or(half, |max| max.min(half)));
+            }
+        }
+        std::borrow::Cow::Owned(preset)
+    }
+
+    /// Returns a warning when the preset was deprecated before the given Unix time (UTC).
+    // 当预设在给定的 Unix 时间（UTC）之前已被弃用时返回警告。
+    pub fn deprecation_warning_at(&self, unix_secs: u64) -> Option<String> {
+        let deprecated_after = self.deprecated_after.as_deref().filter(|&date| crate::rotation::date_at(unix_secs).as_str() > date)?;
+        let replacement = match &self.replaced_by {
+            Some(replacement) => format!("switch to '{}'", replacement),
+            None => "ask its maintainer for a replacement".to_string(),
+        };
+        Some(format!(
+            "preset '{}' was deprecated after {}; {} (the new preset derives different passwords, so change them site by site).",
+            self.name, deprecated_after, replacement
+        ))
+    }
+
+    /// [`Preset::deprecation_warning_at`] for the current time. / 针对当前时间的 [`Preset::deprecation_warning_at`]。
+    pub fn deprecation_warning(&self) -> Option<String> {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        self.deprecation_warning_at(now)
+    }
+
+    /// Checks the length and charsets without deriving anything, so a broken preset can be
+    /// reported as soon as it is loaded.
+    // 在不进行任何派生的情况下检查长度与字符集，以便在加载时就报告有问题的预设。
+    pub fn validate(&self) -> Result<(), AegixPassError> {
+        resolve_checked(self)?;
+        if let Some(date) = self.deprecated_after.as_deref().filter(|date| !crate::rotation::is_valid_date(date)) {
+            return Err(AegixPassError::InvalidDeprecationDate(date.to_string()));
+        }
+        if let Some(passphrase) = &self.passphrase {
+            passphrase.validate()?;
+        }
+        self.salt_bytes()?;
+        Ok(())
+    }
+
+    /// A human-readable identifier of the algorithms this preset derives with, e.g.
+    /// `v1/argon2id(m=19456,t=2,p=1)/chaCha20/fisherYates`.
+    // 该预设派生时所用算法的可读标识，例如 `v1/argon2id(m=19456,t=2,p=1)/chaCha20/fisherYates`。
+    pub fn profile_id(&self) -> String {
+        let hash = match self.hash_algorithm {
+            HashAlgorithm::Argon2id => {
+                let settings = self.argon2.unwrap_or_default();
+                let keyed = if settings.keyed { ",keyed" } else { "" };
+                format!("argon2id(m={},t={},p=1{})", settings.memory_kib, settings.iterations, keyed)
+            }
+            ref other => other.name().to_string(),
+        };
+        let mut id = format!("v{}/{}/{}/{}", self.version, hash, self.rng_algorithm.name(), self.shuffle_algorithm.name());
+        if self.seed_len() != 32 {
+            id += &format!("/seed={}", self.seed_len());
+        }
+        if let Some(rotation) = self.rotation {
+            id += &format!("/rotation={}", rotation.name());
+        }
+        if !self.post_processors.is_empty() {
+            id += &format!("/post={}", self.post_processors.join(","));
+        }
+        id
+    }
+
+    /// A fingerprint of every preset field that affects derived passwords (the name and
+    /// `official` flag do not), as `sha256:` followed by 32 hex digits. Two presets with the
+    /// same fingerprint derive the same passwords.
+    // 对所有影响派生密码的预设字段（名称与 `official` 标志除外）计算的指纹，格式为 `sha256:` 加 32 位十六进制数。
+    // 指纹相同的两个预设会派生出相同的密码。
+    pub fn fingerprint(&self) -> String {
+        let resolved = self.resolved();
+        // A JSON array keeps the field order fixed. / JSON 数组使字段顺序保持固定。
+        let mut canonical = serde_json::json!([
+            self.version,
+            self.hash_algorithm.name(),
+            // Argon2 settings only count where Argon2 is used, and defaults equal omitted ones.
+            // Argon2 参数仅在使用 Argon2 时计入，且默认值与省略时等同。
+            self.argon2_memory_kib().map(|_| {
+                let settings = self.argon2.unwrap_or_default();
+                // A keyed preset adds a third entry, leaving unkeyed fingerprints unchanged.
+                // 带密钥的预设会添加第三项，不带密钥的指纹保持不变。
+                let mut fields = vec![settings.memory_kib, settings.iterations];
+                if settings.keyed {
+                    fields.push(1);
+                }
+                fields
+            }),
+            self.rng_algorithm.name(),
+            self.shuffle_algorithm.name(),
+            self.length,
+            self.platform_id,
+            resolved.charsets,
+            self.post_processors,
+        ]);
+        // Appended only when expanded, so fingerprints pinned before `seedBytes` existed stay valid.
+        // 仅在扩展时追加，使 `seedBytes` 出现之前固定的指纹依然有效。
+        if let (serde_json::Value::Array(fields), true) = (&mut canonical, self.seed_len() != 32) {
+            fields.push(self.seed_len().into());
+        }
+        if let (serde_json::Value::Array(fields), Some(rotation)) = (&mut canonical, self.rotation) {
+            fields.push(rotation.name().into());
+        }
+        if let (serde_json::Value::Array(fields), true) = (&mut canonical, self.no_repeat) {
+            fields.push("noRepeat".into());
+        }
+        if let (serde_json::Value::Array(fields), true) = (&mut canonical, self.no_sequential) {
+            fields.push("noSequential".into());
+        }
+        if let (serde_json::Value::Array(fields), true) = (&mut canonical, self.no_adjacent_group) {
+            fields.push("noAdjacentGroup".into());
+        }
+        if let (serde_json::Value::Array(fields), true) = (&mut canonical, self.salt_domain_tag) {
+            fields.push(SALT_DOMAIN_TAG.into());
+        }
+        // Hex case does not change the salt, so it does not change the fingerprint either.
+        // 十六进制的大小写不改变盐，因此也不改变指纹。
+        if let (serde_json::Value::Array(fields), Some(salt)) = (&mut canonical, &self.salt) {
+            fields.push(serde_json::json!(["salt", salt.to_ascii_lowercase()]));
+        }
+        if let (serde_json::Value::Array(fields), Some(digits)) = (&mut canonical, self.pin) {
+            fields.push(serde_json::json!(["pin", digits]));
+        }
+        if let (serde_json::Value::Array(fields), Some(pattern)) = (&mut canonical, &self.pattern) {
+            fields.push(serde_json::json!(["pattern", pattern]));
+        }
+        if let (serde_json::Value::Array(fields), true) = (&mut canonical, self.graphemes) {
+            fields.push(serde_json::json!(["graphemes", true]));
+        }
+        if let (serde_json::Value::Array(fields), true) = (&mut canonical, self.normalize_inputs) {
+            fields.push(serde_json::json!(["normalizeInputs", true]));
+        }
+        if let (serde_json::Value::Array(fields), Some(class)) = (&mut canonical, resolved.first_char) {
+            fields.push(serde_json::json!(["firstChar", class.name()]));
+        }
+        if let (serde_json::Value::Array(fields), Some(class)) = (&mut canonical, resolved.last_char) {
+            fields.push(serde_json::json!(["lastChar", class.name()]));
+        }
+        // The wordlist counts by its checksum, wherever the file lives. / 词表以其校验和计入，与文件所在位置无关。
+        if let (serde_json::Value::Array(fields), Some(passphrase)) = (&mut canonical, &self.passphrase) {
+            fields.push(serde_json::json!([passphrase.words, passphrase.separator, passphrase.wordlist_sha256]));
+        }
+        // A loosened policy changes the fingerprint, so pinned presets cannot be relaxed silently.
+        // 放宽策略会改变指纹，因此固定了指纹的预设无法被悄悄放宽。
+        if let (serde_json::Value::Array(fields), true) = (&mut canonical, self.output_policy != OutputPolicy::default()) {
+            fields.push(serde_json::json!(self.output_policy));
+        }
+        let digest = Sha256::digest(canonical.to_string().as_bytes());
+        let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("sha256:{}", hex)
+    }
+
+    /// The text a slow hash derives its salt from: the `platformId`, tagged with
+    /// [`SALT_DOMAIN_TAG`] when `saltDomainTag` is set.
+    // 慢哈希派生盐所用的文本：即 `platformId`，设置了 `saltDomainTag` 时再加上 [`SALT_DOMAIN_TAG`] 标签。
+    pub fn salt_source(&self) -> std::borrow::Cow<'_, str> {
+        if self.salt_domain_tag {
+            format!("{}:{}", SALT_DOMAIN_TAG, self.platform_id).into()
+        } else {
+            self.platform_id.as_str().into()
+        }
+    }
+
+    /// The explicit `salt` as bytes, or `None` when slow hashes derive it from `platformId`.
+    /// Fails unless it is valid hex of [`MIN_SALT_BYTES`] to [`MAX_SALT_BYTES`] bytes on an
+    /// `argon2id` or `scrypt` preset without `saltDomainTag`.
+    // 显式 `salt` 的字节；慢哈希由 `platformId` 派生盐时为 `None`。只有当它是 [`MIN_SALT_BYTES`] 到
+    // [`MAX_SALT_BYTES`] 字节的有效十六进制、且预设使用 `argon2id` 或 `scrypt` 并未设置 `saltDomainTag` 时才成功。
+    pub fn salt_bytes(&self) -> Result<Option<Vec<u8>>, AegixPassError> {
+        let Some(salt) = &self.salt else {
+            return Ok(None);
+        };
+        let invalid = |reason: String| Err(AegixPassError::InvalidSalt(reason));
+        if !matches!(self.hash_algorithm, HashAlgorithm::Argon2id | HashAlgorithm::Scrypt) {
+            return invalid(format!("'{}' takes no salt; only argon2id and scrypt do", self.hash_algorithm.name()));
+        }
+        if self.salt_domain_tag {
+            return invalid("saltDomainTag only applies to the salt derived from platformId".to_string());
+        }
+        let Some(bytes) = decode_hex(salt) else {
+            return invalid("it must be written as hex digits".to_string());
+        };
+        if !(MIN_SALT_BYTES..=MAX_SALT_BYTES).contains(&bytes.len()) {
+            return invalid(format!("it is {} bytes long, not {} to {}", bytes.len(), MIN_SALT_BYTES, MAX_SALT_BYTES));
+        }
+        Ok(Some(bytes))
+    }
+
+    /// The number of seed bytes stage C draws from (32 unless `seedBytes` says otherwise).
+    // 阶段 C 所使用的种子字节数（除非 `seedBytes` 另有指定，否则为 32）。
+    pub fn seed_len(&self) -> usize {
+        self.seed_bytes.unwrap_or(32)
+    }
+
+    /// The entropy of one derived password in bits, as [`estimate_entropy`] works it out, or 0
+    /// for an invalid preset.
+    // 单个派生密码的熵（比特），与 [`estimate_entropy`] 的结果相同；无效预设为 0。
+    pub fn entropy_bits(&self) -> f64 {
+        estimate_entropy(self).unwrap_or(0.0)
+    }
+
+    /// The Argon2 memory cost this preset will use, if it uses Argon2 at all.
+    // 该预设将使用的 Argon2 内存成本（若使用 Argon2）。
+    pub fn argon2_memory_kib(&self) -> Option<u32> {
+        match self.hash_algorithm {
+            HashAlgorithm::Argon2id => Some(self.argon2.unwrap_or_default().memory_kib),
+            _ => None,
+        }
+    }
+}
+
+/// Resource caps applied when a preset is loaded, so a malicious shared preset cannot make
+/// the generator allocate gigabytes or run for minutes. The defaults leave plenty of room
+/// for legitimate presets; callers handling trusted input can raise them.
+// 加载预设时应用的资源上限，防止恶意分享的预设让生成器分配数 GB 内存或运行数分钟。
+// 默认值为正常预设留有充足余量；处理可信输入的调用者可以调高这些上限。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PresetLimits {
+    /// Maximum password length. / 最大密码长度。
+    pub max_length: usize,
+    /// Maximum number of characters across all charsets. / 所有字符集合计的最大字符数。
+    pub max_charset_chars: usize,
+    /// Maximum Argon2 memory cost in KiB. / 最大 Argon2 内存成本，单位 KiB。
+    pub max_argon2_memory_kib: u32,
+    /// Maximum Argon2 iteration count. / 最大 Argon2 迭代次数。
+    pub max_argon2_iterations: u32,
+}
+
+impl Default for PresetLimits {
+    fn default() -> Self {
+        Self {
+            max_length: 4096,
+            max_charset_chars: 65536,
+            max_argon2_memory_kib: 1024 * 1024, // 1 GiB
+            max_argon2_iterations: 64,
+        }
+    }
+}
+
+impl PresetLimits {
+    /// Checks a parsed preset against these limits.
+    // 按这些限制检查已解析的预设。
+    pub fn check(&self, preset: &Preset) -> Result<(), AegixPassError> {
+        let exceeded = |field, value, limit| Err(AegixPassError::PresetLimitExceeded { field, value, limit });
+        if preset.length > self.max_length {
+            return exceeded("length", preset.length, self.max_length);
+        }
+        let charset_chars = preset
+            .charsets
+            .iter()
+            .fold(0usize, |total, charset| total.saturating_add(charset.chars.chars().count()));
+        if charset_chars > self.max_charset_chars {
+            return exceeded("charset size", charset_chars, self.max_charset_chars);
+        }
+        if let Some(memory) = preset.argon2_memory_kib()
+            && memory > self.max_argon2_memory_kib
+        {
+            return exceeded("Argon2 memory (KiB)", memory as usize, self.max_argon2_memory_kib as usize);
+        }
+        if let Some(settings) = preset.argon2
+            && settings.iterations > self.max_argon2_iterations
+        {
+            return exceeded("Argon2 iterations", settings.iterations as usize, self.max_argon2_iterations as usize);
+        }
+        Ok(())
+    }
+}
+
+/// Optional inputs mixed into the master seed besides the master password and distinguish key.
+/// The default adds nothing, so the plain functions and the `_with` functions agree.
+// 除主密码与区分密钥外，可选地混入主种子的输入。默认值不添加任何内容，因此普通函数与 `_with` 函数的结果一致。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SeedOptions<'a> {
+    /// Contents of a keyfile, a second factor that must be present to derive anything.
+    // 密钥文件的内容，作为第二因素，缺少它就无法派生任何结果。
+    pub key_material: Option<&'a [u8]>,
+    /// Argon2's secret input, for presets whose Argon2 settings are `keyed`. Unlike the key
+    /// material it is bound inside the (slow) Argon2 computation itself.
+    // Argon2 的密钥输入，用于 Argon2 参数为 `keyed` 的预设。与密钥材料不同，它被绑定在（慢速的）Argon2 计算内部。
+    pub argon2_secret: Option<&'a [u8]>,
+    /// A device secret appended to the hash input (see [`crate::pepper`]), so the master
+    /// password alone is not enough on another machine.
+    // 追加到哈希输入中的设备秘密（参见 [`crate::pepper`]），使得在其它机器上仅凭主密码无法派生。
+    pub pepper: Option<&'a [u8]>,
+    /// Rotation counter: raising it yields a new password for the same site and master
+    /// password. 0, the default, leaves the input unchanged.
+    // 轮换计数器：调高它即可为同一站点和主密码得到新密码。默认值 0 不改变输入。
+    pub counter: u32,
+    /// Rotation epoch to derive for, e.g. `2026-Q4`, for presets with a `rotation` policy.
+    /// When absent, the epoch of the current date is used.
+    // 要派生的轮换周期，例如 `2026-Q4`，用于设置了 `rotation` 策略的预设。省略时使用当前日期所在的周期。
+    pub epoch: Option<&'a str>,
+}
+
+/// What a piece of derived material is for. From preset version 2 on, the master seed is never
+/// used directly: each purpose gets its own subkey, expanded with HKDF-SHA256 using
+/// `AegixPass_Subkey:{purpose}` as `info`, so one KDF run serves every output with clean
+/// domain separation.
+// 派生材料的用途。从预设版本 2 起，主种子不再被直接使用：每种用途都有自己的子密钥，
+// 以 `AegixPass_Subkey:{purpose}` 作为 `info` 通过 HKDF-SHA256 扩展而来，从而一次 KDF 运行即可为所有输出服务，且域相互隔离。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Purpose {
+    Password,
+    Username,
+    Totp,
+    Ssh,
+    /// Labelled key material from [`aegixpass_derive_key`]. / 来自 [`aegixpass_derive_key`] 的带标签密钥材料。
+    Key,
+    /// Wordlist passphrases from [`crate::passphrase`]. / 来自 [`crate::passphrase`] 的词表口令短语。
+    Passphrase,
+    /// Security question answers from [`crate::answer`]. / 来自 [`crate::answer`] 的安全问题答案。
+    Answer,
+    /// Recovery codes from [`crate::recovery`]. / 来自 [`crate::recovery`] 的恢复码。
+    Recovery,
+}
+
+impl Purpose {
+    /// The label used in the HKDF `info`. / HKDF `info` 中使用的标签。
+    pub fn name(&self) -> &'static str {
+        match self {
+            Purpose::Password => "password",
+            Purpose::Username => "username",
+            Purpose::Totp => "totp",
+            Purpose::Ssh => "ssh",
+            Purpose::Key => "key",
+            Purpose::Passphrase => "passphrase",
+            Purpose::Answer => "answer",
+            Purpose::Recovery => "recovery",
+        }
+    }
 }
 
 // --- 2. Core Password Generation Function ---
@@ -100,79 +1245,1374 @@ pub fn aegixpass_generator(
     password_source: &str,
     distinguish_key: &str,
     preset: &Preset,
+) -> Result<String, AegixPassError> {
+    aegixpass_generator_with(password_source, distinguish_key, preset, &SeedOptions::default())
+}
+
+/// [`aegixpass_generator`] with extra seed inputs such as a keyfile.
+// 带有额外种子输入（例如密钥文件）的 [`aegixpass_generator`]。
+pub fn aegixpass_generator_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+) -> Result<String, AegixPassError> {
+    generate_password(password_source, distinguish_key, preset, options, Host::Process)
+}
+
+/// What a derivation may touch besides its inputs. / 派生过程除输入之外还可以访问的内容。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Host {
+    /// Ordinary use: the KDF cache, if enabled, and the available-memory probe.
+    // 普通使用：KDF 缓存（若已启用）以及可用内存探测。
+    Process,
+    /// [`crate::sandbox`]: neither the filesystem nor anything else outside the call.
+    // [`crate::sandbox`]：既不访问文件系统，也不访问调用之外的任何内容。
+    Sandbox,
+}
+
+/// [`aegixpass_generator_with`] on the given host. / 在给定宿主上运行的 [`aegixpass_generator_with`]。
+pub(crate) fn generate_password(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+    host: Host,
 ) -> Result<String, AegixPassError> {
     // --- (Stage A) Input Validation (Partial) ---
     // --- (阶段 A) 输入验证 (部分) ---
     if password_source.is_empty() || distinguish_key.is_empty() {
         return Err(AegixPassError::InputEmpty);
     }
-    if preset.length < preset.charsets.len() {
-        return Err(AegixPassError::LengthTooShort(
-            preset.length,
-            preset.charsets.len(),
-        ));
-    }
-    if preset.charsets.iter().any(|cs| cs.is_empty()) {
-        return Err(AegixPassError::EmptyCharset);
-    }
+    let (resolved, combined_len) = resolve_checked(preset)?;
+    let preset = &*resolved;
 
     // --- (Stage B) Generate the Master Seed ---
     // --- (阶段 B) 生成核心种子 ---
-    let master_seed = generate_master_seed(password_source, distinguish_key, preset)?;
+    let master_seed = generate_master_seed(password_source, distinguish_key, preset, options, host)?;
+    let seed = purpose_root(&master_seed, preset, Purpose::Password)?;
+
+    password_from_seed(seed, preset, combined_len)
+}
+
+/// Generates one password per label from a single master-seed derivation, e.g. a login
+/// password, a database password and an API token for the same service. The (possibly slow)
+/// hash runs once; each label's seed is expanded from the master seed with HKDF-SHA256 using
+/// `AegixPass_Output:{label}` as `info`, so the outputs are independent of each other and of
+/// the plain password for the same distinguish key.
+// 只派生一次主种子，为每个标签各生成一个密码，例如同一服务的登录密码、数据库密码和 API 令牌。
+// （可能很慢的）哈希只运行一次；每个标签的种子以 `AegixPass_Output:{label}` 作为 `info`，
+// 通过 HKDF-SHA256 从主种子扩展而来，因此各输出之间、以及与同一区分密钥的普通密码之间都相互独立。
+pub fn aegixpass_generator_multi(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    labels: &[&str],
+) -> Result<Vec<String>, AegixPassError> {
+    aegixpass_generator_multi_with(password_source, distinguish_key, preset, labels, &SeedOptions::default())
+}
 
+/// [`aegixpass_generator_multi`] with extra seed inputs such as a keyfile.
+// 带有额外种子输入（例如密钥文件）的 [`aegixpass_generator_multi`]。
+pub fn aegixpass_generator_multi_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    labels: &[&str],
+    options: &SeedOptions,
+) -> Result<Vec<String>, AegixPassError> {
+    if password_source.is_empty() || distinguish_key.is_empty() {
+        return Err(AegixPassError::InputEmpty);
+    }
+    let (resolved, combined_len) = resolve_checked(preset)?;
+    let preset = &*resolved;
+    let master_seed = generate_master_seed(password_source, distinguish_key, preset, options, Host::Process)?;
+    let root = purpose_root(&master_seed, preset, Purpose::Password)?;
+    let hkdf = Hkdf::<Sha256>::from_prk(&root).map_err(|_| AegixPassError::InvalidKeyLength(root.len(), 32))?;
+    labels
+        .iter()
+        .map(|label| {
+            let mut seed = [0u8; 32];
+            hkdf.expand(format!("{}{}", OUTPUT_INFO, label).as_bytes(), &mut seed)
+                .map_err(|_| AegixPassError::InvalidKeyLength(seed.len(), 32))?;
+            password_from_seed(seed, preset, combined_len)
+        })
+        .collect()
+}
+
+/// Generates the password of every distinguish key in `keys`, as [`aegixpass_generator`] would,
+/// e.g. for a whole site list. The KDF input contains the distinguish key, so every key costs a
+/// full slow hash, exactly as one call per key would: the speed-up comes only from running the
+/// hashes on as many threads as the CPU count and the memory each one needs allow. The only
+/// work shared by the batch is checking the preset and seed options once. The outer error is
+/// one that would fail every key; a key's own error, such as an empty key, is returned in its place.
+// 为 `keys` 中的每个区分密钥生成密码，与 [`aegixpass_generator`] 的结果相同，例如用于整个站点列表。KDF 输入包含区分密钥，
+// 因此每个密钥都要完整运行一次慢哈希，与逐个调用完全相同：加速只来自于在 CPU 数量与每个哈希所需内存允许的尽可能多的线程上
+// 运行这些哈希。整批共享的工作只有对预设与种子选项的一次检查。外层错误会使所有密钥失败；单个密钥自身的错误（例如空密钥）
+// 则在其位置返回。
+pub fn aegixpass_generate_batch(
+    password_source: &str,
+    keys: &[&str],
+    preset: &Preset,
+) -> Result<Vec<Result<String, AegixPassError>>, AegixPassError> {
+    aegixpass_generate_batch_with(password_source, keys, preset, &SeedOptions::default())
+}
+
+/// [`aegixpass_generate_batch`] with extra seed inputs such as a keyfile.
+// 带有额外种子输入（例如密钥文件）的 [`aegixpass_generate_batch`]。
+pub fn aegixpass_generate_batch_with(
+    password_source: &str,
+    keys: &[&str],
+    preset: &Preset,
+    options: &SeedOptions,
+) -> Result<Vec<Result<String, AegixPassError>>, AegixPassError> {
+    if password_source.is_empty() {
+        return Err(AegixPassError::InputEmpty);
+    }
+    let (resolved, combined_len) = resolve_checked(preset)?;
+    let preset = &*resolved;
+    check_hash_inputs(preset, options, Host::Process)?;
+    let generate = |distinguish_key: &str| {
+        if distinguish_key.is_empty() {
+            return Err(AegixPassError::InputEmpty);
+        }
+        let input_data = master_input(password_source, distinguish_key, preset, options)?;
+        let master_seed = mix_key_material(hash_checked_input(input_data, preset, options, Host::Process)?, options)?;
+        password_from_seed(purpose_root(&master_seed, preset, Purpose::Password)?, preset, combined_len)
+    };
+
+    // Each thread takes every n-th key and tags its results with their index, so they can be sorted
+    // back in order. A panicking worker fails the whole batch with its panic message.
+    // 每个线程处理每隔 n 个的密钥，并为结果标上索引，以便按原顺序排序。若有线程发生 panic，整批以其 panic 信息失败。
+    let threads = batch_threads(preset, keys.len(), available_memory_kib());
+    let mut results: Vec<(usize, Result<String, AegixPassError>)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|first| scope.spawn(move || keys.iter().enumerate().skip(first).step_by(threads).map(|(i, key)| (i, generate(key))).collect::<Vec<_>>()))
+            .collect();
+        workers.into_iter().try_fold(Vec::with_capacity(keys.len()), |mut results, worker| {
+            results.extend(worker.join().map_err(panic_error)?);
+            Ok::<_, AegixPassError>(results)
+        })
+    })?;
+    results.sort_unstable_by_key(|(i, _)| *i);
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+/// Turns a worker thread's panic into an error carrying its message.
+// 将工作线程的 panic 转换为携带其信息的错误。
+fn panic_error(panic: Box<dyn std::any::Any + Send>) -> AegixPassError {
+    let message = match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => panic.downcast_ref::<&str>().map_or("no message", |message| message).to_string(),
+    };
+    AegixPassError::BatchWorkerPanicked(message)
+}
+
+/// How many threads [`aegixpass_generate_batch`] hashes on: one per CPU, but no more than there
+/// are keys, and for the memory-hard hashes no more than three quarters of the available memory
+/// can hold at once.
+// [`aegixpass_generate_batch`] 进行哈希所用的线程数：每个 CPU 一个，但不超过密钥数，对于内存困难型哈希，
+// 还不超过可用内存的四分之三同时能容纳的数量。
+fn batch_threads(preset: &Preset, keys: usize, available_kib: Option<u64>) -> usize {
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let memory_kib = match preset.hash_algorithm {
+        HashAlgorithm::Scrypt => Some(SCRYPT_MEMORY_KIB),
+        _ => preset.argon2_memory_kib().map(u64::from),
+    };
+    let fitting = match (memory_kib, available_kib) {
+        (Some(memory_kib), Some(available_kib)) => (available_kib * 3 / 4 / memory_kib.max(1)) as usize,
+        _ => cpus,
+    };
+    cpus.min(fitting).min(keys).max(1)
+}
+
+/// Derives one 32-byte subkey per purpose from a single master-seed derivation, e.g. to feed a
+/// password, a TOTP secret and an SSH key from one (possibly slow) KDF run. The subkeys do not
+/// depend on the preset version; version 2 presets derive their own outputs from them.
+// 只派生一次主种子，为每种用途各派生一个 32 字节的子密钥，例如让一次（可能很慢的）KDF 运行同时
+// 提供密码、TOTP 秘密与 SSH 密钥。子密钥与预设版本无关；版本 2 的预设自身的输出也由它们派生。
+pub fn aegixpass_subkeys(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    purposes: &[Purpose],
+    options: &SeedOptions,
+) -> Result<Vec<[u8; 32]>, AegixPassError> {
+    if password_source.is_empty() || distinguish_key.is_empty() {
+        return Err(AegixPassError::InputEmpty);
+    }
+    let master_seed = generate_master_seed(password_source, distinguish_key, &preset.resolved(), options, Host::Process)?;
+    purposes.iter().map(|&purpose| subkey(&master_seed, purpose)).collect()
+}
+
+/// What a derivation would use, as worked out by [`aegixpass_preflight`].
+// 由 [`aegixpass_preflight`] 得出的、一次派生将会使用的内容。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preflight {
+    /// The rotation epoch mixed into the seed, for presets with a `rotation` policy.
+    // 混入种子的轮换周期，用于设置了 `rotation` 策略的预设。
+    pub epoch: Option<String>,
+    /// See [`estimate_entropy`]. / 参见 [`estimate_entropy`]。
+    pub entropy_bits: f64,
+}
+
+/// Runs every check a derivation would run, except the KDF itself: the preset, the seed
+/// options, the algorithms in the registry and the memory Argon2 needs. A pre-flight check for
+/// scripts and for CI over preset repositories; no master password is needed.
+// 运行一次派生会执行的所有检查（KDF 本身除外）：预设、种子选项、注册表中的算法以及 Argon2 所需的内存。
+// 用于脚本的预检以及对预设仓库的 CI 检查；无需主密码。
+pub fn aegixpass_preflight(distinguish_key: &str, preset: &Preset, options: &SeedOptions) -> Result<Preflight, AegixPassError> {
+    if distinguish_key.is_empty() {
+        return Err(AegixPassError::InputEmpty);
+    }
+    let (resolved, _) = resolve_checked(preset)?;
+    let preset = &*resolved;
+    let epoch = rotation_epoch(preset, options)?;
+    check_hash_inputs(preset, options, Host::Process)?;
+    let name = preset.rng_algorithm.name();
+    registry::rng_factory(name).ok_or_else(|| missing_algorithm(name))?;
+    for name in &preset.post_processors {
+        registry::post_processor(name).ok_or_else(|| missing_algorithm(name))?;
+    }
+    Ok(Preflight { epoch, entropy_bits: estimate_entropy(preset)? })
+}
+
+/// Estimates the entropy of one derived password, in bits, for comparing presets: log2 of the
+/// number of passwords the preset can produce, as if each were equally likely. It starts from
+/// `length` characters drawn from the distinct characters of all charsets (without repeats for
+/// `noRepeat`), then removes the share that breaks a group's `min` or `max`, taking the groups as
+/// independent. Stage C's guaranteed characters therefore cost a little entropy rather than
+/// adding any. With weights, each character instead counts the Shannon entropy of stage D's
+/// weighted draw, and a group's share is that of its weight. The estimate sees the charsets after
+/// `pin`, `excludeChars` and `noAdjacentGroup` rewrote them; layout rules are not counted
+/// otherwise, and patterns sum the bits of their placeholders. Fails as [`Preset::validate`] does
+/// for an invalid preset.
+// 估计单个派生密码的熵（比特），用于比较预设：即预设所能产生的密码数量的 log2（假设每个密码等可能出现）。
+// 先按从所有字符集的不同字符中抽取 `length` 个字符计算（`noRepeat` 时不重复），再将各分组视为相互独立，
+// 扣除违反某个分组 `min` 或 `max` 的部分。因此阶段 C 保证出现的字符会略微减少熵，而不是增加熵。
+// 有权重时，每个字符改为计入阶段 D 加权抽取的香农熵，分组所占的份额也改为其权重的份额。估计基于经 `pin`、
+// `excludeChars` 与 `noAdjacentGroup` 改写后的字符集；除此之外不计入布局规则，模式则对各占位符的比特数求和。
+// 预设无效时与 [`Preset::validate`] 一样报错。
+pub fn estimate_entropy(preset: &Preset) -> Result<f64, AegixPassError> {
+    let (resolved, _) = resolve_checked(preset)?;
+    // A grapheme cluster counts once. / 一个字素簇计为一个字符。
+    let (mapped, _) = grapheme_placeholders(&resolved)?;
+    let distinct = |chars: &str| chars.chars().collect::<std::collections::BTreeSet<_>>().len();
+    if let Some(pattern) = mapped.pattern.as_deref() {
+        return Ok(parse_pattern(pattern, &mapped)?
+            .iter()
+            .map(|token| match token {
+                PatternToken::Class(chars) => (chars.iter().collect::<std::collections::BTreeSet<_>>().len() as f64).log2(),
+                PatternToken::Literal(_) => 0.0,
+            })
+            .sum());
+    }
+    let alphabet = distinct(&mapped.charsets.iter().map(Charset::as_str).collect::<String>()) as f64;
+    let weighted = mapped.charsets.iter().any(|group| group.weight.is_some());
+    // Each group's share of the characters: by weight as stage D draws them, else by size.
+    // 每个分组在字符中所占的份额：有权重时按阶段 D 抽取所用的权重，否则按大小。
+    let shares: Vec<f64> = if weighted {
+        let weights: Vec<f64> =
+            mapped.charsets.iter().map(|group| group.weight.map_or(group.chars.chars().count() as f64, f64::from)).collect();
+        let total: f64 = weights.iter().sum();
+        weights.iter().map(|weight| weight / total).collect()
+    } else {
+        mapped.charsets.iter().map(|group| (distinct(&group.chars) as f64 / alphabet).min(1.0)).collect()
+    };
+    let mut bits = if weighted {
+        // The group drawn, then a character of it. / 先抽取分组，再抽取其中的字符。
+        let per_char: f64 = mapped.charsets.iter().zip(&shares).map(|(group, share)| share * (distinct(&group.chars) as f64 / share).log2()).sum();
+        mapped.length as f64 * per_char
+    } else if mapped.no_repeat {
+        (0..mapped.length).map(|drawn| (alphabet - drawn as f64).log2()).sum()
+    } else {
+        mapped.length as f64 * alphabet.log2()
+    };
+    for (group, share) in mapped.charsets.iter().zip(shares) {
+        bits += binomial_range_log2(mapped.length, share, group.min, group.max.unwrap_or(mapped.length));
+    }
+    Ok(bits.max(0.0))
+}
+
+/// [`aegixpass_generator_with`], also scoring the password (see [`crate::strength::score_password`])
+/// against the preset's [`estimate_entropy`].
+// 执行 [`aegixpass_generator_with`]，并依据预设的 [`estimate_entropy`] 为密码评分（参见 [`crate::strength::score_password`]）。
+pub fn aegixpass_generator_scored(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+) -> Result<(String, crate::strength::PasswordScore), AegixPassError> {
+    let password = aegixpass_generator_with(password_source, distinguish_key, preset, options)?;
+    let score = crate::strength::score_password(&password, estimate_entropy(preset)?);
+    Ok((password, score))
+}
+
+/// log2 of the probability that `n` draws, each a hit with probability `p`, hit between `low` and
+/// `high` times. Summed in the log domain, so long passwords do not underflow.
+// `n` 次抽取（每次命中的概率为 `p`）命中次数介于 `low` 与 `high` 之间的概率的 log2。在对数域中求和，因此长密码不会下溢。
+fn binomial_range_log2(n: usize, p: f64, low: usize, high: usize) -> f64 {
+    let high = high.min(n);
+    if p >= 1.0 {
+        return if (low..=high).contains(&n) { 0.0 } else { f64::NEG_INFINITY };
+    }
+    // ln P(k), stepped up from ln P(0) = n·ln(1 − p). / ln P(k)，从 ln P(0) = n·ln(1 − p) 逐步递推。
+    let ratio = (p / (1.0 - p)).ln();
+    let ln_pmf = |k: usize, previous: f64| previous + ((n - k + 1) as f64 / k as f64).ln() + ratio;
+    let mut terms = Vec::with_capacity(high.saturating_sub(low) + 1);
+    let mut ln_p = n as f64 * (1.0 - p).ln();
+    for k in 0..=high {
+        if k > 0 {
+            ln_p = ln_pmf(k, ln_p);
+        }
+        if k >= low {
+            terms.push(ln_p);
+        }
+    }
+    let peak = terms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if peak == f64::NEG_INFINITY {
+        return peak;
+    }
+    (peak + terms.iter().map(|term| (term - peak).exp()).sum::<f64>().ln()) / std::f64::consts::LN_2
+}
+
+/// One step of stages C to F, as recorded by [`aegixpass_trace`].
+// 由 [`aegixpass_trace`] 记录的阶段 C 至 F 中的一步。
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceStep {
+    /// Stage C: the group's 4 seed bytes as a little-endian `u32`, modulo the group's size.
+    // 阶段 C：该分组的 4 个种子字节按小端序读作 `u32`，再对分组大小取模。
+    Guaranteed { group: usize, index_seed: u32, index: usize, c: char },
+    /// A further character of a group whose `min` is above 1, drawn from the RNG.
+    // 从 RNG 抽取的、`min` 大于 1 的分组的其余字符。
+    Minimum { group: usize, index: usize, c: char },
+    /// Stage D with weights: the group the next character comes from.
+    // 带权重的阶段 D：下一个字符所来自的分组。
+    WeightedGroup { group: usize },
+    /// Stage D: a fill character and its index in the pool it was drawn from (the combined
+    /// charset, the characters of the open groups, or the weighted group).
+    // 阶段 D：一个填充字符及其在所抽取字符池中的索引（合并字符集、未满分组的字符，或加权选出的分组）。
+    Fill { index: usize, c: char },
+    /// A draw `noRepeat` rejected because the character is already in the password.
+    // 因字符已在密码中而被 `noRepeat` 拒绝的一次抽取。
+    Rejected { c: char },
+    /// Stage E: positions `i` and `j` are swapped. / 阶段 E：交换位置 `i` 与 `j`。
+    Swap { i: usize, j: usize },
+    /// Stage E with `noAdjacentGroup`: position `i` takes the next character `c` of `group`.
+    // 设置 `noAdjacentGroup` 时的阶段 E：位置 `i` 取 `group` 的下一个字符 `c`。
+    Arrange { i: usize, group: usize, c: char },
+    /// Stage E repair: the character at `i`, which breaks a layout rule, is swapped with `j`.
+    // 阶段 E 修复：将违反布局规则的位置 `i` 上的字符与位置 `j` 交换。
+    Repair { i: usize, j: usize },
+    /// Stage F: the password after a post-processor. / 阶段 F：经过某个后处理器之后的密码。
+    PostProcessed { name: String, password: String },
+}
+
+/// Every intermediate value of one password derivation, from [`aegixpass_trace`].
+// 一次密码派生的全部中间值，由 [`aegixpass_trace`] 给出。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    /// The text the hash runs over. It contains the master password, so only trace demo inputs.
+    // 哈希所处理的文本。其中包含主密码，因此只应追踪演示输入。
+    pub hash_input: String,
+    /// The master seed (stage B). / 主种子（阶段 B）。
+    pub master_seed: [u8; 32],
+    /// The seed of the password purpose: the master seed itself for version 1 presets.
+    // 密码用途的种子：版本 1 的预设即为主种子本身。
+    pub seed: [u8; 32],
+    /// The seed bytes stage C reads, 4 per group. / 阶段 C 读取的种子字节，每个分组 4 字节。
+    pub group_seed: Vec<u8>,
+    pub steps: Vec<TraceStep>,
+    pub password: String,
+}
+
+/// [`aegixpass_generator_with`], recording each stage's intermediate values on the way: an
+/// executable specification for people porting the algorithm. See `aegixpass demo`.
+// 执行 [`aegixpass_generator_with`]，并记录途中每个阶段的中间值：供将算法移植到其它语言的人使用的可执行规范。
+// 参见 `aegixpass demo`。
+pub fn aegixpass_trace(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+) -> Result<Trace, AegixPassError> {
+    if password_source.is_empty() || distinguish_key.is_empty() {
+        return Err(AegixPassError::InputEmpty);
+    }
+    let (resolved, combined_len) = resolve_checked(preset)?;
+    let preset = &*resolved;
+    let hash_input = master_input(password_source, distinguish_key, preset, options)?;
+    let master_seed = generate_master_seed(password_source, distinguish_key, preset, options, Host::Process)?;
+    let seed = purpose_root(&master_seed, preset, Purpose::Password)?;
+    let mut tracer = Tracer::tracing();
+    let password = trace_password_from_seed(seed, preset, combined_len, &mut tracer)?;
+    Ok(Trace {
+        hash_input,
+        master_seed,
+        seed,
+        group_seed: expand_group_seed(&seed, preset.seed_len())?,
+        steps: tracer.steps.unwrap_or_default(),
+        password,
+    })
+}
+
+/// RNG output consumed by each stage of one password derivation, from [`aegixpass_rng_accounting`],
+/// counted in 32-bit words (`next_u32` calls; a `next_u64` counts as two). Draws rejected by the
+/// unbiased range sampling or by `noRepeat` count toward the stage that made them. Stage C's first
+/// character per group reads the seed directly and uses no RNG output.
+// 一次密码派生中各阶段消耗的 RNG 输出，由 [`aegixpass_rng_accounting`] 给出，以 32 位字计数（即 `next_u32`
+// 调用次数；一次 `next_u64` 计为两个）。被无偏范围抽样或 `noRepeat` 拒绝的抽取计入发起它的阶段。
+// 阶段 C 中每个分组的第一个字符直接读取种子，不消耗 RNG 输出。
+#[cfg(feature = "rng-accounting")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenerationTrace {
+    /// Further characters of groups with a `min` above 1 (stage C). / `min` 大于 1 的分组的其余字符（阶段 C）。
+    pub minimums: u64,
+    /// Stage D. / 阶段 D。
+    pub fill: u64,
+    /// Stage E's shuffle. / 阶段 E 的洗牌。
+    pub shuffle: u64,
+    /// Stage E's layout repair. / 阶段 E 的布局修复。
+    pub repair: u64,
+    pub password: String,
+}
+
+#[cfg(feature = "rng-accounting")]
+impl GenerationTrace {
+    /// Words consumed over all stages. / 所有阶段消耗的字数之和。
+    pub fn total(&self) -> u64 {
+        self.minimums + self.fill + self.shuffle + self.repair
+    }
+}
+
+/// [`aegixpass_generator_with`], counting the RNG words each stage consumes, so a port can check
+/// it reads the RNG stream exactly as this implementation does. Requires the "rng-accounting" feature.
+// 执行 [`aegixpass_generator_with`]，并统计每个阶段消耗的 RNG 字数，以便移植版本核对自己读取 RNG 流的方式与本实现完全一致。
+// 需要启用 "rng-accounting" 特性。
+#[cfg(feature = "rng-accounting")]
+pub fn aegixpass_rng_accounting(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+) -> Result<GenerationTrace, AegixPassError> {
+    if password_source.is_empty() || distinguish_key.is_empty() {
+        return Err(AegixPassError::InputEmpty);
+    }
+    let (resolved, combined_len) = resolve_checked(preset)?;
+    let preset = &*resolved;
+    let master_seed = generate_master_seed(password_source, distinguish_key, preset, options, Host::Process)?;
+    let seed = purpose_root(&master_seed, preset, Purpose::Password)?;
+    let mut tracer = Tracer { steps: None, accounting: Some(Default::default()) };
+    let password = trace_password_from_seed(seed, preset, combined_len, &mut tracer)?;
+    let (_, consumption) = tracer.accounting.unwrap_or_default();
+    Ok(GenerationTrace { password, ..consumption })
+}
+
+/// Collects [`TraceStep`]s when tracing, and RNG consumption when accounting; costs nothing otherwise.
+// 追踪时收集 [`TraceStep`]，统计时收集 RNG 消耗量；否则没有任何开销。
+#[derive(Default)]
+struct Tracer {
+    steps: Option<Vec<TraceStep>>,
+    /// Words the RNG handed out since the last stage ended, and the stages counted so far.
+    // 自上一阶段结束以来 RNG 给出的字数，以及已统计的各阶段。
+    #[cfg(feature = "rng-accounting")]
+    accounting: Option<(std::rc::Rc<std::cell::Cell<u64>>, GenerationTrace)>,
+}
+
+/// The stages whose RNG consumption [`GenerationTrace`] reports. / [`GenerationTrace`] 报告其 RNG 消耗量的阶段。
+#[derive(Clone, Copy)]
+enum RngStage {
+    Minimums,
+    Fill,
+    Shuffle,
+    Repair,
+}
+
+impl Tracer {
+    /// A tracer that collects every step. / 收集每个步骤的追踪器。
+    fn tracing() -> Self {
+        Tracer {
+            steps: Some(Vec::new()),
+            #[cfg(feature = "rng-accounting")]
+            accounting: None,
+        }
+    }
+
+    fn record(&mut self, step: impl FnOnce() -> TraceStep) {
+        if let Some(steps) = &mut self.steps {
+            steps.push(step());
+        }
+    }
+
+    /// Wraps the derivation's RNG so its output is counted, when accounting.
+    // 统计时包装派生所用的 RNG，以便对其输出计数。
+    fn rng(&self, rng: Box<dyn RngCore>) -> Box<dyn RngCore> {
+        #[cfg(feature = "rng-accounting")]
+        if let Some((words, _)) = &self.accounting {
+            return Box::new(CountingRng { inner: rng, words: words.clone() });
+        }
+        rng
+    }
+
+    /// Assigns the words counted since the previous stage to `stage`.
+    // 将自上一阶段以来统计到的字数记入 `stage`。
+    fn end_stage(&mut self, stage: RngStage) {
+        #[cfg(feature = "rng-accounting")]
+        if let Some((words, consumption)) = &mut self.accounting {
+            let counter = match stage {
+                RngStage::Minimums => &mut consumption.minimums,
+                RngStage::Fill => &mut consumption.fill,
+                RngStage::Shuffle => &mut consumption.shuffle,
+                RngStage::Repair => &mut consumption.repair,
+            };
+            *counter = words.replace(0);
+        }
+        #[cfg(not(feature = "rng-accounting"))]
+        let _ = stage;
+    }
+}
+
+/// An RNG that counts the 32-bit words it hands out. / 统计其给出的 32 位字数的 RNG。
+#[cfg(feature = "rng-accounting")]
+struct CountingRng {
+    inner: Box<dyn RngCore>,
+    words: std::rc::Rc<std::cell::Cell<u64>>,
+}
+
+#[cfg(feature = "rng-accounting")]
+impl RngCore for CountingRng {
+    fn next_u32(&mut self) -> u32 {
+        self.words.set(self.words.get() + 1);
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.words.set(self.words.get() + 2);
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.words.set(self.words.get() + dest.len().div_ceil(4) as u64);
+        self.inner.fill_bytes(dest)
+    }
+}
+
+/// Expands the master seed into the subkey of one purpose. / 将主种子扩展为某一用途的子密钥。
+fn subkey(master_seed: &[u8; 32], purpose: Purpose) -> Result<[u8; 32], AegixPassError> {
+    let hkdf = Hkdf::<Sha256>::from_prk(master_seed).map_err(|_| AegixPassError::InvalidKeyLength(master_seed.len(), 32))?;
+    let mut key = [0u8; 32];
+    hkdf.expand(format!("{}{}", SUBKEY_INFO, purpose.name()).as_bytes(), &mut key)
+        .map_err(|_| AegixPassError::InvalidKeyLength(key.len(), 32))?;
+    Ok(key)
+}
+
+/// The key a purpose derives from: the master seed itself for version 1 presets, so their
+/// outputs never change, and the purpose subkey from version 2 on.
+// 某一用途派生所依据的密钥：版本 1 的预设直接使用主种子，使其输出永不改变；从版本 2 起使用该用途的子密钥。
+fn purpose_root(master_seed: &[u8; 32], preset: &Preset, purpose: Purpose) -> Result<[u8; 32], AegixPassError> {
+    if preset.version >= 2 {
+        subkey(master_seed, purpose)
+    } else {
+        Ok(*master_seed)
+    }
+}
+
+/// [`Preset::resolved`], checked as every derivation checks it, with the combined charset length.
+// 经过与每次派生相同的检查的 [`Preset::resolved`]，并附带合并后字符集的长度。
+fn resolve_checked(preset: &Preset) -> Result<(std::borrow::Cow<'_, Preset>, u32), AegixPassError> {
+    let resolved = preset.resolved();
+    // Name the group excludeChars emptied, rather than just reporting an empty one.
+    // 指出被 excludeChars 清空的分组，而不只是报告存在空分组。
+    if preset.exclude_chars.is_some()
+        && let Some(index) = (0..resolved.charsets.len())
+            .find(|&i| resolved.charsets[i].chars.is_empty() && preset.charsets.get(i).is_some_and(|group| !group.chars.is_empty()))
+    {
+        return Err(AegixPassError::ExcludeCharsEmptiesGroup(index + 1));
+    }
+    let combined_len = validate_charsets(&resolved)?;
+    Ok((resolved, combined_len))
+}
+
+/// Checks the preset's length and charsets, returning the combined charset length.
+// 检查预设的长度与字符集，返回合并后字符集的长度。
+fn validate_charsets(preset: &Preset) -> Result<u32, AegixPassError> {
+    // Grapheme clusters are validated as the single characters derivation sees.
+    // 字素簇按派生所见的单个字符进行验证。
+    let (preset, _) = grapheme_placeholders(preset)?;
+    let preset = &*preset;
+    if !SUPPORTED_VERSIONS.contains(&preset.version) {
+        return Err(AegixPassError::UnsupportedVersion(preset.version));
+    }
+    if !matches!(preset.seed_len(), 32 | 64 | MAX_SEED_BYTES) {
+        return Err(AegixPassError::InvalidSeedBytes(preset.seed_len()));
+    }
+    if preset.charsets.is_empty() {
+        return Err(AegixPassError::NoCharsets);
+    }
+    if preset.no_adjacent_group && preset.charsets.len() < 2 {
+        return Err(AegixPassError::NoAdjacentGroupSingleGroup);
+    }
+    if let Some(digits) = preset.pin.filter(|digits| !(MIN_PIN_DIGITS..=MAX_PIN_DIGITS).contains(digits)) {
+        return Err(AegixPassError::InvalidPinLength(digits));
+    }
+    if let Some(pattern) = &preset.pattern {
+        if preset.pin.is_some() {
+            return Err(AegixPassError::InvalidPattern("it cannot be combined with pin".to_string()));
+        }
+        parse_pattern(pattern, preset)?;
+    }
+    // Each group on its own first, so the feasibility check below only sees sound groups.
+    // 先逐个检查分组，使下面的可行性检查只需面对合理的分组。
+    for (index, cs) in preset.charsets.iter().enumerate() {
+        let group = index + 1;
+        if cs.chars.is_empty() {
+            return Err(AegixPassError::EmptyCharset);
+        }
+        if cs.min == 0 {
+            return Err(AegixPassError::CharsetMinZero);
+        }
+        if cs.weight == Some(0) {
+            return Err(AegixPassError::CharsetWeightZero);
+        }
+        if let Some(max) = cs.max.filter(|&max| max < cs.min) {
+            return Err(AegixPassError::CharsetMaxBelowMin(group, max, cs.min));
+        }
+    }
+    check_length_feasible(preset)?;
+    if preset.no_repeat {
+        check_no_repeat_feasible(preset)?;
+    }
+    if preset.pattern.is_none() {
+        check_positions_feasible(preset)?;
+    }
+    check_group_count(preset)?;
+    combined_charset_len(&preset.charsets)
+}
+
+/// Checks that `length` lies in the window the groups allow: at least the sum of their `min`
+/// and, when every group is capped, at most the sum of their `max`. The errors name the nearest
+/// workable length.
+// 检查 `length` 是否位于分组允许的区间内：不小于各分组 `min` 之和；所有分组都有上限时，不大于各分组 `max` 之和。
+// 错误信息会给出最接近的可行长度。
+fn check_length_feasible(preset: &Preset) -> Result<(), AegixPassError> {
+    let required = preset.charsets.iter().fold(0usize, |total, cs| total.saturating_add(cs.min));
+    if preset.length < required {
+        // With no `min` above 1, the groups themselves are what the length is short of.
+        // 没有大于 1 的 `min` 时，长度不足的原因就是分组本身。
+        return Err(if required == preset.charsets.len() {
+            AegixPassError::LengthTooShort(preset.length, required)
+        } else {
+            AegixPassError::LengthBelowMinimums(preset.length, required)
+        });
+    }
+    // With every group capped, the caps together must leave room for the whole length.
+    // 所有分组都有上限时，上限之和必须容得下整个长度。
+    let allowed = preset.charsets.iter().try_fold(0usize, |total, cs| cs.max.map(|max| total.saturating_add(max)));
+    if let Some(allowed) = allowed.filter(|&allowed| preset.length > allowed) {
+        return Err(AegixPassError::LengthAboveMaximums(preset.length, allowed));
+    }
+    if preset.length > MAX_PASSWORD_LENGTH {
+        return Err(AegixPassError::LengthTooLong(preset.length, MAX_PASSWORD_LENGTH));
+    }
+    Ok(())
+}
+
+/// Pattern placeholders other than `X` (any character of the charsets), with the characters they draw from.
+// 除 `X`（字符集中的任意字符）之外的模式占位符，及其所抽取的字符。
+pub const PATTERN_CLASSES: [(char, &str); 7] = [
+    ('C', "BCDFGHJKLMNPQRSTVWXYZ"),
+    ('c', "bcdfghjklmnpqrstvwxyz"),
+    ('V', "AEIOU"),
+    ('v', "aeiou"),
+    ('A', "ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+    ('a', "abcdefghijklmnopqrstuvwxyz"),
+    ('9', "0123456789"),
+];
+/// Longest pattern accepted, in characters. / 可接受的最长模式（字符数）。
+pub const MAX_PATTERN_CHARS: usize = 256;
+
+/// Number of seed bytes allocated for each charset. / 为每个字符集分配的种子字节数。
+pub const CHUNK_SIZE: usize = 4;
+
+/// Runs stages C to F on a 32-byte seed: picks one character per charset, fills and shuffles.
+// 对 32 字节的种子执行阶段 C 至 F：每个字符集各取一个字符，然后填充并洗牌。
+fn password_from_seed(master_seed: [u8; 32], preset: &Preset, combined_len: u32) -> Result<String, AegixPassError> {
+    trace_password_from_seed(master_seed, preset, combined_len, &mut Tracer::default())
+}
+
+/// [`password_from_seed`], recording each step with `tracer`. / 执行 [`password_from_seed`]，并用 `tracer` 记录每一步。
+fn trace_password_from_seed(master_seed: [u8; 32], preset: &Preset, combined_len: u32, tracer: &mut Tracer) -> Result<String, AegixPassError> {
+    let (mapped, clusters) = grapheme_placeholders(preset)?;
+    // A pattern replaces stages C to E. / 模式取代阶段 C 至 E。
+    let final_password_chars = match &mapped.pattern {
+        Some(pattern) => pattern_password_chars(master_seed, &mapped, pattern, tracer)?,
+        None => charset_password_chars(master_seed, &mapped, combined_len, tracer)?,
+    };
+
+    // --- (阶段 F) 组合并返回结果 ---
+    let mut password = String::new();
+    for c in final_password_chars {
+        match grapheme_cluster(&clusters, c) {
+            Some(cluster) => password += cluster,
+            None => password.push(c),
+        }
+    }
+    for name in &preset.post_processors {
+        let post_processor = registry::post_processor(name).ok_or_else(|| missing_algorithm(name))?;
+        password = post_processor(password)?;
+        tracer.record(|| TraceStep::PostProcessed { name: name.clone(), password: password.clone() });
+    }
+    Ok(password)
+}
+
+/// First of the placeholders that stand in for grapheme clusters of several code points, in
+/// Supplementary Private Use Area-A. Cluster i becomes the code point `GRAPHEME_PLACEHOLDER_BASE + 2·i`:
+/// the gaps keep placeholders from ever forming a `noSequential` run.
+// 代替由多个码位组成的字素簇的占位符的起点，位于补充私人使用区 A。第 i 个字素簇变为码位 `GRAPHEME_PLACEHOLDER_BASE + 2·i`：
+// 间隔使占位符永远不会构成 `noSequential` 序列。
+pub const GRAPHEME_PLACEHOLDER_BASE: u32 = 0xF0000;
+
+/// With `graphemes` on, the preset with each multi-code-point grapheme cluster of its charsets
+/// replaced by a placeholder character, and the clusters in placeholder order; the same cluster
+/// always gets the same placeholder. Stages C to E then work on single characters as usual.
+// 开启 `graphemes` 时，返回将字符集中每个由多个码位组成的字素簇替换为占位符字符后的预设，以及按占位符顺序排列的字素簇；
+// 相同的字素簇总是得到相同的占位符。之后阶段 C 至 E 照常处理单个字符。
+fn grapheme_placeholders(preset: &Preset) -> Result<(std::borrow::Cow<'_, Preset>, Vec<String>), AegixPassError> {
+    if !preset.graphemes {
+        return Ok((std::borrow::Cow::Borrowed(preset), Vec::new()));
+    }
+    let reserved = preset.charsets.iter().map(|charset| charset.chars.as_str()).chain(preset.pattern.as_deref());
+    if let Some(c) = reserved.flat_map(str::chars).find(|&c| c as u32 >= GRAPHEME_PLACEHOLDER_BASE) {
+        return Err(AegixPassError::GraphemePlaceholderUsed(c as u32));
+    }
+    let mut mapped = preset.clone();
+    let mut clusters: Vec<String> = Vec::new();
+    for charset in &mut mapped.charsets {
+        let mut chars = String::with_capacity(charset.chars.len());
+        for grapheme in charset.chars.graphemes(true) {
+            if grapheme.chars().nth(1).is_none() {
+                chars += grapheme;
+                continue;
+            }
+            let index = match clusters.iter().position(|cluster| cluster == grapheme) {
+                Some(index) => index,
+                None => {
+                    clusters.push(grapheme.to_string());
+                    clusters.len() - 1
+                }
+            };
+            let placeholder = u32::try_from(index).ok().and_then(|index| index.checked_mul(2)).and_then(|offset| offset.checked_add(GRAPHEME_PLACEHOLDER_BASE));
+            chars.push(placeholder.and_then(char::from_u32).ok_or(AegixPassError::CharsetTooLarge(clusters.len(), (0x10FFFF - GRAPHEME_PLACEHOLDER_BASE as usize) / 2))?);
+        }
+        charset.chars = chars;
+    }
+    Ok((std::borrow::Cow::Owned(mapped), clusters))
+}
+
+/// The grapheme cluster a placeholder stands for, if `c` is one. / 若 `c` 是占位符，返回它所代表的字素簇。
+fn grapheme_cluster(clusters: &[String], c: char) -> Option<&str> {
+    let offset = (c as u32).checked_sub(GRAPHEME_PLACEHOLDER_BASE)?;
+    if offset % 2 != 0 {
+        return None;
+    }
+    clusters.get(offset as usize / 2).map(String::as_str)
+}
+
+/// Stages C to E: one character per charset, the fill, the shuffle and the layout repair.
+// 阶段 C 至 E：每个字符集各取一个字符，然后填充、洗牌并修复布局。
+fn charset_password_chars(master_seed: [u8; 32], preset: &Preset, combined_len: u32, tracer: &mut Tracer) -> Result<Vec<char>, AegixPassError> {
     // --- (Stage A) Input Validation (Supplemental) ---
     // --- (阶段 A) 输入验证 (补充) ---
-    const CHUNK_SIZE: usize = 4; // Number of seed bytes allocated for each charset.
-    // 为每个字符集分配的种子字节数
-    let max_groups: usize = master_seed.len() / CHUNK_SIZE;
-    if preset.charsets.len() > max_groups {
-        return Err(AegixPassError::TooManyCharsetGroups(
-            preset.charsets.len(),
-            max_groups,
-        ));
-    }
+    let group_seed = expand_group_seed(&master_seed, preset.seed_len())?;
 
     // --- (Stage C) Ensure at least one character from each charset is included (Enhanced Security Version) ---
     // --- (阶段 C) 保证每个字符集至少出现一次 (安全增强版) ---
-    let mut final_password_chars: Vec<char> = Vec::with_capacity(preset.length);
-    for (i, charset_group) in preset.charsets.iter().enumerate() {
-        let start_index = i * CHUNK_SIZE;
-        let end_index = start_index + CHUNK_SIZE;
-        let chunk: [u8; CHUNK_SIZE] = master_seed[start_index..end_index]
-            .try_into()
-            .expect("Chunk size is guaranteed to be valid");
-        let index_seed = u32::from_le_bytes(chunk);
-        let char_index = (index_seed as u64 % charset_group.len() as u64) as usize;
-        let chars: Vec<char> = charset_group.chars().collect();
+    // The capacity is not taken from the untrusted `length`, which could be huge.
+    // 容量不取自不可信的 `length`，因为它可能非常大。
+    let mut final_password_chars: Vec<char> = Vec::with_capacity(preset.charsets.len());
+    for (group, (chunk, charset_group)) in group_seed.chunks_exact(CHUNK_SIZE).zip(&preset.charsets).enumerate() {
+        let index_seed = index_seed(chunk);
+        let chars: Vec<char> = charset_group.chars.chars().collect();
+        // Index by character count, not byte length, so multi-byte charsets stay in bounds.
+        // 按字符数而非字节长度取模，保证多字节字符集不会越界。
+        let char_index = (index_seed as u64 % chars.len() as u64) as usize;
+        tracer.record(|| TraceStep::Guaranteed { group, index_seed, index: char_index, c: chars[char_index] });
         final_password_chars.push(chars[char_index]);
     }
 
     // 从种子创建 RNG 实例
-    let mut rng = create_rng_from_seed(master_seed, &preset.rng_algorithm);
+    let mut rng = tracer.rng(create_rng_from_seed(master_seed, &preset.rng_algorithm)?);
+
+    // Groups with a `min` above 1 draw their further characters from the RNG, before stage D,
+    // so presets without one keep the exact RNG stream.
+    // `min` 大于 1 的分组在阶段 D 之前从 RNG 抽取其余字符，因此未设置 `min` 的预设保持完全相同的 RNG 流。
+    for (group, charset_group) in preset.charsets.iter().enumerate().filter(|(_, group)| group.min > 1) {
+        let chars: Vec<char> = charset_group.chars.chars().collect();
+        for _ in 1..charset_group.min {
+            // With `noRepeat`, a character already in the password is rejected and drawn again.
+            // 设置 `noRepeat` 时，已在密码中的字符会被拒绝并重新抽取。
+            let c = loop {
+                let index = secure_random_range_u32(&mut *rng, chars.len() as u32) as usize;
+                let c = chars[index];
+                if !is_repeat(preset, &final_password_chars, c) {
+                    tracer.record(|| TraceStep::Minimum { group, index, c });
+                    break c;
+                }
+                tracer.record(|| TraceStep::Rejected { c });
+            };
+            final_password_chars.push(c);
+        }
+    }
+    tracer.end_stage(RngStage::Minimums);
 
     // --- (阶段 D) 填充密码剩余长度 ---
-    let remaining_len = preset.length - final_password_chars.len();
-    if remaining_len > 0 {
-        let combined_charset_str: String = preset.charsets.join("");
+    let remaining_len = preset.length.saturating_sub(final_password_chars.len());
+    if preset.charsets.iter().any(|group| group.weight.is_some()) {
+        fill_weighted(&mut final_password_chars, &mut *rng, preset, remaining_len, tracer);
+    } else if preset.no_repeat || preset.charsets.iter().any(|group| group.max.is_some()) {
+        fill_capped(&mut final_password_chars, &mut *rng, preset, remaining_len, tracer);
+    } else if remaining_len > 0 {
+        let combined_charset_str: String = preset.charsets.iter().map(Charset::as_str).collect();
         let combined_charset: Vec<char> = combined_charset_str.chars().collect();
-        let combined_len = combined_charset.len() as u32;
 
         // --- 最终优化：不再洗牌，而是循环随机抽样 ---
         for _ in 0..remaining_len {
             let j = secure_random_range_u32(&mut *rng, combined_len) as usize;
+            tracer.record(|| TraceStep::Fill { index: j, c: combined_charset[j] });
             final_password_chars.push(combined_charset[j]);
         }
     }
+    tracer.end_stage(RngStage::Fill);
 
     // --- (阶段 E) 最终整体洗牌 ---
     // --- 关键优化：同样使用 u32 版本的洗牌逻辑 ---
+    // Fisher-Yates swaps position i with any of 0..=i; Sattolo's variant only with 0..i.
+    // Fisher-Yates 将位置 i 与 0..=i 中的任意位置交换；Sattolo 变体只与 0..i 交换。
+    let inclusive = match preset.shuffle_algorithm {
+        ShuffleAlgorithm::FisherYates => 1,
+        ShuffleAlgorithm::Sattolo => 0,
+    };
     for i in (1..final_password_chars.len()).rev() {
-        let j = secure_random_range_u32(&mut *rng, (i + 1) as u32) as usize;
+        let bound = u32::try_from(i + inclusive).map_err(|_| AegixPassError::LengthTooLong(preset.length, MAX_PASSWORD_LENGTH))?;
+        let j = secure_random_range_u32(&mut *rng, bound) as usize;
+        tracer.record(|| TraceStep::Swap { i, j });
         final_password_chars.swap(i, j);
     }
+    tracer.end_stage(RngStage::Shuffle);
+    // Layout rules are enforced after the shuffle, so presets without one keep the exact RNG stream.
+    // 布局规则在洗牌之后执行，因此未设置布局规则的预设保持完全相同的 RNG 流。
+    repair_layout(&mut final_password_chars, &mut *rng, preset, tracer)?;
+    tracer.end_stage(RngStage::Repair);
+    Ok(final_password_chars)
+}
 
-    // --- (阶段 F) 组合并返回结果 ---
-    Ok(final_password_chars.into_iter().collect())
+/// Fills a pattern: each placeholder draws one character of its class from the RNG, in order,
+/// and literals are copied.
+// 填充模式：每个占位符按顺序从 RNG 抽取其类别中的一个字符，字面字符则原样复制。
+fn pattern_password_chars(seed: [u8; 32], preset: &Preset, pattern: &str, tracer: &mut Tracer) -> Result<Vec<char>, AegixPassError> {
+    let mut rng = tracer.rng(create_rng_from_seed(seed, &preset.rng_algorithm)?);
+    let mut password = Vec::new();
+    for token in parse_pattern(pattern, preset)? {
+        match token {
+            PatternToken::Literal(c) => password.push(c),
+            PatternToken::Class(chars) => {
+                let index = secure_random_range_u32(&mut *rng, chars.len() as u32) as usize;
+                tracer.record(|| TraceStep::Fill { index, c: chars[index] });
+                password.push(chars[index]);
+            }
+        }
+    }
+    tracer.end_stage(RngStage::Fill);
+    Ok(password)
+}
+
+/// One position of a pattern. / 模式中的一个位置。
+enum PatternToken {
+    Literal(char),
+    /// The characters a placeholder draws from. / 占位符所抽取的字符。
+    Class(Vec<char>),
+}
+
+/// Splits a pattern into literals and placeholder classes (see [`PATTERN_CLASSES`]); `X` takes
+/// the characters of all charsets, in order, and `\` makes the next character a literal.
+// 将模式拆分为字面字符与占位符类别（参见 [`PATTERN_CLASSES`]）；`X` 取所有字符集的字符（按顺序），`\` 使下一个字符成为字面字符。
+fn parse_pattern(pattern: &str, preset: &Preset) -> Result<Vec<PatternToken>, AegixPassError> {
+    let invalid = |problem: &str| AegixPassError::InvalidPattern(problem.to_string());
+    if pattern.chars().count() > MAX_PATTERN_CHARS {
+        return Err(invalid(&format!("it is longer than {} characters", MAX_PATTERN_CHARS)));
+    }
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '\\' => PatternToken::Literal(chars.next().ok_or_else(|| invalid("it ends with an unfinished '\\'"))?),
+            'X' => PatternToken::Class(preset.charsets.iter().flat_map(|charset| charset.chars.chars()).collect()),
+            c => match PATTERN_CLASSES.iter().find(|(placeholder, _)| *placeholder == c) {
+                Some((_, class)) => PatternToken::Class(class.chars().collect()),
+                None => PatternToken::Literal(c),
+            },
+        });
+    }
+    if !tokens.iter().any(|token| matches!(token, PatternToken::Class(_))) {
+        return Err(invalid("it has no placeholders"));
+    }
+    Ok(tokens)
+}
+
+/// Stage D for presets with a capped group: each draw is from the characters of the groups that
+/// are still below their `max`, so the pool shrinks whenever a group fills up. Validation
+/// guarantees the pool never runs empty.
+// 含有上限分组的预设所使用的阶段 D：每次都从尚未达到 `max` 的分组的字符中抽取，因此每当某个分组填满时，字符池就会缩小。
+// 验证保证字符池永远不会为空。
+fn fill_capped(password: &mut Vec<char>, rng: &mut dyn RngCore, preset: &Preset, remaining_len: usize, tracer: &mut Tracer) {
+    // Stage C and the minimums put exactly `min` characters of each group in already.
+    // 阶段 C 与最小数量已经为每个分组放入了恰好 `min` 个字符。
+    let mut counts: Vec<usize> = preset.charsets.iter().map(|group| group.min).collect();
+    let open_pool = |counts: &[usize]| -> Vec<(usize, char)> {
+        preset
+            .charsets
+            .iter()
+            .enumerate()
+            .filter(|(g, group)| group.max.is_none_or(|max| counts[*g] < max))
+            .flat_map(|(g, group)| group.chars.chars().map(move |c| (g, c)))
+            .collect()
+    };
+    let mut pool = open_pool(&counts);
+    for _ in 0..remaining_len {
+        let (g, c) = loop {
+            let index = secure_random_range_u32(rng, pool.len() as u32) as usize;
+            let (g, c) = pool[index];
+            if !is_repeat(preset, password, c) {
+                tracer.record(|| TraceStep::Fill { index, c });
+                break (g, c);
+            }
+            tracer.record(|| TraceStep::Rejected { c });
+        };
+        password.push(c);
+        counts[g] += 1;
+        if preset.charsets[g].max == Some(counts[g]) {
+            pool = open_pool(&counts);
+        }
+    }
+}
+
+/// Stage D for presets with weighted groups: each draw first picks a group in proportion to
+/// its weight, among the groups still below their `max`, then a character of that group.
+// 含有加权分组的预设所使用的阶段 D：每次先在尚未达到 `max` 的分组中按权重比例选出一个分组，再从该分组中选出一个字符。
+fn fill_weighted(password: &mut Vec<char>, rng: &mut dyn RngCore, preset: &Preset, remaining_len: usize, tracer: &mut Tracer) {
+    let groups: Vec<Vec<char>> = preset.charsets.iter().map(|group| group.chars.chars().collect()).collect();
+    // At most 32 groups of weight up to 65535, or of up to 65536 characters in total, fit a `u32`.
+    // 至多 32 个权重不超过 65535 的分组，或总计不超过 65536 个字符，均可放入 `u32`。
+    let weights: Vec<u32> = preset.charsets.iter().zip(&groups).map(|(group, chars)| group.weight.map_or(chars.len() as u32, u32::from)).collect();
+    let mut counts: Vec<usize> = preset.charsets.iter().map(|group| group.min).collect();
+    for _ in 0..remaining_len {
+        let open: Vec<usize> = (0..groups.len()).filter(|&g| preset.charsets[g].max.is_none_or(|max| counts[g] < max)).collect();
+        let total: u32 = open.iter().map(|&g| weights[g]).sum();
+        let (g, c) = loop {
+            let mut pick = secure_random_range_u32(rng, total);
+            let mut g = open[0];
+            for &candidate in &open {
+                if pick < weights[candidate] {
+                    g = candidate;
+                    break;
+                }
+                pick -= weights[candidate];
+            }
+            tracer.record(|| TraceStep::WeightedGroup { group: g });
+            let index = secure_random_range_u32(rng, groups[g].len() as u32) as usize;
+            let c = groups[g][index];
+            if !is_repeat(preset, password, c) {
+                tracer.record(|| TraceStep::Fill { index, c });
+                break (g, c);
+            }
+            tracer.record(|| TraceStep::Rejected { c });
+        };
+        password.push(c);
+        counts[g] += 1;
+    }
+}
+
+/// Repair swaps allowed per character before a layout rule is given up on.
+// 放弃某条布局规则之前，每个字符允许的修复交换次数。
+pub const MAX_REPAIR_SWAPS_PER_CHAR: usize = 64;
+
+/// Stage E repair: while the password breaks a layout rule, swaps the offending character with
+/// a position drawn from the RNG. Swaps keep every group's count, so `min` and `max` still hold.
+/// With `noAdjacentGroup`, the groups are laid out by [`arrange_groups`] first and a swap only
+/// draws among the positions of the offending character's group, which keeps that layout.
+// 阶段 E 修复：只要密码违反某条布局规则，就将违规字符与从 RNG 抽取的位置交换。交换不改变各分组的计数，
+// 因此 `min` 与 `max` 依然成立。设置 `noAdjacentGroup` 时，先由 [`arrange_groups`] 排列各分组，
+// 之后的交换只在违规字符所属分组的位置中抽取，从而保持该排列。
+fn repair_layout(password: &mut [char], rng: &mut dyn RngCore, preset: &Preset, tracer: &mut Tracer) -> Result<(), AegixPassError> {
+    if preset.no_adjacent_group {
+        arrange_groups(password, rng, preset, tracer)?;
+    }
+    let limit = MAX_REPAIR_SWAPS_PER_CHAR.saturating_mul(password.len());
+    let mut swaps = 0;
+    while let Some((rule, i)) = layout_violation(preset, password) {
+        if swaps == limit {
+            return Err(AegixPassError::LayoutUnsatisfiable(rule, limit));
+        }
+        let candidates: Vec<usize> = if preset.no_adjacent_group {
+            (0..password.len()).filter(|&k| group_of(preset, password[k]) == group_of(preset, password[i])).collect()
+        } else {
+            (0..password.len()).collect()
+        };
+        let bound = u32::try_from(candidates.len()).map_err(|_| AegixPassError::LengthTooLong(preset.length, MAX_PASSWORD_LENGTH))?;
+        let j = candidates[secure_random_range_u32(rng, bound) as usize];
+        tracer.record(|| TraceStep::Repair { i, j });
+        password.swap(i, j);
+        swaps += 1;
+    }
+    Ok(())
+}
+
+/// Stage E for `noAdjacentGroup`: builds the order of the groups position by position, then
+/// hands each group its own characters back in their shuffled order. Each position draws one
+/// index below the characters left in the groups that may go there, so a group is picked in
+/// proportion to what it has left. A group may go there when it differs from its neighbours and
+/// the rest can still be arranged (see [`arrangeable`]); the `max` of half the length, rounded
+/// up, guarantees a start. With `lastChar`, the last position is settled first, taking the
+/// group's first character of the class, and with `firstChar` position 0 does the same.
+// `noAdjacentGroup` 的阶段 E：逐个位置构造各分组的顺序，再按洗牌后的顺序把各分组自己的字符放回。每个位置抽取一个
+// 小于“可放在此处的分组所剩字符总数”的索引，因此选中某个分组的概率与其剩余字符数成正比。一个分组可放在此处的条件是：
+// 它与相邻位置的分组不同，且其余字符仍可排列（参见 [`arrangeable`]）；长度一半（向上取整）的 `max` 保证总能开始。
+// 设置 `lastChar` 时先确定最后一个位置，取该分组中第一个属于该类别的字符；设置 `firstChar` 时位置 0 同样如此。
+fn arrange_groups(password: &mut [char], rng: &mut dyn RngCore, preset: &Preset, tracer: &mut Tracer) -> Result<(), AegixPassError> {
+    let mut queues = vec![std::collections::VecDeque::new(); preset.charsets.len()];
+    for &c in password.iter() {
+        queues[group_of(preset, c)].push_back(c);
+    }
+    // Whether group `g` may take the next position, then the group right after it.
+    // 分组 `g` 能否占据下一个位置，其后紧接着的是分组 `end`。
+    let fits = |queues: &[std::collections::VecDeque<char>], g: usize, class: Option<CharClass>, previous: Option<usize>, end: Option<usize>| {
+        previous != Some(g) && queues[g].iter().any(|&c| class.is_none_or(|class| class.matches(c))) && {
+            let left: Vec<usize> = queues.iter().enumerate().map(|(h, queue)| queue.len() - usize::from(h == g)).collect();
+            arrangeable(&left, Some(g), end)
+        }
+    };
+    let last = password.len() - 1;
+    let mut end = None;
+    if let Some(class) = preset.last_char {
+        let open: Vec<usize> = (0..queues.len())
+            .filter(|&g| {
+                let mut rest = queues.clone();
+                take(&mut rest[g], Some(class)).is_some() && (0..rest.len()).any(|first| fits(&rest, first, preset.first_char, None, Some(g)))
+            })
+            .collect();
+        let g = draw_group(rng, &open, &queues)?;
+        let c = take(&mut queues[g], Some(class)).ok_or(AegixPassError::Internal("an open group has no character of the class"))?;
+        tracer.record(|| TraceStep::Arrange { i: last, group: g, c });
+        password[last] = c;
+        end = Some(g);
+    }
+    let mut previous = None;
+    for i in 0..password.len() - usize::from(end.is_some()) {
+        let class = if i == 0 { preset.first_char } else { None };
+        let open: Vec<usize> = (0..queues.len()).filter(|&g| fits(&queues, g, class, previous, end)).collect();
+        let g = draw_group(rng, &open, &queues)?;
+        let c = take(&mut queues[g], class).ok_or(AegixPassError::Internal("an open group has no character of the class"))?;
+        tracer.record(|| TraceStep::Arrange { i, group: g, c });
+        password[i] = c;
+        previous = Some(g);
+    }
+    Ok(())
+}
+
+/// Whether groups with `left` characters each can follow group `before` and precede group
+/// `after` with no two neighbours of one group: every group needs at most half of the positions
+/// it may use, rounded up, which excludes the first position after `before` and the last before `after`.
+// 各分组分别剩余 `left` 个字符时，能否排在分组 `before` 之后、分组 `after` 之前，且没有同组字符相邻：每个分组所需的位置
+// 不超过其可用位置的一半（向上取整），可用位置不包括紧接 `before` 的第一个位置与紧挨 `after` 的最后一个位置。
+fn arrangeable(left: &[usize], before: Option<usize>, after: Option<usize>) -> bool {
+    let total: usize = left.iter().sum();
+    if total == 0 {
+        return before.is_none() || before != after;
+    }
+    left.iter().enumerate().all(|(h, &count)| {
+        let usable = total.saturating_sub(usize::from(before == Some(h)) + usize::from(after == Some(h)));
+        count <= usable.div_ceil(2)
+    })
+}
+
+/// Draws one of the `open` groups in proportion to the characters each has left.
+// 按各分组剩余的字符数，按比例从 `open` 中抽取一个分组。
+fn draw_group(rng: &mut dyn RngCore, open: &[usize], queues: &[std::collections::VecDeque<char>]) -> Result<usize, AegixPassError> {
+    let total: usize = open.iter().map(|&g| queues[g].len()).sum();
+    // Only shared characters or clashing firstChar and lastChar leave no group.
+    // 只有分组间共享字符，或 firstChar 与 lastChar 相互冲突时，才会没有可选的分组。
+    if total == 0 {
+        return Err(AegixPassError::LayoutImpossible("noAdjacentGroup"));
+    }
+    let mut pick = secure_random_range_u32(rng, total as u32) as usize;
+    for &g in open {
+        if pick < queues[g].len() {
+            return Ok(g);
+        }
+        pick -= queues[g].len();
+    }
+    Err(AegixPassError::Internal("the group pick exceeded the characters left"))
+}
+
+/// Removes the queue's first character of `class`, or its first character without one.
+// 取出队列中第一个属于 `class` 的字符；未指定类别时取出第一个字符。
+fn take(queue: &mut std::collections::VecDeque<char>, class: Option<CharClass>) -> Option<char> {
+    let index = queue.iter().position(|&c| class.is_none_or(|class| class.matches(c)))?;
+    queue.remove(index)
+}
+
+/// The group a character counts for: the first that contains it. / 字符所计入的分组：第一个包含它的分组。
+fn group_of(preset: &Preset, c: char) -> usize {
+    preset.charsets.iter().position(|group| group.chars.contains(c)).unwrap_or(0)
+}
+
+/// The first layout rule the password breaks and the position of the character to move.
+// 密码违反的第一条布局规则，以及需要移动的字符的位置。
+fn layout_violation(preset: &Preset, password: &[char]) -> Option<(&'static str, usize)> {
+    if preset.no_sequential {
+        let run = |k: usize| {
+            let (a, b, c) = (password[k - 2] as i64, password[k - 1] as i64, password[k] as i64);
+            (b - a == 1 && c - b == 1) || (a - b == 1 && b - c == 1)
+        };
+        if let Some(k) = (2..password.len()).find(|&k| run(k)) {
+            return Some(("noSequential", k));
+        }
+    }
+    if preset.no_adjacent_group
+        && let Some(k) = (1..password.len()).find(|&k| group_of(preset, password[k - 1]) == group_of(preset, password[k]))
+    {
+        return Some(("noAdjacentGroup", k));
+    }
+    if let (Some(class), Some(&c)) = (preset.first_char, password.first())
+        && !class.matches(c)
+    {
+        return Some(("firstChar", 0));
+    }
+    if let (Some(class), Some(&c)) = (preset.last_char, password.last())
+        && !class.matches(c)
+    {
+        return Some(("lastChar", password.len() - 1));
+    }
+    None
+}
+
+/// Checks that every password holds characters for `firstChar` and `lastChar`: a group made only
+/// of the required class guarantees `min` of them, and with both rules set the groups made only
+/// of either class must guarantee two characters between them, one for each end.
+// 检查每个密码都含有满足 `firstChar` 与 `lastChar` 的字符：完全由所需类别组成的分组保证出现 `min` 个这样的字符；
+// 两条规则都设置时，完全由其中任一类别组成的分组合计必须保证两个字符，两端各用一个。
+fn check_positions_feasible(preset: &Preset) -> Result<(), AegixPassError> {
+    let guaranteed = |classes: &[CharClass]| {
+        preset.charsets.iter()
+            .filter(|group| classes.iter().any(|class| group.chars.chars().all(|c| class.matches(c))))
+            .fold(0usize, |total, group| total.saturating_add(group.min))
+    };
+    for (rule, class) in [("firstChar", preset.first_char), ("lastChar", preset.last_char)] {
+        if let Some(class) = class.filter(|&class| guaranteed(&[class]) == 0) {
+            return Err(AegixPassError::PositionUnsatisfiable(rule, format!("no charset group is made only of {} characters", class.name())));
+        }
+    }
+    if let (Some(first), Some(last)) = (preset.first_char, preset.last_char)
+        && guaranteed(&[first, last]) < 2
+    {
+        return Err(AegixPassError::PositionUnsatisfiable(
+            "lastChar",
+            "the groups that guarantee firstChar and lastChar guarantee only one character between them".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether drawing `c` would break the preset's `noRepeat` rule. / 抽取 `c` 是否会违反预设的 `noRepeat` 规则。
+fn is_repeat(preset: &Preset, password: &[char], c: char) -> bool {
+    preset.no_repeat && password.contains(&c)
+}
+
+/// Checks that a `noRepeat` preset can always draw an unused character: the groups share no
+/// character, none needs more distinct characters than it has, and together they offer enough.
+/// This is what keeps the rejection sampling from running forever.
+// 检查 `noRepeat` 预设总能抽到未使用的字符：各分组之间没有共同的字符，没有分组需要的不同字符多于其拥有的，
+// 且合计提供的字符足够。正是这一点保证了拒绝采样不会无限循环。
+fn check_no_repeat_feasible(preset: &Preset) -> Result<(), AegixPassError> {
+    let mut seen = std::collections::BTreeSet::new();
+    let mut available = 0usize;
+    for (index, cs) in preset.charsets.iter().enumerate() {
+        let distinct: std::collections::BTreeSet<char> = cs.chars.chars().collect();
+        if let Some(&shared) = distinct.iter().find(|c| seen.contains(*c)) {
+            return Err(AegixPassError::NoRepeatOverlap(shared));
+        }
+        if distinct.len() < cs.min {
+            return Err(AegixPassError::NoRepeatGroupTooSmall(index + 1, cs.min, distinct.len()));
+        }
+        available = available.saturating_add(cs.max.map_or(distinct.len(), |max| max.min(distinct.len())));
+        seen.extend(distinct);
+    }
+    if preset.length > available {
+        return Err(AegixPassError::NoRepeatTooFewChars(preset.length, available));
+    }
+    Ok(())
+}
+
+/// Each charset group takes `CHUNK_SIZE` bytes of the stage C seed, which bounds the number of groups.
+// 每个字符集分组占用阶段 C 种子中的 `CHUNK_SIZE` 个字节，这限制了分组数量。
+fn check_group_count(preset: &Preset) -> Result<(), AegixPassError> {
+    let max_groups = preset.seed_len() / CHUNK_SIZE;
+    if preset.charsets.len() > max_groups {
+        return Err(AegixPassError::TooManyCharsetGroups(preset.charsets.len(), max_groups));
+    }
+    Ok(())
+}
+
+/// Reads a seed chunk as a little-endian `u32` on every target, big-endian ones included, and
+/// without a fallible slice conversion.
+// 在所有目标平台（包括大端平台）上都将种子块读取为小端序的 `u32`，且无需可能失败的切片转换。
+fn index_seed(chunk: &[u8]) -> u32 {
+    chunk.iter().rev().fold(0u32, |acc, &b| (acc << 8) | b as u32)
+}
+
+/// The seed bytes stage C draws from: the master seed itself when 32 bytes are asked for,
+/// otherwise its HKDF-SHA256 expansion with `AegixPass_Seed` as `info`.
+// 阶段 C 所使用的种子字节：请求 32 字节时直接使用主种子，否则为以 `AegixPass_Seed` 为 `info` 的 HKDF-SHA256 扩展结果。
+fn expand_group_seed(master_seed: &[u8; 32], seed_len: usize) -> Result<Vec<u8>, AegixPassError> {
+    if seed_len == master_seed.len() {
+        return Ok(master_seed.to_vec());
+    }
+    let hkdf = Hkdf::<Sha256>::from_prk(master_seed).map_err(|_| AegixPassError::InvalidSeedBytes(seed_len))?;
+    let mut seed = vec![0u8; seed_len];
+    hkdf.expand(SEED_INFO.as_bytes(), &mut seed).map_err(|_| AegixPassError::InvalidSeedBytes(seed_len))?;
+    Ok(seed)
+}
+
+/// Counts the characters of all charsets together, rejecting totals that do not fit the `u32`
+/// range used for random indices.
+// 统计所有字符集的字符总数，超出随机索引所用 `u32` 范围的总数将被拒绝。
+fn combined_charset_len(charsets: &[Charset]) -> Result<u32, AegixPassError> {
+    let total = charsets
+        .iter()
+        .try_fold(0usize, |total, charset| total.checked_add(charset.chars.chars().count()))
+        .unwrap_or(usize::MAX);
+    u32::try_from(total)
+        .ok()
+        .filter(|_| total <= MAX_CHARSET_CHARS)
+        .ok_or(AegixPassError::CharsetTooLarge(total, MAX_CHARSET_CHARS))
+}
+
+/// Parses an untrusted preset from JSON and generates a password with it. Malformed presets
+/// and presets over the default [`PresetLimits`] are reported as errors; neither parsing nor
+/// generation panics.
+// 从 JSON 解析不可信的预设并用它生成密码。格式错误或超出默认 [`PresetLimits`] 的预设会以错误的形式返回；
+// 解析与生成过程都不会 panic。
+pub fn aegixpass_generator_from_json(
+    password_source: &str,
+    distinguish_key: &str,
+    preset_json: &str,
+) -> Result<String, AegixPassError> {
+    let preset = Preset::from_json(preset_json)?;
+    aegixpass_generator(password_source, distinguish_key, &preset)
+}
+
+/// Fuzzing entry point. The input is split at its first two NUL bytes into the master
+/// password, the distinguish key and the preset JSON; any outcome other than a panic is a pass.
+// 模糊测试入口。输入在前两个 NUL 字节处被拆分为主密码、区分密钥和预设 JSON；只要不 panic 即视为通过。
+#[cfg(fuzzing)]
+pub fn fuzz_generate(data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let mut parts = text.splitn(3, '\0');
+    let (Some(password_source), Some(distinguish_key), Some(preset_json)) = (parts.next(), parts.next(), parts.next()) else {
+        return;
+    };
+    let _ = aegixpass_generator_from_json(password_source, distinguish_key, preset_json);
+}
+
+/// Fuzzing entry point for the preset parser: the input is parsed as preset JSON and, when it
+/// parses, validated, fingerprinted and described. Any outcome other than a panic is a pass.
+// 预设解析器的模糊测试入口：输入被解析为预设 JSON，解析成功时再进行校验、计算指纹并生成描述。只要不 panic 即视为通过。
+#[cfg(fuzzing)]
+pub fn fuzz_preset_json(data: &[u8]) {
+    let Ok(preset) = Preset::from_json(&String::from_utf8_lossy(data)) else {
+        return;
+    };
+    let _ = preset.validate();
+    let _ = preset.fingerprint();
+    let _ = preset.profile_id();
+    let _ = preset.platform_id_warning();
+    let _ = preset.deprecation_warning();
+}
+
+/// Derives `length` bytes of labelled key material (e.g. an HMAC signing key) from the same
+/// master seed used for passwords. The seed is expanded with HKDF-SHA256, using
+/// `AegixPass_Key:{label}` as the `info` parameter so every label yields an independent key.
+// 从与密码相同的主种子派生出 `length` 字节的带标签密钥材料（例如 HMAC 签名密钥）。
+// 主种子通过 HKDF-SHA256 扩展，并以 `AegixPass_Key:{label}` 作为 `info` 参数，使不同标签得到相互独立的密钥。
+pub fn aegixpass_derive_key(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    label: &str,
+    length: usize,
+) -> Result<Vec<u8>, AegixPassError> {
+    aegixpass_derive_key_with(password_source, distinguish_key, preset, label, length, &SeedOptions::default())
+}
+
+/// [`aegixpass_derive_key`] with extra seed inputs such as a keyfile. The labels of keys AegixPass
+/// derives itself are refused (see [`RESERVED_KEY_LABELS`]).
+// 带有额外种子输入（例如密钥文件）的 [`aegixpass_derive_key`]。AegixPass 自己派生的密钥所用的标签会被拒绝
+// （参见 [`RESERVED_KEY_LABELS`]）。
+pub fn aegixpass_derive_key_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    label: &str,
+    length: usize,
+    options: &SeedOptions,
+) -> Result<Vec<u8>, AegixPassError> {
+    if RESERVED_KEY_LABELS.contains(&label) || label.starts_with(ANSWER_LABEL_PREFIX) {
+        return Err(AegixPassError::ReservedKeyLabel(label.to_string()));
+    }
+    derive_purpose_key(password_source, distinguish_key, preset, Purpose::Key, label, length, options)
+}
+
+/// Expands `length` bytes for `label` from the key of `purpose` (see [`purpose_root`]). On version
+/// 1, usernames, answers and recovery codes use [`PURPOSE_KEY_INFO`] instead of [`KEY_INFO`];
+/// OTP secrets and passphrases keep [`KEY_INFO`], as they always have.
+// 从 `purpose` 的密钥（参见 [`purpose_root`]）为 `label` 扩展出 `length` 字节。在版本 1 中，用户名、答案与恢复码使用
+// [`PURPOSE_KEY_INFO`] 而非 [`KEY_INFO`]；OTP 密钥与口令短语一如既往地使用 [`KEY_INFO`]。
+pub(crate) fn derive_purpose_key(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    purpose: Purpose,
+    label: &str,
+    length: usize,
+    options: &SeedOptions,
+) -> Result<Vec<u8>, AegixPassError> {
+    // HKDF-SHA256 can expand to at most 255 blocks of 32 bytes.
+    // HKDF-SHA256 最多可扩展 255 个 32 字节的块。
+    const MAX_KEY_LENGTH: usize = 255 * 32;
+    if password_source.is_empty() || distinguish_key.is_empty() {
+        return Err(AegixPassError::InputEmpty);
+    }
+    if length == 0 || length > MAX_KEY_LENGTH {
+        return Err(AegixPassError::InvalidKeyLength(length, MAX_KEY_LENGTH));
+    }
+
+    if !SUPPORTED_VERSIONS.contains(&preset.version) {
+        return Err(AegixPassError::UnsupportedVersion(preset.version));
+    }
+
+    let master_seed = generate_master_seed(password_source, distinguish_key, &preset.resolved(), options, Host::Process)?;
+    let root = purpose_root(&master_seed, preset, purpose)?;
+    let invalid_length = |_| AegixPassError::InvalidKeyLength(length, MAX_KEY_LENGTH);
+    let hkdf = Hkdf::<Sha256>::from_prk(&root).map_err(|_| AegixPassError::InvalidKeyLength(root.len(), MAX_KEY_LENGTH))?;
+    let mut key = vec![0u8; length];
+    let prefix = match purpose {
+        Purpose::Username | Purpose::Answer | Purpose::Recovery if preset.version < 2 => PURPOSE_KEY_INFO,
+        _ => KEY_INFO,
+    };
+    hkdf.expand(format!("{}{}", prefix, label).as_bytes(), &mut key)
+        .map_err(invalid_length)?;
+    Ok(key)
 }
 
 /// Generates a 32-byte deterministic master seed from all input information.
@@ -181,9 +2621,102 @@ fn generate_master_seed(
     password_source: &str,
     distinguish_key: &str,
     preset: &Preset,
+    options: &SeedOptions,
+    host: Host,
+) -> Result<[u8; 32], AegixPassError> {
+    let seed = hash_master_input(password_source, distinguish_key, preset, options, host)?;
+    mix_key_material(seed, options)
+}
+
+/// Mixes the keyfile's contents, if any, into a hashed seed. / 将密钥文件的内容（如果有）混入哈希得到的种子。
+fn mix_key_material(seed: [u8; 32], options: &SeedOptions) -> Result<[u8; 32], AegixPassError> {
+    // Key material is mixed in after the (slow) hash: keyed by the seed, the HMAC is as hard to
+    // reproduce without the keyfile as without the master password.
+    // 密钥材料在（慢速）哈希之后混入：以种子为键的 HMAC，缺少密钥文件时与缺少主密码时同样无法重现。
+    match options.key_material {
+        Some(key_material) => {
+            let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&seed).map_err(|_| AegixPassError::Internal("HMAC rejected the seed as a key"))?;
+            mac.update(KEYFILE_TAG.as_bytes());
+            mac.update(&Sha256::digest(key_material));
+            Ok(mac.finalize().into_bytes().into())
+        }
+        None => Ok(seed),
+    }
+}
+
+/// Runs the preset's hash over the master password, distinguish key and preset fields.
+// 对主密码、区分密钥及预设字段运行预设的哈希算法。
+fn hash_master_input(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+    host: Host,
 ) -> Result<[u8; 32], AegixPassError> {
-    let input_data = format!(
-        "AegixPass_V{}:{}:{}:{}:{}:{}",
+    let input_data = master_input(password_source, distinguish_key, preset, options)?;
+    check_hash_inputs(preset, options, host)?;
+    hash_checked_input(input_data, preset, options, host)
+}
+
+/// Runs the preset's hash over `input_data` once [`check_hash_inputs`] has passed.
+// 在 [`check_hash_inputs`] 通过之后，对 `input_data` 运行预设的哈希算法。
+fn hash_checked_input(input_data: String, preset: &Preset, options: &SeedOptions, host: Host) -> Result<[u8; 32], AegixPassError> {
+    let argon2_secret = options.argon2_secret;
+    let name = preset.hash_algorithm.name();
+    let hash = registry::hash_function(name).ok_or_else(|| missing_algorithm(name))?;
+    // Presets without `argon2` settings keep the registered function and its exact output.
+    // 未设置 `argon2` 的预设仍使用已注册的函数，输出与以往完全相同。
+    let custom_argon2 = preset.argon2.filter(|_| preset.hash_algorithm == HashAlgorithm::Argon2id);
+    // The platformId and Argon2 settings are part of the cache input, so they separate entries.
+    // platformId 与 Argon2 参数都是缓存输入的一部分，因此能区分缓存条目。
+    let mut cache_input = input_data.clone().into_bytes();
+    if let Some(settings) = custom_argon2 {
+        cache_input.extend(format!(":argon2={}x{}", settings.memory_kib, settings.iterations).bytes());
+    }
+    if preset.salt_domain_tag {
+        cache_input.extend(format!(":salt={}", SALT_DOMAIN_TAG).bytes());
+    }
+    let salt = preset.salt_bytes()?;
+    if let Some(salt) = &salt {
+        cache_input.extend(b":salt-bytes=");
+        cache_input.extend(salt);
+    }
+    if let Some(secret) = argon2_secret {
+        cache_input.extend(b":argon2-secret=");
+        cache_input.extend(Sha256::digest(secret));
+    }
+    let salt_source = preset.salt_source();
+    // An explicit salt bypasses the registered functions, which only know the salt source.
+    // 显式盐绕过已注册的函数，因为它们只接收盐来源。
+    let run = || match (salt.as_deref(), custom_argon2) {
+        #[cfg(feature = "argon2")]
+        (Some(salt), settings) if preset.hash_algorithm == HashAlgorithm::Argon2id => {
+            argon2id_with(input_data.as_bytes(), salt, settings.unwrap_or_default(), argon2_secret)
+        }
+        #[cfg(feature = "scrypt")]
+        (Some(salt), _) if preset.hash_algorithm == HashAlgorithm::Scrypt => scrypt_with(input_data.as_bytes(), salt),
+        #[cfg(feature = "argon2")]
+        (None, Some(settings)) => argon2id_with(input_data.as_bytes(), &platform_salt(&salt_source), settings, argon2_secret),
+        _ => hash(input_data.as_bytes(), &salt_source),
+    };
+    match host {
+        Host::Process => kdf_cache::cached(name, &cache_input, run),
+        Host::Sandbox => run(),
+    }
+}
+
+/// The text the preset's hash runs over. / 预设的哈希所处理的文本。
+pub(crate) fn master_input(password_source: &str, distinguish_key: &str, preset: &Preset, options: &SeedOptions) -> Result<String, AegixPassError> {
+    let normalized;
+    let (password_source, distinguish_key) = if preset.normalize_inputs {
+        normalized = (password_source.nfc().collect::<String>(), distinguish_key.nfc().collect::<String>());
+        (normalized.0.as_str(), normalized.1.as_str())
+    } else {
+        (password_source, distinguish_key)
+    };
+    let mut input_data = format!(
+        "{}{}:{}:{}:{}:{}:{}",
+        HASH_INPUT_PREFIX,
         preset.version,
         preset.platform_id,
         preset.length,
@@ -191,69 +2724,251 @@ fn generate_master_seed(
         distinguish_key,
         serde_json::to_string(&preset.charsets).unwrap_or_default()
     );
+    // The pepper is appended as a hex digest, so binary peppers fit the text input and
+    // derivations without one keep their exact input.
+    // 胡椒值以十六进制摘要的形式追加，使二进制胡椒值也能放入文本输入，且不带胡椒值的派生输入保持不变。
+    if let Some(pepper) = options.pepper {
+        let digest: String = Sha256::digest(pepper).iter().map(|b| format!("{:02x}", b)).collect();
+        input_data += &format!(":pepper={}", digest);
+    }
+    if options.counter != 0 {
+        input_data += &format!(":counter={}", options.counter);
+    }
+    if let Some(epoch) = rotation_epoch(preset, options)? {
+        input_data += &format!(":epoch={}", epoch);
+    }
+    // The pattern separates the seed from that of the same preset without one.
+    // 模式使种子不同于未设置模式的同一预设的种子。
+    if let Some(pattern) = &preset.pattern {
+        input_data += &format!(":pattern={}", pattern);
+    }
+    Ok(input_data)
+}
+
+/// The rotation epoch to mix into the seed: the one given in the options, checked against the
+/// preset's policy, or the current one.
+// 要混入种子的轮换周期：选项中给定的周期（按预设的策略检查），或当前周期。
+fn rotation_epoch(preset: &Preset, options: &SeedOptions) -> Result<Option<String>, AegixPassError> {
+    match (preset.rotation, options.epoch) {
+        (Some(policy), Some(epoch)) if !policy.is_valid_epoch(epoch) => {
+            Err(AegixPassError::InvalidRotationEpoch { epoch: epoch.to_string(), policy })
+        }
+        (Some(policy), epoch) => Ok(Some(epoch.map_or_else(|| policy.current_epoch(), str::to_string))),
+        (None, Some(_)) => Err(AegixPassError::RotationEpochUnused),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Checks that the preset's hash can run: it is registered, gets Argon2's secret exactly when
+/// it is keyed, and fits in the available memory. A sandbox budgets memory itself instead.
+// 检查预设的哈希能否运行：它已注册、仅在带密钥时获得 Argon2 的密钥输入，且可用内存足够。沙箱则自行管理内存预算。
+fn check_hash_inputs(preset: &Preset, options: &SeedOptions, host: Host) -> Result<(), AegixPassError> {
+    let name = preset.hash_algorithm.name();
+    registry::hash_function(name).ok_or_else(|| missing_algorithm(name))?;
+    let keyed = preset.hash_algorithm == HashAlgorithm::Argon2id && preset.argon2.is_some_and(|settings| settings.keyed);
+    match (keyed, options.argon2_secret) {
+        (true, None) => return Err(AegixPassError::Argon2SecretMissing),
+        (false, Some(_)) => return Err(AegixPassError::Argon2SecretUnused),
+        _ => {}
+    }
+    if let (Some(memory_kib), Host::Process) = (preset.argon2_memory_kib(), host) {
+        check_available_memory(memory_kib, available_memory_kib())?;
+    }
+    Ok(())
+}
+
+/// Refuses an Argon2 memory cost larger than the memory currently available, which would
+/// otherwise get the process killed or thrash swap on small devices. The error suggests
+/// scaled-down settings that fit.
+// 拒绝超过当前可用内存的 Argon2 内存成本，否则在小型设备上进程可能被杀死或导致频繁换页。
+// 错误信息会给出适合的缩减参数建议。
+fn check_available_memory(memory_kib: u32, available_kib: Option<u64>) -> Result<(), AegixPassError> {
+    let Some(available_kib) = available_kib.filter(|&available| (memory_kib as u64) > available) else {
+        return Ok(());
+    };
+    // Keep the total work (memory × iterations) close to the standard profile, using at most
+    // three quarters of what is available.
+    // 尽量保持与标准参数相近的总工作量（内存 × 迭代次数），且最多使用可用内存的四分之三。
+    let standard_work = ARGON2_MEMORY_KIB as u64 * ARGON2_ITERATIONS as u64;
+    let suggestions: Vec<String> = [16384u32, 8192, 4096]
+        .into_iter()
+        .filter(|&m| (m as u64) <= available_kib * 3 / 4)
+        .map(|m| {
+            let iterations = standard_work.div_ceil(m as u64).max(ARGON2_ITERATIONS as u64);
+            format!("{{\"memoryKib\": {}, \"iterations\": {}}}", m, iterations)
+        })
+        .collect();
+    Err(AegixPassError::InsufficientMemory {
+        required_kib: memory_kib as u64,
+        available_kib,
+        suggestions: if suggestions.is_empty() { "none fit this device".to_string() } else { suggestions.join(" or ") },
+    })
+}
+
+/// Memory currently available to new allocations, in KiB, where the platform reports it.
+// 平台能报告时，返回当前可供新分配使用的内存（KiB）。
+#[cfg(target_os = "linux")]
+fn available_memory_kib() -> Option<u64> {
+    parse_mem_available(&std::fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn available_memory_kib() -> Option<u64> {
+    None
+}
+
+/// Parses `MemAvailable` from `/proc/meminfo`. / 从 `/proc/meminfo` 中解析 `MemAvailable`。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_mem_available(meminfo: &str) -> Option<u64> {
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))
+        .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+/// The built-in hash algorithms compiled into this build, keyed by their preset names.
+// 本次构建所包含的内置哈希算法，以其在预设中的名称为键。
+pub(crate) fn builtin_hash_functions() -> Vec<(&'static str, HashFunction)> {
+    vec![
+        ("sha256", hash_sha256),
+        #[cfg(feature = "blake3")]
+        ("blake3", hash_blake3),
+        #[cfg(feature = "sha3")]
+        ("sha3_256", hash_sha3_256),
+        #[cfg(feature = "blake2")]
+        ("blake2b", hash_blake2b),
+        #[cfg(feature = "argon2")]
+        ("argon2id", hash_argon2id),
+        #[cfg(feature = "scrypt")]
+        ("scrypt", hash_scrypt),
+    ]
+}
+
+/// The built-in RNG algorithms compiled into this build, keyed by their preset names.
+// 本次构建所包含的内置 RNG 算法，以其在预设中的名称为键。
+pub(crate) fn builtin_rng_factories() -> Vec<(&'static str, RngFactory)> {
+    vec![
+        ("chaCha20", |seed| Box::new(ChaCha20Rng::from_seed(seed))),
+        #[cfg(feature = "hc128")]
+        ("hc128", |seed| Box::new(Hc128Rng::from_seed(seed))),
+        #[cfg(feature = "xoshiro")]
+        ("xoshiro256StarStar", |seed| Box::new(Xoshiro256StarStar::from_seed(seed))),
+    ]
+}
+
+fn hash_sha256(input: &[u8], _platform_id: &str) -> Result<[u8; 32], AegixPassError> {
+    Ok(Sha256::digest(input).into())
+}
+
+#[cfg(feature = "blake3")]
+fn hash_blake3(input: &[u8], _platform_id: &str) -> Result<[u8; 32], AegixPassError> {
+    Ok(blake3::hash(input).into())
+}
+
+#[cfg(feature = "sha3")]
+fn hash_sha3_256(input: &[u8], _platform_id: &str) -> Result<[u8; 32], AegixPassError> {
+    Ok(Sha3_256::digest(input).into())
+}
+
+#[cfg(feature = "blake2")]
+fn hash_blake2b(input: &[u8], _platform_id: &str) -> Result<[u8; 32], AegixPassError> {
+    Ok(Blake2b::<U32>::digest(input).into())
+}
 
-    match preset.hash_algorithm {
-        HashAlgorithm::Sha256 => Ok(Sha256::digest(input_data.as_bytes()).into()),
-        HashAlgorithm::Blake3 => Ok(blake3::hash(input_data.as_bytes()).into()),
-        HashAlgorithm::Sha3_256 => Ok(Sha3_256::digest(input_data.as_bytes()).into()),
-        HashAlgorithm::Argon2id => {
-            // Argon2 需要一个盐。这里我们使用platformId
-            let salt: [u8; 32] = Sha256::digest(preset.platform_id.as_bytes()).into();
-
-            // 设置 Argon2 参数。这些参数在安全性和性能之间取得了平衡。
-            // m_cost (内存成本): 19456 KB = 19 MiB
-            // t_cost (时间成本): 2 次迭代
-            // p_cost (并行度): 1 个线程
-            let params = Params::new(19456, 2, 1, Some(32)).map_err(|e| AegixPassError::Argon2Error(e.to_string()))?;
-
-            // 创建 Argon2 实例
-            let argon2 = Argon2::new(
-                Argon2Algorithm::Argon2id,
-                Argon2Version::V0x13,
-                params,
-            );
+/// The salt slow hashes derive from the salt source when the preset has no explicit `salt`.
+// 预设没有显式 `salt` 时，慢哈希由盐来源派生的盐。
+#[cfg(any(feature = "argon2", feature = "scrypt"))]
+fn platform_salt(platform_id: &str) -> [u8; 32] {
+    // 为了保证确定性，我们使用platformId（或带域标签的platformId，见 `Preset::salt_source`）的 SHA-256 作为盐
+    Sha256::digest(platform_id.as_bytes()).into()
+}
 
-            let mut output_key_material = [0u8; 32]; // 我们需要一个32字节的种子
-            argon2.hash_password_into(
-                input_data.as_bytes(),
-                &salt,
-                &mut output_key_material,
-            ).map_err(|e| AegixPassError::Argon2Error(e.to_string()))?;
+#[cfg(feature = "argon2")]
+fn hash_argon2id(input: &[u8], platform_id: &str) -> Result<[u8; 32], AegixPassError> {
+    argon2id_with(input, &platform_salt(platform_id), Argon2Settings::default(), None)
+}
 
-            Ok(output_key_material)
-        }
-        HashAlgorithm::Scrypt => { // <-- 新增 Scrypt 处理逻辑
-            // 同样，我们使用platformId作为盐
-            let salt: [u8; 32] = Sha256::digest(preset.platform_id.as_bytes()).into();
+/// Argon2id with explicit cost parameters and salt. / 使用指定成本参数与盐的 Argon2id。
+#[cfg(feature = "argon2")]
+fn argon2id_with(
+    input: &[u8],
+    salt: &[u8],
+    settings: Argon2Settings,
+    secret: Option<&[u8]>,
+) -> Result<[u8; 32], AegixPassError> {
+    // 设置 Argon2 参数。默认参数在安全性和性能之间取得了平衡。
+    // m_cost (内存成本): 默认 19456 KB = 19 MiB
+    // t_cost (时间成本): 默认 2 次迭代
+    // p_cost (并行度): 1 个线程
+    let params = Params::new(settings.memory_kib, settings.iterations, 1, Some(32)).map_err(|e| AegixPassError::Argon2Error(e.to_string()))?;
 
-            // 设置 Scrypt 参数。这些参数是 scrypt 社区推荐的“交互式”登录的安全基准。
-            // N=2^15, r=8, p=1
-            let params = ScryptParams::new(15, 8, 1, 32).map_err(|e| AegixPassError::ScryptError(e.to_string()))?;
+    // 创建 Argon2 实例；带密钥时使用其带密钥 (keyed) 的 API。
+    let argon2 = match secret {
+        Some(secret) => Argon2::new_with_secret(secret, Argon2Algorithm::Argon2id, Argon2Version::V0x13, params)
+            .map_err(|e| AegixPassError::Argon2Error(e.to_string()))?,
+        None => Argon2::new(Argon2Algorithm::Argon2id, Argon2Version::V0x13, params),
+    };
 
-            let mut output_key_material = [0u8; 32]; // 我们需要一个32字节的种子
-            scrypt(
-                input_data.as_bytes(),
-                &salt,
-                &params,
-                &mut output_key_material,
-            ).map_err(|e| AegixPassError::ScryptError(e.to_string()))?;
+    let mut output_key_material = [0u8; 32]; // 我们需要一个32字节的种子
+    argon2.hash_password_into(
+        input,
+        salt,
+        &mut output_key_material,
+    ).map_err(|e| AegixPassError::Argon2Error(e.to_string()))?;
 
-            Ok(output_key_material)
-        }
-    }
+    Ok(output_key_material)
+}
+
+#[cfg(feature = "scrypt")]
+fn hash_scrypt(input: &[u8], platform_id: &str) -> Result<[u8; 32], AegixPassError> {
+    // 同样，我们使用platformId（或带域标签的platformId）派生盐
+    scrypt_with(input, &platform_salt(platform_id))
+}
+
+/// Scrypt with an explicit salt. / 使用指定盐的 Scrypt。
+#[cfg(feature = "scrypt")]
+fn scrypt_with(input: &[u8], salt: &[u8]) -> Result<[u8; 32], AegixPassError> {
+    // 设置 Scrypt 参数。这些参数是 scrypt 社区推荐的“交互式”登录的安全基准。
+    let params = ScryptParams::new(SCRYPT_LOG_N, SCRYPT_R, 1, 32).map_err(|e| AegixPassError::ScryptError(e.to_string()))?;
+
+    let mut output_key_material = [0u8; 32]; // 我们需要一个32字节的种子
+    scrypt(
+        input,
+        salt,
+        &params,
+        &mut output_key_material,
+    ).map_err(|e| AegixPassError::ScryptError(e.to_string()))?;
+
+    Ok(output_key_material)
 }
 
 /// Creates a usable deterministic random number generator (RNG) from the master seed and preset algorithm.
 // 根据主种子和预设算法，创建一个可用的确定性随机数生成器 (RNG)。
-fn create_rng_from_seed(seed: [u8; 32], rng_algorithm: &RngAlgorithm) -> Box<dyn RngCore> {
-    match rng_algorithm {
-        RngAlgorithm::ChaCha20 => Box::new(ChaCha20Rng::from_seed(seed)),
-        RngAlgorithm::Hc128 => Box::new(Hc128Rng::from_seed(seed)),
-    }
+pub(crate) fn create_rng_from_seed(seed: [u8; 32], rng_algorithm: &RngAlgorithm) -> Result<Box<dyn RngCore>, AegixPassError> {
+    let name = rng_algorithm.name();
+    let factory = registry::rng_factory(name).ok_or_else(|| missing_algorithm(name))?;
+    Ok(factory(seed))
+}
+
+/// Builds the error for an algorithm missing from the registry: built-in algorithms were
+/// compiled out (their feature names match their preset names), anything else was never registered.
+// 为注册表中缺失的算法构造错误：内置算法是在编译时被排除了（特性名与其在预设中的名称一致），其它算法则是从未注册。
+fn missing_algorithm(name: &str) -> AegixPassError {
+    let (name, feature) = match name {
+        "blake3" => ("blake3", "blake3"),
+        "sha3_256" => ("sha3_256", "sha3"),
+        "blake2b" => ("blake2b", "blake2"),
+        "argon2id" => ("argon2id", "argon2"),
+        "scrypt" => ("scrypt", "scrypt"),
+        "hc128" => ("hc128", "hc128"),
+        "xoshiro256StarStar" => ("xoshiro256StarStar", "xoshiro"),
+        other => return AegixPassError::UnknownAlgorithm(other.to_string()),
+    };
+    AegixPassError::AlgorithmUnavailable { name, feature }
 }
 
 // --- 辅助函数：一个基于 u32 的、清晰、可移植的无偏范围生成器 ---
-fn secure_random_range_u32(rng: &mut dyn RngCore, max: u32) -> u32 {
+pub(crate) fn secure_random_range_u32(rng: &mut dyn RngCore, max: u32) -> u32 {
     let range = max;
     let zone = u32::MAX.wrapping_sub(u32::MAX.wrapping_rem(range));
 
@@ -265,6 +2980,22 @@ fn secure_random_range_u32(rng: &mut dyn RngCore, max: u32) -> u32 {
     }
 }
 
+/// The SHA-256 preset the purpose modules (usernames, answers, recovery codes) test against, at
+/// the given version.
+// 各用途模块（用户名、答案、恢复码）测试所用的 SHA-256 预设，版本由参数指定。
+#[cfg(test)]
+pub(crate) fn load_purpose_preset(version: u32) -> Preset {
+    let json_preset = format!(
+        r#"{{
+          "name": "Test", "version": {}, "hashAlgorithm": "sha256", "rngAlgorithm": "chaCha20",
+          "shuffleAlgorithm": "fisherYates", "length": 16, "platformId": "aegixpass.takuron.com",
+          "charsets": ["0123456789", "abcdefghijklmnopqrstuvwxyz", "ABCDEFGHIJKLMNOPQRSTUVWXYZ", "!@#$%^&*_+-="]
+        }}"#,
+        version
+    );
+    serde_json::from_str(&json_preset).expect("The purpose preset JSON in the test is invalid")
+}
+
 // --- Unit Test Module ---
 // --- 单元测试模块 ---
 #[cfg(test)]
@@ -292,6 +3023,7 @@ mod tests {
         serde_json::from_str(json_preset).expect("The preset JSON in the test is invalid")
     }
 
+    #[cfg(all(feature = "sha3", feature = "hc128"))]
     fn load_sha3_preset() -> Preset {
         let json_preset = r#"
         {
@@ -334,6 +3066,7 @@ mod tests {
         serde_json::from_str(json_preset).expect("The Argon2id preset JSON in the test is invalid")
     }
 
+    #[cfg(feature = "scrypt")]
     fn load_scrypt_preset() -> Preset {
         let json_preset = r#"
         {
@@ -376,8 +3109,480 @@ mod tests {
         let preset = load_default_preset();
         let password = aegixpass_generator("a-very-long-and-random-password", "a-very-long-key", &preset).unwrap();
         for charset in &preset.charsets {
-            assert!(charset.chars().any(|c| password.contains(c)), "Password '{}' must contain characters from charset '{}'", password, charset);
+            assert!(charset.chars.chars().any(|c| password.contains(c)), "Password '{}' must contain characters from charset '{}'", password, charset.chars);
+        }
+    }
+
+    #[test]
+    fn test_charset_minimums() {
+        let plain = load_default_preset();
+        let mut preset: Preset = serde_json::from_str(
+            r#"{"name": "Min", "version": 1, "hashAlgorithm": "sha256", "rngAlgorithm": "chaCha20",
+                "shuffleAlgorithm": "fisherYates", "length": 16, "platformId": "aegixpass.takuron.com",
+                "charsets": [{"chars": "0123456789", "min": 1}, "abcdefghijklmnopqrstuvwxyz", "ABCDEFGHIJKLMNOPQRSTUVWXYZ", "!@#$%^&*()_+-="]}"#,
+        )
+        .unwrap();
+        // A structured group with min 1 is the plain string, down to the derived password.
+        // min 为 1 的结构化分组与纯字符串完全相同，派生出的密码也一样。
+        assert_eq!(preset.charsets, plain.charsets);
+        assert_eq!(preset.fingerprint(), plain.fingerprint());
+
+        preset.charsets[0].min = 3;
+        preset.charsets[3].min = 4;
+        for key in ["example.com", "example.org", "example.net", "mail.example.com"] {
+            let password = aegixpass_generator("password", key, &preset).unwrap();
+            assert!(password.chars().filter(char::is_ascii_digit).count() >= 3, "{}", password);
+            assert!(password.chars().filter(|c| "!@#$%^&*()_+-=".contains(*c)).count() >= 4, "{}", password);
+        }
+        assert_ne!(preset.fingerprint(), plain.fingerprint());
+
+        preset.length = 8;
+        assert_eq!(preset.validate(), Err(AegixPassError::LengthBelowMinimums(8, 9)));
+        preset.charsets[1].min = 0;
+        assert_eq!(preset.validate(), Err(AegixPassError::CharsetMinZero));
+    }
+
+    #[test]
+    fn test_charset_maximums() {
+        let mut preset = load_default_preset();
+        let symbols: Charset = serde_json::from_str(r#"{"chars": "!@#$%^&*()_+-=", "max": 2}"#).unwrap();
+        assert_eq!((symbols.min, symbols.max), (1, Some(2)));
+        preset.charsets[3] = symbols;
+        preset.charsets[0].min = 2;
+        for key in ["example.com", "example.org", "example.net", "mail.example.com"] {
+            let password = aegixpass_generator("password", key, &preset).unwrap();
+            let count = password.chars().filter(|c| "!@#$%^&*()_+-=".contains(*c)).count();
+            assert!((1..=2).contains(&count), "{}", password);
+            assert!(password.chars().filter(char::is_ascii_digit).count() >= 2, "{}", password);
+        }
+        assert_eq!(serde_json::to_string(&preset.charsets[3]).unwrap(), r#"{"chars":"!@#$%^&*()_+-=","min":1,"max":2}"#);
+
+        // Capping every group bounds the length. / 所有分组都有上限时，长度也随之受限。
+        let mut capped = load_default_preset();
+        capped.length = 6;
+        for charset in &mut capped.charsets {
+            charset.max = Some(2);
+        }
+        let password = aegixpass_generator("password", "example.com", &capped).unwrap();
+        assert_eq!(password.chars().count(), 6);
+        capped.length = 9;
+        assert_eq!(capped.validate(), Err(AegixPassError::LengthAboveMaximums(9, 8)));
+        capped.charsets[2].max = None;
+        assert_eq!(capped.validate(), Ok(()));
+        capped.charsets[0].min = 3;
+        assert_eq!(capped.validate(), Err(AegixPassError::CharsetMaxBelowMin(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_charset_weights() {
+        let mut preset = load_default_preset();
+        preset.length = 4000;
+        for (charset, weight) in preset.charsets.iter_mut().zip([30, 35, 25, 10]) {
+            charset.weight = Some(weight);
+        }
+        let password = aegixpass_generator("password", "example.com", &preset).unwrap();
+        // Roughly 10% symbols, where uniform sampling would give 14/76, about 18%.
+        // 符号约占 10%，而均匀抽样约为 14/76，即约 18%。
+        let symbols = password.chars().filter(|c| "!@#$%^&*()_+-=".contains(*c)).count();
+        assert!((300..500).contains(&symbols), "{}", symbols);
+        assert_eq!(aegixpass_generator("password", "example.com", &preset), Ok(password));
+
+        // Weights still respect `max`. / 权重依然遵守 `max`。
+        preset.length = 16;
+        preset.charsets[3].max = Some(1);
+        preset.charsets[3].weight = Some(1000);
+        let password = aegixpass_generator("password", "example.com", &preset).unwrap();
+        assert_eq!(password.chars().filter(|c| "!@#$%^&*()_+-=".contains(*c)).count(), 1);
+        assert_eq!(serde_json::to_string(&preset.charsets[0]).unwrap(), r#"{"chars":"0123456789","min":1,"weight":30}"#);
+        preset.charsets[0].weight = Some(0);
+        assert_eq!(preset.validate(), Err(AegixPassError::CharsetWeightZero));
+    }
+
+    #[test]
+    fn test_charset_aliases() {
+        let with_aliases = |charsets: &str| {
+            let json = format!(r#"{{"name": "Aliases", "version": 1, "hashAlgorithm": "sha256", "rngAlgorithm": "chaCha20",
+                "shuffleAlgorithm": "fisherYates", "length": 16, "platformId": "aegixpass.takuron.com", "charsets": {}}}"#, charsets);
+            Preset::from_json(&json)
+        };
+        let preset = with_aliases(r#"["@digits", "@lower", {"chars": "@upper", "min": 1}, "@symbols"]"#).unwrap();
+        assert_eq!(preset.charsets, load_default_preset().charsets);
+        assert_eq!(preset.fingerprint(), Preset { name: "Aliases".to_string(), ..load_default_preset() }.fingerprint());
+        assert_eq!(with_aliases(r#"["@symbols-safe"]"#).unwrap().charsets[0].as_str(), "!@#$%^&*_+-=");
+        // Symbol sets that merely start with `@` stay literal. / 仅以 `@` 开头的符号集保持字面含义。
+        assert_eq!(with_aliases(r#"["@#$", "@"]"#).unwrap().charsets[0].as_str(), "@#$");
+        let Err(AegixPassError::PresetParseError(message)) = with_aliases(r#"["@digts"]"#) else { panic!("typo accepted") };
+        assert!(message.contains("unknown charset alias '@digts'"), "{}", message);
+    }
+
+    #[test]
+    fn test_exclude_ambiguous() {
+        let mut preset = load_default_preset();
+        preset.exclude_ambiguous = true;
+        let mut stripped = load_default_preset();
+        stripped.charsets = ["23456789", "abcdefghijkmnpqrstuvwxyz", "ABCDEFGHJKLMNPQRSTUVWXYZ", "!@#$%^&*()_+-="].map(Charset::from).to_vec();
+        for key in ["example.com", "example.org", "example.net"] {
+            let password = aegixpass_generator("password", key, &preset).unwrap();
+            assert!(!password.contains(|c| AMBIGUOUS_CHARS.contains(c)), "{}", password);
+            assert_eq!(aegixpass_generator("password", key, &stripped), Ok(password));
+        }
+        assert_eq!(preset.fingerprint(), stripped.fingerprint());
+        assert_ne!(preset.fingerprint(), load_default_preset().fingerprint());
+        preset.charsets.push("0O".into());
+        assert_eq!(preset.validate(), Err(AegixPassError::EmptyCharset));
+    }
+
+    #[test]
+    fn test_exclude_chars() {
+        let preset = Preset { exclude_chars: Some("$%^&".to_string()), ..load_default_preset() };
+        let mut by_hand = load_default_preset();
+        for charset in &mut by_hand.charsets {
+            charset.chars.retain(|c| !"$%^&".contains(c));
+        }
+        assert_ne!(by_hand, load_default_preset());
+        for key in ["example.com", "example.org", "example.net"] {
+            let password = aegixpass_generator("password", key, &preset).unwrap();
+            assert_eq!(aegixpass_generator("password", key, &by_hand), Ok(password));
+        }
+        assert_eq!(preset.fingerprint(), by_hand.fingerprint());
+
+        // Both exclusions combine. / 两种排除可以同时使用。
+        let both = Preset { exclude_ambiguous: true, exclude_chars: Some("abc".to_string()), ..load_default_preset() };
+        assert!(!both.resolved().charsets[1].chars.contains(['a', 'o']));
+        let emptied = Preset { exclude_chars: Some("0123456789".to_string()), ..load_default_preset() };
+        assert_eq!(emptied.validate(), Err(AegixPassError::ExcludeCharsEmptiesGroup(1)));
+        // Derivation reports it the same way. / 派生以同样的方式报告该错误。
+        assert_eq!(aegixpass_generator("password", "example.com", &emptied), Err(AegixPassError::ExcludeCharsEmptiesGroup(1)));
+        assert_eq!(aegixpass_generate_batch("password", &["example.com"], &emptied), Err(AegixPassError::ExcludeCharsEmptiesGroup(1)));
+        assert_eq!(estimate_entropy(&emptied), Err(AegixPassError::ExcludeCharsEmptiesGroup(1)));
+    }
+
+    #[test]
+    fn test_length_feasibility_suggestions() {
+        let mut preset = load_default_preset();
+        preset.charsets[0].min = 4;
+        preset.length = 3;
+        // The minimums, not just the number of groups, decide the shortest workable length.
+        // 最短可行长度由最小数量决定，而不只是分组数量。
+        let error = preset.validate().unwrap_err();
+        assert_eq!(error, AegixPassError::LengthBelowMinimums(3, 7));
+        assert_eq!(error.hint().unwrap(), "increase `length` to at least 7, or lower the groups' `min` by 4 in total");
+
+        let mut capped = load_default_preset();
+        for charset in &mut capped.charsets {
+            charset.max = Some(3);
+        }
+        let error = capped.validate().unwrap_err();
+        assert_eq!(error, AegixPassError::LengthAboveMaximums(16, 12));
+        assert!(error.hint().unwrap().contains("at most 12, raise the groups' `max` by 4 in total"));
+
+        // Too many groups are reported before any derivation runs. / 分组过多会在任何派生运行之前报告。
+        let mut crowded = load_default_preset();
+        crowded.charsets = vec!["a".into(); 9];
+        assert_eq!(aegixpass_preflight("example.com", &crowded, &SeedOptions::default()), Err(AegixPassError::TooManyCharsetGroups(9, 8)));
+    }
+
+    #[test]
+    fn test_no_repeat() {
+        let mut preset = load_default_preset();
+        preset.no_repeat = true;
+        preset.length = 40;
+        preset.charsets[0].min = 5;
+        preset.charsets[3].max = Some(6);
+        for key in ["example.com", "example.org", "example.net", "mail.example.com"] {
+            let password = aegixpass_generator("password", key, &preset).unwrap();
+            let distinct: std::collections::BTreeSet<char> = password.chars().collect();
+            assert_eq!(distinct.len(), 40, "{}", password);
+            assert_eq!(aegixpass_generator("password", key, &preset), Ok(password));
+        }
+        preset.charsets[1].weight = Some(1);
+        let password = aegixpass_generator("password", "example.com", &preset).unwrap();
+        assert_eq!(password.chars().collect::<std::collections::BTreeSet<char>>().len(), 40);
+        assert_ne!(preset.fingerprint(), load_default_preset().fingerprint());
+
+        preset.length = 69;
+        assert_eq!(preset.validate(), Err(AegixPassError::NoRepeatTooFewChars(69, 68)));
+        preset.charsets[0].min = 11;
+        assert_eq!(preset.validate(), Err(AegixPassError::NoRepeatGroupTooSmall(1, 11, 10)));
+        preset.charsets[0].min = 5;
+        preset.charsets.push("xyz".into());
+        assert_eq!(preset.validate(), Err(AegixPassError::NoRepeatOverlap('x')));
+    }
+
+    #[test]
+    fn test_no_sequential() {
+        let has_run = |password: &str| {
+            let chars: Vec<i64> = password.chars().map(|c| c as i64).collect();
+            chars.windows(3).any(|w| (w[1] - w[0]).abs() == 1 && w[2] - w[1] == w[1] - w[0])
+        };
+        let mut preset = load_default_preset();
+        preset.length = 64;
+        let mut sequential = preset.clone();
+        sequential.no_sequential = true;
+        let mut repaired = 0;
+        for n in 0..50 {
+            let key = format!("site{}.example", n);
+            let plain = aegixpass_generator("password", &key, &preset).unwrap();
+            let password = aegixpass_generator("password", &key, &sequential).unwrap();
+            assert!(!has_run(&password), "{}", password);
+            if has_run(&plain) {
+                repaired += 1;
+                let sorted = |s: &str| { let mut v: Vec<char> = s.chars().collect(); v.sort(); v };
+                assert_eq!(sorted(&plain), sorted(&password));
+            } else {
+                assert_eq!(plain, password);
+            }
+            assert_eq!(aegixpass_generator("password", &key, &sequential), Ok(password));
+        }
+        assert!(repaired > 0);
+        assert_ne!(sequential.fingerprint(), preset.fingerprint());
+    }
+
+    #[test]
+    fn test_no_adjacent_group() {
+        let mut preset = load_default_preset();
+        preset.no_adjacent_group = true;
+        let group = |c: char| preset.charsets.iter().position(|group| group.chars.contains(c));
+        for n in 0..50 {
+            let key = format!("site{}.example", n);
+            let password: Vec<char> = aegixpass_generator("password", &key, &preset).unwrap().chars().collect();
+            assert!(password.windows(2).all(|w| group(w[0]) != group(w[1])), "{:?}", password);
+        }
+        // Two groups and an even length leave only strict alternation.
+        // 两个分组且长度为偶数时，只剩下严格交替一种排列。
+        let mut tight = preset.clone();
+        tight.charsets.truncate(2);
+        let password: Vec<char> = aegixpass_generator("password", "example.com", &tight).unwrap().chars().collect();
+        assert!(password.windows(2).all(|w| group(w[0]) != group(w[1])), "{:?}", password);
+        assert_eq!(password.iter().filter(|c| c.is_ascii_digit()).count(), 8);
+
+        // Long passwords of two uneven groups sit right at the cap of half the length.
+        // 由两个大小悬殊的分组组成的长密码恰好处于长度一半的上限。
+        let mut long = Preset { charsets: vec!["abcdefghijklmnopqrstuvwxyz".into(), "0123456789".into()], ..preset.clone() };
+        for length in [63, 64, 100, 128] {
+            long.length = length;
+            for n in 0..500 {
+                let password: Vec<char> = aegixpass_generator("password", &format!("site{}.example", n), &long).unwrap().chars().collect();
+                assert_eq!(password.len(), length);
+                assert!(password.windows(2).all(|w| group(w[0]) != group(w[1])), "{:?}", password);
+            }
+        }
+        // The ends are settled within the layout, so firstChar and lastChar still hold.
+        // 两端在排列中确定，因此 firstChar 与 lastChar 依然成立。
+        let ends = Preset { first_char: Some(CharClass::Letter), last_char: Some(CharClass::Digit), ..preset.clone() };
+        for n in 0..100 {
+            let password: Vec<char> = aegixpass_generator("password", &format!("site{}.example", n), &ends).unwrap().chars().collect();
+            assert!(password[0].is_ascii_alphabetic() && password[15].is_ascii_digit(), "{:?}", password);
+            assert!(password.windows(2).all(|w| group(w[0]) != group(w[1])), "{:?}", password);
+        }
+        long.length = 4096;
+        for n in 0..20 {
+            let password: Vec<char> = aegixpass_generator("password", &format!("site{}.example", n), &long).unwrap().chars().collect();
+            assert!(password.windows(2).all(|w| group(w[0]) != group(w[1])));
+        }
+
+        tight.charsets.truncate(1);
+        assert_eq!(tight.validate(), Err(AegixPassError::NoAdjacentGroupSingleGroup));
+        assert_ne!(preset.fingerprint(), load_default_preset().fingerprint());
+    }
+
+    #[test]
+    fn test_first_last_char() {
+        let preset = Preset { first_char: Some(CharClass::Letter), last_char: Some(CharClass::Alnum), ..load_default_preset() };
+        for key in ["example.com", "github.com", "bank.example", "a", "b", "c", "d", "e"] {
+            let password: Vec<char> = aegixpass_generator("password", key, &preset).unwrap().chars().collect();
+            assert!(password[0].is_ascii_alphabetic() && password[15].is_ascii_alphanumeric(), "{:?}", password);
+            // The repair only swaps, so the characters are those of the unconstrained password.
+            // 修复只做交换，因此字符与无约束时的密码相同。
+            let mut unconstrained: Vec<char> = aegixpass_generator("password", key, &load_default_preset()).unwrap().chars().collect();
+            let mut sorted = password.clone();
+            sorted.sort();
+            unconstrained.sort();
+            assert_eq!(sorted, unconstrained);
+        }
+        assert_ne!(preset.fingerprint(), load_default_preset().fingerprint());
+
+        let mut digits_only = Preset { first_char: Some(CharClass::Digit), last_char: Some(CharClass::Digit), ..load_default_preset() };
+        digits_only.charsets.truncate(2);
+        digits_only.length = 8;
+        assert!(matches!(digits_only.validate(), Err(AegixPassError::PositionUnsatisfiable("lastChar", _))));
+        digits_only.charsets[0].min = 2;
+        let password = aegixpass_generator("password", "example.com", &digits_only).unwrap();
+        assert!(password.starts_with(|c: char| c.is_ascii_digit()) && password.ends_with(|c: char| c.is_ascii_digit()), "{}", password);
+
+        let upper = Preset { first_char: Some(CharClass::Upper), charsets: vec!["abc".into(), "123".into()], ..load_default_preset() };
+        assert!(matches!(upper.validate(), Err(AegixPassError::PositionUnsatisfiable("firstChar", _))));
+        // A PIN drops the rules. / PIN 会去掉这些规则。
+        assert_eq!(Preset { pin: Some(6), ..upper }.validate(), Ok(()));
+    }
+
+    #[cfg(feature = "rng-accounting")]
+    #[test]
+    fn test_rng_accounting() {
+        let mut preset = load_default_preset();
+        preset.charsets[0].min = 3;
+        preset.no_sequential = true;
+        let options = SeedOptions::default();
+        let accounting = aegixpass_rng_accounting("password", "example.com", &preset, &options).unwrap();
+        assert_eq!(Ok(accounting.password.clone()), aegixpass_generator("password", "example.com", &preset));
+        // Each draw below takes one word unless the range sampling rejects it, which these small ranges make rare.
+        // 除非被范围抽样拒绝，下面每次抽取都只消耗一个字；范围很小，拒绝极为罕见。
+        let trace = aegixpass_trace("password", "example.com", &preset, &options).unwrap();
+        let count = |matches: fn(&TraceStep) -> bool| trace.steps.iter().filter(|step| matches(step)).count() as u64;
+        assert_eq!(accounting.minimums, 2);
+        assert_eq!(accounting.fill, count(|step| matches!(step, TraceStep::Fill { .. })));
+        assert_eq!(accounting.shuffle, 15);
+        assert_eq!(accounting.repair, count(|step| matches!(step, TraceStep::Repair { .. })));
+        assert_eq!(accounting.total(), 2 + 10 + 15 + accounting.repair);
+    }
+
+    #[test]
+    fn test_pin() {
+        let preset = Preset { pin: Some(6), ..load_default_preset() };
+        let pin = aegixpass_generator("password", "bank.example", &preset).unwrap();
+        assert_eq!(pin.len(), 6);
+        assert!(pin.chars().all(|c| c.is_ascii_digit()), "{}", pin);
+        // Exactly the preset with the digits written out by hand. / 与手动写出数字的预设完全等价。
+        let by_hand = Preset { charsets: vec![PIN_DIGITS.into()], length: 6, ..load_default_preset() };
+        assert_eq!(aegixpass_generator("password", "bank.example", &by_hand), Ok(pin));
+        assert_eq!(preset.entropy_bits(), 6.0 * 10f64.log2());
+        assert_ne!(preset.fingerprint(), load_default_preset().fingerprint());
+
+        let rules = Preset { pin: Some(12), no_repeat: true, exclude_ambiguous: true, ..load_default_preset() };
+        assert_eq!(aegixpass_generator("password", "bank.example", &rules).unwrap().len(), 12);
+        assert_eq!(Preset { pin: Some(3), ..load_default_preset() }.validate(), Err(AegixPassError::InvalidPinLength(3)));
+        assert_eq!(aegixpass_generator("password", "bank.example", &Preset { pin: Some(13), ..load_default_preset() }), Err(AegixPassError::InvalidPinLength(13)));
+    }
+
+    #[test]
+    fn test_estimate_entropy() {
+        // Two groups of two over length 2: 16 strings, each group present in 3/4 of them. Taken as
+        // independent that leaves 16 · 9/16 = 9 (the exact count is 8).
+        // 长度为 2、两个各含两个字符的分组：共 16 个字符串，每个分组出现在其中 3/4 中。视为相互独立时剩下 16 · 9/16 = 9 个（精确值为 8）。
+        let preset = Preset { charsets: vec!["ab".into(), "01".into()], length: 2, ..load_default_preset() };
+        assert!((estimate_entropy(&preset).unwrap() - 9f64.log2()).abs() < 1e-9, "{:?}", estimate_entropy(&preset));
+        assert_eq!(preset.entropy_bits(), estimate_entropy(&preset).unwrap());
+
+        // The guarantee costs almost nothing at the default length. / 在默认长度下，保证出现几乎不损失熵。
+        let default = load_default_preset();
+        let plain = 16.0 * 76f64.log2();
+        let estimate = estimate_entropy(&default).unwrap();
+        assert!(estimate < plain && estimate > plain - 1.0, "{}", estimate);
+        let capped = Preset { charsets: vec![default.charsets[0].clone(), Charset { max: Some(2), ..default.charsets[1].clone() }], ..default.clone() };
+        // Capping a group rules passwords out. / 为分组设置上限会排除一部分密码。
+        assert!(estimate_entropy(&capped).unwrap() < estimate);
+        assert!(estimate_entropy(&Preset { length: 4096, ..default.clone() }).unwrap().is_finite());
+        assert_eq!(estimate_entropy(&Preset { length: 0, ..default.clone() }), Err(AegixPassError::LengthTooShort(0, 4)));
+        assert_eq!(Preset { length: 0, ..default.clone() }.entropy_bits(), 0.0);
+
+        // Weights skew the draw, which costs entropy. / 权重使抽取产生偏斜，从而损失熵。
+        let mut weighted = default.clone();
+        for (charset, weight) in weighted.charsets.iter_mut().zip([30, 35, 25, 10]) {
+            charset.weight = Some(weight);
+        }
+        assert!(estimate_entropy(&weighted).unwrap() < estimate);
+        // Weights in proportion to the group sizes change nothing. / 与分组大小成比例的权重不改变任何东西。
+        let mut proportional = default.clone();
+        for charset in &mut proportional.charsets {
+            charset.weight = Some(charset.chars.chars().count() as u16);
+        }
+        assert!((estimate_entropy(&proportional).unwrap() - 16.0 * 76f64.log2() - (estimate - plain)).abs() < 1e-9);
+
+        // The implied caps of noAdjacentGroup count, and fail as validate() does.
+        // noAdjacentGroup 隐含的上限同样计入，并与 validate() 一样报错。
+        let adjacent = Preset {
+            charsets: vec![
+                Charset { max: Some(2), .."0123456789".into() },
+                Charset { max: Some(2), .."!@#$".into() },
+                "abcdefghijklmnopqrstuvwxyz".into(),
+            ],
+            length: 16,
+            no_adjacent_group: true,
+            ..default
+        };
+        assert_eq!(adjacent.validate(), Err(AegixPassError::LengthAboveMaximums(16, 12)));
+        assert_eq!(estimate_entropy(&adjacent), Err(AegixPassError::LengthAboveMaximums(16, 12)));
+    }
+
+    #[test]
+    fn test_generator_scored() {
+        let preset = load_default_preset();
+        let (password, score) = aegixpass_generator_scored("password", "example.com", &preset, &SeedOptions::default()).unwrap();
+        assert_eq!(aegixpass_generator("password", "example.com", &preset), Ok(password));
+        assert_eq!(score.score, 4);
+        let digits = Preset { charsets: vec!["0123456789".into()], length: 6, ..preset };
+        assert!(aegixpass_generator_scored("password", "example.com", &digits, &SeedOptions::default()).unwrap().1.score <= 1);
+    }
+
+    #[test]
+    fn test_normalize_inputs() {
+        let preset = Preset { normalize_inputs: true, ..load_default_preset() };
+        let (nfc, nfd) = ("caf\u{e9}", "cafe\u{301}");
+        let password = aegixpass_generator(nfc, "example.com", &preset).unwrap();
+        assert_eq!(aegixpass_generator(nfd, "example.com", &preset), Ok(password.clone()));
+        assert_eq!(aegixpass_generator("password", "caf\u{e9}.example", &preset), aegixpass_generator("password", "cafe\u{301}.example", &preset));
+        // Off, the two spellings differ; NFC input derives what it always did.
+        // 关闭时两种写法结果不同；NFC 输入的派生结果保持不变。
+        let plain = load_default_preset();
+        assert_ne!(aegixpass_generator(nfd, "example.com", &plain), Ok(password.clone()));
+        assert_eq!(aegixpass_generator(nfc, "example.com", &plain), Ok(password));
+        assert_ne!(preset.fingerprint(), plain.fingerprint());
+    }
+
+    #[test]
+    fn test_graphemes() {
+        let clusters = ["👍🏽", "e\u{301}", "🇯🇵", "1\u{FE0F}\u{20E3}"];
+        let preset = Preset {
+            charsets: vec![clusters.concat().as_str().into(), "abc".into()],
+            length: 24,
+            graphemes: true,
+            ..load_default_preset()
+        };
+        let password = aegixpass_generator("password", "example.com", &preset).unwrap();
+        let graphemes: Vec<&str> = password.graphemes(true).collect();
+        assert_eq!(graphemes.len(), 24, "{}", password);
+        assert!(graphemes.iter().all(|g| clusters.contains(g) || "abc".contains(*g)), "{}", password);
+        assert!(graphemes.iter().any(|g| clusters.contains(g)), "{}", password);
+        assert!((preset.entropy_bits() - 24.0 * 7f64.log2()).abs() < 0.01, "{}", preset.entropy_bits());
+        assert_ne!(preset.fingerprint(), Preset { graphemes: false, ..preset.clone() }.fingerprint());
+
+        // excludeAmbiguous keeps the keycap one. / excludeAmbiguous 保留数字键帽 1。
+        let keycap = Preset { exclude_ambiguous: true, no_repeat: true, length: 6, ..preset.clone() };
+        assert_eq!(aegixpass_generator("password", "example.com", &keycap).unwrap().graphemes(true).count(), 6);
+
+        // Single code points derive exactly what they did. / 单码位字符的派生结果完全不变。
+        let plain = load_default_preset();
+        assert_eq!(
+            aegixpass_generator("password", "example.com", &Preset { graphemes: true, ..plain.clone() }),
+            aegixpass_generator("password", "example.com", &plain)
+        );
+        let reserved = Preset { charsets: vec!["ab\u{F0000}".into()], length: 4, graphemes: true, ..plain };
+        assert_eq!(reserved.validate(), Err(AegixPassError::GraphemePlaceholderUsed(0xF0000)));
+    }
+
+    #[test]
+    fn test_pattern() {
+        let preset = Preset { pattern: Some("Cvcv-9999-XX\\9".to_string()), ..load_default_preset() };
+        let password = aegixpass_generator("password", "example.com", &preset).unwrap();
+        let chars: Vec<char> = password.chars().collect();
+        assert_eq!(chars.len(), 13, "{}", password);
+        assert!(chars[0].is_ascii_uppercase() && !"AEIOU".contains(chars[0]), "{}", password);
+        assert!("aeiou".contains(chars[1]) && "aeiou".contains(chars[3]), "{}", password);
+        assert!(chars[5..9].iter().all(char::is_ascii_digit), "{}", password);
+        assert_eq!((chars[4], chars[9], chars[12]), ('-', '-', '9'));
+        assert_eq!(aegixpass_generator("password", "example.com", &preset), Ok(password.clone()));
+        // The pattern is part of the hash input. / 模式是哈希输入的一部分。
+        let other = Preset { pattern: Some("Cvcv-9999-XX9".to_string()), ..load_default_preset() };
+        assert_ne!(aegixpass_generator("password", "example.com", &other).unwrap()[..12], password[..12]);
+        assert_ne!(preset.fingerprint(), load_default_preset().fingerprint());
+        assert!((Preset { pattern: Some("C9".to_string()), ..load_default_preset() }.entropy_bits() - 21f64.log2() - 10f64.log2()).abs() < 1e-9);
+
+        for pattern in ["---", "Cv\\", ""] {
+            let preset = Preset { pattern: Some(pattern.to_string()), ..load_default_preset() };
+            assert!(matches!(preset.validate(), Err(AegixPassError::InvalidPattern(_))), "{:?}", pattern);
         }
+        let with_pin = Preset { pattern: Some("9999".to_string()), pin: Some(4), ..load_default_preset() };
+        assert!(matches!(with_pin.validate(), Err(AegixPassError::InvalidPattern(_))));
     }
 
     #[test]
@@ -392,15 +3597,260 @@ mod tests {
     fn test_error_on_too_many_groups() {
         let mut preset = load_default_preset();
         preset.charsets = vec![
-            "1".to_string(), "2".to_string(), "3".to_string(),
-            "4".to_string(), "5".to_string(), "6".to_string(),
-            "7".to_string(), "8".to_string(), "9".to_string(),
+            "1".into(), "2".into(), "3".into(),
+            "4".into(), "5".into(), "6".into(),
+            "7".into(), "8".into(), "9".into(),
         ];
         preset.length = 10;
         let result = aegixpass_generator("password", "example.com", &preset);
         assert_eq!(result, Err(AegixPassError::TooManyCharsetGroups(9, 8)));
     }
 
+    #[test]
+    fn test_generate_batch() {
+        let preset = load_default_preset();
+        let keys = ["example.com", "", "mail.example.org", "example.com"];
+        let results = aegixpass_generate_batch("password", &keys, &preset).unwrap();
+        assert_eq!(results.len(), keys.len());
+        for (key, result) in keys.iter().zip(&results) {
+            assert_eq!(result, &aegixpass_generator("password", key, &preset));
+        }
+        assert_eq!(results[1], Err(AegixPassError::InputEmpty));
+        assert_eq!(aegixpass_generate_batch("password", &[], &preset), Ok(vec![]));
+        // Errors of the preset fail the whole batch. / 预设的错误会使整批失败。
+        let broken = Preset { charsets: vec![], ..preset.clone() };
+        assert_eq!(aegixpass_generate_batch("password", &keys, &broken), Err(AegixPassError::NoCharsets));
+        assert_eq!(aegixpass_generate_batch("", &keys, &preset), Err(AegixPassError::InputEmpty));
+
+        // Memory-hard hashes run on no more threads than the available memory holds.
+        // 内存困难型哈希所用的线程数不超过可用内存能容纳的数量。
+        let argon2 = load_argon2id_preset();
+        assert_eq!(batch_threads(&argon2, 200, Some(ARGON2_MEMORY_KIB as u64)), 1);
+        assert_eq!(batch_threads(&argon2, 1, None), 1);
+        assert!(batch_threads(&preset, 200, Some(1)) >= 1);
+
+        // A worker's panic becomes an error with its message. / 工作线程的 panic 会变为携带其信息的错误。
+        let panic = std::thread::spawn(|| panic!("worker {} failed", 3)).join().unwrap_err();
+        assert_eq!(panic_error(panic), AegixPassError::BatchWorkerPanicked("worker 3 failed".to_string()));
+        let panic = std::thread::spawn(|| std::panic::panic_any("static")).join().unwrap_err();
+        assert_eq!(panic_error(panic), AegixPassError::BatchWorkerPanicked("static".to_string()));
+
+        // Argon2id takes the memory-capped thread count, and the seed options reach every key.
+        // Argon2id 使用受内存限制的线程数，且种子选项作用于每个密钥。
+        #[cfg(feature = "argon2")]
+        {
+            let keyed = SeedOptions { key_material: Some(b"contents of a keyfile"), ..SeedOptions::default() };
+            let keys = ["a.example", "", "b.example", "c.example", "a.example"];
+            let results = aegixpass_generate_batch_with("password", &keys, &argon2, &keyed).unwrap();
+            assert_eq!(results.len(), keys.len());
+            for (key, result) in keys.iter().zip(results) {
+                assert_eq!(result, aegixpass_generator_with("password", key, &argon2, &keyed));
+            }
+        }
+    }
+
+    #[test]
+    fn test_expanded_seed_allows_more_groups() {
+        let mut preset = load_default_preset();
+        let standard = aegixpass_generator("password", "example.com", &preset).unwrap();
+        let fingerprint = preset.fingerprint();
+        // Asking for the default 32 bytes explicitly changes nothing. / 显式请求默认的 32 字节不会改变任何结果。
+        preset.seed_bytes = Some(32);
+        assert_eq!(aegixpass_generator("password", "example.com", &preset).unwrap(), standard);
+        assert_eq!(preset.fingerprint(), fingerprint);
+
+        preset.seed_bytes = Some(64);
+        preset.charsets = (b'a'..=b'p').map(|c| Charset::from((c as char).to_string())).collect();
+        preset.length = 20;
+        preset.validate().unwrap();
+        let password = aegixpass_generator("password", "example.com", &preset).unwrap();
+        assert!(preset.charsets.iter().all(|group| password.contains(group.as_str())));
+        assert_ne!(preset.fingerprint(), fingerprint);
+        assert!(preset.profile_id().ends_with("/seed=64"));
+
+        preset.charsets.push("q".into());
+        assert_eq!(preset.validate(), Err(AegixPassError::TooManyCharsetGroups(17, 16)));
+        preset.seed_bytes = Some(48);
+        assert_eq!(preset.validate(), Err(AegixPassError::InvalidSeedBytes(48)));
+    }
+
+    #[test]
+    fn test_key_material_is_a_second_factor() {
+        let preset = load_default_preset();
+        let plain = aegixpass_generator("password", "example.com", &preset).unwrap();
+        assert_eq!(aegixpass_generator_with("password", "example.com", &preset, &SeedOptions::default()).unwrap(), plain);
+        let keyed = SeedOptions { key_material: Some(b"contents of a keyfile"), ..SeedOptions::default() };
+        let with_keyfile = aegixpass_generator_with("password", "example.com", &preset, &keyed).unwrap();
+        assert_ne!(with_keyfile, plain);
+        let other = SeedOptions { key_material: Some(b"another keyfile"), ..SeedOptions::default() };
+        assert_ne!(aegixpass_generator_with("password", "example.com", &preset, &other).unwrap(), with_keyfile);
+        assert_ne!(
+            aegixpass_derive_key_with("password", "example.com", &preset, "jwt", 32, &keyed).unwrap(),
+            aegixpass_derive_key("password", "example.com", &preset, "jwt", 32).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pepper_changes_the_seed() {
+        let preset = load_default_preset();
+        let plain = aegixpass_generator("password", "example.com", &preset).unwrap();
+        let peppered = |pepper: &[u8]| {
+            let options = SeedOptions { pepper: Some(pepper), ..SeedOptions::default() };
+            aegixpass_generator_with("password", "example.com", &preset, &options).unwrap()
+        };
+        assert_ne!(peppered(b"device secret"), plain);
+        assert_eq!(peppered(b"device secret"), peppered(b"device secret"));
+        assert_ne!(peppered(b"device secret"), peppered(b"another secret"));
+    }
+
+    #[test]
+    fn test_counter_rotates_the_password() {
+        let preset = load_default_preset();
+        let with_counter = |counter| {
+            let options = SeedOptions { counter, ..SeedOptions::default() };
+            aegixpass_generator_with("password", "example.com", &preset, &options).unwrap()
+        };
+        assert_eq!(with_counter(0), aegixpass_generator("password", "example.com", &preset).unwrap());
+        assert_ne!(with_counter(1), with_counter(0));
+        assert_ne!(with_counter(2), with_counter(1));
+        assert_eq!(with_counter(2), with_counter(2));
+    }
+
+    #[test]
+    fn test_rotation_epoch_is_mixed_into_the_seed() {
+        let plain = load_default_preset();
+        let rotating = Preset { rotation: Some(RotationPolicy::Quarterly), ..load_default_preset() };
+        let in_epoch = |epoch| {
+            let options = SeedOptions { epoch, ..SeedOptions::default() };
+            aegixpass_generator_with("password", "example.com", &rotating, &options)
+        };
+        let current = RotationPolicy::Quarterly.current_epoch();
+        assert_eq!(in_epoch(None), in_epoch(Some(&current)));
+        assert_ne!(in_epoch(Some("2026-Q3")).unwrap(), in_epoch(Some("2026-Q4")).unwrap());
+        assert_ne!(in_epoch(None).unwrap(), aegixpass_generator("password", "example.com", &plain).unwrap());
+        assert_ne!(rotating.fingerprint(), plain.fingerprint());
+        assert_eq!(
+            in_epoch(Some("2026-10")),
+            Err(AegixPassError::InvalidRotationEpoch { epoch: "2026-10".to_string(), policy: RotationPolicy::Quarterly })
+        );
+        let options = SeedOptions { epoch: Some("2026-Q4"), ..SeedOptions::default() };
+        assert_eq!(aegixpass_generator_with("password", "example.com", &plain, &options), Err(AegixPassError::RotationEpochUnused));
+    }
+
+    #[test]
+    fn test_derive_key_labels_are_independent() {
+        let preset = load_default_preset();
+        let jwt = aegixpass_derive_key("MySecretPassword123!", "example.com", &preset, "jwt", 64).unwrap();
+        assert_eq!(jwt.len(), 64);
+        assert_eq!(jwt, aegixpass_derive_key("MySecretPassword123!", "example.com", &preset, "jwt", 64).unwrap());
+        let other = aegixpass_derive_key("MySecretPassword123!", "example.com", &preset, "api", 64).unwrap();
+        assert_ne!(jwt, other, "Different labels should produce different keys");
+        assert_eq!(
+            aegixpass_derive_key("MySecretPassword123!", "example.com", &preset, "jwt", 0),
+            Err(AegixPassError::InvalidKeyLength(0, 255 * 32))
+        );
+        // Labels of built-in keys are refused, so a caller cannot reproduce those keys.
+        // 内置密钥的标签会被拒绝，因此调用者无法重现这些密钥。
+        for label in RESERVED_KEY_LABELS.iter().copied().chain(["answer:mother s maiden name"]) {
+            assert_eq!(
+                aegixpass_derive_key("MySecretPassword123!", "example.com", &preset, label, 32),
+                Err(AegixPassError::ReservedKeyLabel(label.to_string()))
+            );
+        }
+        assert!(aegixpass_derive_key("MySecretPassword123!", "example.com", &preset, "answers", 32).is_ok());
+    }
+
+    #[test]
+    fn test_multi_output_labels_are_independent() {
+        let preset = load_default_preset();
+        let outputs = aegixpass_generator_multi("MySecretPassword123!", "example.com", &preset, &["login", "db", "api"]).unwrap();
+        assert_eq!(outputs.len(), 3);
+        assert!(outputs.iter().all(|p| p.chars().count() == preset.length));
+        assert_ne!(outputs[0], outputs[1]);
+        assert_ne!(outputs[1], outputs[2]);
+        assert_ne!(outputs[0], aegixpass_generator("MySecretPassword123!", "example.com", &preset).unwrap());
+        // A label's output does not depend on the other labels requested with it.
+        // 某个标签的输出不依赖于与其一同请求的其它标签。
+        let db = aegixpass_generator_multi("MySecretPassword123!", "example.com", &preset, &["db"]).unwrap();
+        assert_eq!(db[0], outputs[1]);
+    }
+
+    #[test]
+    fn test_version_2_derives_from_purpose_subkeys() {
+        let v1 = load_default_preset();
+        let v2 = Preset { version: 2, ..load_default_preset() };
+        let options = SeedOptions::default();
+        let subkeys = aegixpass_subkeys("MySecretPassword123!", "example.com", &v2, &[Purpose::Password, Purpose::Totp, Purpose::Key], &options).unwrap();
+        assert_ne!(subkeys[0], subkeys[1]);
+        assert_ne!(subkeys[1], subkeys[2]);
+
+        // A version 2 password is built from the password subkey, not the master seed.
+        // 版本 2 的密码由密码子密钥而非主种子构建。
+        let password = aegixpass_generator("MySecretPassword123!", "example.com", &v2).unwrap();
+        let combined_len = validate_charsets(&v2).unwrap();
+        assert_eq!(password, password_from_seed(subkeys[0], &v2, combined_len).unwrap());
+        assert_ne!(password, aegixpass_generator("MySecretPassword123!", "example.com", &v1).unwrap());
+
+        let totp = crate::otp::derive_otp_secret("MySecretPassword123!", "example.com", &v2).unwrap();
+        let hkdf = Hkdf::<Sha256>::from_prk(&subkeys[1]).unwrap();
+        let mut expected = vec![0u8; totp.len()];
+        hkdf.expand(b"AegixPass_Key:totp", &mut expected).unwrap();
+        assert_eq!(totp, expected);
+        let key_purpose = derive_purpose_key("MySecretPassword123!", "example.com", &v2, Purpose::Key, "totp", totp.len(), &options).unwrap();
+        assert_ne!(totp, key_purpose);
+        assert_eq!(
+            aegixpass_derive_key("MySecretPassword123!", "example.com", &v2, "totp", totp.len()),
+            Err(AegixPassError::ReservedKeyLabel("totp".to_string()))
+        );
+
+        let v3 = Preset { version: 3, ..load_default_preset() };
+        assert_eq!(v3.validate(), Err(AegixPassError::UnsupportedVersion(3)));
+        assert!(aegixpass_derive_key("MySecretPassword123!", "example.com", &v3, "jwt", 32).is_err());
+    }
+
+    #[test]
+    fn test_version_1_purposes_are_not_key_labels() {
+        // Version 1 has no purpose subkeys, so only the `info` keeps these seeds apart from key labels.
+        // 版本 1 没有用途子密钥，因此只有 `info` 使这些种子与密钥标签相互分离。
+        let preset = load_purpose_preset(1);
+        let options = SeedOptions::default();
+        let answer = format!("{}{}", ANSWER_LABEL_PREFIX, crate::answer::normalize_question("Mother's maiden name?"));
+        // Each purpose with its label, its output and the version 1 known answer.
+        // 每种用途及其标签、输出与版本 1 的已知答案。
+        type Derive = fn(&Preset) -> String;
+        let purposes: [(Purpose, &str, Derive, &str); 3] = [
+            (
+                Purpose::Username,
+                "username",
+                |preset| crate::username::derive_username("MySecretPassword123!", "example.com", preset, crate::username::UsernameStyle::Pronounceable).unwrap(),
+                "votujala90",
+            ),
+            (
+                Purpose::Answer,
+                &answer,
+                |preset| crate::answer::derive_answer("MySecretPassword123!", "example.com", "Mother's maiden name?", preset).unwrap(),
+                "moraja begupe movoje",
+            ),
+            (
+                Purpose::Recovery,
+                "recovery",
+                |preset| crate::recovery::derive_recovery_codes("MySecretPassword123!", "example.com", preset, 1, 8).unwrap().concat(),
+                "01240209",
+            ),
+        ];
+        for (purpose, label, derive, known) in purposes {
+            let seed = derive_purpose_key("MySecretPassword123!", "example.com", &preset, purpose, label, 32, &options).unwrap();
+            let key = derive_purpose_key("MySecretPassword123!", "example.com", &preset, Purpose::Key, label, 32, &options).unwrap();
+            assert_ne!(seed, key, "{:?}", purpose);
+            assert_eq!(
+                aegixpass_derive_key("MySecretPassword123!", "example.com", &preset, label, 32),
+                Err(AegixPassError::ReservedKeyLabel(label.to_string()))
+            );
+            assert_eq!(derive(&preset), known, "{:?}", purpose);
+        }
+    }
+
+    #[cfg(all(feature = "sha3", feature = "hc128"))]
     #[test]
     fn test_determinism_sha3() {
         let preset = load_sha3_preset();
@@ -409,6 +3859,75 @@ mod tests {
         assert_eq!(pass1, pass2, "The same input should produce the same password");
     }
 
+    // --- Portability vectors ---
+    // --- 可移植性向量 ---
+    // Fixed expected values, so a target that reads bytes in another order or indexes UTF-8
+    // differently (see the `portability` CI job on big-endian and 32-bit targets) fails here.
+    // 固定的期望值：若某个目标平台以不同的字节顺序读取字节或以不同方式索引 UTF-8
+    // （参见 CI 中针对大端与 32 位目标的 `portability` 作业），就会在这里失败。
+
+    #[test]
+    fn test_portable_byte_order() {
+        assert_eq!(index_seed(&[0x01, 0x02, 0x03, 0x04]), 0x0403_0201);
+        assert_eq!(index_seed(&[0xff, 0x00, 0x00, 0x80]), 0x8000_00ff);
+        for chunk in [[0u8; 4], [0xde, 0xad, 0xbe, 0xef], [7, 0, 0, 0]] {
+            assert_eq!(index_seed(&chunk), u32::from_le_bytes(chunk));
+        }
+    }
+
+    #[test]
+    fn test_portable_stages_c_to_f() {
+        // A fixed seed skips the hash, so only the index math, the RNG and the shuffle are tested.
+        // 固定的种子跳过了哈希，因此只测试索引计算、RNG 与洗牌。
+        let seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let preset = load_default_preset();
+        let combined_len = validate_charsets(&preset).unwrap();
+        assert_eq!(password_from_seed(seed, &preset, combined_len).unwrap(), "-vosEZw6^W#2GNAJ");
+    }
+
+    #[test]
+    fn test_sattolo_shuffle_is_dispatched() {
+        let seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let fisher_yates = load_default_preset();
+        let sattolo = Preset { shuffle_algorithm: ShuffleAlgorithm::Sattolo, ..load_default_preset() };
+        let combined_len = validate_charsets(&sattolo).unwrap();
+        let shuffled = password_from_seed(seed, &sattolo, combined_len).unwrap();
+        assert_ne!(shuffled, password_from_seed(seed, &fisher_yates, combined_len).unwrap());
+        // Both shuffles permute the same characters. / 两种洗牌算法排列的是相同的字符。
+        let sorted = |s: &str| {
+            let mut chars: Vec<char> = s.chars().collect();
+            chars.sort();
+            chars
+        };
+        assert_eq!(sorted(&shuffled), sorted(&password_from_seed(seed, &fisher_yates, combined_len).unwrap()));
+        assert_eq!(sattolo.profile_id(), "v1/sha256/chaCha20/sattolo");
+    }
+
+    #[test]
+    fn test_portable_utf8_charsets() {
+        // Multi-byte characters are indexed by character, never by byte.
+        // 多字节字符按字符而非字节进行索引。
+        let preset: Preset = serde_json::from_str(
+            r#"{"name": "UTF-8", "version": 1, "hashAlgorithm": "sha256", "rngAlgorithm": "chaCha20",
+                "shuffleAlgorithm": "fisherYates", "length": 12, "platformId": "aegixpass.takuron.com",
+                "charsets": ["αβγδεζ", "日本語漢字", "🙂😀🎉", "0123456789"]}"#,
+        )
+        .unwrap();
+        let password = aegixpass_generator("pässwörd", "例え.jp", &preset).unwrap();
+        assert_eq!(password, "9🎉δ🙂ε漢γε8語1😀");
+        assert_eq!(password.chars().count(), 12);
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn test_blake2b_is_blake2b_256() {
+        // Known answer: BLAKE2b-256 of "abc". / 已知答案："abc" 的 BLAKE2b-256 值。
+        let digest = hash_blake2b(b"abc", "").unwrap();
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319");
+    }
+
+    #[cfg(feature = "argon2")]
     #[test]
     fn test_determinism_argon2id() {
         let preset = load_argon2id_preset();
@@ -420,6 +3939,7 @@ mod tests {
         assert_ne!(pass1, pass3, "Different passwords should produce different results with Argon2id");
     }
 
+    #[cfg(feature = "scrypt")]
     #[test]
     fn test_determinism_scrypt() {
         let preset = load_scrypt_preset();
@@ -430,4 +3950,262 @@ mod tests {
         let pass3 = aegixpass_generator("AnotherPassword!", "example.com", &preset).unwrap();
         assert_ne!(pass1, pass3, "Different passwords should produce different results with Scrypt");
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "scrypt")]
+    #[test]
+    fn test_salt_domain_tag() {
+        let preset = load_scrypt_preset();
+        let tagged = Preset { salt_domain_tag: true, ..preset.clone() };
+        assert_eq!(tagged.salt_source(), "AegixPass-salt-v2:aegixpass.takuron.com");
+        assert_eq!(preset.salt_source(), "aegixpass.takuron.com");
+        assert_ne!(
+            aegixpass_generator("MySecretPassword123!", "example.com", &tagged),
+            aegixpass_generator("MySecretPassword123!", "example.com", &preset)
+        );
+        assert_ne!(tagged.fingerprint(), preset.fingerprint());
+        // Unsalted hashes ignore the salt source. / 不加盐的哈希忽略盐来源。
+        let sha256 = load_default_preset();
+        assert_eq!(
+            aegixpass_generator("MySecretPassword123!", "example.com", &Preset { salt_domain_tag: true, ..sha256.clone() }),
+            aegixpass_generator("MySecretPassword123!", "example.com", &sha256)
+        );
+    }
+
+    #[cfg(feature = "scrypt")]
+    #[test]
+    fn test_explicit_salt() {
+        let preset = load_scrypt_preset();
+        // Spelling out the salt derived from platformId changes nothing, so the bytes are used as they are.
+        // 显式写出由 platformId 派生的盐不会改变任何结果，说明这些字节被原样使用。
+        let derived: String = Sha256::digest(preset.platform_id.as_bytes()).iter().map(|b| format!("{:02X}", b)).collect();
+        let spelled_out = Preset { salt: Some(derived), ..preset.clone() };
+        assert_eq!(
+            aegixpass_generator("MySecretPassword123!", "example.com", &spelled_out),
+            aegixpass_generator("MySecretPassword123!", "example.com", &preset)
+        );
+        let fixed = Preset { salt: Some("00112233445566778899aabbccddeeff".to_string()), ..preset.clone() };
+        assert_eq!(fixed.salt_bytes().unwrap().map(|salt| salt.len()), Some(16));
+        assert_ne!(
+            aegixpass_generator("MySecretPassword123!", "example.com", &fixed),
+            aegixpass_generator("MySecretPassword123!", "example.com", &preset)
+        );
+        assert_ne!(fixed.fingerprint(), preset.fingerprint());
+        assert_eq!(Preset { salt: Some("00112233445566778899AABBCCDDEEFF".to_string()), ..preset.clone() }.fingerprint(), fixed.fingerprint());
+
+        let invalid = |salt: &str, preset: &Preset| matches!(Preset { salt: Some(salt.to_string()), ..preset.clone() }.validate(), Err(AegixPassError::InvalidSalt(_)));
+        assert!(invalid("0011223344556677889", &preset));
+        assert!(invalid("001122334455667g", &preset));
+        assert!(invalid("00112233445566", &preset));
+        assert!(invalid(&"00".repeat(MAX_SALT_BYTES + 1), &preset));
+        assert!(invalid("0011223344556677", &Preset { salt_domain_tag: true, ..preset.clone() }));
+        assert!(invalid("0011223344556677", &load_default_preset()));
+        assert_eq!(Preset { salt: Some("0011223344556677".to_string()), ..preset }.validate(), Ok(()));
+    }
+
+    #[cfg(not(feature = "argon2"))]
+    #[test]
+    fn test_error_on_unavailable_algorithm() {
+        let preset = load_argon2id_preset();
+        let result = aegixpass_generator("MySecretPassword123!", "example.com", &preset);
+        assert_eq!(
+            result,
+            Err(AegixPassError::AlgorithmUnavailable { name: "argon2id", feature: "argon2" })
+        );
+    }
+
+    #[test]
+    fn test_malformed_presets_return_errors() {
+        let base = r#""name":"x","version":1,"hashAlgorithm":"sha256","rngAlgorithm":"chaCha20","shuffleAlgorithm":"fisherYates","platformId":"p""#;
+        let generate = |rest: &str| aegixpass_generator_from_json("password", "example.com", &format!("{{{},{}}}", base, rest));
+        assert_eq!(generate(r#""length":4,"charsets":[]"#), Err(AegixPassError::NoCharsets));
+        assert_eq!(generate(r#""length":0,"charsets":[]"#), Err(AegixPassError::NoCharsets));
+        assert_eq!(
+            generate(r#""length":18446744073709551615,"charsets":["a"]"#),
+            Err(AegixPassError::PresetLimitExceeded { field: "length", value: usize::MAX, limit: 4096 })
+        );
+        assert!(matches!(generate(r#""length":-1,"charsets":["a"]"#), Err(AegixPassError::PresetParseError(_))));
+        // Multi-byte charsets are indexed by character, not by byte.
+        // 多字节字符集按字符而非字节取索引。
+        let password = generate(r#""length":8,"charsets":["éü","日本語"]"#).unwrap();
+        assert_eq!(password.chars().count(), 8);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_length_bounds() {
+        let mut preset = load_default_preset();
+        preset.length = MAX_PASSWORD_LENGTH + 1;
+        assert_eq!(
+            aegixpass_generator("password", "example.com", &preset),
+            Err(AegixPassError::LengthTooLong(MAX_PASSWORD_LENGTH + 1, MAX_PASSWORD_LENGTH))
+        );
+        assert_eq!(combined_charset_len(&preset.charsets), Ok(10 + 26 + 26 + 14));
+    }
+
+    #[test]
+    fn test_preset_limits() {
+        let mut preset = load_default_preset();
+        let limits = PresetLimits::default();
+        assert_eq!(limits.check(&preset), Ok(()));
+        preset.length = limits.max_length + 1;
+        assert_eq!(
+            limits.check(&preset),
+            Err(AegixPassError::PresetLimitExceeded { field: "length", value: 4097, limit: 4096 })
+        );
+
+        let argon2 = load_argon2id_preset();
+        let strict = PresetLimits { max_argon2_memory_kib: 8192, ..PresetLimits::default() };
+        assert!(matches!(strict.check(&argon2), Err(AegixPassError::PresetLimitExceeded { .. })));
+    }
+
+    #[cfg(feature = "argon2")]
+    #[test]
+    fn test_argon2_settings() {
+        let json = |argon2: &str| {
+            format!(
+                r#"{{"name": "t", "version": 1, "hashAlgorithm": "argon2id", "rngAlgorithm": "chaCha20",
+                    "shuffleAlgorithm": "fisherYates", "length": 16, "platformId": "aegixpass.takuron.com",
+                    "charsets": ["0123456789", "abcdefghijklmnopqrstuvwxyz"]{}}}"#,
+                argon2
+            )
+        };
+        let standard = Preset::from_json(&json("")).unwrap();
+        let explicit = Preset::from_json(&json(r#", "argon2": {"memoryKib": 19456, "iterations": 2}"#)).unwrap();
+        let low = Preset::from_json(&json(r#", "argon2": {"memoryKib": 8192, "iterations": 5}"#)).unwrap();
+        assert_eq!(low.argon2_memory_kib(), Some(8192));
+        let generate = |preset: &Preset| aegixpass_generator("MySecretPassword123!", "example.com", preset).unwrap();
+        assert_eq!(generate(&standard), generate(&explicit));
+        assert_ne!(generate(&standard), generate(&low));
+        assert!(matches!(
+            Preset::from_json(&json(r#", "argon2": {"memoryKib": 8192, "iterations": 100000}"#)),
+            Err(AegixPassError::PresetLimitExceeded { field: "Argon2 iterations", .. })
+        ));
+    }
+
+    #[cfg(feature = "argon2")]
+    #[test]
+    fn test_keyed_argon2() {
+        let mut preset = load_argon2id_preset();
+        preset.argon2 = Some(Argon2Settings { memory_kib: 1024, iterations: 1, keyed: true });
+        let generate = |preset: &Preset, secret: Option<&[u8]>| {
+            let options = SeedOptions { argon2_secret: secret, ..SeedOptions::default() };
+            aegixpass_generator_with("MySecretPassword123!", "example.com", preset, &options)
+        };
+        assert_eq!(generate(&preset, None), Err(AegixPassError::Argon2SecretMissing));
+        let keyed = generate(&preset, Some(b"pepper")).unwrap();
+        assert_eq!(generate(&preset, Some(b"pepper")).unwrap(), keyed);
+        assert_ne!(generate(&preset, Some(b"other pepper")).unwrap(), keyed);
+        assert!(preset.profile_id().contains("argon2id(m=1024,t=1,p=1,keyed)"));
+
+        let fingerprint = preset.fingerprint();
+        preset.argon2 = Some(Argon2Settings { memory_kib: 1024, iterations: 1, keyed: false });
+        assert_ne!(preset.fingerprint(), fingerprint);
+        assert_eq!(generate(&preset, Some(b"pepper")), Err(AegixPassError::Argon2SecretUnused));
+        assert_ne!(generate(&preset, None).unwrap(), keyed);
+    }
+
+    #[test]
+    fn test_insufficient_memory() {
+        assert_eq!(parse_mem_available("MemTotal:  65536 kB\nMemFree:  1000 kB\nMemAvailable:   12000 kB\n"), Some(12000));
+        assert_eq!(parse_mem_available("MemTotal:  65536 kB\n"), None);
+        assert_eq!(check_available_memory(ARGON2_MEMORY_KIB, None), Ok(()));
+        assert_eq!(check_available_memory(ARGON2_MEMORY_KIB, Some(1 << 20)), Ok(()));
+        let Err(AegixPassError::InsufficientMemory { suggestions, .. }) = check_available_memory(ARGON2_MEMORY_KIB, Some(12000)) else {
+            panic!("expected an InsufficientMemory error");
+        };
+        assert_eq!(suggestions, r#"{"memoryKib": 8192, "iterations": 5} or {"memoryKib": 4096, "iterations": 10}"#);
+    }
+
+    #[test]
+    fn test_profile_id_and_fingerprint() {
+        assert_eq!(load_argon2id_preset().profile_id(), "v1/argon2id(m=19456,t=2,p=1)/chaCha20/fisherYates");
+        let preset = load_default_preset();
+        assert_eq!(preset.profile_id(), "v1/sha256/chaCha20/fisherYates");
+        let fingerprint = preset.fingerprint();
+        assert!(fingerprint.starts_with("sha256:") && fingerprint.len() == 7 + 32);
+        let mut renamed = load_default_preset();
+        renamed.name = "Renamed".to_string();
+        assert_eq!(renamed.fingerprint(), fingerprint);
+        let mut longer = load_default_preset();
+        longer.length = 17;
+        assert_ne!(longer.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn test_preflight_runs_the_checks_without_the_kdf() {
+        let preset = load_default_preset();
+        let preflight = aegixpass_preflight("example.com", &preset, &SeedOptions::default()).unwrap();
+        assert_eq!(preflight.epoch, None);
+        assert_eq!(preflight.entropy_bits, estimate_entropy(&preset).unwrap());
+        assert_eq!(aegixpass_preflight("", &preset, &SeedOptions::default()), Err(AegixPassError::InputEmpty));
+        let options = SeedOptions { epoch: Some("2026-Q4"), ..SeedOptions::default() };
+        assert_eq!(aegixpass_preflight("example.com", &preset, &options), Err(AegixPassError::RotationEpochUnused));
+
+        let mut rotating = load_default_preset();
+        rotating.rotation = Some(RotationPolicy::Quarterly);
+        assert_eq!(aegixpass_preflight("example.com", &rotating, &options).unwrap().epoch.as_deref(), Some("2026-Q4"));
+        let mut short = load_default_preset();
+        short.length = 3;
+        assert_eq!(aegixpass_preflight("example.com", &short, &SeedOptions::default()), Err(AegixPassError::LengthTooShort(3, 4)));
+    }
+
+    #[test]
+    fn test_output_policy() {
+        let mut preset = load_default_preset();
+        preset.output_policy = serde_json::from_str(r#"{"clipboardOnly": true}"#).unwrap();
+        assert_eq!(preset.output_policy.check(Destination::Clipboard), Ok(()));
+        assert_eq!(preset.output_policy.check(Destination::Stdout), Err(AegixPassError::OutputNotAllowed("printing to standard output")));
+        assert!(preset.output_policy.check(Destination::Export).is_err());
+        let no_export = OutputPolicy { no_export: true, ..OutputPolicy::default() };
+        assert!(no_export.check(Destination::Stdout).is_ok() && no_export.check(Destination::Export).is_err());
+
+        // The policy never changes derived passwords, but relaxing it changes the fingerprint.
+        // 策略从不改变派生密码，但放宽它会改变指纹。
+        let default = load_default_preset();
+        assert_eq!(aegixpass_generator("pw", "example.com", &preset), aegixpass_generator("pw", "example.com", &default));
+        assert_ne!(preset.fingerprint(), default.fingerprint());
+    }
+
+    #[test]
+    fn test_error_hints() {
+        let mut preset = load_default_preset();
+        preset.length = 3;
+        let error = preset.validate().unwrap_err();
+        assert_eq!(error, AegixPassError::LengthTooShort(3, 4));
+        assert_eq!(
+            error.hint().as_deref(),
+            Some("length 3 < 4 charset groups: increase `length` to at least 4 or remove 1 charset group(s)")
+        );
+        preset.length = 16;
+        preset.charsets = vec!["a".into(); 9];
+        assert_eq!(preset.validate(), Err(AegixPassError::TooManyCharsetGroups(9, 8)));
+        assert_eq!(AegixPassError::Argon2Error("x".to_string()).hint(), None);
+    }
+
+    #[test]
+    fn test_platform_id_warning() {
+        let mut preset = load_default_preset();
+        assert_eq!(preset.platform_id_warning(), None);
+        preset.platform_id = "aegixpass.example.org".to_string();
+        assert!(preset.platform_id_warning().is_some());
+        preset.official = true;
+        assert!(preset.platform_id_warning().unwrap().contains("marked official"));
+    }
+
+    #[test]
+    fn test_deprecation_warning() {
+        // 2026-10-16T00:00:00Z.
+        let today = 1_792_108_800;
+        let mut preset = load_default_preset();
+        assert_eq!(preset.deprecation_warning_at(today), None);
+        preset.deprecated_after = Some("2026-10-16".to_string());
+        assert_eq!(preset.deprecation_warning_at(today), None);
+        preset.deprecated_after = Some("2026-10-15".to_string());
+        assert!(preset.deprecation_warning_at(today).unwrap().contains("ask its maintainer"));
+        preset.replaced_by = Some("AegixPass - Argon2id".to_string());
+        assert!(preset.deprecation_warning_at(today).unwrap().contains("switch to 'AegixPass - Argon2id'"));
+        assert_eq!(preset.validate(), Ok(()));
+        preset.deprecated_after = Some("2026-10".to_string());
+        assert_eq!(preset.validate(), Err(AegixPassError::InvalidDeprecationDate("2026-10".to_string())));
+    }
+}
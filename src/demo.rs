@@ -0,0 +1,139 @@
+// --- Demo ---
+// --- 演示 ---
+// Runs one derivation with fixed, public inputs and prints every intermediate value: the hash
+// input, the seeds, the guaranteed characters, the fill draws and the shuffle swaps. Someone
+// porting the algorithm to another language can compare their implementation against it stage
+// by stage instead of only against the final password.
+// 使用固定的公开输入运行一次派生，并输出每个中间值：哈希输入、种子、保证字符、填充抽取以及洗牌交换。
+// 将算法移植到其它语言的人可以逐个阶段地与之对照，而不仅仅是对照最终密码。
+
+use std::fmt::Write;
+
+use crate::core::{aegixpass_trace, AegixPassError, SeedOptions, ShuffleAlgorithm, Trace, TraceStep};
+use crate::presets::PresetTier;
+
+/// The demo master password. It is public: never use it for anything.
+// 演示用主密码。它是公开的：切勿用于任何用途。
+pub const DEMO_PASSWORD: &str = "AegixPass demo";
+/// The demo distinguish key. / 演示用区分密钥。
+pub const DEMO_DISTINGUISH_KEY: &str = "demo.example";
+/// The tier whose preset the demo uses: SHA-256, so it runs instantly.
+// 演示所使用预设的档位：SHA-256，因此可以即时运行。
+pub const DEMO_TIER: PresetTier = PresetTier::Fast;
+
+/// Runs the demo derivation and renders its stages as text.
+// 运行演示派生，并将其各阶段渲染为文本。
+pub fn demo() -> Result<String, AegixPassError> {
+    let preset = DEMO_TIER.preset()?;
+    let trace = aegixpass_trace(DEMO_PASSWORD, DEMO_DISTINGUISH_KEY, &preset, &SeedOptions::default())?;
+    let shuffle = match preset.shuffle_algorithm {
+        ShuffleAlgorithm::FisherYates => "Fisher-Yates",
+        ShuffleAlgorithm::Sattolo => "Sattolo",
+    };
+    let charsets: Vec<Vec<char>> = preset.charsets.iter().map(|group| group.chars.chars().collect()).collect();
+    Ok(render(&trace, DEMO_TIER.json().trim_end(), &charsets, preset.hash_algorithm.name(), preset.rng_algorithm.name(), shuffle))
+}
+
+fn render(trace: &Trace, preset_json: &str, charsets: &[Vec<char>], hash: &str, rng: &str, shuffle: &str) -> String {
+    // `write!` into a `String` cannot fail. / 向 `String` 中 `write!` 不会失败。
+    let mut out = String::new();
+    let _ = writeln!(out, "AegixPass demo: one derivation with public inputs, stage by stage.");
+    let _ = writeln!(out, "Never use these inputs for a real password.\n");
+    let _ = writeln!(out, "Preset ({} tier):\n{}\n", DEMO_TIER.name(), preset_json);
+    let _ = writeln!(out, "Master password: {:?}", DEMO_PASSWORD);
+    let _ = writeln!(out, "Distinguish key: {:?}\n", DEMO_DISTINGUISH_KEY);
+    let _ = writeln!(out, "Stage B: hash input\n  {}", trace.hash_input);
+    let _ = writeln!(out, "Stage B: master seed ({})\n  {}", hash, hex(&trace.master_seed));
+    if trace.seed != trace.master_seed {
+        let _ = writeln!(out, "Password seed (HKDF-SHA256, info \"AegixPass_Subkey:password\")\n  {}", hex(&trace.seed));
+    }
+
+    let mut password: Vec<char> = Vec::new();
+    let mut heading = "";
+    for step in &trace.steps {
+        let stage = match step {
+            TraceStep::Guaranteed { .. } => "Stage C: one character per charset (4 seed bytes as a little-endian u32, mod the charset size)",
+            TraceStep::Minimum { .. } => "Stage C: further characters of charsets with a min above 1",
+            TraceStep::WeightedGroup { .. } | TraceStep::Fill { .. } | TraceStep::Rejected { .. } => "Stage D: fill the remaining length",
+            TraceStep::Swap { .. } => "Stage E: shuffle",
+            TraceStep::Arrange { .. } => "Stage E: group layout (noAdjacentGroup)",
+            TraceStep::Repair { .. } => "Stage E: layout repair",
+            TraceStep::PostProcessed { .. } => "Stage F: post-processors",
+        };
+        if stage != heading {
+            if !password.is_empty() {
+                let _ = writeln!(out, "  -> {}", password.iter().collect::<String>());
+            }
+            heading = stage;
+            let _ = match step {
+                TraceStep::Swap { .. } => writeln!(out, "{} ({}, RNG {})", stage, shuffle, rng),
+                TraceStep::Arrange { .. } | TraceStep::Repair { .. } => writeln!(out, "{} (RNG {})", stage, rng),
+                TraceStep::Minimum { .. } | TraceStep::WeightedGroup { .. } | TraceStep::Fill { .. } | TraceStep::Rejected { .. } => {
+                    writeln!(out, "{} (RNG {} seeded with the password seed)", stage, rng)
+                }
+                _ => writeln!(out, "{}", stage),
+            };
+        }
+        let _ = match step {
+            TraceStep::Guaranteed { group, index_seed, index, c } => {
+                password.push(*c);
+                let bytes = &trace.group_seed[group * 4..group * 4 + 4];
+                let size = charsets[*group].len();
+                writeln!(out, "  charset {}: bytes {} -> {} mod {} = {} -> {:?}", group + 1, hex(bytes), index_seed, size, index, c)
+            }
+            TraceStep::Minimum { group, index, c } => {
+                password.push(*c);
+                writeln!(out, "  charset {}: draw {} of {} -> {:?}", group + 1, index, charsets[*group].len(), c)
+            }
+            TraceStep::WeightedGroup { group } => writeln!(out, "  weighted pick: charset {}", group + 1),
+            TraceStep::Fill { index, c } => {
+                password.push(*c);
+                writeln!(out, "  index {} -> {:?}", index, c)
+            }
+            TraceStep::Rejected { c } => writeln!(out, "  draw {:?} rejected (noRepeat)", c),
+            TraceStep::Swap { i, j } => {
+                password.swap(*i, *j);
+                writeln!(out, "  swap {} <-> {}", i, j)
+            }
+            TraceStep::Arrange { i, group, c } => {
+                password[*i] = *c;
+                writeln!(out, "  position {}: charset {} -> {:?}", i, group + 1, c)
+            }
+            TraceStep::Repair { i, j } => {
+                password.swap(*i, *j);
+                writeln!(out, "  move {} <-> {}", i, j)
+            }
+            TraceStep::PostProcessed { name, password } => writeln!(out, "  {} -> {}", name, password),
+        };
+    }
+    if !password.is_empty() && heading != "Stage F: post-processors" {
+        let _ = writeln!(out, "  -> {}", password.iter().collect::<String>());
+    }
+    let _ = writeln!(out, "\nPassword: {}", trace.password);
+    out
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aegixpass_generator;
+
+    #[test]
+    fn test_demo_replays_to_the_real_password() {
+        let report = demo().unwrap();
+        let password = aegixpass_generator(DEMO_PASSWORD, DEMO_DISTINGUISH_KEY, &DEMO_TIER.preset().unwrap()).unwrap();
+        assert!(report.ends_with(&format!("\nPassword: {}\n", password)));
+        // The state printed after the last swap is the password itself.
+        // 最后一次交换之后输出的状态就是密码本身。
+        assert!(report.contains(&format!("  -> {}\n\nPassword", password)));
+        assert_eq!(password, "#jMiT^Y$Yp$5aRtF");
+        assert_eq!(report.matches("  swap ").count(), 15);
+        assert_eq!(report.matches("  charset ").count(), 4);
+    }
+}
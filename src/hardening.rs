@@ -0,0 +1,105 @@
+// --- Hardened mode ---
+// --- 加固模式 ---
+// Checks how exposed derived secrets are to being written out of RAM: locks the process
+// memory with mlockall(2) so it cannot be paged out, and detects enabled swap devices.
+// Each problem found is reported so the caller can warn or refuse to proceed.
+// 检查派生出的秘密被写出内存的风险：通过 mlockall(2) 锁定进程内存使其无法被换出，并检测已启用的交换设备。
+// 发现的每个问题都会被报告，由调用者决定是发出警告还是拒绝继续。
+
+/// Memory headroom locked on top of the preset's own needs (64 MiB).
+// 在预设自身需求之外额外锁定的内存余量 (64 MiB)。
+pub const LOCK_HEADROOM_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Locks all current and future memory of the process and checks for swap, returning a
+/// description of every exposure found. `required_bytes` is how much memory the derivation
+/// will need locked; with less allowed, a locked allocation would abort the process, so memory
+/// is left unlocked and the shortfall is reported instead.
+// 锁定进程当前及将来的全部内存并检查交换分区，返回所发现的每个风险的描述。
+// `required_bytes` 是派生过程需要锁定的内存量；若允许的量不足，被锁定的内存分配会使进程中止，
+// 因此此时不锁定内存，而是报告不足之处。
+pub fn harden(required_bytes: u64) -> Vec<String> {
+    let mut issues = Vec::new();
+    if let Err(e) = lock_memory(required_bytes.saturating_add(LOCK_HEADROOM_BYTES)) {
+        issues.push(format!("memory could not be locked: {}", e));
+    }
+    let swaps = active_swap_devices();
+    if !swaps.is_empty() {
+        issues.push(format!("swap is enabled ({}); secrets may be paged to disk", swaps.join(", ")));
+    }
+    issues
+}
+
+#[cfg(unix)]
+fn lock_memory(required_bytes: u64) -> Result<(), String> {
+    // SAFETY: getrlimit/geteuid/mlockall take no pointers other than the valid `limit` below.
+    // SAFETY: 除下方有效的 `limit` 外，getrlimit/geteuid/mlockall 不接受任何指针。
+    unsafe {
+        let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        // Root normally holds CAP_IPC_LOCK, which bypasses RLIMIT_MEMLOCK. The cast is needed
+        // where `rlim_t` is not u64.
+        // root 通常拥有 CAP_IPC_LOCK，可绕过 RLIMIT_MEMLOCK。在 `rlim_t` 不是 u64 的平台上需要该转换。
+        #[allow(clippy::unnecessary_cast)]
+        if libc::geteuid() != 0
+            && libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) == 0
+            && limit.rlim_cur != libc::RLIM_INFINITY
+            && (limit.rlim_cur as u64) < required_bytes
+        {
+            return Err(format!(
+                "RLIMIT_MEMLOCK is {} KiB but {} KiB are needed (raise it with `ulimit -l`)",
+                limit.rlim_cur / 1024,
+                required_bytes / 1024
+            ));
+        }
+        if libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) != 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn lock_memory(_required_bytes: u64) -> Result<(), String> {
+    Err("memory locking is not supported on this platform".to_string())
+}
+
+/// Lists active swap devices backed by storage. zram devices live in RAM and are ignored.
+// 列出以存储设备为后端的已启用交换设备。zram 设备位于内存中，因此被忽略。
+#[cfg(target_os = "linux")]
+fn active_swap_devices() -> Vec<String> {
+    std::fs::read_to_string("/proc/swaps")
+        .map(|swaps| parse_swaps(&swaps))
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn active_swap_devices() -> Vec<String> {
+    Vec::new()
+}
+
+/// Parses the contents of `/proc/swaps`. / 解析 `/proc/swaps` 的内容。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_swaps(swaps: &str) -> Vec<String> {
+    swaps
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|device| !device.starts_with("/dev/zram"))
+        .map(str::to_string)
+        .collect()
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_swaps() {
+        let swaps = "Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n\
+            /swapfile                               file\t\t2097148\t\t0\t\t-2\n\
+            /dev/zram0                              partition\t4194300\t\t0\t\t100\n";
+        assert_eq!(parse_swaps(swaps), vec!["/swapfile".to_string()]);
+        assert!(parse_swaps("Filename\tType\tSize\tUsed\tPriority\n").is_empty());
+    }
+}
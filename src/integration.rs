@@ -0,0 +1,76 @@
+// --- Integration errors ---
+// --- 集成错误 ---
+// Failures of the programs, services and files around a derivation rather than of the derivation
+// itself: the clipboard, pinentry, Vault, the keychain, the site list, the escrow store, backups
+// and so on. They reach callers wrapped in `AegixPassError::Integration`, so library functions
+// keep a single error type while `AegixPassError` itself only describes derivation errors.
+// 派生过程周边的程序、服务与文件（剪贴板、pinentry、Vault、钥匙串、站点列表、托管存储、备份等）发生的错误，而不是派生本身的错误。
+// 它们被包装在 `AegixPassError::Integration` 中交给调用方，因此库函数仍只有一种错误类型，而 `AegixPassError` 本身只描述派生错误。
+
+use thiserror::Error;
+
+/// A failure of something the derivation talks to. / 派生过程所交互的外部事物发生的错误。
+#[derive(Error, Debug, PartialEq)]
+pub enum IntegrationError {
+    #[error("Could not use the clipboard: {0}")]
+    Clipboard(String),
+    #[error("Could not deliver the output: {0}")]
+    OutputSink(String),
+    #[error("QR code rendering failed: {0}")]
+    QrCode(String),
+    #[error("Vault write failed: {0}")]
+    Vault(String),
+    #[error("Age encryption failed: {0}")]
+    Age(String),
+    #[error("Site list error: {0}")]
+    Sites(String),
+    #[error("Escrow store error: {0}")]
+    Store(String),
+    #[error("The escrow store cannot be decrypted with this master password and preset.")]
+    StoreWrongKey,
+    #[error("Import failed: {0}")]
+    Import(String),
+    #[error("Could not fetch the remote preset: {0}")]
+    RemotePreset(String),
+    #[error("KDF cache error: {0}")]
+    Cache(String),
+    #[error("Could not read the pepper: {0}")]
+    Pepper(String),
+    #[error("pinentry failed: {0}")]
+    Pinentry(String),
+    #[error("Invalid settings file: {0}")]
+    Settings(String),
+    #[error("Backup failed: {0}")]
+    Backup(String),
+    #[error("Audit failed: {0}")]
+    Audit(String),
+    #[error("Hardened mode refused to proceed: {0}")]
+    MemoryExposure(String),
+}
+
+impl IntegrationError {
+    /// A concrete suggestion for fixing the error (see [`crate::AegixPassError::hint`]).
+    // 修复该错误的具体建议（参见 [`crate::AegixPassError::hint`]）。
+    pub fn hint(&self) -> Option<String> {
+        use IntegrationError::*;
+        Some(match self {
+            Clipboard(_) => "install pbcopy (macOS), wl-copy (Wayland) or xclip (X11); on Windows, clip.exe is built in".to_string(),
+            OutputSink(_) => "check that the destination is writable; the keychain needs secret-tool (libsecret)".to_string(),
+            Vault(_) => "check VAULT_ADDR and VAULT_TOKEN, and that the token may write to the target path".to_string(),
+            Sites(_) => "check the listed sites with `aegixpass find`; a damaged site list can be moved aside to start over".to_string(),
+            Store(_) => "list the entries with `aegixpass store list`; a damaged store can be restored from a backup".to_string(),
+            StoreWrongKey => "use the master password and preset the store's entries were saved with".to_string(),
+            Import(_) => "export a CSV from Bitwarden, Chrome or Firefox; its header needs a `login_uri` or `url` column".to_string(),
+            RemotePreset(_) => "check the URL and your network; only https:// URLs are accepted".to_string(),
+            Cache(_) => "run `aegixpass cache clear` to reset the cache, or drop --kdf-cache".to_string(),
+            Pepper(_) => "set AEGIXPASS_PEPPER, or store the pepper in the keychain with `secret-tool store --label=AegixPass service aegixpass account pepper` (Linux) or `security add-generic-password -s aegixpass -a pepper -w` (macOS)".to_string(),
+            Pinentry(_) => "install a pinentry (pinentry-gnome3, pinentry-qt, pinentry-mac or Gpg4win), or set \"pinentryProgram\" in settings.json".to_string(),
+            Settings(_) => "fix settings.json in the config directory, or move it aside to use the defaults".to_string(),
+            Audit(_) => "pass the directory holding your presets with --dir".to_string(),
+            Backup(_) => "encrypted backups need the passphrase they were created with; pass --force to replace files that differ".to_string(),
+            MemoryExposure(_) => "raise the memory-lock limit with `ulimit -l`, disable swap, or rerun with --hardened=warn".to_string(),
+            // The underlying message already says what went wrong. / 底层信息已经说明了出错原因。
+            QrCode(_) | Age(_) => return None,
+        })
+    }
+}
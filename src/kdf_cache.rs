@@ -0,0 +1,253 @@
+// --- KDF result cache ---
+// --- KDF 结果缓存 ---
+// An opt-in, on-disk cache of master seeds produced by the slow hash algorithms (Argon2id,
+// scrypt), so repeated CLI runs skip the multi-hundred-millisecond derivation. Each entry is
+// looked up by, and encrypted with, keys derived from the full hash input (which contains the
+// master password) and a random key that never leaves this device. Without the device key the
+// cache reveals nothing; with it, an attacker can test master-password guesses at HMAC speed
+// instead of KDF speed, which is why the cache is off by default and entries expire.
+// 可选启用的磁盘缓存，保存慢速哈希算法（Argon2id、scrypt）生成的主种子，使命令行的重复运行无需再等待
+// 数百毫秒的派生。每个条目的查找键与加密密钥都由完整的哈希输入（其中包含主密码）和一个从不离开本设备的
+// 随机密钥派生。没有设备密钥时缓存不会泄露任何信息；若攻击者拿到设备密钥，则可以按 HMAC 的速度而非 KDF
+// 的速度猜测主密码，因此缓存默认关闭且条目会过期。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+use crate::write_guard;
+
+/// Hash algorithms slow enough to be worth caching. / 慢到值得缓存的哈希算法。
+pub const CACHED_ALGORITHMS: [&str; 2] = ["argon2id", "scrypt"];
+
+/// How long entries stay valid when no TTL is given (one hour).
+// 未指定 TTL 时条目的有效期（一小时）。
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+const CACHE_VERSION: u32 = 1;
+const CACHE_FILE: &str = "kdf-cache.json";
+const DEVICE_KEY_FILE: &str = "device.key";
+
+static ACTIVE: Mutex<Option<KdfCache>> = Mutex::new(None);
+
+/// A KDF cache stored in a directory, with entries valid for `ttl`.
+// 存放在某个目录中的 KDF 缓存，条目在 `ttl` 内有效。
+#[derive(Debug, Clone, PartialEq)]
+pub struct KdfCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    entries: Vec<CacheEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    id: String,
+    created: u64,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl KdfCache {
+    /// A cache in `dir` whose entries expire after `ttl`. / 位于 `dir`、条目在 `ttl` 后过期的缓存。
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self { dir: dir.into(), ttl }
+    }
+
+    /// The directory holding the cache. / 存放缓存的目录。
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the cached seed for this hash input, if present, unexpired and authentic.
+    // 返回该哈希输入对应的缓存种子（若存在、未过期且验证通过）。
+    pub fn get(&self, algorithm: &str, input: &[u8]) -> Option<[u8; 32]> {
+        let device_key = self.read_device_key().ok()??;
+        let (id, key) = entry_keys(&device_key, algorithm, input);
+        let now = unix_time();
+        let entry = self
+            .load()
+            .ok()?
+            .entries
+            .into_iter()
+            .find(|e| e.id == id && !self.expired(e, now))?;
+        let nonce = BASE64.decode(entry.nonce).ok().filter(|n| n.len() == 12)?;
+        let ciphertext = BASE64.decode(entry.ciphertext).ok()?;
+        let plaintext = ChaCha20Poly1305::new(&key.into()).decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref()).ok()?;
+        plaintext.try_into().ok()
+    }
+
+    /// Encrypts and stores a seed, dropping expired entries and any older copy of it.
+    // 加密并保存种子，同时删除过期条目及其旧副本。
+    pub fn put(&self, algorithm: &str, input: &[u8], seed: &[u8; 32]) -> Result<(), AegixPassError> {
+        // The lock also covers creating the device key. / 该锁同时保护设备密钥的创建。
+        let cache_path = self.dir.join(CACHE_FILE);
+        write_guard::create_dir_all(&self.dir).map_err(|e| self.error("", e))?;
+        let _lock = write_guard::lock(&cache_path).map_err(|e| self.error(CACHE_FILE, e))?;
+        let device_key = self.device_key()?;
+        let (id, key) = entry_keys(&device_key, algorithm, input);
+        let mut nonce = [0u8; 12];
+        rand::rng().fill_bytes(&mut nonce);
+        let ciphertext = ChaCha20Poly1305::new(&key.into())
+            .encrypt(Nonce::from_slice(&nonce), seed.as_ref())
+            .map_err(|e| IntegrationError::Cache(e.to_string()))?;
+
+        let now = unix_time();
+        let mut file = self.load().unwrap_or_default();
+        file.version = CACHE_VERSION;
+        file.entries.retain(|e| e.id != id && !self.expired(e, now));
+        file.entries.push(CacheEntry {
+            id,
+            created: now,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        });
+        let json = serde_json::to_string_pretty(&file).map_err(|e| IntegrationError::Cache(e.to_string()))?;
+        write_guard::write(&cache_path, json + "\n").map_err(|e| self.error(CACHE_FILE, e))
+    }
+
+    /// Deletes every entry and the device key, so old entries can never be decrypted again.
+    /// Returns whether anything was removed.
+    // 删除所有条目及设备密钥，使旧条目再也无法被解密。返回是否删除了任何内容。
+    pub fn clear(&self) -> Result<bool, AegixPassError> {
+        let mut removed = false;
+        for name in [CACHE_FILE, DEVICE_KEY_FILE] {
+            match write_guard::remove_file(&self.dir.join(name)) {
+                Ok(()) => removed = true,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(self.error(name, e)),
+            }
+        }
+        Ok(removed)
+    }
+
+    fn expired(&self, entry: &CacheEntry, now: u64) -> bool {
+        entry.created.saturating_add(self.ttl.as_secs()) <= now || entry.created > now
+    }
+
+    fn load(&self) -> Result<CacheFile, AegixPassError> {
+        let json = match std::fs::read_to_string(self.dir.join(CACHE_FILE)) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(CacheFile::default()),
+            Err(e) => return Err(self.error(CACHE_FILE, e)),
+        };
+        let file: CacheFile = serde_json::from_str(&json).map_err(|e| self.error(CACHE_FILE, e))?;
+        if file.version != CACHE_VERSION {
+            return Ok(CacheFile::default());
+        }
+        Ok(file)
+    }
+
+    fn read_device_key(&self) -> Result<Option<[u8; 32]>, AegixPassError> {
+        match std::fs::read(self.dir.join(DEVICE_KEY_FILE)) {
+            Ok(bytes) => Ok(bytes.try_into().ok()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(self.error(DEVICE_KEY_FILE, e)),
+        }
+    }
+
+    /// Reads the device key, creating it on first use. / 读取设备密钥，首次使用时创建。
+    fn device_key(&self) -> Result<[u8; 32], AegixPassError> {
+        if let Some(key) = self.read_device_key()? {
+            return Ok(key);
+        }
+        let mut key = [0u8; 32];
+        rand::rng().fill_bytes(&mut key);
+        write_guard::write(&self.dir.join(DEVICE_KEY_FILE), key).map_err(|e| self.error(DEVICE_KEY_FILE, e))?;
+        Ok(key)
+    }
+
+    fn error(&self, name: &str, e: impl std::fmt::Display) -> AegixPassError {
+        IntegrationError::Cache(format!("{}: {}", self.dir.join(name).display(), e)).into()
+    }
+}
+
+/// The entry ID and encryption key for a hash input, both keyed by the device key.
+// 哈希输入对应的条目 ID 与加密密钥，二者均以设备密钥为键。
+fn entry_keys(device_key: &[u8; 32], algorithm: &str, input: &[u8]) -> (String, [u8; 32]) {
+    let derive = |purpose: &str| -> [u8; 32] {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(device_key).expect("HMAC accepts keys of any length");
+        for part in [purpose.as_bytes(), algorithm.as_bytes(), input] {
+            mac.update(&(part.len() as u64).to_le_bytes());
+            mac.update(part);
+        }
+        mac.finalize().into_bytes().into()
+    };
+    (BASE64.encode(derive("AegixPass_KdfCache_Id")), derive("AegixPass_KdfCache_Key"))
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Turns on the process-wide cache used by every derivation, or turns it off with `None`.
+// 开启整个进程中所有派生共用的缓存；传入 `None` 则关闭。
+pub fn set_active(cache: Option<KdfCache>) {
+    *ACTIVE.lock().unwrap_or_else(|e| e.into_inner()) = cache;
+}
+
+/// Runs `compute` unless the active cache already holds its result. Cache failures never fail
+/// the derivation; the seed is simply computed again.
+// 若活动缓存中已有结果则直接返回，否则运行 `compute`。缓存出错不会导致派生失败，只会重新计算种子。
+pub(crate) fn cached(
+    algorithm: &str,
+    input: &[u8],
+    compute: impl FnOnce() -> Result<[u8; 32], AegixPassError>,
+) -> Result<[u8; 32], AegixPassError> {
+    let cache = ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let Some(cache) = cache.filter(|_| CACHED_ALGORITHMS.contains(&algorithm)) else {
+        return compute();
+    };
+    if let Some(seed) = cache.get(algorithm, input) {
+        return Ok(seed);
+    }
+    let seed = compute()?;
+    let _ = cache.put(algorithm, input, &seed);
+    Ok(seed)
+}
+
+/// The default cache directory, e.g. `~/.cache/aegixpass` on Linux.
+// 默认的缓存目录，例如 Linux 上的 `~/.cache/aegixpass`。
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("aegixpass"))
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip_and_clear() {
+        let dir = std::env::temp_dir().join(format!("aegixpass-kdf-cache-{}", std::process::id()));
+        let cache = KdfCache::new(&dir, DEFAULT_TTL);
+        let seed = [7u8; 32];
+        assert_eq!(cache.get("argon2id", b"input"), None);
+        cache.put("argon2id", b"input", &seed).unwrap();
+        assert_eq!(cache.get("argon2id", b"input"), Some(seed));
+        assert_eq!(cache.get("argon2id", b"other input"), None);
+        assert_eq!(cache.get("scrypt", b"input"), None);
+        // A zero TTL expires every entry. / TTL 为零时所有条目都已过期。
+        assert_eq!(KdfCache::new(&dir, Duration::ZERO).get("argon2id", b"input"), None);
+        assert!(cache.clear().unwrap());
+        assert_eq!(cache.get("argon2id", b"input"), None);
+        assert!(!cache.clear().unwrap());
+        let _ = std::fs::remove_dir(&dir);
+    }
+}
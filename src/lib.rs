@@ -1,8 +1,86 @@
 // 声明 `core` 模块，它包含了所有的核心实现。
 pub mod core;
+// 声明 `answer` 模块，为安全问题派生确定性的无意义答案。
+pub mod answer;
+// 声明 `audit` 模块，离线检查本地预设与站点列表并给出评分。
+pub mod audit;
+// 声明 `backup` 模块，将非机密的配置打包备份并在新机器上恢复。
+pub mod backup;
+// 声明 `cert` 模块，用于确定性地生成自签名证书。
+pub mod cert;
+// 声明 `charsets` 模块，在写入预设之前检查候选字符集。
+pub mod charsets;
+// 声明 `clipboard` 模块，通过平台自带的命令行工具复制到剪贴板。
+pub mod clipboard;
+// 声明 `compare` 模块，用于找出导致两个预设派生结果不同的字段。
+pub mod compare;
+// 声明 `demo` 模块，以公开输入运行一次派生并输出每个阶段的中间值。
+pub mod demo;
+// 声明 `hardening` 模块，在加固模式下锁定内存并检测交换分区。
+pub mod hardening;
+// 声明 `integration` 模块，定义派生之外的剪贴板、Vault、站点列表等集成错误。
+pub mod integration;
+// 声明 `kdf_cache` 模块，可选地在磁盘上缓存加密后的慢速 KDF 结果。
+pub mod kdf_cache;
+// 声明 `openpgp` 模块，仅在启用 "pgp" 特性时编译。
+#[cfg(feature = "pgp")]
+pub mod openpgp;
+// 声明 `otp` 模块，负责派生 TOTP 密钥并生成验证码。
+pub mod otp;
+// 声明 `output` 模块，负责将密码渲染为各种输出格式。
+pub mod output;
+// 声明 `passphrase` 模块，从预设指定的词表派生口令短语。
+pub mod passphrase;
+// 声明 `pepper` 模块，从环境变量或操作系统钥匙串读取胡椒值。
+pub mod pepper;
+// 声明 `pinentry` 模块，在没有终端时通过 pinentry 对话框读取秘密。
+pub mod pinentry;
+// 声明 `presets` 模块，提供 `setup` 可选的官方预设档位。
+pub mod presets;
+// 声明 `recovery` 模块，为站点派生一组确定性的恢复码。
+pub mod recovery;
+// 声明 `remote` 模块，仅在启用 "remote-preset" 特性时编译。
+#[cfg(feature = "remote-preset")]
+pub mod remote;
+// 声明 `registry` 模块，保存哈希、RNG 与后处理算法的全局注册表。
+pub mod registry;
+// 声明 `rotation` 模块，根据日期计算预设的轮换周期。
+pub mod rotation;
+// 声明 `sandbox` 模块，在资源上限之内为不受信任的多租户调用者派生密码。
+pub mod sandbox;
+// 声明 `selftest` 模块，对已编译的算法进行已知答案测试。
+pub mod selftest;
+// 声明 `settings` 模块，负责读取程序自身的偏好设置 `settings.json`。
+pub mod settings;
+// 声明 `sink` 模块，定义派生秘密的输出目标（标准输出、剪贴板、钥匙串、文件、回调）。
+pub mod sink;
+// 声明 `sites` 模块，负责读写不含密码的站点列表 `sites.json`。
+pub mod sites;
+// 声明 `spec` 模块，根据代码所用的常量生成算法规范。
+pub mod spec;
+// 声明 `store` 模块，以主密码派生的密钥加密保存无法派生的旧密码。
+pub mod store;
+// 声明 `strength` 模块，用于估算主密码的强度。
+pub mod strength;
+// 声明 `username` 模块，为每个站点派生可读的或由单词组成的用户名。
+pub mod username;
+// 声明 `vault` 模块，仅在启用 "vault" 特性时编译。
+#[cfg(feature = "vault")]
+pub mod vault;
+// 声明 `write_guard` 模块，所有文件写入都经过它，以便在临时模式下拒绝写入。
+pub mod write_guard;
 
 // 将 `core` 模块中的关键公共项重新导出到库的顶层命名空间。
 // 这样外部使用者就可以通过 `aegixpass::aegixpass_generator` 的方式直接调用，
 // 而不是 `aegixpass::core::aegixpass_generator`，让 API 更简洁。
-pub use crate::core::{aegixpass_generator, AegixPassError, Preset};
-
+pub use crate::core::{
+    aegixpass_derive_key, aegixpass_derive_key_with, aegixpass_generate_batch, aegixpass_generate_batch_with, aegixpass_generator,
+    aegixpass_generator_from_json,
+    aegixpass_generator_multi, aegixpass_generator_multi_with, aegixpass_generator_scored, aegixpass_generator_with, aegixpass_preflight,
+    aegixpass_subkeys, aegixpass_trace, estimate_entropy, AegixPassError, Preflight, Preset, PresetLimits, Purpose, SeedOptions, Trace,
+    TraceStep,
+};
+pub use crate::integration::IntegrationError;
+// 调试用的 RNG 消耗量统计，仅在启用 "rng-accounting" 特性时编译。
+#[cfg(feature = "rng-accounting")]
+pub use crate::core::{aegixpass_rng_accounting, GenerationTrace};
@@ -1,40 +1,1165 @@
-use std::path::PathBuf;
-use clap::Parser;
+use std::io::{IsTerminal, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde_json::Value;
 // 从我们自己的库 `aegixpass` 中导入所需的函数和结构体。
-use aegixpass::{aegixpass_generator, AegixPassError, Preset};
+use aegixpass::{aegixpass_derive_key_with, aegixpass_generate_batch_with, aegixpass_generator_multi_with, aegixpass_generator_with, aegixpass_preflight};
+use aegixpass::{AegixPassError, IntegrationError, Preset, PresetLimits, SeedOptions};
+use aegixpass::{answer, kdf_cache, otp, output, passphrase, recovery, username, write_guard};
+use aegixpass::username::UsernameStyle;
+use aegixpass::core::{Destination, SUPPORTED_VERSIONS};
+use aegixpass::settings::{Settings, SETTINGS_FILE};
+use aegixpass::backup::Backup;
+use aegixpass::kdf_cache::KdfCache;
+use aegixpass::presets::PresetTier;
+use aegixpass::sink::{self, ClipboardSink, StdoutSink};
+use aegixpass::sites::SitesFile;
+use aegixpass::store::{StoreFile, StoreKey, STORE_FILE};
+
+// 交互式输入主密码（带实时强度条），仅供命令行使用。
+mod prompt;
+
+/// Output formats supported by the command line.
+// 命令行支持的输出格式。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum OutputFormat {
+    /// The bare password. / 仅输出密码本身。
+    Plain,
+    /// A `KEY='password'` line for dotenv files. / 适用于 dotenv 文件的 `KEY='password'` 行。
+    Dotenv,
+    /// A PostgreSQL SCRAM-SHA-256 verifier. / PostgreSQL 的 SCRAM-SHA-256 校验值。
+    #[value(name = "scram-sha-256")]
+    ScramSha256,
+    /// A MySQL mysql_native_password hash. / MySQL 的 mysql_native_password 哈希。
+    MysqlNative,
+    /// A PowerShell expression evaluating to a SecureString. / 求值结果为 SecureString 的 PowerShell 表达式。
+    #[value(name = "ps-securestring")]
+    PsSecureString,
+    /// An Apache htpasswd line with a bcrypt hash (requires --user). / 带有 bcrypt 哈希的 Apache htpasswd 行（需要 --user）。
+    Htpasswd,
+    /// A crypt(3) SHA-512 hash for /etc/shadow. / 用于 /etc/shadow 的 crypt(3) SHA-512 哈希。
+    #[value(alias = "shadow")]
+    Sha512Crypt,
+    /// A base64url-encoded HMAC signing key. / base64url 编码的 HMAC 签名密钥。
+    JwtKey,
+    /// An HMAC signing key as a JSON Web Key. / 以 JSON Web Key 形式表示的 HMAC 签名密钥。
+    Jwk,
+    /// Raw key material as hex, e.g. for encryption keys (see --key-label). / 十六进制的原始密钥材料，例如用作加密密钥（参见 --key-label）。
+    KeyHex,
+    /// Raw key material as standard base64. / 标准 base64 形式的原始密钥材料。
+    KeyBase64,
+    /// The current one-time code (see --otp-type). / 当前的一次性验证码（参见 --otp-type）。
+    #[value(alias = "totp")]
+    Otp,
+    /// The base32 OTP secret. / base32 编码的 OTP 密钥。
+    #[value(alias = "totp-secret")]
+    OtpSecret,
+    /// An otpauth:// URI for authenticator apps. / 供身份验证器应用使用的 otpauth:// URI。
+    OtpauthUri,
+    /// Words from the preset's wordlist (see "passphrase" in the preset). / 取自预设词表的单词（参见预设中的 "passphrase"）。
+    Passphrase,
+    /// A username for the site (see --username-style). / 该站点的用户名（参见 --username-style）。
+    Username,
+}
+
+/// Username styles accepted by `--username-style`.
+// `--username-style` 接受的用户名方式。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum UsernameStyleArg {
+    /// Consonant-vowel syllables and two digits, e.g. tebikami81. / 辅音-元音音节加两位数字，例如 tebikami81。
+    Pronounceable,
+    /// Two words from the preset's passphrase wordlist and two digits. / 取自预设口令短语词表的两个单词加两位数字。
+    Words,
+}
+
+/// OTP flavors accepted by `--otp-type`.
+// `--otp-type` 接受的 OTP 类型。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum OtpTypeArg {
+    /// Time-based codes (RFC 6238). / 基于时间的验证码 (RFC 6238)。
+    Totp,
+    /// Counter-based codes (RFC 4226); see --hotp-counter. / 基于计数器的验证码 (RFC 4226)；参见 --hotp-counter。
+    Hotp,
+    /// Steam Guard 5-character codes. / Steam 令牌的 5 位验证码。
+    Steam,
+}
+
+/// Byte encoding of what is printed. / 打印内容的字节编码。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum OutputEncodingArg {
+    /// Write text as the terminal expects it: UTF-8, shown through the Unicode console API on
+    /// Windows. / 按终端的预期写出文本：UTF-8，在 Windows 上通过 Unicode 控制台 API 显示。
+    Console,
+    /// UTF-8 bytes. / UTF-8 字节。
+    Utf8,
+    /// UTF-16 little-endian bytes, as Windows PowerShell writes files. / UTF-16 小端序字节，与 Windows PowerShell 写文件时一致。
+    Utf16le,
+}
+
+/// Where `--pepper` reads the pepper from. / `--pepper` 读取胡椒值的来源。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum PepperArg {
+    /// The AEGIXPASS_PEPPER environment variable. / 环境变量 AEGIXPASS_PEPPER。
+    Env,
+    /// The OS keychain entry with service 'aegixpass' and account 'pepper'. / 服务名为 'aegixpass'、账户名为 'pepper' 的操作系统钥匙串条目。
+    Keychain,
+}
+
+/// Shells `--shell-escape` can quote for. / `--shell-escape` 可以为之加引号的 shell。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ShellEscapeArg {
+    /// Print the value as it is. / 原样输出。
+    None,
+    /// bash and other POSIX shells. / bash 及其它 POSIX shell。
+    Bash,
+    Powershell,
+}
+
+/// Line endings of what is printed. / 打印内容的换行符。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum NewlineArg {
+    Lf,
+    Crlf,
+}
+
+/// How salts are chosen for verifier outputs.
+// 校验值类输出的盐选择方式。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum SaltArg {
+    /// Derive the salt from the master seed (reproducible). / 由主种子派生盐（可重复生成）。
+    Deterministic,
+    /// Use a fresh random salt. / 使用新的随机盐。
+    Random,
+}
+
 
 /// 使用 clap 定义命令行参数的结构体。
+/// Without a subcommand the program generates a password, as it always has.
+// 不带子命令时，程序与以往一样直接生成密码。
 #[derive(Parser, Debug)]
+#[command(subcommand_negates_reqs = true)]
 struct CliArgs {
-    /// Path to the preset JSON configuration file.
-    // 指定预设的JSON配置文件路径。
-    #[arg(short, long, value_name = "FILE_PATH")]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the preset JSON configuration file; '-' reads it from stdin, and an https:// URL
+    /// fetches it (with the remote-preset feature and --config-fingerprint).
+    // 指定预设的JSON配置文件路径；'-' 表示从标准输入读取，https:// URL 表示远程获取（需要 remote-preset 特性及 --config-fingerprint）。
+    #[arg(short, long, value_name = "FILE_PATH", global = true)]
     config: Option<PathBuf>,
 
-    /// Your master password, known only to you.
-    // 你的主密码，只有你自己知道。
+    /// Expected preset fingerprint ('sha256:...', see --version-of-output); required for URLs.
+    // 预期的预设指纹（'sha256:...'，参见 --version-of-output）；使用 URL 时必须指定。
+    #[arg(long, value_name = "FINGERPRINT", global = true)]
+    config_fingerprint: Option<String>,
+
+    /// Guarantee that nothing is written to disk (no state, cache or site list), for shared machines.
+    // 保证不向磁盘写入任何内容（无状态、缓存或站点列表），适用于共享的机器。
+    #[arg(long, global = true)]
+    ephemeral: bool,
+
+    /// Lock memory and check for swap before deriving; on a problem, warn or refuse (the default).
+    // 派生前锁定内存并检查交换分区；发现问题时发出警告或拒绝继续（默认）。
+    #[arg(
+        long,
+        value_enum,
+        value_name = "POLICY",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "refuse",
+        global = true
+    )]
+    hardened: Option<HardenedPolicy>,
+
+    /// Cache slow Argon2id/scrypt results on this device, encrypted, to speed up repeated runs.
+    // 在本设备上加密缓存较慢的 Argon2id/scrypt 结果，以加快重复运行。
+    #[arg(long, global = true)]
+    kdf_cache: bool,
+
+    /// How long cached KDF results stay valid, in seconds.
+    // 缓存的 KDF 结果的有效期（秒）。
+    #[arg(long, value_name = "SECONDS", default_value_t = aegixpass::kdf_cache::DEFAULT_TTL.as_secs(), global = true)]
+    cache_ttl: u64,
+
+    /// For the integration tests only: use DIR as the config and cache directory, answer every
+    /// prompt with the next line of stdin, and copy to DIR/clipboard.txt instead of the clipboard.
+    // 仅供集成测试使用：以 DIR 作为配置与缓存目录，用标准输入的下一行回答每个提示，并复制到 DIR/clipboard.txt 而不是剪贴板。
+    #[arg(long, value_name = "DIR", global = true, hide = true)]
+    test_mode: Option<PathBuf>,
+
+    #[command(flatten)]
+    generate: GenerateArgs,
+}
+
+/// What `--hardened` does when memory cannot be protected.
+// `--hardened` 在无法保护内存时的处理方式。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum HardenedPolicy {
+    /// Print a warning and continue. / 打印警告并继续。
+    Warn,
+    /// Stop with an error. / 以错误终止。
+    Refuse,
+}
+
+/// Subcommands for derivations other than a plain password.
+// 用于密码之外的其它派生的子命令。
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Derive a key pair and emit a self-signed X.509 certificate and private key as PEM.
+    // 派生密钥对，并以 PEM 格式输出自签名 X.509 证书及私钥。
+    Cert(CertArgs),
+
+    /// Derive an OpenPGP key (Ed25519 primary, Curve25519 subkey) and print it ASCII-armored.
+    // 派生 OpenPGP 密钥（Ed25519 主密钥，Curve25519 子密钥）并以 ASCII 封装格式输出。
+    #[cfg(feature = "pgp")]
+    Pgp(PgpArgs),
+
+    /// Create the config directory, choose a default preset tier and run the self-test.
+    // 创建配置目录、选择默认预设档位并运行自检。
+    Setup(SetupArgs),
+
+    /// Check that this build derives the expected passwords for every compiled-in algorithm.
+    // 检查本次构建对每种已编译算法派生出的密码是否符合预期。
+    SelfTest,
+
+    /// Derive a password from fixed public inputs, printing every stage's intermediate values.
+    // 使用固定的公开输入派生密码，并输出每个阶段的中间值。
+    Demo,
+
+    /// Print the algorithm specification, generated from the constants this build derives with.
+    // 输出算法规范，由本次构建派生所用的常量生成。
+    Spec(SpecArgs),
+
+    /// Add the sites of a Bitwarden, Chrome or Firefox CSV export to sites.json (no secrets are stored).
+    // 将 Bitwarden、Chrome 或 Firefox 的 CSV 导出文件中的站点添加到 sites.json（不保存任何秘密）。
+    Import(ImportArgs),
+
+    /// List which sites still use passwords stored in the old manager and which use derived ones.
+    // 列出哪些站点仍在使用旧密码管理器中保存的密码，哪些已改用派生密码。
+    MigrateReport(MigrateReportArgs),
+
+    /// List the sites whose derived passwords are older than their maxAgeDays allows.
+    // 列出派生密码的使用天数超过其 maxAgeDays 的站点。
+    Status(StatusArgs),
+
+    /// Derive every listed site's password under the old and new master passwords, as a checklist
+    /// for changing the master password.
+    // 以新旧两个主密码分别派生每个已列出站点的密码，作为更换主密码时的核对清单。
+    Rekey(RekeyArgs),
+
+    /// Search the site list by distinguish key, username, login URL or notes.
+    // 按区分密钥、用户名、登录 URL 或备注搜索站点列表。
+    Find(FindArgs),
+
+    /// Explain which fields of two preset files make them derive different passwords.
+    // 解释两个预设文件中哪些字段导致它们派生出不同的密码。
+    WhyDifferent(WhyDifferentArgs),
+
+    /// Manage the KDF result cache enabled by --kdf-cache.
+    // 管理由 --kdf-cache 启用的 KDF 结果缓存。
+    #[command(subcommand)]
+    Cache(CacheCommand),
+
+    /// Bundle the presets and site list into one file, or restore them on another machine.
+    // 将预设与站点列表打包为一个文件，或在另一台机器上恢复它们。
+    #[command(subcommand)]
+    Backup(BackupCommand),
+
+    /// Keep passwords that cannot be derived, encrypted with a key derived from the master password.
+    // 保存无法派生的密码，以主密码派生的密钥加密。
+    #[command(subcommand)]
+    Store(StoreCommand),
+
+    /// Print the password for KEY: the saved secret when the store has one, the derived password otherwise.
+    // 输出 KEY 的密码：托管存储中有保存的秘密时输出该秘密，否则输出派生的密码。
+    Get(GetArgs),
+
+    /// Derive a nonsense answer to one of KEY's security questions, e.g. "mother's maiden name".
+    // 为 KEY 的某个安全问题派生无意义的答案，例如 "mother's maiden name"。
+    Answer(AnswerArgs),
+
+    /// Derive a set of numeric recovery codes for KEY, the same set every time.
+    // 为 KEY 派生一组数字恢复码，每次都是同一组。
+    RecoveryCodes(RecoveryCodesArgs),
+
+    /// Inspect candidate charset strings before putting them in a preset.
+    // 在写入预设之前检查候选字符集字符串。
+    #[command(subcommand)]
+    Charsets(CharsetsCommand),
+
+    /// Check the local presets and site list for weak settings, offline, and print a scored report.
+    // 离线检查本地预设与站点列表中的薄弱设置，并输出带评分的报告。
+    Audit(AuditArgs),
+}
+
+/// Formats of the `spec` subcommand. / `spec` 子命令的格式。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum SpecFormat {
+    /// Markdown. / Markdown 格式。
+    Md,
+}
+
+/// Arguments of the `spec` subcommand.
+// `spec` 子命令的参数。
+#[derive(Args, Debug)]
+struct SpecArgs {
+    #[arg(long, value_enum, default_value_t = SpecFormat::Md)]
+    format: SpecFormat,
+}
+
+/// Arguments of the `audit` subcommand.
+// `audit` 子命令的参数。
+#[derive(Args, Debug)]
+struct AuditArgs {
+    /// The directory to audit; defaults to the config directory.
+    // 要审计的目录；默认为配置目录。
+    #[arg(long, value_name = "DIR")]
+    dir: Option<PathBuf>,
+}
+
+/// Arguments of the `find` subcommand.
+// `find` 子命令的参数。
+#[derive(Args, Debug)]
+struct FindArgs {
+    /// Text to look for, ignoring case.
+    // 要查找的文本（不区分大小写）。
+    query: String,
+
+    /// The site list to search; defaults to sites.json in the config directory.
+    // 要搜索的站点列表；默认为配置目录中的 sites.json。
+    #[arg(long, value_name = "FILE_PATH")]
+    sites: Option<PathBuf>,
+}
+
+/// Arguments of the `why-different` subcommand.
+// `why-different` 子命令的参数。
+#[derive(Args, Debug)]
+struct WhyDifferentArgs {
+    /// The preset that derives the expected passwords.
+    // 派生出预期密码的预设。
+    left: PathBuf,
+
+    /// The preset that derives different passwords.
+    // 派生出不同密码的预设。
+    right: PathBuf,
+}
+
+/// Actions of the `cache` subcommand.
+// `cache` 子命令的操作。
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Delete all cached KDF results and the device key that encrypts them.
+    // 删除所有缓存的 KDF 结果及加密它们的设备密钥。
+    Clear,
+}
+
+/// Formats of the `get` subcommand. / `get` 子命令的格式。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum GetFormat {
+    /// The bare password. / 仅输出密码本身。
+    Plain,
+    /// A JSON object with the key, the password and where it came from ("store" or "derived").
+    // 包含密钥、密码及其来源（"store" 或 "derived"）的 JSON 对象。
+    Json,
+}
+
+/// Arguments of the `get` subcommand.
+// `get` 子命令的参数。
+#[derive(Args, Debug)]
+struct GetArgs {
+    /// The distinguish key, which is also the name of a stored entry (e.g., 'example.com').
+    // 区分密钥，同时也是保存条目的名称（例如 'example.com'）。
+    key: String,
+
+    /// Your master password, known only to you; pass '-' to be prompted for it.
+    // 你的主密码，只有你自己知道；传入 '-' 则会提示你输入。
+    password_source: String,
+
+    #[arg(long, value_enum, default_value_t = GetFormat::Plain)]
+    output: GetFormat,
+
+    /// Copy the password to the clipboard instead of printing it.
+    // 将密码复制到剪贴板，而不是打印出来。
+    #[arg(long, conflicts_with = "output")]
+    clip: bool,
+
+    /// The same seed inputs as generate, for both the store key and derived passwords.
+    // 与 generate 相同的种子输入，同时用于存储密钥与派生的密码。
+    #[command(flatten)]
+    seed: SeedArgs,
+}
+
+/// Arguments of the `answer` subcommand.
+// `answer` 子命令的参数。
+#[derive(Args, Debug)]
+struct AnswerArgs {
+    /// The distinguish key of the site (e.g., 'example.com').
+    // 站点的区分密钥（例如 'example.com'）。
+    key: String,
+
+    /// The security question; case, spacing and punctuation do not change the answer.
+    // 安全问题；大小写、空白与标点不会改变答案。
+    question: String,
+
+    /// Your master password; defaults to '-', which prompts for it.
+    // 你的主密码；默认为 '-'，即提示你输入。
+    #[arg(default_value = "-")]
+    password_source: String,
+
+    /// Copy the answer to the clipboard instead of printing it.
+    // 将答案复制到剪贴板，而不是打印出来。
+    #[arg(long)]
+    clip: bool,
+
+    /// The same seed inputs as generate. / 与 generate 相同的种子输入。
+    #[command(flatten)]
+    seed: SeedArgs,
+}
+
+/// Arguments of the `recovery-codes` subcommand.
+// `recovery-codes` 子命令的参数。
+#[derive(Args, Debug)]
+struct RecoveryCodesArgs {
+    /// The distinguish key of the site (e.g., 'example.com').
+    // 站点的区分密钥（例如 'example.com'）。
+    key: String,
+
+    /// Your master password; defaults to '-', which prompts for it.
+    // 你的主密码；默认为 '-'，即提示你输入。
+    #[arg(default_value = "-")]
+    password_source: String,
+
+    /// How many codes to print; asking for more later keeps the earlier ones.
+    // 要输出的恢复码数量；日后请求更多时，之前的恢复码保持不变。
+    #[arg(long, default_value_t = 10)]
+    count: usize,
+
+    /// Digits of each code.
+    // 每个恢复码的位数。
+    #[arg(long, default_value_t = 8)]
+    digits: usize,
+
+    /// The same seed inputs as generate. / 与 generate 相同的种子输入。
+    #[command(flatten)]
+    seed: SeedArgs,
+}
+
+/// Actions of the `store` subcommand.
+// `store` 子命令的操作。
+#[derive(Subcommand, Debug)]
+enum StoreCommand {
+    /// Encrypt and save a secret under NAME; the secret is read from the terminal or the next line of stdin.
+    // 加密并以 NAME 保存一个秘密；秘密从终端或标准输入的下一行读取。
+    Set {
+        /// Name of the entry, e.g. 'intranet.example'.
+        // 条目名称，例如 'intranet.example'。
+        name: String,
+
+        /// Your master password, known only to you; pass '-' to be prompted for it.
+        // 你的主密码，只有你自己知道；传入 '-' 则会提示你输入。
+        password_source: String,
+
+        /// The same seed inputs as generate; the store key is derived with them.
+        // 与 generate 相同的种子输入；存储密钥使用它们派生。
+        #[command(flatten)]
+        seed: SeedArgs,
+    },
+
+    /// Decrypt and print the secret saved under NAME.
+    // 解密并输出以 NAME 保存的秘密。
+    Get {
+        /// Name of the entry.
+        // 条目名称。
+        name: String,
+
+        /// Your master password, known only to you; pass '-' to be prompted for it.
+        // 你的主密码，只有你自己知道；传入 '-' 则会提示你输入。
+        password_source: String,
+
+        /// Copy the secret to the clipboard instead of printing it.
+        // 将秘密复制到剪贴板，而不是打印出来。
+        #[arg(long)]
+        clip: bool,
+
+        /// The seed inputs the secret was stored with. / 保存该秘密时使用的种子输入。
+        #[command(flatten)]
+        seed: SeedArgs,
+    },
+
+    /// List the names of the saved entries.
+    // 列出已保存条目的名称。
+    List,
+
+    /// Delete the entry saved under NAME.
+    // 删除以 NAME 保存的条目。
+    Remove {
+        /// Name of the entry.
+        // 条目名称。
+        name: String,
+    },
+}
+
+/// Actions of the `charsets` subcommand.
+// `charsets` 子命令的操作。
+#[derive(Subcommand, Debug)]
+enum CharsetsCommand {
+    /// Report the size, duplicates, ambiguous characters and composition of a charset string.
+    // 报告字符集字符串的大小、重复字符、易混淆字符及其组成。
+    Test {
+        /// The candidate charset, e.g. "abcdefghijkmnpqrstuvwxyz".
+        // 候选字符集，例如 "abcdefghijkmnpqrstuvwxyz"。
+        chars: String,
+    },
+}
+
+/// Actions of the `backup` subcommand.
+// `backup` 子命令的操作。
+#[derive(Subcommand, Debug)]
+enum BackupCommand {
+    /// Write every preset and the site list in the config directory to FILE.
+    // 将配置目录中的所有预设及站点列表写入 FILE。
+    Create {
+        /// The backup file to write.
+        // 要写入的备份文件。
+        file: PathBuf,
+
+        /// Encrypt the backup with a passphrase, read from AEGIXPASS_BACKUP_PASSPHRASE or prompted for.
+        // 使用口令加密备份；口令从 AEGIXPASS_BACKUP_PASSPHRASE 读取，或提示输入。
+        #[arg(long)]
+        encrypt: bool,
+    },
+
+    /// Restore the files of a backup into the config directory.
+    // 将备份中的文件恢复到配置目录。
+    Restore {
+        /// The backup file to read.
+        // 要读取的备份文件。
+        file: PathBuf,
+
+        /// Replace existing files whose contents differ from the backup.
+        // 替换内容与备份不同的已有文件。
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Arguments of the `migrate-report` subcommand.
+// `migrate-report` 子命令的参数。
+#[derive(Args, Debug)]
+struct MigrateReportArgs {
+    /// Record that a site now uses its derived password; may be given multiple times.
+    // 记录某站点已改用派生密码；可以多次指定。
+    #[arg(long, value_name = "DISTINGUISH_KEY")]
+    done: Vec<String>,
+
+    /// The site list to read; defaults to sites.json in the config directory.
+    // 要读取的站点列表；默认为配置目录中的 sites.json。
+    #[arg(long, value_name = "FILE_PATH")]
+    sites: Option<PathBuf>,
+}
+
+/// Arguments of the `status` subcommand.
+// `status` 子命令的参数。
+#[derive(Args, Debug)]
+struct StatusArgs {
+    /// The site list to read; defaults to sites.json in the config directory.
+    // 要读取的站点列表；默认为配置目录中的 sites.json。
+    #[arg(long, value_name = "FILE_PATH")]
+    sites: Option<PathBuf>,
+}
+
+/// Formats of the `rekey` checklist. / `rekey` 核对清单的格式。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum RekeyFormat {
+    Csv,
+    Json,
+}
+
+/// Arguments of the `rekey` subcommand.
+// `rekey` 子命令的参数。
+#[derive(Args, Debug)]
+struct RekeyArgs {
+    /// The current master password; pass '-' to be prompted for it.
+    // 当前的主密码；传入 '-' 则会提示你输入。
+    old_password: String,
+
+    /// The new master password; pass '-' to be prompted for it twice.
+    // 新的主密码；传入 '-' 则会提示你输入两次。
+    new_password: String,
+
+    #[arg(long, value_enum, default_value_t = RekeyFormat::Csv)]
+    output: RekeyFormat,
+
+    /// The site list to read; defaults to sites.json in the config directory.
+    // 要读取的站点列表；默认为配置目录中的 sites.json。
+    #[arg(long, value_name = "FILE_PATH")]
+    sites: Option<PathBuf>,
+
+    /// The same seed inputs as generate; both master passwords are derived with them.
+    // 与 generate 相同的种子输入；新旧两个主密码都使用它们派生。
+    #[command(flatten)]
+    seed: SeedArgs,
+}
+
+/// The extra seed inputs shared by `generate` and the subcommands that derive (see [`SeedOptions`]).
+// `generate` 与各个进行派生的子命令共用的额外种子输入（参见 [`SeedOptions`]）。
+#[derive(Args, Debug)]
+struct SeedArgs {
+    /// Mix the contents of this file into the derivation as a second factor (e.g. a file on a USB stick).
+    // 将该文件的内容作为第二因素混入派生过程（例如 U 盘中的文件）。
+    #[arg(long, value_name = "PATH")]
+    keyfile: Option<PathBuf>,
+
+    /// Read Argon2's secret input from this file, for presets with "keyed": true in their argon2 settings.
+    // 从该文件读取 Argon2 的密钥输入，用于 argon2 参数中设置了 "keyed": true 的预设。
+    #[arg(long, value_name = "PATH")]
+    argon2_secret_file: Option<PathBuf>,
+
+    /// Mix a device secret (pepper) into the derivation, read from the environment or the OS keychain.
+    // 将设备秘密（胡椒值）混入派生过程，从环境变量或操作系统钥匙串读取。
+    #[arg(long, value_enum, value_name = "SOURCE")]
+    pepper: Option<PepperArg>,
+
+    /// Rotation counter: raise it when a site forces a password change (0 keeps the original password).
+    // 轮换计数器：当网站强制更换密码时调高它（0 保持原密码）。
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    counter: u32,
+
+    /// Derive for this rotation epoch (e.g. 2026-Q4) instead of the current one, for presets with a rotation policy.
+    // 为该轮换周期（例如 2026-Q4）而非当前周期派生，用于设置了轮换策略的预设。
+    #[arg(long, value_name = "EPOCH")]
+    epoch: Option<String>,
+}
+
+/// The secrets named by [`SeedArgs`], read once so their [`SeedOptions`] can borrow them.
+// 由 [`SeedArgs`] 指定的秘密，只读取一次，使 [`SeedOptions`] 可以借用它们。
+struct SeedInputs<'a> {
+    args: &'a SeedArgs,
+    key_material: Option<Vec<u8>>,
+    argon2_secret: Option<Vec<u8>>,
+    pepper: Option<Vec<u8>>,
+}
+
+impl SeedArgs {
+    /// Reads the keyfile, the Argon2 secret and the pepper. / 读取密钥文件、Argon2 密钥输入与胡椒值。
+    fn read(&self) -> Result<SeedInputs<'_>, Box<dyn std::error::Error>> {
+        let pepper = match self.pepper {
+            Some(PepperArg::Env) => Some(aegixpass::pepper::from_env()?),
+            Some(PepperArg::Keychain) => Some(aegixpass::pepper::from_keychain()?),
+            None => None,
+        };
+        Ok(SeedInputs {
+            args: self,
+            key_material: self.keyfile.as_deref().map(read_keyfile).transpose()?,
+            argon2_secret: self.argon2_secret_file.as_deref().map(read_argon2_secret).transpose()?,
+            pepper,
+        })
+    }
+}
+
+impl SeedInputs<'_> {
+    fn options(&self) -> SeedOptions<'_> {
+        SeedOptions {
+            key_material: self.key_material.as_deref(),
+            argon2_secret: self.argon2_secret.as_deref(),
+            pepper: self.pepper.as_deref(),
+            counter: self.args.counter,
+            epoch: self.args.epoch.as_deref(),
+        }
+    }
+}
+
+/// Arguments of the `import` subcommand.
+// `import` 子命令的参数。
+#[derive(Args, Debug)]
+struct ImportArgs {
+    /// The CSV export to read.
+    // 要读取的 CSV 导出文件。
+    #[arg(long, value_name = "CSV_FILE")]
+    from: PathBuf,
+
+    /// The site list to update; defaults to sites.json in the config directory.
+    // 要更新的站点列表；默认为配置目录中的 sites.json。
+    #[arg(long, value_name = "FILE_PATH")]
+    sites: Option<PathBuf>,
+}
+
+/// Arguments of the `setup` subcommand.
+// `setup` 子命令的参数。
+#[derive(Args, Debug)]
+struct SetupArgs {
+    /// Preset tier to install; asked interactively when omitted.
+    // 要安装的预设档位；省略时以交互方式询问。
+    #[arg(long, value_enum)]
+    tier: Option<TierArg>,
+
+    /// Overwrite an existing default.json in the config directory.
+    // 覆盖配置目录中已存在的 default.json。
+    #[arg(long)]
+    force: bool,
+}
+
+/// Preset tiers accepted by `setup --tier`.
+// `setup --tier` 接受的预设档位。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum TierArg {
+    /// Argon2id, 16 characters (recommended). / Argon2id，16 位（推荐）。
+    Standard,
+    /// SHA-256, 16 characters, for slow devices. / SHA-256，16 位，适用于性能较弱的设备。
+    Fast,
+    /// Argon2id, 24 characters. / Argon2id，24 位。
+    Strong,
+    /// Argon2id with 8 MiB of memory, for devices with 64 MB of RAM. / Argon2id，8 MiB 内存，适用于只有 64 MB 内存的设备。
+    #[value(name = "lowMemory", alias = "low-memory")]
+    LowMemory,
+}
+
+impl From<TierArg> for PresetTier {
+    fn from(arg: TierArg) -> Self {
+        match arg {
+            TierArg::Standard => PresetTier::Standard,
+            TierArg::Fast => PresetTier::Fast,
+            TierArg::Strong => PresetTier::Strong,
+            TierArg::LowMemory => PresetTier::LowMemory,
+        }
+    }
+}
+
+/// Arguments of the `pgp` subcommand.
+// `pgp` 子命令的参数。
+#[cfg(feature = "pgp")]
+#[derive(Args, Debug)]
+struct PgpArgs {
+    /// User ID of the key, e.g. 'Alice <alice@example.com>'.
+    // 密钥的用户 ID，例如 'Alice <alice@example.com>'。
+    #[arg(long, value_name = "USER_ID")]
+    uid: String,
+
+    /// Fixed creation time as a Unix timestamp; it is part of the fingerprint.
+    // 固定的创建时间（Unix 时间戳）；它是指纹的一部分。
+    #[arg(long, value_name = "UNIX_TIME", default_value_t = aegixpass::openpgp::DEFAULT_CREATION_TIME)]
+    created: u32,
+
+    /// Print the public key instead of the secret key.
+    // 输出公钥而不是私钥。
+    #[arg(long)]
+    public: bool,
+
+    /// Your master password, known only to you; pass '-' to be prompted for it.
+    // 你的主密码，只有你自己知道；传入 '-' 则会提示你输入。
     password_source: String,
 
+    /// Distinguish key for the derivation; defaults to the user ID.
+    // 派生使用的区分密钥；默认为用户 ID。
+    distinguish_key: Option<String>,
+
+    /// The same seed inputs as generate. / 与 generate 相同的种子输入。
+    #[command(flatten)]
+    seed: SeedArgs,
+}
+
+/// Arguments of the `cert` subcommand.
+// `cert` 子命令的参数。
+#[derive(Args, Debug)]
+struct CertArgs {
+    /// Common name (and DNS subject alternative name) of the certificate.
+    // 证书的通用名称（同时作为 DNS 主题备用名称）。
+    #[arg(long, value_name = "HOST")]
+    cn: String,
+
+    /// Key algorithm of the derived key pair.
+    // 派生密钥对的算法。
+    #[arg(long, value_enum, default_value_t = CertKeyArg::Ed25519)]
+    key_type: CertKeyArg,
+
+    /// Your master password, known only to you; pass '-' to be prompted for it.
+    // 你的主密码，只有你自己知道；传入 '-' 则会提示你输入。
+    password_source: String,
+
+    /// Distinguish key for the derivation; defaults to the common name.
+    // 派生使用的区分密钥；默认为通用名称。
+    distinguish_key: Option<String>,
+
+    /// The same seed inputs as generate. / 与 generate 相同的种子输入。
+    #[command(flatten)]
+    seed: SeedArgs,
+}
+
+/// Key algorithms accepted by `cert --key-type`.
+// `cert --key-type` 接受的密钥算法。
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum CertKeyArg {
+    Ed25519,
+}
+
+/// Arguments for the default password generation mode.
+// 默认密码生成模式的参数。
+#[derive(Args, Debug)]
+struct GenerateArgs {
+    /// Your master password, known only to you; pass '-' to be prompted for it.
+    // 你的主密码，只有你自己知道；传入 '-' 则会提示你输入。
+    #[arg(required = true)]
+    password_source: Option<String>,
+
     /// A key to distinguish between different websites or applications (e.g., 'example.com').
     // 用于区分不同网站或应用的密钥 (例如 'example.com')。
-    distinguish_key: String,
+    #[arg(required = true)]
+    distinguish_key: Option<String>,
+
+    /// Write the derived password into HashiCorp Vault at 'mount/path#field' instead of printing it.
+    // 将派生出的密码写入 HashiCorp Vault 的 'mount/path#field'，而不是打印出来。
+    #[cfg(feature = "vault")]
+    #[arg(long, value_name = "MOUNT/PATH#FIELD", conflicts_with_all = ["qr", "count", "label_suffix", "clip", "shell_escape"])]
+    vault_write: Option<String>,
+
+    #[command(flatten)]
+    seed: SeedArgs,
+
+    /// Output format of the derived password.
+    // 派生密码的输出格式。
+    #[arg(long, value_enum, default_value_t = OutputFormat::Plain)]
+    output: OutputFormat,
+
+    /// Derive N independent passwords for the site in one pass, one per line, labelled 1 to N.
+    // 一次派生该站点的 N 个相互独立的密码，每行一个，标签依次为 1 到 N。
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=1000))]
+    count: Option<u32>,
+
+    /// Label of one of several passwords derived in one pass (e.g. 'db'); may be given multiple times.
+    // 一次派生的多个密码之一的标签（例如 'db'）；可以多次指定。
+    #[arg(long, value_name = "LABEL")]
+    label_suffix: Vec<String>,
+
+    /// Variable name for '--output dotenv'; defaults to the distinguish key in upper snake case.
+    // '--output dotenv' 使用的变量名；默认为区分密钥的大写下划线形式。
+    #[arg(long, value_name = "NAME")]
+    env_key: Option<String>,
+
+    /// User name for '--output htpasswd'.
+    // '--output htpasswd' 使用的用户名。
+    #[arg(long, value_name = "USER", required_if_eq("output", "htpasswd"))]
+    user: Option<String>,
+
+    /// Salt selection for the scram-sha-256, htpasswd and sha512-crypt outputs.
+    // scram-sha-256、htpasswd 与 sha512-crypt 输出的盐选择方式。
+    #[arg(long, value_enum, default_value_t = SaltArg::Deterministic)]
+    salt: SaltArg,
+
+    /// Key size in bits for the jwt-key, jwk, key-hex and key-base64 outputs.
+    // jwt-key、jwk、key-hex 与 key-base64 输出的密钥长度（比特）。
+    #[arg(long, default_value_t = 256, value_parser = PossibleValuesParser::new(["256", "512"]).map(|s| s.parse::<usize>().unwrap()))]
+    key_bits: usize,
+
+    /// Domain-separation label for derived keys, so one site can have several independent keys
+    /// ('jwt' by default for jwt-key and jwk, 'key' for key-hex and key-base64).
+    // 派生密钥的域分离标签，使同一站点可以拥有多个相互独立的密钥（jwt-key 与 jwk 默认为 'jwt'，key-hex 与 key-base64 默认为 'key'）。
+    #[arg(long, value_name = "LABEL")]
+    key_label: Option<String>,
+
+    /// Issuer shown by authenticator apps for '--output otpauth-uri'; defaults to the distinguish key.
+    // '--output otpauth-uri' 在身份验证器应用中显示的发行方；默认为区分密钥。
+    #[arg(long, value_name = "ISSUER")]
+    issuer: Option<String>,
+
+    /// Account name shown by authenticator apps for '--output otpauth-uri'.
+    // '--output otpauth-uri' 在身份验证器应用中显示的账户名。
+    #[arg(long, value_name = "ACCOUNT", default_value = "")]
+    account: String,
+
+    /// OTP flavor for the otp and otpauth-uri outputs.
+    // otp 与 otpauth-uri 输出所使用的 OTP 类型。
+    #[arg(long, value_enum, default_value_t = OtpTypeArg::Totp)]
+    otp_type: OtpTypeArg,
+
+    /// Counter value for '--otp-type hotp'.
+    // '--otp-type hotp' 使用的计数器值。
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    hotp_counter: u64,
+
+    /// What '--output username' builds the username from.
+    // '--output username' 用来构成用户名的方式。
+    #[arg(long, value_enum, default_value_t = UsernameStyleArg::Pronounceable)]
+    username_style: UsernameStyleArg,
+
+    /// Also print a line recording the algorithm profile and preset fingerprint used, for re-deriving later.
+    // 同时输出一行记录所用算法配置与预设指纹的信息，便于日后重新派生。
+    #[arg(long)]
+    version_of_output: bool,
+
+    /// Byte encoding of the output, so non-ASCII passwords survive being piped into PowerShell or files.
+    // 输出的字节编码，使含非 ASCII 字符的密码在通过管道传给 PowerShell 或写入文件时保持完整。
+    #[arg(long, value_enum, default_value_t = OutputEncodingArg::Console)]
+    output_encoding: OutputEncodingArg,
+
+    /// Quote the password so it can be pasted into a shell command as one literal argument.
+    // 为密码加上引号，使其可以作为一个字面参数粘贴到 shell 命令中。
+    #[arg(long, value_enum, value_name = "SHELL", default_value_t = ShellEscapeArg::None)]
+    shell_escape: ShellEscapeArg,
+
+    /// Start the output with a byte order mark (needs '--output-encoding utf8' or 'utf16le').
+    // 在输出开头添加字节顺序标记（需要 '--output-encoding utf8' 或 'utf16le'）。
+    #[arg(long)]
+    bom: bool,
+
+    /// Line endings of the output.
+    // 输出的换行符。
+    #[arg(long, value_enum, default_value_t = NewlineArg::Lf)]
+    newline: NewlineArg,
+
+    /// Render the output as a QR code in the terminal.
+    // 在终端中将输出渲染为二维码。
+    #[arg(long)]
+    qr: bool,
+
+    /// Copy the output to the clipboard instead of printing it.
+    // 将输出复制到剪贴板，而不是打印出来。
+    #[arg(long)]
+    clip: bool,
+
+    /// Print the password with all but the first and last N characters masked, to check it by eye.
+    // 打印密码时只显示首尾各 N 个字符、遮盖其余部分，以便目测核对。
+    #[arg(long, value_name = "N")]
+    peek: Option<usize>,
+
+    /// Leave out easily confused characters such as 0/O and 1/l/I, as if the preset set "excludeAmbiguous": true.
+    // 去除 0/O、1/l/I 等容易混淆的字符，如同预设设置了 "excludeAmbiguous": true。
+    #[arg(long)]
+    exclude_ambiguous: bool,
+
+    /// Derive a PIN of N digits (4 to 12) instead of a password, as if the preset set "pin": N.
+    // 派生 N 位（4 至 12 位）PIN 而非密码，如同预设设置了 "pin": N。
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(4..=12))]
+    pin: Option<u32>,
+
+    /// Run every check except the KDF and report what would be derived; no master password is asked for.
+    // 运行除 KDF 之外的所有检查，并报告将会派生的内容；不会询问主密码。
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Encrypt the rendered output to an age recipient ('age1...'); may be given multiple times.
+    // 使用 age 接收者 ('age1...') 加密渲染后的输出；可以多次指定。
+    #[cfg(feature = "age")]
+    #[arg(long, value_name = "RECIPIENT")]
+    encrypt_age: Vec<String>,
 }
 
 /// Run the program and handle the main logic, returning a Result for error handling.
 // 运行程序并处理主要逻辑，返回 Result 类型以便于错误处理。
-fn run() -> Result<String, Box<dyn std::error::Error>> {
+fn run() -> Result<(), Box<dyn std::error::Error>> {
     let args = CliArgs::parse();
+    let config = args.config.as_deref();
+    write_guard::set_ephemeral(args.ephemeral);
+    if let Some(dir) = &args.test_mode {
+        aegixpass::clipboard::set_fake(Some(dir.join(TEST_CLIPBOARD_FILE)));
+        let _ = TEST_DIR.set(dir.clone());
+    }
+    // Ephemeral mode leaves nothing behind, cache entries included.
+    // 临时模式不留下任何内容，缓存条目也不例外。
+    if args.kdf_cache && !args.ephemeral {
+        let dir = cache_dir().ok_or("Could not determine the user cache directory.")?;
+        kdf_cache::set_active(Some(KdfCache::new(dir, Duration::from_secs(args.cache_ttl))));
+    }
+
+    // Commands that derive secrets load the preset and apply `--hardened` first.
+    // 派生秘密的命令会先加载预设并应用 `--hardened`。
+    let load = || -> Result<Preset, Box<dyn std::error::Error>> {
+        let mut preset = load_preset(config, args.config_fingerprint.as_deref())?;
+        if args.generate.exclude_ambiguous {
+            preset.exclude_ambiguous = true;
+            preset.validate()?;
+        }
+        if let Some(digits) = args.generate.pin {
+            preset.pin = Some(digits as usize);
+            preset.validate()?;
+        }
+        if let Some(policy) = args.hardened {
+            harden(policy, &preset)?;
+        }
+        Ok(preset)
+    };
+
+    match &args.command {
+        Some(Command::Cert(cert_args)) => run_cert(cert_args, &load()?),
+        #[cfg(feature = "pgp")]
+        Some(Command::Pgp(pgp_args)) => run_pgp(pgp_args, &load()?),
+        // These run before any preset exists. / 这些子命令在预设存在之前运行。
+        Some(Command::Setup(setup_args)) => run_setup(setup_args),
+        Some(Command::SelfTest) => run_self_test(),
+        Some(Command::Demo) => run_demo(),
+        Some(Command::Spec(spec_args)) => run_spec(spec_args),
+        Some(Command::Import(import_args)) => run_import(import_args),
+        Some(Command::MigrateReport(report_args)) => run_migrate_report(report_args),
+        Some(Command::Status(status_args)) => run_status(status_args, &load()?),
+        Some(Command::Rekey(rekey_args)) => run_rekey(rekey_args, &load()?),
+        Some(Command::Find(find_args)) => run_find(find_args),
+        Some(Command::WhyDifferent(why_args)) => run_why_different(why_args),
+        Some(Command::Cache(CacheCommand::Clear)) => run_cache_clear(),
+        Some(Command::Backup(backup_command)) => run_backup(backup_command),
+        Some(Command::Store(store_command)) => run_store(store_command, load),
+        Some(Command::Get(get_args)) => run_get(get_args, &load()?),
+        Some(Command::Answer(answer_args)) => run_answer(answer_args, &load()?),
+        Some(Command::RecoveryCodes(recovery_args)) => run_recovery_codes(recovery_args, &load()?),
+        Some(Command::Charsets(CharsetsCommand::Test { chars })) => run_charsets_test(chars),
+        Some(Command::Audit(audit_args)) => run_audit(audit_args),
+        None => run_generate(&args.generate, &load()?),
+    }
+}
+
+/// Locks memory and checks for swap, warning about or refusing on any exposure found.
+// 锁定内存并检查交换分区；发现任何风险时发出警告或拒绝继续。
+fn harden(policy: HardenedPolicy, preset: &Preset) -> Result<(), AegixPassError> {
+    let required_bytes = preset.argon2_memory_kib().map_or(0, |kib| kib as u64 * 1024);
+    let issues = aegixpass::hardening::harden(required_bytes);
+    if issues.is_empty() {
+        return Ok(());
+    }
+    match policy {
+        HardenedPolicy::Refuse => Err(IntegrationError::MemoryExposure(issues.join("; ")).into()),
+        HardenedPolicy::Warn => {
+            for issue in issues {
+                eprintln!("Warning: {}", issue);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The directory `--test-mode` replaces the config and cache directories with.
+// `--test-mode` 用来代替配置目录与缓存目录的目录。
+static TEST_DIR: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+/// The file standing in for the clipboard under `--test-mode`. / `--test-mode` 下代替剪贴板的文件。
+const TEST_CLIPBOARD_FILE: &str = "clipboard.txt";
+
+/// Whether `--test-mode` is on. / 是否开启了 `--test-mode`。
+fn test_mode() -> bool {
+    TEST_DIR.get().is_some()
+}
+
+/// The per-user cache directory of the KDF cache. / KDF 缓存所在的每用户缓存目录。
+fn cache_dir() -> Option<PathBuf> {
+    match TEST_DIR.get() {
+        Some(dir) => Some(dir.join("cache")),
+        None => kdf_cache::default_dir(),
+    }
+}
+
+/// The per-user config directory, e.g. `~/.config/aegixpass` on Linux.
+// 每个用户的配置目录，例如 Linux 上的 `~/.config/aegixpass`。
+fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = TEST_DIR.get() {
+        return Some(dir.clone());
+    }
+    dirs::config_dir().map(|dir| dir.join("aegixpass"))
+}
+
+/// Loads and version-checks the preset, falling back to `default.json` in the config directory
+/// and then next to the executable, and checks it against a pinned fingerprint.
+// 加载预设并检查版本；未指定时依次使用配置目录和可执行文件同目录下的 `default.json`；并按固定的指纹进行校验。
+fn load_preset(config: Option<&Path>, pinned_fingerprint: Option<&str>) -> Result<Preset, Box<dyn std::error::Error>> {
+    let (config_path, json_content) = read_config(config)?;
+    let remote = config_path.to_str().is_some_and(|path| path.contains("://"));
+    let in_file = |source| PresetFileError { path: config_path.clone(), source };
+
+    // --- 版本检查逻辑 ---
+    // 1. 先将 JSON 字符串解析为一个通用的 Value 类型。
+    let json_value: Value = serde_json::from_str(&json_content)
+        .map_err(|e| in_file(AegixPassError::PresetParseError(e.to_string())))?;
+
+    // 2. 检查 version 字段。
+    match json_value.get("version").and_then(|v| v.as_u64()) {
+        Some(version) if SUPPORTED_VERSIONS.iter().any(|&v| u64::from(v) == version) => {
+            // 版本正确，现在可以安全地将 Value 反序列化为 Preset 结构体。
+            // 这样做比重新从字符串解析更高效。
+            let mut preset: Preset = serde_json::from_value(json_value)
+                .map_err(|e| in_file(AegixPassError::PresetParseError(e.to_string())))?;
+            // 3. 检查资源上限，防止恶意预设耗尽内存或长时间运行；并在派生之前检查长度与字符集。
+            PresetLimits::default().check(&preset).map_err(in_file)?;
+            preset.validate().map_err(in_file)?;
+            // 4. 校验固定的指纹；远程预设必须固定指纹。
+            match pinned_fingerprint {
+                Some(expected) if preset.fingerprint() != expected => {
+                    return Err(in_file(AegixPassError::FingerprintMismatch {
+                        expected: expected.to_string(),
+                        actual: preset.fingerprint(),
+                    })
+                    .into());
+                }
+                None if remote => {
+                    return Err(format!(
+                        "Remote presets must be pinned with --config-fingerprint; its current fingerprint is {}.",
+                        preset.fingerprint()
+                    )
+                    .into());
+                }
+                _ => {}
+            }
+            // 5. 若 platformId 不是公认的值则发出警告，因为它会改变所有派生密码。
+            if let Some(warning) = preset.platform_id_warning() {
+                eprintln!("Warning: {}", warning);
+            }
+            if let Some(warning) = preset.deprecation_warning() {
+                eprintln!("Warning: {}", warning);
+            }
+            // 6. 词表的相对路径以预设文件所在目录为起点。
+            if let (Some(settings), Some(dir)) = (preset.passphrase.as_mut(), config_path.parent().filter(|_| config_path.is_file())) {
+                settings.wordlist = dir.join(&settings.wordlist).to_string_lossy().into_owned();
+            }
+            Ok(preset)
+        }
+        Some(version) => {
+            // 如果版本号存在但不受支持，则返回错误。
+            Err(format!(
+                "Unsupported config file version: {}. This program supports versions 1 and 2.",
+                version
+            ).into())
+        }
+        None => {
+            // 如果 "version" 字段不存在或其类型不是一个有效的数字。
+            Err("Config file is missing a valid 'version' field.".into())
+        }
+    }
+}
+
+/// Set once stdin has been read for `--config -`, so it is not also read for the master password.
+// 当 `--config -` 读取了标准输入后置位，避免再从标准输入读取主密码。
+static STDIN_CONSUMED: AtomicBool = AtomicBool::new(false);
+
+/// Reads the preset document named by `--config` (a file, `-` for stdin or a URL), returning
+/// where it came from and its contents.
+// 读取 `--config` 指定的预设文档（文件、表示标准输入的 `-` 或 URL），返回其来源与内容。
+fn read_config(config: Option<&Path>) -> Result<(PathBuf, String), Box<dyn std::error::Error>> {
+    match config.and_then(Path::to_str) {
+        Some("-") => {
+            let mut json = String::new();
+            std::io::stdin().read_to_string(&mut json)?;
+            STDIN_CONSUMED.store(true, Ordering::SeqCst);
+            return Ok((PathBuf::from("<stdin>"), json));
+        }
+        Some(url) if url.starts_with("https://") || url.starts_with("http://") => {
+            #[cfg(feature = "remote-preset")]
+            return Ok((PathBuf::from(url), aegixpass::remote::fetch_preset_json(url)?));
+            #[cfg(not(feature = "remote-preset"))]
+            return Err("Fetching presets from URLs needs a build with `--features remote-preset`.".into());
+        }
+        _ => {}
+    }
 
     // Determine the path of the configuration file.
     // 确定配置文件的路径。
-    let config_path = match args.config {
+    let user_default = config_dir().map(|dir| dir.join("default.json")).filter(|path| path.is_file());
+    let config_path = match (config, user_default) {
         // If the user provides a path with -c or --config, use it.
         // 如果用户通过 -c 或 --config 提供了路径，则使用该路径。
-        Some(path) => path,
-        // Otherwise, construct a path to "default.json" in the same directory as the executable.
-        // 否则，构建一个指向可执行文件同目录下 "default.json" 的路径。
-        None => {
+        (Some(path), _) => path.to_path_buf(),
+        // Otherwise, prefer the "default.json" written by `aegixpass setup`.
+        // 否则，优先使用 `aegixpass setup` 写入的 "default.json"。
+        (None, Some(path)) => path,
+        // Finally, construct a path to "default.json" in the same directory as the executable.
+        // 最后，构建一个指向可执行文件同目录下 "default.json" 的路径。
+        (None, None) => {
             let mut path = std::env::current_exe()?;
             path.pop(); // Remove the executable's filename. / 移除可执行文件名。
             path.push("default.json"); // Add the default config filename. / 添加默认配置文件名。
@@ -52,53 +1177,1076 @@ fn run() -> Result<String, Box<dyn std::error::Error>> {
         )
     })?;
 
-    // --- 版本检查逻辑 ---
-    // 1. 先将 JSON 字符串解析为一个通用的 Value 类型。
-    let json_value: Value = serde_json::from_str(&json_content)
-        .map_err(|e| AegixPassError::PresetParseError(e.to_string()))?;
+    Ok((config_path, json_content))
+}
 
-    // 2. 检查 version 字段。
-    match json_value.get("version").and_then(|v| v.as_u64()) {
-        Some(1) => {
-            // 版本正确，现在可以安全地将 Value 反序列化为 Preset 结构体。
-            // 这样做比重新从字符串解析更高效。
-            let preset: Preset = serde_json::from_value(json_value)
-                .map_err(|e| AegixPassError::PresetParseError(e.to_string()))?;
+/// An error in a preset file, reported together with the file's path.
+// 预设文件中的错误，报告时附带该文件的路径。
+#[derive(Debug)]
+struct PresetFileError {
+    path: PathBuf,
+    source: AegixPassError,
+}
+
+impl std::fmt::Display for PresetFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl std::error::Error for PresetFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Prints an error as its cause, the offending file (if any) and a suggestion for fixing it.
+// 打印错误：其原因、出错的文件（如有）以及修复建议。
+fn report_error(error: &(dyn std::error::Error + 'static)) {
+    eprintln!("Error: {}", error);
+    let original = error;
+    let (path, error) = match error.downcast_ref::<PresetFileError>() {
+        Some(e) => (Some(&e.path), Some(&e.source)),
+        None => (None, error.downcast_ref::<AegixPassError>()),
+    };
+    if let Some(path) = path {
+        eprintln!("  File: {}", path.display());
+    }
+    // Commands may also return an integration error without wrapping it. / 命令也可能直接返回未经包装的集成错误。
+    let integration = || error.is_none().then(|| original.downcast_ref::<IntegrationError>()).flatten();
+    if let Some(hint) = error.and_then(AegixPassError::hint).or_else(|| integration().and_then(IntegrationError::hint)) {
+        eprintln!("  Hint: {}", hint);
+    }
+}
+
+/// Runs the `setup` subcommand: installs a preset tier into the config directory and runs the self-test.
+// 运行 `setup` 子命令：将预设档位安装到配置目录并运行自检。
+fn run_setup(args: &SetupArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = config_dir().ok_or("Could not determine the user config directory.")?;
+    let path = dir.join("default.json");
+    if path.exists() && !args.force {
+        return Err(format!(
+            "'{}' already exists; rerun with --force to replace it (this changes every derived password).",
+            path.display()
+        )
+        .into());
+    }
+
+    let tier = match args.tier {
+        Some(tier) => tier.into(),
+        None => choose_tier()?,
+    };
+    write_guard::create_dir_all(&dir)?;
+    write_guard::write(&path, tier.json())?;
+    println!("Installed the '{}' preset to {}", tier.name(), path.display());
+
+    run_self_test()?;
+    println!("Setup complete. Try: aegixpass - example.com");
+    Ok(())
+}
 
-            // 调用核心函数生成密码。
-            let password = aegixpass_generator(&args.password_source, &args.distinguish_key, &preset)?;
-            Ok(password)
+/// Asks which preset tier to install, defaulting to the standard tier.
+// 询问要安装哪个预设档位，默认为标准档位。
+fn choose_tier() -> Result<PresetTier, Box<dyn std::error::Error>> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(PresetTier::Standard);
+    }
+    println!("Choose a default preset:");
+    for (i, tier) in PresetTier::ALL.iter().enumerate() {
+        println!("  {}) {:<10} {}", i + 1, tier.name(), tier.description());
+    }
+    loop {
+        print!("Preset [1]: ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        let answer = line.trim();
+        if answer.is_empty() {
+            return Ok(PresetTier::Standard);
         }
-        Some(version) => {
-            // 如果版本号存在但不是 1，则返回错误。
-            Err(format!(
-                "Unsupported config file version: {}. This program only supports version 1.",
-                version
-            ).into())
+        let chosen = answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| PresetTier::ALL.get(n.wrapping_sub(1)))
+            .or_else(|| PresetTier::ALL.iter().find(|tier| tier.name() == answer));
+        match chosen {
+            Some(tier) => return Ok(*tier),
+            None => println!("Please enter a number between 1 and {}.", PresetTier::ALL.len()),
         }
-        None => {
-            // 如果 "version" 字段不存在或其类型不是一个有效的数字。
-            Err("Config file is missing a valid 'version' field.".into())
+    }
+}
+
+/// Runs the known-answer self-test and reports the algorithms checked.
+// 运行已知答案自检，并报告所检查的算法。
+fn run_self_test() -> Result<(), Box<dyn std::error::Error>> {
+    let passed = aegixpass::selftest::self_test()?;
+    println!("Self-test passed: {}", passed.join(", "));
+    Ok(())
+}
+
+fn run_demo() -> Result<(), Box<dyn std::error::Error>> {
+    print!("{}", aegixpass::demo::demo()?);
+    Ok(())
+}
+
+fn run_spec(args: &SpecArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.format {
+        SpecFormat::Md => print!("{}", aegixpass::spec::markdown()?),
+    }
+    Ok(())
+}
+
+/// Runs the `store` subcommand against `store.json` in the config directory. Only `set` and
+/// `get` load the preset, since only they derive the store key.
+// 针对配置目录中的 `store.json` 运行 `store` 子命令。只有 `set` 与 `get` 会加载预设，因为只有它们需要派生存储密钥。
+fn run_store(
+    command: &StoreCommand,
+    load: impl FnOnce() -> Result<Preset, Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = config_dir().ok_or("Could not determine the user config directory.")?.join(STORE_FILE);
+    match command {
+        StoreCommand::Set { name, password_source, seed } => {
+            let preset = load()?;
+            let seed_inputs = seed.read()?;
+            let password_source = master_password(password_source)?;
+            let secret = prompt::read_passphrase("Secret to store", &load_settings()?)?;
+            let key = StoreKey::derive(&password_source, &preset, &seed_inputs.options())?;
+            StoreFile::update(&path, |store| store.set(&key, name, &secret))?;
+            eprintln!("Stored '{}' in {}.", name, path.display());
+        }
+        StoreCommand::Get { name, password_source, clip, seed } => {
+            let preset = load()?;
+            preset.output_policy.check(if *clip { Destination::Clipboard } else { Destination::Stdout })?;
+            let store = StoreFile::load(&path)?;
+            let seed_inputs = seed.read()?;
+            let password_source = master_password(password_source)?;
+            let key = StoreKey::derive(&password_source, &preset, &seed_inputs.options())?;
+            let secret = store.get(&key, name)?;
+            if *clip {
+                sink::deliver(&mut ClipboardSink::default(), &preset.output_policy, &secret)?;
+                eprintln!("Copied to the clipboard.");
+            } else {
+                println!("{}", secret);
+            }
+        }
+        StoreCommand::List => {
+            for name in StoreFile::load(&path)?.names() {
+                println!("{}", name);
+            }
+        }
+        StoreCommand::Remove { name } => {
+            if !StoreFile::update(&path, |store| Ok(store.remove(name)))? {
+                return Err(IntegrationError::Store(format!("no entry named '{}'", name)).into());
+            }
+            eprintln!("Removed '{}' from {}.", name, path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `get` subcommand: a secret saved in the store takes precedence over derivation, so
+/// one command serves every site however its password is managed.
+// 运行 `get` 子命令：托管存储中保存的秘密优先于派生，因此无论密码以何种方式管理，一个命令即可适用于所有站点。
+fn run_get(args: &GetArgs, preset: &Preset) -> Result<(), Box<dyn std::error::Error>> {
+    preset.output_policy.check(if args.clip { Destination::Clipboard } else { Destination::Stdout })?;
+    let store = StoreFile::load(&config_dir().ok_or("Could not determine the user config directory.")?.join(STORE_FILE))?;
+    let seed_inputs = args.seed.read()?;
+    let options = seed_inputs.options();
+    let password_source = master_password(&args.password_source)?;
+    let (source, password) = if store.contains(&args.key) {
+        let key = StoreKey::derive(&password_source, preset, &options)?;
+        ("store", store.get(&key, &args.key)?)
+    } else {
+        ("derived", aegixpass_generator_with(&password_source, &args.key, preset, &options)?)
+    };
+    if args.clip {
+        sink::deliver(&mut ClipboardSink::default(), &preset.output_policy, &password)?;
+        eprintln!("Copied the {} password to the clipboard.", source);
+        return Ok(());
+    }
+    match args.output {
+        GetFormat::Plain => println!("{}", password),
+        GetFormat::Json => println!("{}", serde_json::json!({ "key": args.key, "source": source, "password": password })),
+    }
+    Ok(())
+}
+
+/// Runs the `answer` subcommand. / 运行 `answer` 子命令。
+fn run_answer(args: &AnswerArgs, preset: &Preset) -> Result<(), Box<dyn std::error::Error>> {
+    preset.output_policy.check(if args.clip { Destination::Clipboard } else { Destination::Stdout })?;
+    let seed_inputs = args.seed.read()?;
+    let password_source = master_password(&args.password_source)?;
+    let answer = answer::derive_answer_with(&password_source, &args.key, &args.question, preset, &seed_inputs.options())?;
+    if args.clip {
+        sink::deliver(&mut ClipboardSink::default(), &preset.output_policy, &answer)?;
+        eprintln!("Copied the answer to the clipboard.");
+    } else {
+        println!("{}", answer);
+    }
+    Ok(())
+}
+
+/// Runs the `recovery-codes` subcommand, printing one code per line.
+// 运行 `recovery-codes` 子命令，每行输出一个恢复码。
+fn run_recovery_codes(args: &RecoveryCodesArgs, preset: &Preset) -> Result<(), Box<dyn std::error::Error>> {
+    preset.output_policy.check(Destination::Stdout)?;
+    let seed_inputs = args.seed.read()?;
+    let password_source = master_password(&args.password_source)?;
+    let codes = recovery::derive_recovery_codes_with(&password_source, &args.key, preset, args.count, args.digits, &seed_inputs.options())?;
+    for code in codes {
+        println!("{}", code);
+    }
+    Ok(())
+}
+
+/// The site list used when `--sites` is not given. / 未指定 `--sites` 时使用的站点列表。
+fn default_sites_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(config_dir().ok_or("Could not determine the user config directory.")?.join("sites.json"))
+}
+
+/// Runs the `import` subcommand, merging the sites of a CSV export into the site list.
+// 运行 `import` 子命令，将 CSV 导出文件中的站点合并到站点列表中。
+fn run_import(args: &ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match &args.sites {
+        Some(path) => path.clone(),
+        None => default_sites_path()?,
+    };
+    let export = std::fs::File::open(&args.from)
+        .map_err(|e| format!("Could not read '{}': {}", args.from.display(), e))?;
+    let imported = aegixpass::sites::import_csv(export)?;
+
+    let total = imported.len();
+    let added = SitesFile::update(&path, |sites| Ok(imported.into_iter().filter(|site| sites.add(site.clone())).count()))?;
+    println!(
+        "Imported {} new site(s) into {} ({} already listed).",
+        added,
+        path.display(),
+        total - added
+    );
+    Ok(())
+}
+
+/// Runs the `migrate-report` subcommand, recording `--done` sites before listing progress.
+// 运行 `migrate-report` 子命令：先记录 `--done` 指定的站点，再列出迁移进度。
+fn run_migrate_report(args: &MigrateReportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match &args.sites {
+        Some(path) => path.clone(),
+        None => default_sites_path()?,
+    };
+    let sites = if args.done.is_empty() {
+        SitesFile::load(&path)?
+    } else {
+        SitesFile::update(&path, |sites| {
+            for key in &args.done {
+                if sites.mark_migrated(key) == 0 {
+                    return Err(IntegrationError::Sites(format!("'{}' is not listed in {}", key, path.display())).into());
+                }
+            }
+            Ok(sites.clone())
+        })?
+    };
+
+    let (migrated, pending): (Vec<_>, Vec<_>) = sites.sites.iter().partition(|site| site.migrated);
+    let total = sites.sites.len();
+    println!("Using derived passwords ({}/{}):", migrated.len(), total);
+    for site in &migrated {
+        println!("  {}", site.display_name());
+    }
+    println!("Still using stored passwords ({}/{}):", pending.len(), total);
+    for site in &pending {
+        println!("  {}", site.display_name());
+    }
+    Ok(())
+}
+
+/// Runs the `status` subcommand, comparing each site's password age with its `maxAgeDays`.
+// 运行 `status` 子命令，将每个站点的密码使用天数与其 `maxAgeDays` 比较。
+fn run_status(args: &StatusArgs, preset: &Preset) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match &args.sites {
+        Some(path) => path.clone(),
+        None => default_sites_path()?,
+    };
+    let sites = SitesFile::load(&path)?;
+    let now = unix_time()?;
+    let aging = sites.aging(preset.rotation, now);
+    if aging.is_empty() {
+        println!("No site in {} has a maxAgeDays.", path.display());
+        return Ok(());
+    }
+    match preset.rotation {
+        Some(policy) => println!(
+            "Rotation: {}, epoch {} since {}.",
+            policy.name(),
+            policy.epoch_at(now),
+            aegixpass::rotation::date_at(policy.epoch_start_at(now))
+        ),
+        None => println!("The preset has no rotation, so derived passwords never change; set `rotation` in it to renew them."),
+    }
+    let (overdue, current): (Vec<_>, Vec<_>) = aging.iter().partition(|age| age.overdue());
+    let line = |age: &aegixpass::sites::SiteAge| match age.age_days {
+        Some(days) => format!("  {}: {} days old, max {}", age.site.display_name(), days, age.max_age_days),
+        None => format!("  {}: never changes, max {}", age.site.display_name(), age.max_age_days),
+    };
+    println!("Older than allowed ({}/{}):", overdue.len(), aging.len());
+    for age in &overdue {
+        println!("{}", line(age));
+    }
+    println!("Within their maximum age ({}/{}):", current.len(), aging.len());
+    for age in &current {
+        println!("{}", line(age));
+    }
+    Ok(())
+}
+
+/// One line of the `rekey` checklist. / `rekey` 核对清单中的一行。
+#[derive(serde::Serialize)]
+struct RekeyRow<'a> {
+    #[serde(rename = "distinguishKey")]
+    distinguish_key: &'a str,
+    username: Option<&'a str>,
+    url: Option<&'a str>,
+    #[serde(rename = "oldPassword")]
+    old_password: String,
+    #[serde(rename = "newPassword")]
+    new_password: String,
+    /// Left for the user to tick off. / 留给用户勾选。
+    done: bool,
+}
+
+/// Runs the `rekey` subcommand: derives each listed site's password under both master passwords
+/// and prints them side by side, so the sites can be changed one by one.
+// 运行 `rekey` 子命令：以两个主密码分别派生每个已列出站点的密码并并排输出，以便逐个修改各站点。
+fn run_rekey(args: &RekeyArgs, preset: &Preset) -> Result<(), Box<dyn std::error::Error>> {
+    // The checklist prints every password at once, like an export. / 核对清单一次输出所有密码，如同导出。
+    preset.output_policy.check(Destination::Stdout)?;
+    preset.output_policy.check(Destination::Export)?;
+    let path = match &args.sites {
+        Some(path) => path.clone(),
+        None => default_sites_path()?,
+    };
+    let sites = SitesFile::load(&path)?;
+    if sites.sites.is_empty() {
+        return Err(format!("No sites are listed in {}; add them with 'aegixpass import' first.", path.display()).into());
+    }
+    let old_password = rekey_password(&args.old_password, "Old master password", false)?;
+    let new_password = rekey_password(&args.new_password, "New master password", true)?;
+    if args.new_password == "-" && rekey_password("-", "Repeat the new master password", false)? != new_password {
+        return Err("The new master passwords do not match.".into());
+    }
+    if old_password == new_password {
+        return Err("The new master password is the same as the old one.".into());
+    }
+
+    let keys: Vec<&str> = sites.sites.iter().map(|site| site.distinguish_key.as_str()).collect();
+    let seed_inputs = args.seed.read()?;
+    let options = seed_inputs.options();
+    let old_passwords = aegixpass_generate_batch_with(&old_password, &keys, preset, &options)?;
+    let new_passwords = aegixpass_generate_batch_with(&new_password, &keys, preset, &options)?;
+    let mut rows = Vec::with_capacity(sites.sites.len());
+    for ((site, old), new) in sites.sites.iter().zip(old_passwords).zip(new_passwords) {
+        rows.push(RekeyRow {
+            distinguish_key: &site.distinguish_key,
+            username: site.username.as_deref(),
+            url: site.url.as_deref(),
+            old_password: old?,
+            new_password: new?,
+            done: false,
+        });
+    }
+    match args.output {
+        RekeyFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+        RekeyFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(std::io::stdout());
+            for row in &rows {
+                csv.serialize(row)?;
+            }
+            csv.flush()?;
+        }
+    }
+    // Escrowed secrets are encrypted under the old master password. / 托管的秘密以旧主密码加密。
+    if let Some(store) = config_dir().map(|dir| dir.join(STORE_FILE)).filter(|path| path.is_file())
+        && StoreFile::load(&store)?.names().next().is_some()
+    {
+        eprintln!("Note: the entries in {} still open with the old master password; save them again with 'aegixpass store set'.", store.display());
+    }
+    Ok(())
+}
+
+/// Reads one of the `rekey` master passwords, prompting with `label` for '-'. The new one is
+/// prompted for with the strength bar, as a master password being chosen.
+// 读取 `rekey` 的一个主密码；值为 '-' 时以 `label` 提示输入。新主密码作为正在选择的主密码，提示时显示强度条。
+fn rekey_password(value: &str, label: &str, new: bool) -> Result<String, Box<dyn std::error::Error>> {
+    if value != "-" {
+        return Ok(value.to_string());
+    }
+    if STDIN_CONSUMED.load(Ordering::SeqCst) {
+        return Err("stdin was already used by '--config -'; pass the master passwords another way.".into());
+    }
+    if new {
+        prompt::read_new_master_password(label, &load_settings()?)
+    } else {
+        prompt::read_existing_secret(label, &load_settings()?)
+    }
+}
+
+/// Runs the `find` subcommand, listing the matching sites with their URL and notes.
+// 运行 `find` 子命令，列出匹配的站点及其 URL 与备注。
+fn run_find(args: &FindArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match &args.sites {
+        Some(path) => path.clone(),
+        None => default_sites_path()?,
+    };
+    let sites = SitesFile::load(&path)?;
+    let found = sites.find(&args.query);
+    if found.is_empty() {
+        return Err(format!("No site in {} matches '{}'.", path.display(), args.query).into());
+    }
+    for site in found {
+        println!("{}", site.display_name());
+        if let Some(url) = &site.url {
+            println!("  URL:   {}", url);
+        }
+        if let Some(notes) = &site.notes {
+            println!("  Notes: {}", notes);
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `why-different` subcommand, listing differing fields and which of them change the output.
+// 运行 `why-different` 子命令，列出不同的字段以及其中哪些会改变输出。
+fn run_why_different(args: &WhyDifferentArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let read = |path: &PathBuf| -> Result<String, Box<dyn std::error::Error>> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Could not read config file '{}': {}", path.display(), e))?;
+        // Report a broken preset against its own file. / 针对出错的那个文件报告有问题的预设。
+        Preset::from_json(&json)
+            .and_then(|preset| preset.validate())
+            .map_err(|source| PresetFileError { path: path.clone(), source })?;
+        Ok(json)
+    };
+    let comparison = aegixpass::compare::why_different(&read(&args.left)?, &read(&args.right)?)?;
+    if comparison.differences.is_empty() {
+        println!("The presets are identical.");
+        return Ok(());
+    }
+    let absent = "(absent)".to_string();
+    for difference in &comparison.differences {
+        println!(
+            "{}: {} -> {}  [{}]",
+            difference.field,
+            difference.left.as_ref().unwrap_or(&absent),
+            difference.right.as_ref().unwrap_or(&absent),
+            if difference.changes_output { "changes output" } else { "no effect" }
+        );
+    }
+    let causes: Vec<&str> = comparison
+        .differences
+        .iter()
+        .filter(|d| d.changes_output)
+        .map(|d| d.field.as_str())
+        .collect();
+    if !comparison.outputs_differ {
+        println!("Both presets derive the same passwords; the differences are cosmetic.");
+    } else if comparison.combination_only() {
+        println!("No single field explains the divergence; it comes from the fields changing together.");
+    } else {
+        println!("Passwords differ because of: {}", causes.join(", "));
+    }
+    Ok(())
+}
+
+/// Environment variable holding the backup passphrase. / 保存备份口令的环境变量。
+const BACKUP_PASSPHRASE_ENV: &str = "AEGIXPASS_BACKUP_PASSPHRASE";
+
+/// Runs `backup create` or `backup restore` against the config directory.
+// 针对配置目录运行 `backup create` 或 `backup restore`。
+fn run_backup(command: &BackupCommand) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = config_dir().ok_or("Could not determine the user config directory.")?;
+    let passphrase = || -> Result<String, Box<dyn std::error::Error>> {
+        match std::env::var(BACKUP_PASSPHRASE_ENV) {
+            Ok(passphrase) => Ok(passphrase),
+            Err(_) => prompt::read_passphrase("Backup passphrase", &load_settings()?),
+        }
+    };
+    match command {
+        BackupCommand::Create { file, encrypt } => {
+            let created = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            let backup = Backup::collect(&dir, created)?;
+            if backup.files.is_empty() {
+                return Err(format!("There is nothing to back up in {}; run `aegixpass setup` first.", dir.display()).into());
+            }
+            let passphrase = if *encrypt { Some(passphrase()?) } else { None };
+            let json = backup.to_json(passphrase.as_deref())?;
+            write_guard::write(file, json).map_err(|e| format!("Could not write '{}': {}", file.display(), e))?;
+            println!(
+                "Backed up {} to {}",
+                backup.files.keys().cloned().collect::<Vec<_>>().join(", "),
+                file.display()
+            );
+        }
+        BackupCommand::Restore { file, force } => {
+            let json = std::fs::read_to_string(file).map_err(|e| format!("Could not read '{}': {}", file.display(), e))?;
+            let passphrase = if Backup::is_encrypted(&json) { Some(passphrase()?) } else { None };
+            let restored = Backup::from_json(&json, passphrase.as_deref())?.restore(&dir, *force)?;
+            println!("Restored {} into {}", restored.join(", "), dir.display());
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `audit` subcommand, printing each finding and the overall score.
+// 运行 `audit` 子命令，输出每个发现项及总分。
+fn run_audit(args: &AuditArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = match &args.dir {
+        Some(dir) => dir.clone(),
+        None => config_dir().ok_or("Could not determine the user config directory.")?,
+    };
+    let report = aegixpass::audit::audit_dir(&dir)?;
+    println!("Audited {} file(s) in {}", report.files.len(), dir.display());
+    for finding in &report.findings {
+        println!("  [{}] {}: {}", finding.severity.label(), finding.file, finding.message);
+    }
+    if report.findings.is_empty() {
+        println!("No issues found.");
+    }
+    println!("Score: {}/100", report.score());
+    Ok(())
+}
+
+/// Runs `charsets test`, describing a candidate charset string.
+// 运行 `charsets test`，描述候选字符集字符串。
+fn run_charsets_test(chars: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let report = aegixpass::charsets::analyze(chars);
+    println!("Characters: {} ({} visible)", report.chars, report.graphemes);
+    println!("Bytes: {} in UTF-8, {} UTF-16 units", report.utf8_bytes, report.utf16_units);
+    println!("Entropy: {:.2} bits per character", report.bits_per_char);
+    let categories: Vec<String> = report.categories.iter().map(|(category, count)| format!("{} {}", count, category.label())).collect();
+    println!("Composition: {}", if categories.is_empty() { "-".to_string() } else { categories.join(", ") });
+    if !report.duplicates.is_empty() {
+        let duplicates: Vec<String> = report.duplicates.iter().map(|(c, count)| format!("{:?} ×{}", c, count)).collect();
+        println!("Duplicates: {}", duplicates.join(", "));
+    }
+    if !report.ambiguous.is_empty() {
+        println!("Ambiguous: {}", report.ambiguous.iter().collect::<String>());
+    }
+    for warning in report.warnings() {
+        println!("Warning: {}.", warning);
+    }
+    Ok(())
+}
+
+/// Runs `cache clear`, deleting the KDF cache and its device key.
+// 运行 `cache clear`，删除 KDF 缓存及其设备密钥。
+fn run_cache_clear() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = cache_dir().ok_or("Could not determine the user cache directory.")?;
+    let cache = KdfCache::new(dir, kdf_cache::DEFAULT_TTL);
+    if cache.clear()? {
+        println!("Cleared the KDF cache in {}", cache.dir().display());
+    } else {
+        println!("The KDF cache in {} is already empty.", cache.dir().display());
+    }
+    Ok(())
+}
+
+/// Resolves the master password argument, prompting for it when it is `-`.
+// 解析主密码参数；当其为 `-` 时提示用户输入。
+fn master_password(value: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if value == "-" && STDIN_CONSUMED.load(Ordering::SeqCst) {
+        return Err("stdin was already used by '--config -'; pass the master password another way.".into());
+    }
+    if value == "-" {
+        prompt::read_master_password(&load_settings()?)
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Loads `settings.json` from the config directory, using the defaults when there is none.
+// 从配置目录加载 `settings.json`；不存在时使用默认值。
+fn load_settings() -> Result<Settings, Box<dyn std::error::Error>> {
+    match config_dir() {
+        Some(dir) => Ok(Settings::load(&dir.join(SETTINGS_FILE))?),
+        None => Ok(Settings::default()),
+    }
+}
+
+/// Runs the `cert` subcommand, printing the certificate followed by its private key.
+// 运行 `cert` 子命令，依次输出证书及其私钥。
+fn run_cert(args: &CertArgs, preset: &Preset) -> Result<(), Box<dyn std::error::Error>> {
+    let key_type = match args.key_type {
+        CertKeyArg::Ed25519 => aegixpass::cert::CertKeyType::Ed25519,
+    };
+    // The private key is printed. / 私钥会被打印出来。
+    preset.output_policy.check(Destination::Stdout)?;
+    let distinguish_key = args.distinguish_key.as_deref().unwrap_or(&args.cn);
+    let seed_inputs = args.seed.read()?;
+    let password_source = master_password(&args.password_source)?;
+    let pair = aegixpass::cert::self_signed_certificate_with(
+        &password_source,
+        distinguish_key,
+        preset,
+        &args.cn,
+        key_type,
+        &seed_inputs.options(),
+    )?;
+    print!("{}{}", pair.cert_pem, pair.key_pem);
+    Ok(())
+}
+
+/// Runs the `pgp` subcommand, printing the armored key and reporting its fingerprint on stderr.
+// 运行 `pgp` 子命令，输出 ASCII 封装的密钥，并在标准错误输出中报告其指纹。
+#[cfg(feature = "pgp")]
+fn run_pgp(args: &PgpArgs, preset: &Preset) -> Result<(), Box<dyn std::error::Error>> {
+    if !args.public {
+        preset.output_policy.check(Destination::Stdout)?;
+    }
+    let distinguish_key = args.distinguish_key.as_deref().unwrap_or(&args.uid);
+    let seed_inputs = args.seed.read()?;
+    let password_source = master_password(&args.password_source)?;
+    let key = aegixpass::openpgp::derive_openpgp_key_with(
+        &password_source,
+        distinguish_key,
+        preset,
+        &args.uid,
+        args.created,
+        &seed_inputs.options(),
+    )?;
+    eprintln!("Fingerprint: {}", key.fingerprint);
+    if args.public {
+        print!("{}", key.public_armored);
+    } else {
+        print!("{}", key.secret_armored);
+    }
+    Ok(())
+}
+
+/// Generates a password (or derived key) and renders it in the requested output format.
+// 生成密码（或派生密钥），并以所请求的输出格式渲染。
+fn run_generate(args: &GenerateArgs, preset: &Preset) -> Result<(), Box<dyn std::error::Error>> {
+    // Both positionals are enforced by clap when no subcommand is given.
+    // 未提供子命令时，clap 保证这两个位置参数都存在。
+    // The preset's output policy is checked before the master password is even asked for.
+    // 在询问主密码之前就检查预设的输出策略。
+    for destination in output_destinations(args) {
+        preset.output_policy.check(destination)?;
+    }
+    // Vault stores the bare password, never a rendering of it such as a dotenv line.
+    // Vault 只保存密码本身，而不是它的某种渲染结果（例如 dotenv 行）。
+    #[cfg(feature = "vault")]
+    if args.vault_write.is_some() && args.output != OutputFormat::Plain {
+        return Err("--vault-write stores the bare password; drop --output.".into());
+    }
+    let distinguish_key = args.distinguish_key.as_deref().unwrap_or_default();
+    let labels = output_labels(args)?;
+    let password_based = !matches!(
+        args.output,
+        OutputFormat::JwtKey
+            | OutputFormat::Jwk
+            | OutputFormat::KeyHex
+            | OutputFormat::KeyBase64
+            | OutputFormat::Otp
+            | OutputFormat::OtpSecret
+            | OutputFormat::OtpauthUri
+            | OutputFormat::Passphrase
+            | OutputFormat::Username
+    );
+    if !labels.is_empty() && !password_based {
+        return Err("--count and --label-suffix only apply to password outputs.".into());
+    }
+    if args.shell_escape != ShellEscapeArg::None && !matches!(args.output, OutputFormat::Plain | OutputFormat::Passphrase) {
+        return Err("--shell-escape only applies to plain and passphrase output.".into());
+    }
+    if args.peek.is_some() {
+        if !matches!(args.output, OutputFormat::Plain | OutputFormat::Passphrase) {
+            return Err("--peek only applies to plain and passphrase output.".into());
+        }
+        // A masked password is only good for looking at. / 被遮盖的密码只能用来看。
+        if output_destinations(args) != [Destination::Stdout] || args.qr || args.shell_escape != ShellEscapeArg::None {
+            return Err("--peek only prints to the terminal; drop --clip, --qr, --shell-escape and export options.".into());
+        }
+    }
+    if args.bom && args.output_encoding == OutputEncodingArg::Console {
+        return Err("--bom needs '--output-encoding utf8' or 'utf16le'.".into());
+    }
+    let seed_inputs = args.seed.read()?;
+    let options = seed_inputs.options();
+    if args.dry_run {
+        return report_dry_run(args, preset, distinguish_key, &labels, &options);
+    }
+    let password_source = &master_password(args.password_source.as_deref().unwrap_or_default())?;
+
+    // Render the requested output format.
+    // 渲染所请求的输出格式。
+    let rendered = match args.output {
+        // Key outputs derive raw key material instead of a charset password.
+        // 密钥类输出派生原始密钥材料，而不是基于字符集的密码。
+        // The master seed itself is never printed: it also yields the site's password and OTP secret.
+        // 从不输出主种子本身：它同时还派生出该站点的密码与 OTP 密钥。
+        OutputFormat::JwtKey | OutputFormat::Jwk | OutputFormat::KeyHex | OutputFormat::KeyBase64 => {
+            let default_label = if matches!(args.output, OutputFormat::JwtKey | OutputFormat::Jwk) { "jwt" } else { "key" };
+            let key = aegixpass_derive_key_with(
+                password_source,
+                distinguish_key,
+                preset,
+                args.key_label.as_deref().unwrap_or(default_label),
+                args.key_bits / 8,
+                &options,
+            )?;
+            match args.output {
+                OutputFormat::Jwk => format!("{}\n", output::render_jwk(&key, distinguish_key)),
+                OutputFormat::KeyHex => format!("{}\n", output::render_hex(&key)),
+                OutputFormat::KeyBase64 => format!("{}\n", output::render_base64(&key)),
+                _ => format!("{}\n", output::render_base64url(&key)),
+            }
+        }
+        // OTP outputs use a derived shared secret. / OTP 类输出使用派生出的共享密钥。
+        OutputFormat::Otp | OutputFormat::OtpSecret | OutputFormat::OtpauthUri => {
+            let secret = otp::derive_otp_secret_with(password_source, distinguish_key, preset, &options)?;
+            let kind = match args.otp_type {
+                OtpTypeArg::Totp => otp::OtpKind::Totp,
+                OtpTypeArg::Hotp => otp::OtpKind::Hotp(args.hotp_counter),
+                OtpTypeArg::Steam => otp::OtpKind::Steam,
+            };
+            let value = match args.output {
+                OutputFormat::Otp => otp::otp_code(&secret, kind, unix_time()?),
+                OutputFormat::OtpSecret => otp::base32_encode(&secret),
+                _ => otp::otpauth_uri(
+                    &secret,
+                    kind,
+                    args.issuer.as_deref().unwrap_or(distinguish_key),
+                    &args.account,
+                ),
+            };
+            format!("{}\n", value)
+        }
+        OutputFormat::Passphrase => {
+            let wordlist = load_wordlist(preset)?;
+            let passphrase = passphrase::derive_passphrase_with(password_source, distinguish_key, preset, &wordlist, &options)?;
+            format!("{}\n", displayed(args, &passphrase))
+        }
+        OutputFormat::Username => {
+            let wordlist = match args.username_style {
+                UsernameStyleArg::Words => Some(load_wordlist(preset)?),
+                UsernameStyleArg::Pronounceable => None,
+            };
+            let style = wordlist.as_deref().map_or(UsernameStyle::Pronounceable, UsernameStyle::Words);
+            format!("{}\n", username::derive_username_with(password_source, distinguish_key, preset, style, &options)?)
+        }
+        password_output => {
+            let env_key = args.env_key.clone().unwrap_or_else(|| output::env_key_from(distinguish_key));
+            // Only the verifier formats need a salt; the deterministic one costs another derivation.
+            // 只有校验值类格式需要盐；确定性盐需要再进行一次派生。
+            let salt = match args.salt {
+                SaltArg::Deterministic
+                    if matches!(password_output, OutputFormat::ScramSha256 | OutputFormat::Htpasswd | OutputFormat::Sha512Crypt) =>
+                {
+                    output::SaltMode::Deterministic(output::derive_salt_key_with(password_source, distinguish_key, preset, &options)?)
+                }
+                _ => output::SaltMode::Random,
+            };
+            // Warned once from the preset, however many passwords follow. PINs are short by design.
+            // 无论随后输出多少个密码，都只按预设警告一次。PIN 本来就很短。
+            let score = aegixpass::strength::score_entropy(aegixpass::estimate_entropy(preset)?);
+            if score.score < 3 && preset.pin.is_none() {
+                eprintln!(
+                    "Warning: this preset derives weak passwords (score {}/4, about {:.0} bits, guessed offline in {}); raise `length` or add charsets.",
+                    score.score,
+                    score.entropy_bits,
+                    score.crack_time()
+                );
+            }
+            if labels.is_empty() {
+                // 调用核心函数生成密码。
+                let password = aegixpass_generator_with(password_source, distinguish_key, preset, &options)?;
+                stamped(args, preset, &options, None, render_password(args, &env_key, password_output, &password, salt)?)
+            } else {
+                // Several labelled passwords from a single derivation, in the order of the labels.
+                // 由一次派生得到的多个带标签密码，按标签顺序输出。
+                let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+                let passwords = aegixpass_generator_multi_with(password_source, distinguish_key, preset, &labels, &options)?;
+                let mut rendered = String::new();
+                for (label, password) in labels.iter().zip(&passwords) {
+                    let env_key = output::env_key_from(&format!("{}_{}", env_key, label));
+                    rendered += &stamped(args, preset, &options, Some(label), render_password(args, &env_key, password_output, password, salt)?);
+                }
+                rendered
+            }
+        }
+    };
+
+    // Optionally turn the value into a scannable QR code.
+    // 可选地将结果转换为可扫描的二维码。
+    let rendered = if args.qr {
+        output::render_qr(rendered.trim_end())?
+    } else {
+        rendered
+    };
+
+    // Write to Vault if requested; otherwise print the value to standard output.
+    // 如果指定了 Vault 目标则写入 Vault，否则将结果打印到标准输出。
+    #[cfg(feature = "vault")]
+    if let Some(target) = &args.vault_write {
+        let target = aegixpass::vault::VaultTarget::parse(target)?;
+        let stamp = args.version_of_output.then(|| output_stamp(preset, &options, None));
+        aegixpass::vault::write_secret(&target, rendered.trim_end(), stamp.as_deref())?;
+        eprintln!(
+            "Wrote derived value to Vault at {}/{}#{}.",
+            target.mount, target.path, target.field
+        );
+        if let Some(stamp) = stamp {
+            eprintln!("{} (also saved as {})", stamp, target.stamp_field());
+        }
+        return Ok(());
+    }
+
+    // Optionally encrypt the rendered text so it can be committed safely.
+    // 可选地加密渲染结果，以便安全地提交到版本库。
+    #[cfg(feature = "age")]
+    let rendered = if args.encrypt_age.is_empty() {
+        rendered
+    } else {
+        output::encrypt_age(&rendered, &args.encrypt_age)?
+    };
+
+    if args.clip {
+        sink::deliver(&mut ClipboardSink::default(), &preset.output_policy, &rendered)?;
+        eprintln!("Copied to the clipboard.");
+        if args.version_of_output {
+            eprintln!("{}", output_stamp(preset, &options, None));
+        }
+        return Ok(());
+    }
+
+    let mut text = rendered;
+    // Dotenv entries already carry the stamp as comments. / dotenv 条目已经以注释形式带有记录。
+    if args.version_of_output && args.output != OutputFormat::Dotenv {
+        text += &format!("{}\n", output_stamp(preset, &options, None));
+    }
+    let encoding = match args.output_encoding {
+        OutputEncodingArg::Console | OutputEncodingArg::Utf8 => output::TextEncoding::Utf8,
+        OutputEncodingArg::Utf16le => output::TextEncoding::Utf16Le,
+    };
+    let mut stdout = StdoutSink { encoding, bom: args.bom, crlf: args.newline == NewlineArg::Crlf };
+    sink::deliver(&mut stdout, &preset.output_policy, &text)?;
+    Ok(())
+}
+
+/// Prints what `--dry-run` found: everything a real run would use, short of the derived value.
+// 打印 `--dry-run` 的结果：真正运行时会用到的一切，派生值本身除外。
+fn report_dry_run(
+    args: &GenerateArgs,
+    preset: &Preset,
+    distinguish_key: &str,
+    labels: &[String],
+    options: &SeedOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let preflight = aegixpass_preflight(distinguish_key, preset, options)?;
+    let output = args.output.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default();
+    let destinations: Vec<&str> = output_destinations(args).iter().map(Destination::name).collect();
+    println!("Dry run: all checks passed; nothing was derived.");
+    println!("  preset:          {} ({})", preset.name, preset.profile_id());
+    println!("  fingerprint:     {}", preset.fingerprint());
+    println!("  distinguish key: {}", distinguish_key);
+    println!("  output:          {}, {}", output, destinations.join(" and "));
+    if !labels.is_empty() {
+        println!("  labels:          {}", labels.join(", "));
+    }
+    if let Some(epoch) = &preflight.epoch {
+        println!("  rotation epoch:  {}", epoch);
+    }
+    if options.counter != 0 {
+        println!("  counter:         {}", options.counter);
+    }
+    match args.output {
+        OutputFormat::JwtKey | OutputFormat::Jwk | OutputFormat::KeyHex | OutputFormat::KeyBase64 => {
+            println!("  key size:        {} bits", args.key_bits)
+        }
+        OutputFormat::Otp | OutputFormat::OtpSecret | OutputFormat::OtpauthUri => {}
+        OutputFormat::Passphrase => {
+            let wordlist = load_wordlist(preset)?;
+            let words = preset.passphrase.as_ref().map_or(0, |settings| settings.words);
+            println!("  entropy:         about {:.0} bits per passphrase", words as f64 * wordlist.bits_per_word());
+        }
+        OutputFormat::Username => {
+            let bits = match args.username_style {
+                UsernameStyleArg::Words => UsernameStyle::Words(&*load_wordlist(preset)?).entropy_bits(),
+                UsernameStyleArg::Pronounceable => UsernameStyle::Pronounceable.entropy_bits(),
+            };
+            println!("  entropy:         about {:.0} bits per username", bits);
+        }
+        _ => {
+            println!("  entropy:         about {:.1} bits per password (estimate_entropy)", preflight.entropy_bits);
+        }
+    }
+    Ok(())
+}
+
+/// Reads and checks the wordlist of the preset's passphrase settings.
+// 读取并检查预设口令短语设置中的词表。
+fn load_wordlist(preset: &Preset) -> Result<std::sync::Arc<passphrase::Wordlist>, AegixPassError> {
+    let settings = preset.passphrase.as_ref().ok_or(AegixPassError::PassphraseNotConfigured)?;
+    passphrase::Wordlist::load_shared(Path::new(&settings.wordlist), &settings.wordlist_sha256)
+}
+
+/// The destinations the requested output reaches, for the preset's output policy.
+// 所请求的输出会到达的目的地，用于检查预设的输出策略。
+fn output_destinations(args: &GenerateArgs) -> Vec<Destination> {
+    #[cfg(feature = "vault")]
+    if args.vault_write.is_some() {
+        return vec![Destination::Export];
+    }
+    #[cfg(feature = "age")]
+    let encrypted = !args.encrypt_age.is_empty();
+    #[cfg(not(feature = "age"))]
+    let encrypted = false;
+    let delivered = if args.clip { Destination::Clipboard } else { Destination::Stdout };
+    if encrypted || args.output == OutputFormat::Dotenv {
+        vec![Destination::Export, delivered]
+    } else {
+        vec![delivered]
+    }
+}
+
+/// Reads a keyfile, refusing an empty one, which would silently add no second factor.
+// 读取密钥文件；拒绝空文件，因为空文件会在不知不觉中使第二因素失效。
+fn read_keyfile(path: &Path) -> Result<Vec<u8>, String> {
+    let contents = std::fs::read(path).map_err(|e| format!("Could not read keyfile '{}': {}", path.display(), e))?;
+    if contents.is_empty() {
+        return Err(format!("The keyfile '{}' is empty.", path.display()));
+    }
+    Ok(contents)
+}
+
+/// Reads an Argon2 secret, ignoring a trailing newline so `echo secret > file` works.
+// 读取 Argon2 密钥，忽略末尾的换行符，使 `echo secret > file` 也能正常使用。
+fn read_argon2_secret(path: &Path) -> Result<Vec<u8>, String> {
+    let mut contents = std::fs::read(path).map_err(|e| format!("Could not read Argon2 secret '{}': {}", path.display(), e))?;
+    while contents.last().is_some_and(|&b| b == b'\n' || b == b'\r') {
+        contents.pop();
+    }
+    if contents.is_empty() {
+        return Err(format!("The Argon2 secret file '{}' is empty.", path.display()));
+    }
+    Ok(contents)
+}
+
+/// A machine-parsable line recording how an output was derived: the tool version, algorithm
+/// profile and preset fingerprint, then the counter, rotation epoch and label where they apply.
+// 记录输出派生方式的、可供机器解析的一行信息：工具版本、算法配置与预设指纹，以及适用时的计数器、轮换周期与标签。
+fn output_stamp(preset: &Preset, options: &SeedOptions, label: Option<&str>) -> String {
+    let mut stamp = format!(
+        "aegixpass-stamp: tool={} profile={} preset={}",
+        env!("CARGO_PKG_VERSION"),
+        preset.profile_id(),
+        preset.fingerprint()
+    );
+    if options.counter != 0 {
+        stamp += &format!(" counter={}", options.counter);
+    }
+    if let Some(rotation) = preset.rotation {
+        stamp += &format!(" epoch={}", options.epoch.map_or_else(|| rotation.current_epoch(), str::to_string));
+    }
+    if let Some(label) = label {
+        stamp += &format!(" label={}", label);
+    }
+    stamp
+}
+
+/// Puts the stamp of a dotenv entry above it as a comment, with `--version-of-output`, so every
+/// exported variable can be traced back to its derivation.
+// 在 `--version-of-output` 下，将 dotenv 条目的记录作为注释放在其上方，使每个导出的变量都能追溯到其派生方式。
+fn stamped(args: &GenerateArgs, preset: &Preset, options: &SeedOptions, label: Option<&str>, entry: String) -> String {
+    if args.version_of_output && args.output == OutputFormat::Dotenv {
+        format!("# {}\n{}", output_stamp(preset, options, label), entry)
+    } else {
+        entry
+    }
+}
+
+/// The labels requested with `--count` / `--label-suffix`; empty for a single password.
+// 通过 `--count` / `--label-suffix` 请求的标签；单个密码时为空。
+fn output_labels(args: &GenerateArgs) -> Result<Vec<String>, String> {
+    if args.label_suffix.is_empty() {
+        return Ok(args.count.map_or_else(Vec::new, |count| (1..=count).map(|i| i.to_string()).collect()));
+    }
+    match args.count {
+        Some(count) if count as usize != args.label_suffix.len() => Err(format!(
+            "--count {} does not match the {} --label-suffix values given.",
+            count,
+            args.label_suffix.len()
+        )),
+        _ => Ok(args.label_suffix.clone()),
+    }
+}
+
+/// Renders a generated password in one of the password-based output formats.
+// 以某种基于密码的输出格式渲染生成的密码。
+fn render_password(
+    args: &GenerateArgs,
+    env_key: &str,
+    format: OutputFormat,
+    password: &str,
+    salt: output::SaltMode,
+) -> Result<String, AegixPassError> {
+    Ok(match format {
+        OutputFormat::Plain => format!("{}\n", displayed(args, password)),
+        OutputFormat::Dotenv => output::render_dotenv(env_key, password),
+        OutputFormat::ScramSha256 => {
+            format!("{}\n", output::render_scram_sha256(password, salt))
+        }
+        OutputFormat::MysqlNative => format!("{}\n", output::render_mysql_native(password)),
+        OutputFormat::PsSecureString => format!("{}\n", output::render_ps_securestring(password)),
+        OutputFormat::Htpasswd => {
+            let user = args.user.as_deref().unwrap_or_default();
+            format!("{}\n", output::render_htpasswd(user, password, salt)?)
         }
+        OutputFormat::Sha512Crypt => {
+            format!("{}\n", output::render_sha512_crypt(password, salt)?)
+        }
+        OutputFormat::JwtKey
+        | OutputFormat::Jwk
+        | OutputFormat::KeyHex
+        | OutputFormat::KeyBase64
+        | OutputFormat::Otp
+        | OutputFormat::OtpSecret
+        | OutputFormat::OtpauthUri
+        | OutputFormat::Passphrase
+        | OutputFormat::Username => format!("{}\n", password),
+    })
+}
+
+/// The value as `--peek` masks it or quoted for the shell chosen with `--shell-escape`, if any.
+// 按 `--peek` 遮盖后的值，或按 `--shell-escape` 所选的 shell 加引号后的值（若有）。
+fn displayed<'a>(args: &GenerateArgs, value: &'a str) -> std::borrow::Cow<'a, str> {
+    if let Some(reveal) = args.peek {
+        return output::render_peek(value, reveal).into();
+    }
+    match args.shell_escape {
+        ShellEscapeArg::None => value.into(),
+        ShellEscapeArg::Bash => output::shell_quote(value, output::Shell::Bash).into(),
+        ShellEscapeArg::Powershell => output::shell_quote(value, output::Shell::PowerShell).into(),
     }
 }
 
+/// Returns the current Unix time in seconds.
+// 返回当前的 Unix 时间（秒）。
+fn unix_time() -> Result<u64, std::time::SystemTimeError> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
+
 /// Program entry point.
 // 程序入口。
 fn main() {
     // Execute the run function and handle any potential errors.
     // 执行 run 函数并处理可能发生的任何错误。
-    match run() {
-        Ok(password) => {
-            // On success, print the generated password to standard output.
-            // 成功时，将生成的密码打印到标准输出。
-            println!("{}", password);
-        }
-        Err(e) => {
-            // On failure, print the error message to standard error and exit with a non-zero status code.
-            // 失败时，将错误信息打印到标准错误输出，并以非零状态码退出。
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
+    // On success the output has already been written by `run`.
+    // 成功时输出已由 `run` 完成。
+    if let Err(e) = run() {
+        // On failure, print the error and a hint to standard error and exit with a non-zero status code.
+        // 失败时，将错误信息及修复建议打印到标准错误输出，并以非零状态码退出。
+        report_error(e.as_ref());
+        std::process::exit(1);
     }
 }
\ No newline at end of file
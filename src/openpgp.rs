@@ -0,0 +1,361 @@
+// --- Deterministic OpenPGP keys (feature "pgp") ---
+// --- 确定性 OpenPGP 密钥 (需启用 "pgp" 特性) ---
+// Builds a version 4 OpenPGP transferable secret key (RFC 4880 / RFC 6637) from derived
+// key material: an Ed25519 primary key for certification and signing plus a Curve25519
+// subkey for encryption. Both keys, their creation timestamp and all signatures are
+// deterministic, so the whole identity can be rebuilt from the master password.
+// 根据派生出的密钥材料构建一个第 4 版 OpenPGP 可传输私钥 (RFC 4880 / RFC 6637)：
+// 一个用于认证和签名的 Ed25519 主密钥，以及一个用于加密的 Curve25519 子密钥。
+// 两个密钥、它们的创建时间以及所有签名都是确定性的，因此整个身份可以仅凭主密码重建。
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signer, SigningKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::core::{derive_purpose_key, AegixPassError, Preset, Purpose, SeedOptions};
+
+/// Default key creation time (2020-01-01T00:00:00Z). It is part of the fingerprint, so it
+/// must stay fixed for the key to be reproducible.
+// 默认的密钥创建时间 (2020-01-01T00:00:00Z)。它是指纹的一部分，必须保持固定才能重现密钥。
+pub const DEFAULT_CREATION_TIME: u32 = 1_577_836_800;
+
+// --- OpenPGP constants / OpenPGP 常量 ---
+const ALGO_EDDSA: u8 = 22;
+const ALGO_ECDH: u8 = 18;
+const HASH_SHA256: u8 = 8;
+const SYM_AES128: u8 = 7;
+const OID_ED25519: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01];
+const OID_CURVE25519: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01];
+
+/// An ASCII-armored OpenPGP key pair.
+// ASCII 封装的 OpenPGP 密钥对。
+#[derive(Debug)]
+pub struct OpenPgpKey {
+    /// Upper-case hex fingerprint of the primary key. / 主密钥的大写十六进制指纹。
+    pub fingerprint: String,
+    pub secret_armored: String,
+    pub public_armored: String,
+}
+
+/// Derives an OpenPGP identity for `user_id` (e.g. `Alice <alice@example.com>`).
+// 为 `user_id`（例如 `Alice <alice@example.com>`）派生 OpenPGP 身份。
+pub fn derive_openpgp_key(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    user_id: &str,
+    creation_time: u32,
+) -> Result<OpenPgpKey, AegixPassError> {
+    derive_openpgp_key_with(password_source, distinguish_key, preset, user_id, creation_time, &SeedOptions::default())
+}
+
+/// [`derive_openpgp_key`] with extra seed inputs such as a keyfile.
+// 带有额外种子输入（例如密钥文件）的 [`derive_openpgp_key`]。
+pub fn derive_openpgp_key_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    user_id: &str,
+    creation_time: u32,
+    options: &SeedOptions,
+) -> Result<OpenPgpKey, AegixPassError> {
+    if user_id.is_empty() {
+        return Err(AegixPassError::InputEmpty);
+    }
+
+    // 1. Derive independent seeds for the primary key and the encryption subkey.
+    // 1. 分别为主密钥和加密子密钥派生相互独立的种子。
+    let sign_seed: [u8; 32] = derive_seed(password_source, distinguish_key, preset, "openpgp-ed25519", options)?;
+    let mut enc_secret: [u8; 32] = derive_seed(password_source, distinguish_key, preset, "openpgp-cv25519", options)?;
+    enc_secret[0] &= 248;
+    enc_secret[31] &= 127;
+    enc_secret[31] |= 64;
+
+    let signing_key = SigningKey::from_bytes(&sign_seed);
+    let sign_public = signing_key.verifying_key().to_bytes();
+    let enc_public = x25519_dalek::x25519(enc_secret, x25519_dalek::X25519_BASEPOINT_BYTES);
+
+    // 2. Public key bodies, shared by the public and secret key packets.
+    // 2. 公钥主体，由公钥包与私钥包共用。
+    let primary_public = public_key_body(creation_time, ALGO_EDDSA, OID_ED25519, &sign_public, None);
+    let subkey_public = public_key_body(
+        creation_time,
+        ALGO_ECDH,
+        OID_CURVE25519,
+        &enc_public,
+        Some(&[0x03, 0x01, HASH_SHA256, SYM_AES128]),
+    );
+    let fingerprint: [u8; 20] = Sha1::digest(key_hash_prefix(&primary_public)).into();
+    let key_id = &fingerprint[12..];
+
+    // 3. Self-signatures: a positive certification of the user ID and a subkey binding.
+    // 3. 自签名：对用户 ID 的肯定认证签名，以及子密钥绑定签名。
+    let mut certified = key_hash_prefix(&primary_public);
+    certified.push(0xb4);
+    certified.extend_from_slice(&(user_id.len() as u32).to_be_bytes());
+    certified.extend_from_slice(user_id.as_bytes());
+    let uid_signature = signature_packet(
+        &signing_key,
+        0x13,
+        creation_time,
+        &[0x01 | 0x02], // certify + sign / 认证 + 签名
+        true,
+        &fingerprint,
+        key_id,
+        certified,
+    );
+
+    let mut bound = key_hash_prefix(&primary_public);
+    bound.extend_from_slice(&key_hash_prefix(&subkey_public));
+    let subkey_signature = signature_packet(
+        &signing_key,
+        0x18,
+        creation_time,
+        &[0x04 | 0x08], // encrypt communications + storage / 加密通信 + 存储
+        false,
+        &fingerprint,
+        key_id,
+        bound,
+    );
+
+    // 4. Assemble both transferable keys.
+    // 4. 组装公钥与私钥两种可传输密钥。
+    let mut uid_packet = Vec::new();
+    push_packet(&mut uid_packet, 13, user_id.as_bytes());
+
+    // Cv25519 secrets are stored as a big-endian MPI, i.e. byte-reversed (RFC 6637 / GnuPG).
+    // Cv25519 私钥以大端序 MPI 存储，即字节顺序反转 (RFC 6637 / GnuPG)。
+    let mut enc_secret_be = enc_secret;
+    enc_secret_be.reverse();
+
+    let mut secret = Vec::new();
+    push_packet(&mut secret, 5, &secret_key_body(&primary_public, &sign_seed));
+    secret.extend_from_slice(&uid_packet);
+    secret.extend_from_slice(&uid_signature);
+    push_packet(&mut secret, 7, &secret_key_body(&subkey_public, &enc_secret_be));
+    secret.extend_from_slice(&subkey_signature);
+
+    let mut public = Vec::new();
+    push_packet(&mut public, 6, &primary_public);
+    public.extend_from_slice(&uid_packet);
+    public.extend_from_slice(&uid_signature);
+    push_packet(&mut public, 14, &subkey_public);
+    public.extend_from_slice(&subkey_signature);
+
+    Ok(OpenPgpKey {
+        fingerprint: fingerprint.iter().map(|b| format!("{:02X}", b)).collect(),
+        secret_armored: armor("PRIVATE KEY BLOCK", &secret),
+        public_armored: armor("PUBLIC KEY BLOCK", &public),
+    })
+}
+
+fn derive_seed(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    label: &str,
+    options: &SeedOptions,
+) -> Result<[u8; 32], AegixPassError> {
+    let key = derive_purpose_key(password_source, distinguish_key, preset, Purpose::Key, label, 32, options)?;
+    Ok(key.try_into().expect("Derived key length is exactly 32 bytes"))
+}
+
+/// Encodes an unsigned big-endian integer as an OpenPGP multiprecision integer.
+// 将无符号大端整数编码为 OpenPGP 多精度整数 (MPI)。
+fn mpi(bytes: &[u8]) -> Vec<u8> {
+    let first = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    let value = &bytes[first..];
+    let bits = match value.first() {
+        Some(&b) => (value.len() - 1) * 8 + (8 - b.leading_zeros() as usize),
+        None => 0,
+    };
+    let mut out = (bits as u16).to_be_bytes().to_vec();
+    out.extend_from_slice(value);
+    out
+}
+
+fn public_key_body(creation_time: u32, algo: u8, oid: &[u8], point: &[u8; 32], kdf: Option<&[u8]>) -> Vec<u8> {
+    let mut body = vec![4];
+    body.extend_from_slice(&creation_time.to_be_bytes());
+    body.push(algo);
+    body.push(oid.len() as u8);
+    body.extend_from_slice(oid);
+    // Native point encoding: 0x40 prefix followed by the 32-byte point.
+    // 原生点编码：0x40 前缀加 32 字节的点。
+    let mut prefixed = vec![0x40];
+    prefixed.extend_from_slice(point);
+    body.extend_from_slice(&mpi(&prefixed));
+    if let Some(kdf) = kdf {
+        body.extend_from_slice(kdf);
+    }
+    body
+}
+
+fn secret_key_body(public_body: &[u8], secret: &[u8]) -> Vec<u8> {
+    let mut body = public_body.to_vec();
+    body.push(0); // Unprotected secret key material. / 未加密的私钥材料。
+    let secret_mpi = mpi(secret);
+    let checksum = secret_mpi.iter().fold(0u16, |acc, &b| acc.wrapping_add(b as u16));
+    body.extend_from_slice(&secret_mpi);
+    body.extend_from_slice(&checksum.to_be_bytes());
+    body
+}
+
+/// The `0x99 || length || body` framing used when hashing a public key.
+// 对公钥计算哈希时使用的 `0x99 || 长度 || 主体` 格式。
+fn key_hash_prefix(public_body: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x99];
+    out.extend_from_slice(&(public_body.len() as u16).to_be_bytes());
+    out.extend_from_slice(public_body);
+    out
+}
+
+fn push_subpacket(out: &mut Vec<u8>, kind: u8, data: &[u8]) {
+    out.push((data.len() + 1) as u8);
+    out.push(kind);
+    out.extend_from_slice(data);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn signature_packet(
+    signing_key: &SigningKey,
+    sig_type: u8,
+    creation_time: u32,
+    key_flags: &[u8],
+    with_preferences: bool,
+    fingerprint: &[u8; 20],
+    key_id: &[u8],
+    mut hashed_data: Vec<u8>,
+) -> Vec<u8> {
+    let mut hashed = Vec::new();
+    push_subpacket(&mut hashed, 2, &creation_time.to_be_bytes());
+    push_subpacket(&mut hashed, 27, key_flags);
+    if with_preferences {
+        push_subpacket(&mut hashed, 11, &[9, 8, SYM_AES128]); // AES256, AES192, AES128
+        push_subpacket(&mut hashed, 21, &[10, 9, HASH_SHA256]); // SHA512, SHA384, SHA256
+        push_subpacket(&mut hashed, 22, &[2, 1, 0]); // ZLIB, ZIP, none
+        push_subpacket(&mut hashed, 30, &[0x01]); // Modification detection / 修改检测
+    }
+    let mut issuer_fpr = vec![4];
+    issuer_fpr.extend_from_slice(fingerprint);
+    push_subpacket(&mut hashed, 33, &issuer_fpr);
+
+    let mut header = vec![4, sig_type, ALGO_EDDSA, HASH_SHA256];
+    header.extend_from_slice(&(hashed.len() as u16).to_be_bytes());
+    header.extend_from_slice(&hashed);
+
+    hashed_data.extend_from_slice(&header);
+    hashed_data.extend_from_slice(&[0x04, 0xff]);
+    hashed_data.extend_from_slice(&(header.len() as u32).to_be_bytes());
+    let digest = Sha256::digest(&hashed_data);
+    // Legacy EdDSA signs the digest itself. / 旧式 EdDSA 直接对摘要签名。
+    let signature = signing_key.sign(&digest).to_bytes();
+
+    let mut unhashed = Vec::new();
+    push_subpacket(&mut unhashed, 16, key_id);
+
+    let mut body = header;
+    body.extend_from_slice(&(unhashed.len() as u16).to_be_bytes());
+    body.extend_from_slice(&unhashed);
+    body.extend_from_slice(&digest[..2]);
+    body.extend_from_slice(&mpi(&signature[..32]));
+    body.extend_from_slice(&mpi(&signature[32..]));
+
+    let mut packet = Vec::new();
+    push_packet(&mut packet, 2, &body);
+    packet
+}
+
+/// Appends a new-format packet with the given tag.
+// 追加一个使用新格式包头的数据包。
+fn push_packet(out: &mut Vec<u8>, tag: u8, body: &[u8]) {
+    out.push(0xc0 | tag);
+    let len = body.len();
+    if len < 192 {
+        out.push(len as u8);
+    } else if len < 8384 {
+        let v = len - 192;
+        out.push(((v >> 8) + 192) as u8);
+        out.push((v & 0xff) as u8);
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(body);
+}
+
+/// Wraps binary OpenPGP data in ASCII armor with a CRC-24 checksum.
+// 将二进制 OpenPGP 数据封装为带 CRC-24 校验的 ASCII 格式。
+fn armor(kind: &str, data: &[u8]) -> String {
+    let mut crc: u32 = 0xb704ce;
+    for &b in data {
+        crc ^= (b as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864cfb;
+            }
+        }
+    }
+    let crc = crc & 0xffffff;
+
+    let encoded = BASE64.encode(data);
+    let mut out = format!("-----BEGIN PGP {}-----\n\n", kind);
+    for line in encoded.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).expect("Base64 output is ASCII"));
+        out.push('\n');
+    }
+    out.push('=');
+    out.push_str(&BASE64.encode(&crc.to_be_bytes()[1..]));
+    out.push_str(&format!("\n-----END PGP {}-----\n", kind));
+    out
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_fast_preset() -> Preset {
+        let json_preset = r#"
+        {
+          "name": "AegixPass - Sha256",
+          "version": 1,
+          "hashAlgorithm": "sha256",
+          "rngAlgorithm": "chaCha20",
+          "shuffleAlgorithm": "fisherYates",
+          "length": 16,
+          "platformId": "aegixpass.takuron.com",
+          "charsets": ["0123456789", "abcdefghijklmnopqrstuvwxyz"]
+        }
+        "#;
+        serde_json::from_str(json_preset).expect("The preset JSON in the test is invalid")
+    }
+
+    #[test]
+    fn test_mpi_encoding() {
+        assert_eq!(mpi(&[0x00, 0x01]), vec![0x00, 0x01, 0x01]);
+        assert_eq!(mpi(&[0x40, 0xff]), vec![0x00, 0x0f, 0x40, 0xff]);
+    }
+
+    #[test]
+    fn test_openpgp_key_is_reproducible() {
+        let preset = load_fast_preset();
+        let uid = "Alice <alice@example.com>";
+        let first = derive_openpgp_key("master", uid, &preset, uid, DEFAULT_CREATION_TIME).unwrap();
+        let second = derive_openpgp_key("master", uid, &preset, uid, DEFAULT_CREATION_TIME).unwrap();
+        assert_eq!(first.secret_armored, second.secret_armored);
+        assert_eq!(first.fingerprint.len(), 40);
+        assert!(first.public_armored.starts_with("-----BEGIN PGP PUBLIC KEY BLOCK-----"));
+
+        let later = derive_openpgp_key("master", uid, &preset, uid, DEFAULT_CREATION_TIME + 1).unwrap();
+        assert_ne!(first.fingerprint, later.fingerprint, "The creation time is part of the fingerprint");
+
+        let keyed = SeedOptions { key_material: Some(b"contents of a keyfile"), ..SeedOptions::default() };
+        let third = derive_openpgp_key_with("master", uid, &preset, uid, DEFAULT_CREATION_TIME, &keyed).unwrap();
+        assert_ne!(first.fingerprint, third.fingerprint, "The seed inputs reach the key");
+    }
+}
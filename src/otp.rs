@@ -0,0 +1,214 @@
+// --- One-time passwords ---
+// --- 一次性密码 ---
+// Derives an OTP shared secret from the master seed, computes TOTP (RFC 6238), HOTP
+// (RFC 4226) or Steam Guard codes for it and renders the `otpauth://` enrollment URI
+// understood by authenticator apps.
+// 从主种子派生 OTP 共享密钥，为其计算 TOTP (RFC 6238)、HOTP (RFC 4226) 或 Steam 令牌验证码，
+// 并渲染身份验证器应用可识别的 `otpauth://` 注册 URI。
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::core::{derive_purpose_key, AegixPassError, Preset, Purpose, SeedOptions};
+
+/// Length of derived OTP secrets in bytes (160 bits, as recommended by RFC 4226).
+// 派生 OTP 密钥的字节长度（160 比特，符合 RFC 4226 的建议）。
+pub const OTP_SECRET_LENGTH: usize = 20;
+/// Number of digits in a TOTP code. / TOTP 验证码的位数。
+pub const TOTP_DIGITS: u32 = 6;
+/// TOTP time step in seconds. / TOTP 时间步长（秒）。
+pub const TOTP_PERIOD: u64 = 30;
+/// Alphabet of Steam Guard codes. / Steam 令牌验证码使用的字母表。
+const STEAM_ALPHABET: &[u8; 26] = b"23456789BCDFGHJKMNPQRTVWXY";
+/// Number of characters in a Steam Guard code. / Steam 令牌验证码的字符数。
+const STEAM_DIGITS: usize = 5;
+
+/// The OTP flavor a site expects.
+// 站点所使用的 OTP 类型。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OtpKind {
+    /// Time-based codes (RFC 6238). / 基于时间的验证码 (RFC 6238)。
+    Totp,
+    /// Counter-based codes (RFC 4226) at the given counter. / 基于计数器的验证码 (RFC 4226)，使用给定的计数器值。
+    Hotp(u64),
+    /// Steam Guard: TOTP with a 5-character alphanumeric alphabet. / Steam 令牌：使用 5 位字母数字字母表的 TOTP。
+    Steam,
+}
+
+/// Derives the shared OTP secret for a site.
+// 为站点派生 OTP 共享密钥。
+pub fn derive_otp_secret(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+) -> Result<Vec<u8>, AegixPassError> {
+    derive_otp_secret_with(password_source, distinguish_key, preset, &SeedOptions::default())
+}
+
+/// [`derive_otp_secret`] with extra seed inputs such as a keyfile.
+// 带有额外种子输入（例如密钥文件）的 [`derive_otp_secret`]。
+pub fn derive_otp_secret_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+) -> Result<Vec<u8>, AegixPassError> {
+    derive_purpose_key(password_source, distinguish_key, preset, Purpose::Totp, "totp", OTP_SECRET_LENGTH, options)
+}
+
+/// Encodes bytes as unpadded RFC 4648 base32, the format authenticator apps expect.
+// 将字节编码为无填充的 RFC 4648 base32，这是身份验证器应用所需的格式。
+pub fn base32_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// Computes an RFC 4226 HOTP value for the given counter.
+// 为给定计数器计算 RFC 4226 HOTP 值。
+fn hotp_value(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    // Dynamic truncation. / 动态截断。
+    let offset = (digest[19] & 0x0f) as usize;
+    u32::from_be_bytes([digest[offset] & 0x7f, digest[offset + 1], digest[offset + 2], digest[offset + 3]])
+}
+
+/// Computes the RFC 4226 HOTP code for a counter value.
+// 为计数器值计算 RFC 4226 HOTP 验证码。
+pub fn hotp_code(secret: &[u8], counter: u64) -> String {
+    let value = hotp_value(secret, counter) % 10u32.pow(TOTP_DIGITS);
+    format!("{:0width$}", value, width = TOTP_DIGITS as usize)
+}
+
+/// Computes the RFC 6238 TOTP code for a Unix timestamp.
+// 为 Unix 时间戳计算 RFC 6238 TOTP 验证码。
+pub fn totp_code(secret: &[u8], unix_time: u64) -> String {
+    hotp_code(secret, unix_time / TOTP_PERIOD)
+}
+
+/// Computes the Steam Guard code for a Unix timestamp.
+// 为 Unix 时间戳计算 Steam 令牌验证码。
+pub fn steam_code(secret: &[u8], unix_time: u64) -> String {
+    let mut value = hotp_value(secret, unix_time / TOTP_PERIOD);
+    let mut code = String::with_capacity(STEAM_DIGITS);
+    for _ in 0..STEAM_DIGITS {
+        code.push(STEAM_ALPHABET[(value % 26) as usize] as char);
+        value /= 26;
+    }
+    code
+}
+
+/// Computes the code of the given OTP flavor; `unix_time` is ignored for HOTP.
+// 计算指定类型的验证码；HOTP 会忽略 `unix_time`。
+pub fn otp_code(secret: &[u8], kind: OtpKind, unix_time: u64) -> String {
+    match kind {
+        OtpKind::Totp => totp_code(secret, unix_time),
+        OtpKind::Hotp(counter) => hotp_code(secret, counter),
+        OtpKind::Steam => steam_code(secret, unix_time),
+    }
+}
+
+/// Renders an `otpauth://` URI for enrolling the secret in an authenticator app.
+/// Steam secrets use the `otpauth://steam/` form understood by Aegis.
+// 渲染用于在身份验证器应用中注册密钥的 `otpauth://` URI。
+// Steam 密钥使用 Aegis 可识别的 `otpauth://steam/` 形式。
+pub fn otpauth_uri(secret: &[u8], kind: OtpKind, issuer: &str, account: &str) -> String {
+    let label = if account.is_empty() {
+        percent_encode(issuer)
+    } else {
+        format!("{}:{}", percent_encode(issuer), percent_encode(account))
+    };
+    let (kind_name, parameters) = match kind {
+        OtpKind::Totp => ("totp", format!("digits={}&period={}", TOTP_DIGITS, TOTP_PERIOD)),
+        OtpKind::Hotp(counter) => ("hotp", format!("digits={}&counter={}", TOTP_DIGITS, counter)),
+        OtpKind::Steam => ("steam", format!("digits={}&period={}", STEAM_DIGITS, TOTP_PERIOD)),
+    };
+    format!(
+        "otpauth://{}/{}?secret={}&issuer={}&algorithm=SHA1&{}",
+        kind_name,
+        label,
+        base32_encode(secret),
+        percent_encode(issuer),
+        parameters
+    )
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+// 对 RFC 3986 非保留字符之外的所有字符进行百分号编码。
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base32_encode() {
+        // RFC 4648 test vectors (without padding). / RFC 4648 测试向量（无填充）。
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_encode(b"fooba"), "MZXW6YTB");
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        // SHA-1 test vectors from RFC 6238 Appendix B, truncated to 6 digits.
+        // 来自 RFC 6238 附录 B 的 SHA-1 测试向量，截取为 6 位。
+        let secret = b"12345678901234567890";
+        assert_eq!(totp_code(secret, 59), "287082");
+        assert_eq!(totp_code(secret, 1111111109), "081804");
+        assert_eq!(totp_code(secret, 20000000000), "353130");
+    }
+
+    #[test]
+    fn test_hotp_rfc4226_vectors() {
+        // Test vectors from RFC 4226 Appendix D. / 来自 RFC 4226 附录 D 的测试向量。
+        let secret = b"12345678901234567890";
+        assert_eq!(hotp_code(secret, 0), "755224");
+        assert_eq!(hotp_code(secret, 1), "287082");
+        assert_eq!(hotp_code(secret, 9), "520489");
+    }
+
+    #[test]
+    fn test_steam_code_alphabet() {
+        let code = steam_code(b"12345678901234567890", 1111111109);
+        assert_eq!(code.len(), 5);
+        assert!(code.bytes().all(|b| STEAM_ALPHABET.contains(&b)));
+        assert_eq!(code, otp_code(b"12345678901234567890", OtpKind::Steam, 1111111109));
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        let secret = b"12345678901234567890";
+        assert_eq!(
+            otpauth_uri(secret, OtpKind::Totp, "Example Co", "alice@example.com"),
+            "otpauth://totp/Example%20Co:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=Example%20Co&algorithm=SHA1&digits=6&period=30"
+        );
+        assert!(otpauth_uri(secret, OtpKind::Hotp(7), "Example", "").ends_with("&digits=6&counter=7"));
+        assert!(otpauth_uri(secret, OtpKind::Steam, "Steam", "").starts_with("otpauth://steam/Steam?"));
+    }
+}
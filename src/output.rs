@@ -0,0 +1,485 @@
+// --- Output formats ---
+// --- 输出格式 ---
+// Renders a derived password into the textual forms understood by other tools.
+// 将派生出的密码渲染为其它工具可以直接使用的文本格式。
+
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64URL};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::core::{derive_purpose_key, AegixPassError, Preset, Purpose, SeedOptions};
+use crate::integration::IntegrationError;
+
+/// PBKDF2 iteration count used for SCRAM-SHA-256 verifiers (PostgreSQL's default).
+// SCRAM-SHA-256 校验值使用的 PBKDF2 迭代次数（与 PostgreSQL 默认值一致）。
+pub const SCRAM_ITERATIONS: u32 = 4096;
+
+/// bcrypt cost factor used for htpasswd lines.
+// htpasswd 行使用的 bcrypt 成本因子。
+pub const BCRYPT_COST: u32 = 10;
+
+/// Key label under which the deterministic verifier salt key is derived.
+// 派生确定性校验值盐密钥时使用的密钥标签。
+pub const VERIFIER_SALT_LABEL: &str = "verifier-salt";
+
+/// How salts are chosen for the verifier outputs (SCRAM, bcrypt, crypt).
+// 校验值类输出（SCRAM、bcrypt、crypt）的盐选择方式。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SaltMode {
+    /// Derive the salt from a key of the master seed (see [`derive_salt_key_with`]), so the
+    /// verifier is reproducible but cannot be precomputed from the password alone.
+    // 由主种子派生出的密钥（参见 [`derive_salt_key_with`]）派生盐，使校验值可以被重复生成，却无法仅凭密码预先计算。
+    Deterministic([u8; 32]),
+    /// Draw the salt from the operating system's random source.
+    // 从操作系统随机源获取盐。
+    Random,
+}
+
+/// Derives the key behind [`SaltMode::Deterministic`] from the master seed, under the reserved
+/// label [`VERIFIER_SALT_LABEL`].
+// 在保留标签 [`VERIFIER_SALT_LABEL`] 下，由主种子派生 [`SaltMode::Deterministic`] 所用的密钥。
+pub fn derive_salt_key_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    options: &SeedOptions,
+) -> Result<[u8; 32], AegixPassError> {
+    let key = derive_purpose_key(password_source, distinguish_key, preset, Purpose::Key, VERIFIER_SALT_LABEL, 32, options)?;
+    let mut salt_key = [0u8; 32];
+    salt_key.copy_from_slice(&key);
+    Ok(salt_key)
+}
+
+/// Produces a 16-byte verifier salt. Deterministic salts are domain-separated by `label`
+/// so different verifier formats never share a salt.
+// 生成一个 16 字节的校验值盐。确定性盐通过 `label` 进行域分离，确保不同格式的校验值不会共用同一个盐。
+fn verifier_salt(label: &str, mode: SaltMode) -> [u8; 16] {
+    match mode {
+        SaltMode::Deterministic(key) => {
+            let digest = Sha256::new().chain_update(key).chain_update(format!("AegixPass_{}", label)).finalize();
+            let mut salt = [0u8; 16];
+            salt.copy_from_slice(&digest[..16]);
+            salt
+        }
+        SaltMode::Random => rand::rng().random(),
+    }
+}
+
+/// Builds a conventional environment variable name from a distinguish key,
+/// e.g. `example.com` becomes `EXAMPLE_COM`.
+// 根据区分密钥构建一个常规的环境变量名，例如 `example.com` 变为 `EXAMPLE_COM`。
+pub fn env_key_from(distinguish_key: &str) -> String {
+    let mut key: String = distinguish_key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    // Variable names must not start with a digit.
+    // 变量名不能以数字开头。
+    if key.starts_with(|c: char| c.is_ascii_digit()) {
+        key.insert(0, '_');
+    }
+    key
+}
+
+/// Renders a single `KEY=value` line for a dotenv file.
+/// Values are single-quoted so that `$`, `#` and spaces are taken literally; values
+/// containing a single quote fall back to an escaped double-quoted form.
+// 渲染 dotenv 文件中的一行 `KEY=value`。
+// 值使用单引号包裹，使 `$`、`#` 和空格按字面解释；若值本身包含单引号，则改用转义后的双引号形式。
+pub fn render_dotenv(key: &str, value: &str) -> String {
+    if !value.contains('\'') {
+        return format!("{}='{}'\n", key, value);
+    }
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    format!("{}=\"{}\"\n", key, escaped)
+}
+
+/// Masks the middle of a password with `*`, keeping its length, so it can be checked by eye
+/// without showing it all. `reveal` characters stay visible at each end, but never more than a
+/// quarter of the password at either.
+// 用 `*` 遮盖密码的中间部分并保持其长度，以便目测核对而不完全显示。两端各保留 `reveal` 个可见字符，但每端都不超过密码的四分之一。
+pub fn render_peek(password: &str, reveal: usize) -> String {
+    let chars: Vec<char> = password.chars().collect();
+    let reveal = reveal.min(chars.len() / 4);
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| if i < reveal || i >= chars.len() - reveal { c } else { '*' })
+        .collect()
+}
+
+/// Shells whose quoting [`shell_quote`] knows. / [`shell_quote`] 所了解其引号规则的 shell。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    /// bash and other POSIX shells. / bash 及其它 POSIX shell。
+    Bash,
+    PowerShell,
+}
+
+/// Quotes a value so it can be pasted into a command of `shell` as one literal argument: `$`,
+/// backticks, quotes and spaces lose their meaning. Both shells use single quotes, inside which
+/// nothing expands; bash closes and reopens them around a `'`, PowerShell doubles it (including the
+/// typographic single quotes it also accepts).
+// 为值加上引号，使其可以作为一个字面参数粘贴到 `shell` 的命令中：`$`、反引号、引号与空格都失去特殊含义。
+// 两种 shell 都使用单引号，其中不会发生任何展开；bash 在 `'` 处关闭并重新打开引号，PowerShell 则将其双写（包括它同样接受的排版单引号）。
+pub fn shell_quote(value: &str, shell: Shell) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+    for c in value.chars() {
+        match (shell, c) {
+            (Shell::Bash, '\'') => quoted.push_str("'\\''"),
+            (Shell::PowerShell, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') => {
+                quoted.push(c);
+                quoted.push(c);
+            }
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Renders a PostgreSQL `SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>` verifier.
+/// With a deterministic salt the verifier is as reproducible as the password, so
+/// re-provisioning a user is idempotent.
+// 渲染 PostgreSQL 的 `SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>` 校验值。
+// 使用确定性盐时，校验值与密码一样可重复生成，重复创建用户不会产生变化。
+pub fn render_scram_sha256(password: &str, salt_mode: SaltMode) -> String {
+    let salt = verifier_salt("SCRAM", salt_mode);
+
+    let mut salted_password = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, SCRAM_ITERATIONS, &mut salted_password);
+
+    let hmac = |key: &[u8], message: &[u8]| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(message);
+        mac.finalize().into_bytes()
+    };
+    let client_key = hmac(&salted_password, b"Client Key");
+    let stored_key = Sha256::digest(client_key);
+    let server_key = hmac(&salted_password, b"Server Key");
+
+    format!(
+        "SCRAM-SHA-256${}:{}${}:{}",
+        SCRAM_ITERATIONS,
+        BASE64.encode(salt),
+        BASE64.encode(stored_key),
+        BASE64.encode(server_key)
+    )
+}
+
+/// Renders a MySQL `mysql_native_password` hash: `*` followed by `SHA1(SHA1(password))` in upper-case hex.
+// 渲染 MySQL `mysql_native_password` 哈希：`*` 加上大写十六进制的 `SHA1(SHA1(password))`。
+pub fn render_mysql_native(password: &str) -> String {
+    let digest = Sha1::digest(Sha1::digest(password.as_bytes()));
+    let hex: String = digest.iter().map(|b| format!("{:02X}", b)).collect();
+    format!("*{}", hex)
+}
+
+/// Renders a PowerShell expression that evaluates to a read-only `SecureString` holding the
+/// password. The password is spelled out as UTF-16 code units and appended one character at a
+/// time, so it never exists as a plain .NET `String` in the consuming session.
+// 渲染一个 PowerShell 表达式，其求值结果为保存该密码的只读 `SecureString`。密码以 UTF-16 码元的形式写出，
+// 并逐个字符追加，因此在使用它的会话中从不以普通 .NET `String` 的形式存在。
+pub fn render_ps_securestring(password: &str) -> String {
+    let code_units: Vec<String> = password.encode_utf16().map(|unit| unit.to_string()).collect();
+    format!(
+        "& {{ $s = [System.Security.SecureString]::new(); foreach ($c in @({})) {{ $s.AppendChar([char]$c) }}; $s.MakeReadOnly(); $s }}",
+        code_units.join(",")
+    )
+}
+
+/// Renders an Apache `htpasswd` line (`user:$2y$...`) holding a bcrypt hash of the password.
+// 渲染一行 Apache `htpasswd` 记录 (`user:$2y$...`)，其中包含密码的 bcrypt 哈希。
+pub fn render_htpasswd(user: &str, password: &str, salt_mode: SaltMode) -> Result<String, AegixPassError> {
+    if user.is_empty() || user.contains(':') {
+        return Err(AegixPassError::BcryptError(format!(
+            "Invalid htpasswd user name '{}'; it must be non-empty and must not contain ':'.",
+            user
+        )));
+    }
+
+    let salt = verifier_salt("bcrypt", salt_mode);
+    let hash = bcrypt::hash_with_salt(password, BCRYPT_COST, salt)
+        .map_err(|e| AegixPassError::BcryptError(e.to_string()))?;
+
+    Ok(format!("{}:{}", user, hash.format_for_version(bcrypt::Version::TwoY)))
+}
+
+/// Renders a crypt(3) SHA-512 hash (`$6$salt$...`) as stored in `/etc/shadow` and accepted
+/// by cloud-init and Ansible, using the default 5000 rounds.
+// 渲染 crypt(3) 的 SHA-512 哈希 (`$6$salt$...`)，即 `/etc/shadow` 中的格式，可被 cloud-init 与 Ansible 接受，
+// 使用默认的 5000 轮。
+pub fn render_sha512_crypt(password: &str, salt_mode: SaltMode) -> Result<String, AegixPassError> {
+    // crypt(3) salts use a 64-character alphabet; 256 is a multiple of 64 so the mapping is unbiased.
+    // crypt(3) 的盐使用 64 个字符的字母表；256 是 64 的倍数，因此映射没有偏差。
+    const CRYPT_ALPHABET: &[u8; 64] =
+        b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    let salt: String = verifier_salt("sha512crypt", salt_mode)
+        .iter()
+        .map(|b| CRYPT_ALPHABET[(b % 64) as usize] as char)
+        .collect();
+
+    pwhash::sha512_crypt::hash_with(format!("$6${}", salt).as_str(), password)
+        .map_err(|e| AegixPassError::CryptError(e.to_string()))
+}
+
+/// Encodes raw key material as unpadded base64url, the encoding used by JOSE/JWT tooling.
+// 将原始密钥材料编码为无填充的 base64url，这是 JOSE/JWT 工具使用的编码。
+pub fn render_base64url(key: &[u8]) -> String {
+    BASE64URL.encode(key)
+}
+
+/// Encodes raw key material as lowercase hex. / 将原始密钥材料编码为小写十六进制。
+pub fn render_hex(key: &[u8]) -> String {
+    key.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Encodes raw key material as padded standard base64. / 将原始密钥材料编码为带填充的标准 base64。
+pub fn render_base64(key: &[u8]) -> String {
+    BASE64.encode(key)
+}
+
+/// Renders an HMAC signing key as a symmetric (`"kty": "oct"`) JSON Web Key.
+/// The algorithm is chosen from the key size: 32 bytes → HS256, 48 → HS384, otherwise HS512.
+// 将 HMAC 签名密钥渲染为对称 (`"kty": "oct"`) JSON Web Key。
+// 算法由密钥长度决定：32 字节 → HS256，48 → HS384，其余 → HS512。
+pub fn render_jwk(key: &[u8], kid: &str) -> String {
+    let alg = match key.len() {
+        32 => "HS256",
+        48 => "HS384",
+        _ => "HS512",
+    };
+    serde_json::json!({
+        "kty": "oct",
+        "use": "sig",
+        "alg": alg,
+        "kid": kid,
+        "k": render_base64url(key),
+    })
+    .to_string()
+}
+
+/// Renders `text` as a QR code made of Unicode half blocks, drawn light-on-dark so it scans
+/// from a typical terminal.
+// 使用 Unicode 半角方块将 `text` 渲染为二维码，以深色背景上的浅色绘制，便于在常见终端中扫描。
+pub fn render_qr(text: &str) -> Result<String, AegixPassError> {
+    use qrcode::render::unicode::Dense1x2;
+
+    let code = qrcode::QrCode::new(text.as_bytes()).map_err(|e| IntegrationError::QrCode(e.to_string()))?;
+    let mut rendered = code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build();
+    rendered.push('\n');
+    Ok(rendered)
+}
+
+/// Byte encodings for text written to a file or pipe.
+// 写入文件或管道的文本所使用的字节编码。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-16 little endian, what Windows PowerShell's `Out-File` and `>` write by default.
+    // UTF-16 小端序，即 Windows PowerShell 中 `Out-File` 与 `>` 默认写出的编码。
+    Utf16Le,
+}
+
+/// Encodes `text` as bytes, optionally prefixed with a byte order mark and with every `\n`
+/// turned into `\r\n`.
+// 将 `text` 编码为字节，可选地添加字节顺序标记 (BOM)，并可将每个 `\n` 转换为 `\r\n`。
+pub fn encode_text(text: &str, encoding: TextEncoding, bom: bool, crlf: bool) -> Vec<u8> {
+    let text = if crlf { text.replace("\r\n", "\n").replace('\n', "\r\n") } else { text.to_string() };
+    let text = if bom { format!("\u{feff}{}", text) } else { text };
+    match encoding {
+        TextEncoding::Utf8 => text.into_bytes(),
+        TextEncoding::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+    }
+}
+
+/// Encrypts `plaintext` to the given age recipients (`age1...`) and returns the
+/// ASCII-armored ciphertext.
+// 使用给定的 age 接收者 (`age1...`) 加密 `plaintext`，并返回 ASCII 封装的密文。
+#[cfg(feature = "age")]
+pub fn encrypt_age(plaintext: &str, recipients: &[String]) -> Result<String, AegixPassError> {
+    use std::io::Write;
+
+    let age_error = |e: &dyn std::fmt::Display| IntegrationError::Age(e.to_string());
+
+    let recipients = recipients
+        .iter()
+        .map(|r| {
+            r.parse::<age::x25519::Recipient>()
+                .map_err(|e| IntegrationError::Age(format!("Invalid recipient '{}': {}", r, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let encryptor = age::Encryptor::with_recipients(
+        recipients.iter().map(|r| r as &dyn age::Recipient),
+    )
+    .map_err(|e| age_error(&e))?;
+
+    let mut encrypted = Vec::new();
+    let armor = age::armor::ArmoredWriter::wrap_output(&mut encrypted, age::armor::Format::AsciiArmor)
+        .map_err(|e| age_error(&e))?;
+    let mut writer = encryptor.wrap_output(armor).map_err(|e| age_error(&e))?;
+    writer.write_all(plaintext.as_bytes()).map_err(|e| age_error(&e))?;
+    writer
+        .finish()
+        .and_then(|armor| armor.finish())
+        .map_err(|e| age_error(&e))?;
+
+    String::from_utf8(encrypted).map_err(|e| age_error(&e).into())
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_text() {
+        assert_eq!(encode_text("é\n", TextEncoding::Utf8, false, false), "é\n".as_bytes());
+        assert_eq!(encode_text("é\n", TextEncoding::Utf8, true, true), b"\xef\xbb\xbf\xc3\xa9\r\n");
+        assert_eq!(encode_text("é\r\n", TextEncoding::Utf16Le, true, true), b"\xff\xfe\xe9\x00\r\x00\n\x00");
+    }
+
+    #[test]
+    fn test_render_ps_securestring() {
+        assert_eq!(
+            render_ps_securestring("a'€"),
+            "& { $s = [System.Security.SecureString]::new(); foreach ($c in @(97,39,8364)) { $s.AppendChar([char]$c) }; $s.MakeReadOnly(); $s }"
+        );
+    }
+
+    #[test]
+    fn test_env_key_from() {
+        assert_eq!(env_key_from("example.com"), "EXAMPLE_COM");
+        assert_eq!(env_key_from("1password-sync"), "_1PASSWORD_SYNC");
+    }
+
+    #[test]
+    fn test_render_dotenv_quoting() {
+        assert_eq!(render_dotenv("KEY", "a$b#c"), "KEY='a$b#c'\n");
+        assert_eq!(render_dotenv("KEY", "it's\"$"), "KEY=\"it's\\\"\\$\"\n");
+    }
+
+    #[test]
+    fn test_render_peek() {
+        assert_eq!(render_peek("#jMiT^Y$Yp$5aRtF", 2), "#j************tF");
+        assert_eq!(render_peek("#jMiT^Y$Yp$5aRtF", 9), "#jMi********aRtF");
+        assert_eq!(render_peek("a€cd", 0), "****");
+        assert_eq!(render_peek("abc", 1), "***");
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("a$b`c d\"", Shell::Bash), "'a$b`c d\"'");
+        assert_eq!(shell_quote("it's", Shell::Bash), "'it'\\''s'");
+        assert_eq!(shell_quote("it's $x", Shell::PowerShell), "'it''s $x'");
+        assert_eq!(shell_quote("a\u{2019}b", Shell::PowerShell), "'a\u{2019}\u{2019}b'");
+    }
+
+    #[test]
+    fn test_render_mysql_native() {
+        // Known value from MySQL's PASSWORD('password').
+        // 已知值，来自 MySQL 的 PASSWORD('password')。
+        assert_eq!(render_mysql_native("password"), "*2470C0C06DEE42FD1618BB99005ADCA2EC9D1E19");
+    }
+
+    /// A deterministic salt mode derived from `password_source`.
+    // 由 `password_source` 派生的确定性盐模式。
+    fn deterministic(password_source: &str) -> SaltMode {
+        let preset = crate::core::load_purpose_preset(2);
+        SaltMode::Deterministic(derive_salt_key_with(password_source, "example.com", &preset, &SeedOptions::default()).unwrap())
+    }
+
+    #[test]
+    fn test_render_scram_sha256() {
+        let salt = deterministic("MySecretPassword123!");
+        let verifier = render_scram_sha256("password", salt);
+        assert_eq!(
+            verifier,
+            "SCRAM-SHA-256$4096:zxISEjLq8ew1Fi6hbBW8vw==$PW8CnJInRsy00YdnQp6H1yMpJjyesorXYieyGXd68CQ=:DdV+11LHL0VJWafJqdgbnW3QZy3IBAuFaYd/sgcjA8w="
+        );
+        assert_eq!(verifier, render_scram_sha256("password", salt));
+        assert_ne!(verifier, render_scram_sha256("password", SaltMode::Random));
+        // The salt follows the master seed, not the password alone.
+        // 盐随主种子变化，而不仅仅取决于密码。
+        assert_ne!(verifier, render_scram_sha256("password", deterministic("AnotherSecret456?")));
+        let (scheme, rest) = verifier.split_once('$').unwrap();
+        assert_eq!(scheme, "SCRAM-SHA-256");
+        assert!(rest.starts_with("4096:"));
+        assert_eq!(rest.split(['$', ':']).count(), 4);
+    }
+
+    #[test]
+    fn test_render_htpasswd() {
+        let line = render_htpasswd("alice", "password", deterministic("MySecretPassword123!")).unwrap();
+        let (user, hash) = line.split_once(':').unwrap();
+        assert_eq!(user, "alice");
+        assert!(hash.starts_with("$2y$10$"));
+        assert!(bcrypt::verify("password", hash).unwrap());
+        assert!(render_htpasswd("al:ice", "password", SaltMode::Random).is_err());
+    }
+
+    #[test]
+    fn test_render_sha512_crypt() {
+        let salt = deterministic("MySecretPassword123!");
+        let hash = render_sha512_crypt("password", salt).unwrap();
+        assert!(hash.starts_with("$6$"));
+        assert!(pwhash::sha512_crypt::verify("password", &hash));
+        assert_eq!(hash, render_sha512_crypt("password", salt).unwrap());
+    }
+
+    #[test]
+    fn test_render_raw_key() {
+        assert_eq!(render_hex(&[0x00, 0xfb, 0x10]), "00fb10");
+        assert_eq!(render_base64(&[0xfb; 4]), "+/v7+w==");
+    }
+
+    #[test]
+    fn test_render_jwk() {
+        let jwk: serde_json::Value = serde_json::from_str(&render_jwk(&[0xfb; 32], "example.com")).unwrap();
+        assert_eq!(jwk["kty"], "oct");
+        assert_eq!(jwk["alg"], "HS256");
+        assert_eq!(jwk["kid"], "example.com");
+        assert_eq!(jwk["k"], "-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_s");
+    }
+
+    #[test]
+    fn test_render_qr() {
+        let qr = render_qr("otpauth://totp/Example?secret=MZXW6YTB").unwrap();
+        assert!(qr.lines().count() > 10);
+        assert!(qr.lines().all(|line| line.chars().count() == qr.lines().next().unwrap().chars().count()));
+    }
+
+    #[cfg(feature = "age")]
+    #[test]
+    fn test_encrypt_age_round_trip() {
+        use std::io::Read;
+
+        let identity = age::x25519::Identity::generate();
+        let recipient = identity.to_public().to_string();
+        let armored = encrypt_age("KEY='secret'\n", &[recipient]).unwrap();
+        assert!(armored.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+
+        let decryptor = age::Decryptor::new(age::armor::ArmoredReader::new(armored.as_bytes())).unwrap();
+        let mut reader = decryptor
+            .decrypt(std::iter::once(&identity as &dyn age::Identity))
+            .unwrap();
+        let mut decrypted = String::new();
+        reader.read_to_string(&mut decrypted).unwrap();
+        assert_eq!(decrypted, "KEY='secret'\n");
+    }
+}
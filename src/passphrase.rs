@@ -0,0 +1,282 @@
+// --- Passphrases ---
+// --- 口令短语 ---
+// Derives a passphrase of words drawn from a wordlist file named in the preset, for sites and
+// devices where a few words are easier to type than a charset password. The preset records the
+// file's SHA-256, so a wordlist that was edited, re-sorted or replaced is refused instead of
+// silently yielding other passphrases.
+// 从预设中指定的词表文件抽取单词，派生由若干单词组成的口令短语，适用于输入几个单词比输入字符集密码更方便的站点与设备。
+// 预设记录了该文件的 SHA-256，因此被编辑、重新排序或替换过的词表会被拒绝，而不会悄悄产生不同的口令短语。
+//
+// A wordlist is kept as the file's text plus an index of word boundaries, never one allocation per
+// word, and [`Wordlist::load_shared`] keeps each checked list in memory for the life of the process,
+// so batch callers deriving many passphrases from a list of hundreds of thousands of words read and
+// index it once.
+// 词表以文件文本加上单词边界索引的形式保存，从不为每个单词单独分配内存；[`Wordlist::load_shared`] 会在进程生命周期内
+// 保留每个检查过的词表，因此从包含数十万单词的词表派生大量口令短语的批量调用者只需读取并索引一次。
+
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::core::{create_rng_from_seed, derive_purpose_key, secure_random_range_u32, AegixPassError, Preset, Purpose, SeedOptions};
+
+/// Fewest words a wordlist may have: below this, each word adds under 10 bits.
+// 词表至少应包含的单词数：低于此值时，每个单词贡献的熵不足 10 比特。
+pub const MIN_WORDLIST_WORDS: usize = 1024;
+/// Longest word accepted, in characters. / 可接受的最长单词（字符数）。
+pub const MAX_WORD_CHARS: usize = 64;
+/// Most words a passphrase may have. / 口令短语最多可包含的单词数。
+pub const MAX_PASSPHRASE_WORDS: usize = 64;
+/// Largest wordlist file accepted, in bytes: word boundaries are indexed as `u32` offsets.
+// 可接受的最大词表文件（字节）：单词边界以 `u32` 偏移量索引。
+pub const MAX_WORDLIST_BYTES: u64 = u32::MAX as u64;
+
+/// Wordlists checked by [`Wordlist::load_shared`], by checksum. / 由 [`Wordlist::load_shared`] 检查过的词表，以校验和为键。
+static LOADED: Mutex<Vec<(String, Arc<Wordlist>)>> = Mutex::new(Vec::new());
+
+/// The `passphrase` block of a preset. / 预设中的 `passphrase` 配置块。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PassphraseSettings {
+    /// Number of words. / 单词数。
+    #[serde(default = "default_words")]
+    pub words: usize,
+    #[serde(default = "default_separator")]
+    pub separator: String,
+    /// Path of the wordlist, one word per line; relative paths start at the preset's directory.
+    // 词表路径，每行一个单词；相对路径以预设所在目录为起点。
+    pub wordlist: String,
+    /// Lowercase hex SHA-256 of the wordlist file. / 词表文件的小写十六进制 SHA-256。
+    #[serde(rename = "wordlistSha256")]
+    pub wordlist_sha256: String,
+}
+
+fn default_words() -> usize {
+    6
+}
+
+fn default_separator() -> String {
+    "-".to_string()
+}
+
+impl PassphraseSettings {
+    /// Checks the word count and the form of the checksum. / 检查单词数与校验和的格式。
+    pub fn validate(&self) -> Result<(), AegixPassError> {
+        if !(1..=MAX_PASSPHRASE_WORDS).contains(&self.words) {
+            return Err(AegixPassError::InvalidPassphraseSettings(format!(
+                "words must be between 1 and {}, not {}",
+                MAX_PASSPHRASE_WORDS, self.words
+            )));
+        }
+        if self.wordlist_sha256.len() != 64 || !self.wordlist_sha256.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return Err(AegixPassError::InvalidPassphraseSettings("wordlistSha256 must be 64 lowercase hex digits".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// A checked wordlist: the file's text and the byte range of each word in it.
+// 经过检查的词表：文件文本及其中每个单词的字节范围。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wordlist {
+    text: String,
+    spans: Vec<(u32, u32)>,
+}
+
+impl Wordlist {
+    /// Reads and checks the wordlist a preset names. / 读取并检查预设所指定的词表。
+    pub fn load(path: &Path, expected_sha256: &str) -> Result<Self, AegixPassError> {
+        let io_error = |e: std::io::Error| AegixPassError::WordlistError(format!("{}: {}", path.display(), e));
+        // Checked before reading, so an oversized file is never loaded. / 在读取之前检查，因此从不加载过大的文件。
+        let size = std::fs::metadata(path).map_err(io_error)?.len();
+        if size > MAX_WORDLIST_BYTES {
+            return Err(AegixPassError::WordlistError(format!("{}: the file is larger than {} bytes", path.display(), MAX_WORDLIST_BYTES)));
+        }
+        let bytes = std::fs::read(path).map_err(io_error)?;
+        Self::from_vec(bytes, expected_sha256)
+    }
+
+    /// [`Wordlist::load`], reusing a list with the same checksum checked earlier in this process.
+    /// Equal checksums mean equal contents, so the file is only read the first time.
+    // 即 [`Wordlist::load`]，但会复用本进程中先前检查过的、校验和相同的词表。校验和相同即内容相同，因此文件只在第一次被读取。
+    pub fn load_shared(path: &Path, expected_sha256: &str) -> Result<Arc<Self>, AegixPassError> {
+        let find = || {
+            let loaded = LOADED.lock().unwrap_or_else(PoisonError::into_inner);
+            loaded.iter().find(|(checksum, _)| checksum == expected_sha256).map(|(_, wordlist)| Arc::clone(wordlist))
+        };
+        if let Some(wordlist) = find() {
+            return Ok(wordlist);
+        }
+        // Loaded without holding the lock; a concurrent load of the same list is kept only once.
+        // 加载时不持有锁；同一词表的并发加载只会保留一份。
+        let wordlist = Arc::new(Self::load(path, expected_sha256)?);
+        let mut loaded = LOADED.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, existing)) = loaded.iter().find(|(checksum, _)| checksum == expected_sha256) {
+            return Ok(Arc::clone(existing));
+        }
+        loaded.push((expected_sha256.to_string(), Arc::clone(&wordlist)));
+        Ok(wordlist)
+    }
+
+    /// Checks the contents of a wordlist file against its recorded checksum, then its words.
+    // 先按记录的校验和检查词表文件的内容，再检查其中的单词。
+    pub fn from_bytes(bytes: &[u8], expected_sha256: &str) -> Result<Self, AegixPassError> {
+        Self::from_vec(bytes.to_vec(), expected_sha256)
+    }
+
+    /// [`Wordlist::from_bytes`], keeping the buffer as the list's text instead of copying it.
+    // 即 [`Wordlist::from_bytes`]，但直接将缓冲区保留为词表文本，而不复制。
+    fn from_vec(bytes: Vec<u8>, expected_sha256: &str) -> Result<Self, AegixPassError> {
+        let actual = sha256_hex(&bytes);
+        if actual != expected_sha256 {
+            return Err(AegixPassError::WordlistChecksumMismatch { expected: expected_sha256.to_string(), actual });
+        }
+        if bytes.len() as u64 > MAX_WORDLIST_BYTES {
+            return Err(AegixPassError::WordlistError(format!("the file is larger than {} bytes", MAX_WORDLIST_BYTES)));
+        }
+        let text = String::from_utf8(bytes).map_err(|_| AegixPassError::WordlistError("the file is not UTF-8".to_string()))?;
+        let mut spans: Vec<(u32, u32)> = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_error = |problem: &str| AegixPassError::WordlistError(format!("line {} {}", index + 1, problem));
+            if line.is_empty() {
+                return Err(line_error("is empty"));
+            }
+            if line.chars().any(char::is_whitespace) {
+                return Err(line_error("contains whitespace"));
+            }
+            if line.chars().count() > MAX_WORD_CHARS {
+                return Err(line_error(&format!("is longer than {} characters", MAX_WORD_CHARS)));
+            }
+            // `line` borrows from `text`, whose length fits in `u32`. / `line` 借用自 `text`，其长度在 `u32` 范围内。
+            let start = line.as_ptr() as usize - text.as_ptr() as usize;
+            spans.push((start as u32, (start + line.len()) as u32));
+        }
+        let wordlist = Self { text, spans };
+        if wordlist.len() < MIN_WORDLIST_WORDS {
+            return Err(AegixPassError::WordlistError(format!("it has {} words; at least {} are needed", wordlist.len(), MIN_WORDLIST_WORDS)));
+        }
+        // Sorting indices rather than words keeps the check to one `u32` per word.
+        // 排序索引而非单词本身，使该检查每个单词只占用一个 `u32`。
+        let mut order: Vec<u32> = (0..wordlist.len() as u32).collect();
+        order.sort_unstable_by_key(|&index| wordlist.word(index as usize));
+        if let Some(pair) = order.windows(2).find(|pair| wordlist.word(pair[0] as usize) == wordlist.word(pair[1] as usize)) {
+            return Err(AegixPassError::WordlistError(format!("'{}' appears more than once", wordlist.word(pair[0] as usize))));
+        }
+        Ok(wordlist)
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// The word at `index`, counting from 0 in file order. / 按文件顺序从 0 开始计数的第 `index` 个单词。
+    pub fn word(&self, index: usize) -> &str {
+        let (start, end) = self.spans[index];
+        &self.text[start as usize..end as usize]
+    }
+
+    /// Entropy of one word, in bits. / 单个单词的熵（比特）。
+    pub fn bits_per_word(&self) -> f64 {
+        (self.len() as f64).log2()
+    }
+}
+
+/// Lowercase hex SHA-256 of a file's contents, the form `wordlistSha256` takes.
+// 文件内容的小写十六进制 SHA-256，即 `wordlistSha256` 的格式。
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Derives the passphrase for a site. / 为站点派生口令短语。
+pub fn derive_passphrase(password_source: &str, distinguish_key: &str, preset: &Preset, wordlist: &Wordlist) -> Result<String, AegixPassError> {
+    derive_passphrase_with(password_source, distinguish_key, preset, wordlist, &SeedOptions::default())
+}
+
+/// [`derive_passphrase`] with extra seed inputs such as a keyfile. The words are drawn with the
+/// preset's RNG, seeded from the `passphrase` purpose key, using the same unbiased range
+/// sampling as passwords.
+// 带有额外种子输入（例如密钥文件）的 [`derive_passphrase`]。单词由预设的 RNG 抽取，该 RNG 以 `passphrase`
+// 用途的密钥为种子，并使用与密码相同的无偏范围抽样。
+pub fn derive_passphrase_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    wordlist: &Wordlist,
+    options: &SeedOptions,
+) -> Result<String, AegixPassError> {
+    let settings = preset.passphrase.as_ref().ok_or(AegixPassError::PassphraseNotConfigured)?;
+    settings.validate()?;
+    let key = derive_purpose_key(password_source, distinguish_key, preset, Purpose::Passphrase, "passphrase", 32, options)?;
+    let seed: [u8; 32] = key.try_into().map_err(|key: Vec<u8>| AegixPassError::InvalidKeyLength(key.len(), 32))?;
+    let mut rng = create_rng_from_seed(seed, &preset.rng_algorithm)?;
+    // The file size limit keeps the word count within `u32`. / 文件大小限制保证单词数在 `u32` 范围内。
+    let words: Vec<&str> = (0..settings.words)
+        .map(|_| wordlist.word(secure_random_range_u32(&mut *rng, wordlist.len() as u32) as usize))
+        .collect();
+    Ok(words.join(&settings.separator))
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wordlist_file(words: usize) -> String {
+        (0..words).map(|n| format!("word{}\n", n)).collect()
+    }
+
+    #[test]
+    fn test_wordlist_checks() {
+        let file = wordlist_file(2000);
+        let checksum = sha256_hex(file.as_bytes());
+        assert_eq!(Wordlist::from_bytes(file.as_bytes(), &checksum).unwrap().len(), 2000);
+        assert!(matches!(Wordlist::from_bytes(file.as_bytes(), &"0".repeat(64)), Err(AegixPassError::WordlistChecksumMismatch { .. })));
+
+        let check = |file: &str| Wordlist::from_bytes(file.as_bytes(), &sha256_hex(file.as_bytes()));
+        assert_eq!(check(&wordlist_file(100)), Err(AegixPassError::WordlistError("it has 100 words; at least 1024 are needed".to_string())));
+        assert_eq!(check(&(wordlist_file(2000) + "word7\n")), Err(AegixPassError::WordlistError("'word7' appears more than once".to_string())));
+        assert_eq!(check(&(wordlist_file(2000) + "two words\n")), Err(AegixPassError::WordlistError("line 2001 contains whitespace".to_string())));
+        assert_eq!(check(&(wordlist_file(2000) + "\n")), Err(AegixPassError::WordlistError("line 2001 is empty".to_string())));
+
+        let crlf = wordlist_file(2000).replace('\n', "\r\n");
+        let wordlist = check(&crlf).unwrap();
+        assert_eq!((wordlist.word(0), wordlist.word(1999)), ("word0", "word1999"));
+    }
+
+    #[test]
+    fn test_load_shared() {
+        let file = wordlist_file(1500) + "shared\n";
+        let checksum = sha256_hex(file.as_bytes());
+        let path = std::env::temp_dir().join(format!("aegixpass-wordlist-{}.txt", std::process::id()));
+        std::fs::write(&path, &file).unwrap();
+        let first = Wordlist::load_shared(&path, &checksum).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // Already checked, so the file is not read again. / 已经检查过，因此不会再次读取文件。
+        let second = Wordlist::load_shared(&path, &checksum).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(second.word(1500), "shared");
+        assert!(matches!(Wordlist::load_shared(&path, &"0".repeat(64)), Err(AegixPassError::WordlistError(_))));
+    }
+
+    #[test]
+    fn test_derive_passphrase() {
+        let file = wordlist_file(2000);
+        let checksum = sha256_hex(file.as_bytes());
+        let wordlist = Wordlist::from_bytes(file.as_bytes(), &checksum).unwrap();
+        let mut preset = crate::presets::PresetTier::Fast.preset().unwrap();
+        assert_eq!(derive_passphrase("password", "example.com", &preset, &wordlist), Err(AegixPassError::PassphraseNotConfigured));
+
+        preset.passphrase = Some(PassphraseSettings { words: 5, separator: " ".to_string(), wordlist: "words.txt".to_string(), wordlist_sha256: checksum });
+        let passphrase = derive_passphrase("password", "example.com", &preset, &wordlist).unwrap();
+        assert_eq!(passphrase.split(' ').count(), 5);
+        assert!(passphrase.split(' ').all(|word| word.starts_with("word")));
+        assert_eq!(derive_passphrase("password", "example.com", &preset, &wordlist), Ok(passphrase.clone()));
+        assert_ne!(derive_passphrase("password", "example.org", &preset, &wordlist), Ok(passphrase));
+    }
+}
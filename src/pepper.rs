@@ -0,0 +1,81 @@
+// --- Pepper sources ---
+// --- 胡椒值来源 ---
+// A pepper is a device secret mixed into every derivation (see `SeedOptions::pepper`). Someone
+// who learns the master password still cannot derive anything without it. It is read from the
+// `AEGIXPASS_PEPPER` environment variable or from the OS keychain entry with service
+// `aegixpass` and account `pepper`, using the platform's own command-line client.
+// 胡椒值 (pepper) 是混入每次派生的设备秘密（参见 `SeedOptions::pepper`）。即使有人得知主密码，
+// 没有它也无法派生任何结果。它从环境变量 `AEGIXPASS_PEPPER` 读取，或通过平台自带的命令行客户端，
+// 从服务名为 `aegixpass`、账户名为 `pepper` 的操作系统钥匙串条目中读取。
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+
+/// Environment variable holding the pepper. / 保存胡椒值的环境变量。
+pub const PEPPER_ENV: &str = "AEGIXPASS_PEPPER";
+/// Keychain service name of the pepper entry. / 胡椒值条目在钥匙串中的服务名。
+pub const KEYCHAIN_SERVICE: &str = "aegixpass";
+/// Keychain account name of the pepper entry. / 胡椒值条目在钥匙串中的账户名。
+pub const KEYCHAIN_ACCOUNT: &str = "pepper";
+
+/// Reads the pepper from `AEGIXPASS_PEPPER`. / 从 `AEGIXPASS_PEPPER` 读取胡椒值。
+pub fn from_env() -> Result<Vec<u8>, AegixPassError> {
+    let pepper = std::env::var(PEPPER_ENV).map_err(|e| IntegrationError::Pepper(format!("{}: {}", PEPPER_ENV, e)))?;
+    non_empty(pepper.into_bytes(), PEPPER_ENV)
+}
+
+/// Reads the pepper from the OS keychain: the login keychain on macOS and the Secret Service
+/// (GNOME Keyring, KWallet) elsewhere on Unix.
+// 从操作系统钥匙串读取胡椒值：macOS 上为登录钥匙串，其它 Unix 系统上为 Secret Service（GNOME 钥匙环、KWallet）。
+pub fn from_keychain() -> Result<Vec<u8>, AegixPassError> {
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "macos") {
+        ("security", &["find-generic-password", "-s", KEYCHAIN_SERVICE, "-a", KEYCHAIN_ACCOUNT, "-w"])
+    } else if cfg!(unix) {
+        ("secret-tool", &["lookup", "service", KEYCHAIN_SERVICE, "account", KEYCHAIN_ACCOUNT])
+    } else {
+        return Err(IntegrationError::Pepper("the keychain is not supported on this platform".to_string()).into());
+    };
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| IntegrationError::Pepper(format!("could not run `{}`: {}", program, e)))?;
+    if !output.status.success() {
+        return Err(IntegrationError::Pepper(format!(
+            "no keychain entry with service '{}' and account '{}' ({})",
+            KEYCHAIN_SERVICE,
+            KEYCHAIN_ACCOUNT,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )).into());
+    }
+    non_empty(trim_newline(output.stdout), "the keychain entry")
+}
+
+/// Drops the trailing newline the keychain clients print. / 去掉钥匙串客户端输出末尾的换行符。
+fn trim_newline(mut bytes: Vec<u8>) -> Vec<u8> {
+    if bytes.last() == Some(&b'\n') {
+        bytes.pop();
+    }
+    bytes
+}
+
+fn non_empty(pepper: Vec<u8>, source: &str) -> Result<Vec<u8>, AegixPassError> {
+    if pepper.is_empty() {
+        return Err(IntegrationError::Pepper(format!("{} is empty", source)).into());
+    }
+    Ok(pepper)
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_newline_and_empty() {
+        assert_eq!(trim_newline(b"secret\n".to_vec()), b"secret");
+        assert_eq!(trim_newline(b"secret\n\n".to_vec()), b"secret\n");
+        assert!(non_empty(Vec::new(), "x").is_err());
+    }
+}
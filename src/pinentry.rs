@@ -0,0 +1,136 @@
+// --- pinentry ---
+// --- pinentry ---
+// Asks for a secret with a pinentry program (pinentry-gnome3, pinentry-qt, pinentry-mac,
+// Gpg4win's pinentry, ...), the dialog GnuPG uses. It speaks the Assuan protocol over its
+// stdin and stdout, so it works without a terminal, e.g. when invoked from a launcher.
+// 通过 pinentry 程序（pinentry-gnome3、pinentry-qt、pinentry-mac、Gpg4win 自带的 pinentry 等，即 GnuPG 使用的对话框）
+// 请求输入秘密。它通过标准输入与标准输出使用 Assuan 协议通信，因此无需终端即可工作，例如由启动器调用时。
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+
+/// Assuan error code pinentry returns when the dialog is cancelled.
+// 对话框被取消时 pinentry 返回的 Assuan 错误码。
+const CANCELLED: &str = "83886179";
+
+/// Shows a pinentry dialog and returns the entered secret.
+// 显示 pinentry 对话框并返回输入的秘密。
+pub fn get_pin(program: &str, description: &str, prompt: &str) -> Result<String, AegixPassError> {
+    let pinentry_error = |e: &dyn std::fmt::Display| IntegrationError::Pinentry(format!("{}: {}", program, e));
+    let mut child = Command::new(program)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| pinentry_error(&e))?;
+    let (Some(mut input), Some(output)) = (child.stdin.take(), child.stdout.take()) else {
+        return Err(pinentry_error(&"could not connect to its stdin and stdout").into());
+    };
+    let result = converse(&mut BufReader::new(output), &mut input, description, prompt);
+    let _ = writeln!(input, "BYE");
+    drop(input);
+    let _ = child.wait();
+    result.map_err(|e| match e {
+        AegixPassError::Integration(IntegrationError::Pinentry(message)) => pinentry_error(&message).into(),
+        other => other,
+    })
+}
+
+/// Runs the Assuan exchange: waits for the greeting, sets the texts and asks for the PIN.
+// 执行 Assuan 交互：等待问候语、设置文本并请求 PIN。
+fn converse(reader: &mut impl BufRead, writer: &mut impl Write, description: &str, prompt: &str) -> Result<String, AegixPassError> {
+    read_response(reader)?;
+    for command in [
+        "SETTITLE AegixPass".to_string(),
+        format!("SETDESC {}", escape(description)),
+        format!("SETPROMPT {}", escape(prompt)),
+    ] {
+        send(writer, &command)?;
+        read_response(reader)?;
+    }
+    send(writer, "GETPIN")?;
+    read_response(reader).map(Option::unwrap_or_default)
+}
+
+fn send(writer: &mut impl Write, command: &str) -> Result<(), AegixPassError> {
+    writeln!(writer, "{}", command)
+        .and_then(|()| writer.flush())
+        .map_err(|e| IntegrationError::Pinentry(e.to_string()).into())
+}
+
+/// Reads lines up to the closing `OK`, returning the decoded `D` data if any.
+// 读取各行直到结束的 `OK`，返回解码后的 `D` 数据（若有）。
+fn read_response(reader: &mut impl BufRead) -> Result<Option<String>, AegixPassError> {
+    let mut data: Option<Vec<u8>> = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|e| IntegrationError::Pinentry(e.to_string()))? == 0 {
+            return Err(IntegrationError::Pinentry("exited unexpectedly".to_string()).into());
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line == "OK" || line.starts_with("OK ") {
+            return data
+                .map(|bytes| String::from_utf8(bytes).map_err(|_| IntegrationError::Pinentry("the entered text is not UTF-8".to_string()).into()))
+                .transpose();
+        } else if let Some(error) = line.strip_prefix("ERR ") {
+            let message = if error.starts_with(CANCELLED) { "the dialog was cancelled" } else { error };
+            return Err(IntegrationError::Pinentry(message.to_string()).into());
+        } else if let Some(chunk) = line.strip_prefix("D ") {
+            data.get_or_insert_with(Vec::new).extend(unescape(chunk));
+        }
+        // Status (`S`) and comment (`#`) lines carry nothing we need. / 状态行（`S`）与注释行（`#`）不含所需内容。
+    }
+}
+
+/// Percent-escapes the characters Assuan reserves. / 对 Assuan 保留的字符进行百分号转义。
+fn escape(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Decodes `%XX` escapes. / 解码 `%XX` 转义。
+fn unescape(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_converse() {
+        let mut replies = "OK Pleased to meet you\nOK\nOK\nOK\nS PASSWORD_FROM_CACHE\nD p%25ss w%C3%B6rd\nOK\n".as_bytes();
+        let mut sent = Vec::new();
+        let pin = converse(&mut replies, &mut sent, "Enter the\nmaster password", "Master password:").unwrap();
+        assert_eq!(pin, "p%ss wörd");
+        let sent = String::from_utf8(sent).unwrap();
+        assert!(sent.contains("SETDESC Enter the%0Amaster password\n") && sent.ends_with("GETPIN\n"));
+
+        let mut cancelled = "OK\nOK\nOK\nOK\nERR 83886179 Operation cancelled <Pinentry>\n".as_bytes();
+        assert_eq!(
+            converse(&mut cancelled, &mut Vec::new(), "", ""),
+            Err(IntegrationError::Pinentry("the dialog was cancelled".to_string()).into())
+        );
+        assert!(converse(&mut "OK\n".as_bytes(), &mut Vec::new(), "", "").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+// --- Built-in preset tiers ---
+// --- 内置预设档位 ---
+// The official presets offered by `aegixpass setup`. They share the official platformId, so
+// each tier is a stable, well-known configuration rather than a one-off.
+// `aegixpass setup` 提供的官方预设。它们共享官方的 platformId，因此每个档位都是稳定、公认的配置，而非一次性配置。
+
+use crate::core::{AegixPassError, Preset};
+
+/// The official preset tiers.
+// 官方预设档位。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PresetTier {
+    /// Argon2id, 16 characters: the shipped `default.json`. / Argon2id，16 位：随程序分发的 `default.json`。
+    Standard,
+    /// SHA-256, 16 characters, for slow devices; weaker against master-password guessing.
+    // SHA-256，16 位，适用于性能较弱的设备；抵御主密码猜测的能力较弱。
+    Fast,
+    /// Argon2id, 24 characters. / Argon2id，24 位。
+    Strong,
+    /// Argon2id with 8 MiB and 5 iterations, 16 characters: still memory-hard, but fits routers
+    /// and phones with 64 MB of RAM.
+    // Argon2id，8 MiB、5 次迭代，16 位：仍然是内存困难的，但适合只有 64 MB 内存的路由器和手机。
+    LowMemory,
+}
+
+impl PresetTier {
+    /// All tiers, in the order they are offered. / 按提供顺序排列的所有档位。
+    pub const ALL: [PresetTier; 4] = [PresetTier::Standard, PresetTier::Fast, PresetTier::Strong, PresetTier::LowMemory];
+
+    /// The tier's name. / 档位名称。
+    pub fn name(&self) -> &'static str {
+        match self {
+            PresetTier::Standard => "standard",
+            PresetTier::Fast => "fast",
+            PresetTier::Strong => "strong",
+            PresetTier::LowMemory => "lowMemory",
+        }
+    }
+
+    /// A one-line description of the tier. / 档位的一行说明。
+    pub fn description(&self) -> &'static str {
+        match self {
+            PresetTier::Standard => "Argon2id, 16 characters (recommended)",
+            PresetTier::Fast => "SHA-256, 16 characters, for slow devices",
+            PresetTier::Strong => "Argon2id, 24 characters",
+            PresetTier::LowMemory => "Argon2id with 8 MiB of memory, for devices with 64 MB of RAM",
+        }
+    }
+
+    /// The preset file contents for this tier. / 该档位的预设文件内容。
+    pub fn json(&self) -> &'static str {
+        match self {
+            PresetTier::Standard => include_str!("../default.json"),
+            PresetTier::Fast => FAST_JSON,
+            PresetTier::Strong => STRONG_JSON,
+            PresetTier::LowMemory => LOW_MEMORY_JSON,
+        }
+    }
+
+    /// Parses this tier's preset. / 解析该档位的预设。
+    pub fn preset(&self) -> Result<Preset, AegixPassError> {
+        Preset::from_json(self.json())
+    }
+}
+
+const FAST_JSON: &str = r#"{
+  "name": "AegixPass - Fast",
+  "version": 1,
+  "hashAlgorithm": "sha256",
+  "rngAlgorithm": "chaCha20",
+  "shuffleAlgorithm": "fisherYates",
+  "length": 16,
+  "platformId": "aegixpass.takuron.com",
+  "official": true,
+  "charsets": [
+    "0123456789",
+    "abcdefghijklmnopqrstuvwxyz",
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+    "!@#$%^&*_+-="
+  ]
+}
+"#;
+
+const STRONG_JSON: &str = r#"{
+  "name": "AegixPass - Strong",
+  "version": 1,
+  "hashAlgorithm": "argon2id",
+  "rngAlgorithm": "chaCha20",
+  "shuffleAlgorithm": "fisherYates",
+  "length": 24,
+  "platformId": "aegixpass.takuron.com",
+  "official": true,
+  "charsets": [
+    "0123456789",
+    "abcdefghijklmnopqrstuvwxyz",
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+    "!@#$%^&*_+-="
+  ]
+}
+"#;
+
+const LOW_MEMORY_JSON: &str = r#"{
+  "name": "AegixPass - Low Memory",
+  "version": 1,
+  "hashAlgorithm": "argon2id",
+  "rngAlgorithm": "chaCha20",
+  "shuffleAlgorithm": "fisherYates",
+  "length": 16,
+  "platformId": "aegixpass.takuron.com",
+  "official": true,
+  "argon2": { "memoryKib": 8192, "iterations": 5 },
+  "charsets": [
+    "0123456789",
+    "abcdefghijklmnopqrstuvwxyz",
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+    "!@#$%^&*_+-="
+  ]
+}
+"#;
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers_parse() {
+        for tier in PresetTier::ALL {
+            let preset = tier.preset().unwrap();
+            assert!(preset.official);
+            assert_eq!(preset.platform_id_warning(), None);
+        }
+    }
+}
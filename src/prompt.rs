@@ -0,0 +1,243 @@
+// --- Interactive master password prompt ---
+// --- 交互式主密码输入 ---
+// Reads the master password without echoing it. The first time per terminal session, and whenever a
+// new master password is chosen, a live strength bar is drawn on stderr while the user types and
+// a weak password must be confirmed. The bar comes from a class-pool heuristic (see
+// `aegixpass::strength`), not zxcvbn. When stdin is not a terminal the password is read from the
+// first line of stdin instead.
+// 在不回显的情况下读取主密码。每个终端会话中的第一次输入，以及每次选择新主密码时，都会在用户输入期间于标准错误输出上
+// 绘制实时强度条，且弱密码需要确认后才会被接受。强度条来自字符池启发式估算（参见 `aegixpass::strength`），而非 zxcvbn。
+// 当标准输入不是终端时，改为从标准输入的第一行读取密码。
+//
+// Without a terminal (e.g. when invoked from a launcher), `promptFallback` in settings.json
+// decides between stdin and a pinentry dialog; by default pinentry is used when stdin is empty.
+// 没有终端时（例如由启动器调用），由 settings.json 中的 `promptFallback` 决定使用标准输入还是
+// pinentry 对话框；默认在标准输入为空时使用 pinentry。
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use aegixpass::pinentry;
+use aegixpass::settings::{PromptFallback, Settings};
+use aegixpass::strength::{estimate_class_pool, StrengthLevel};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{cursor, queue};
+
+/// Width of the strength bar in cells. / 强度条的宽度（字符格数）。
+const BAR_WIDTH: usize = 20;
+/// Entropy that fills the whole bar. / 填满整个强度条所需的熵。
+const BAR_FULL_BITS: f64 = 100.0;
+
+/// Disables raw mode when dropped, so the terminal is restored on every exit path.
+// 在析构时关闭原始模式，确保任何退出路径都能恢复终端。
+struct RawModeGuard;
+
+impl RawModeGuard {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Prompts for the master password, with the strength bar on the first prompt of the session.
+// 提示输入主密码；会话中的第一次提示会显示强度条。
+pub fn read_master_password(settings: &Settings) -> Result<String, Box<dyn std::error::Error>> {
+    read_master("Master password", settings, first_prompt_of_session)
+}
+
+/// Prompts for a master password being chosen, such as `rekey`'s new one: always with the
+/// strength bar, and a weak one must be confirmed.
+// 提示输入正在选择的主密码，例如 `rekey` 的新主密码：总是显示强度条，且弱密码需要确认。
+pub fn read_new_master_password(label: &str, settings: &Settings) -> Result<String, Box<dyn std::error::Error>> {
+    read_master(label, settings, || true)
+}
+
+/// Prompts for another secret, such as a backup passphrase, showing the same strength bar but
+/// without insisting on a strong one.
+// 提示输入其它秘密（例如备份口令），同样显示强度条，但不强制要求足够强。
+pub fn read_passphrase(label: &str, settings: &Settings) -> Result<String, Box<dyn std::error::Error>> {
+    read_secret(label, settings, true)
+}
+
+/// Prompts for a secret that already exists, such as `rekey`'s old master password, without the strength bar.
+// 提示输入一个已存在的秘密（例如 `rekey` 的旧主密码），不显示强度条。
+pub fn read_existing_secret(label: &str, settings: &Settings) -> Result<String, Box<dyn std::error::Error>> {
+    read_secret(label, settings, false)
+}
+
+/// Reads a master password on the terminal, checking its strength when `meter` says so.
+// 在终端上读取主密码；`meter` 为真时检查其强度。
+fn read_master(label: &str, settings: &Settings, meter: impl FnOnce() -> bool) -> Result<String, Box<dyn std::error::Error>> {
+    if crate::test_mode() {
+        return read_test_answer(label);
+    }
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return read_without_terminal("Enter your AegixPass master password.", &format!("{}:", label), settings);
+    }
+    if !meter() {
+        return read_masked(label, false);
+    }
+    loop {
+        let password = read_masked(label, true)?;
+        let strength = estimate_class_pool(&password);
+        if strength.level >= StrengthLevel::Fair || confirm_weak(strength.level, strength.entropy_bits)? {
+            return Ok(password);
+        }
+    }
+}
+
+/// Reads a secret on the terminal, or without one as `promptFallback` says.
+// 在终端上读取秘密；没有终端时按 `promptFallback` 的设置读取。
+fn read_secret(label: &str, settings: &Settings, meter: bool) -> Result<String, Box<dyn std::error::Error>> {
+    if crate::test_mode() {
+        return read_test_answer(label);
+    }
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return read_without_terminal(&format!("Enter the {}.", label.to_lowercase()), &format!("{}:", label), settings);
+    }
+    read_masked(label, meter)
+}
+
+/// Whether this is the first master password prompt of the session, marking the session as
+/// prompted. On Unix the session is the terminal's (`getsid`), remembered by a marker file in
+/// the runtime directory, which is emptied at logout; elsewhere it is the process.
+// 这是否为本会话中的第一次主密码提示，并将本会话标记为已提示。在 Unix 上，会话即终端所属的会话（`getsid`），
+// 由运行时目录（登出时清空）中的标记文件记住；其它平台上则以进程为会话。
+fn first_prompt_of_session() -> bool {
+    static PROMPTED: AtomicBool = AtomicBool::new(false);
+    if PROMPTED.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        // SAFETY: getsid(0) and getuid() take no pointers and only read the calling process's IDs.
+        // SAFETY: getsid(0) 与 getuid() 不接受指针，只读取调用进程的 ID。
+        let (session, user) = unsafe { (libc::getsid(0), libc::getuid()) };
+        let dir = dirs::runtime_dir().unwrap_or_else(std::env::temp_dir);
+        let marker = dir.join(format!("aegixpass-prompted-{}-{}", user, session));
+        // Only an existing marker means an earlier prompt; any other failure keeps the bar.
+        // 只有标记已存在才说明此前已提示过；其它任何失败都保留强度条。
+        if let Err(error) = std::fs::OpenOptions::new().write(true).create_new(true).open(marker) {
+            return error.kind() != io::ErrorKind::AlreadyExists;
+        }
+    }
+    true
+}
+
+/// Under `--test-mode`, the next line of stdin, with no strength check, terminal or pinentry.
+// 在 `--test-mode` 下读取标准输入的下一行，不检查强度，也不使用终端或 pinentry。
+fn read_test_answer(label: &str) -> Result<String, Box<dyn std::error::Error>> {
+    read_stdin_line()?.ok_or_else(|| format!("--test-mode: no answer on stdin for '{}'", label).into())
+}
+
+/// Reads a secret from the first line of stdin or from pinentry, as `promptFallback` says.
+// 按 `promptFallback` 的设置，从标准输入的第一行或 pinentry 读取秘密。
+fn read_without_terminal(description: &str, prompt: &str, settings: &Settings) -> Result<String, Box<dyn std::error::Error>> {
+    let from_pinentry = || pinentry::get_pin(&settings.pinentry_program, description, prompt);
+    match settings.prompt_fallback {
+        PromptFallback::Pinentry => Ok(from_pinentry()?),
+        PromptFallback::Stdin => Ok(read_stdin_line()?.unwrap_or_default()),
+        PromptFallback::Auto => match read_stdin_line()? {
+            Some(line) => Ok(line),
+            None => Ok(from_pinentry()?),
+        },
+    }
+}
+
+/// The first line of stdin, or `None` when stdin is empty. / 标准输入的第一行；标准输入为空时为 `None`。
+fn read_stdin_line() -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// Reads one password from the terminal in raw mode, redrawing the masked input, and the strength
+/// bar with `meter`, on each key.
+// 以原始模式从终端读取一个密码，每次按键后重绘掩码后的输入；`meter` 为真时还重绘强度条。
+fn read_masked(label: &str, meter: bool) -> Result<String, Box<dyn std::error::Error>> {
+    let mut stderr = io::stderr();
+    let mut password = String::new();
+    let _guard = RawModeGuard::enable()?;
+    draw(&mut stderr, label, &password, meter)?;
+    loop {
+        let Event::Key(KeyEvent { code, modifiers, kind, .. }) = event::read()? else {
+            continue;
+        };
+        if kind == KeyEventKind::Release {
+            continue;
+        }
+        match code {
+            KeyCode::Enter => break,
+            KeyCode::Backspace => {
+                password.pop();
+            }
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(stderr, "\r\n")?;
+                return Err("Aborted.".into());
+            }
+            KeyCode::Esc => {
+                write!(stderr, "\r\n")?;
+                return Err("Aborted.".into());
+            }
+            KeyCode::Char(c) => password.push(c),
+            _ => {}
+        }
+        draw(&mut stderr, label, &password, meter)?;
+    }
+    write!(stderr, "\r\n")?;
+    Ok(password)
+}
+
+/// Redraws the prompt line: masked input, followed by the strength bar with `meter`.
+// 重绘提示行：掩码后的输入；`meter` 为真时其后为强度条。
+fn draw(out: &mut impl Write, label: &str, password: &str, meter: bool) -> io::Result<()> {
+    queue!(out, cursor::MoveToColumn(0), Clear(ClearType::CurrentLine))?;
+    if !meter {
+        write!(out, "{}: {}", label, "*".repeat(password.chars().count()))?;
+        return out.flush();
+    }
+    let strength = estimate_class_pool(password);
+    let filled = ((strength.entropy_bits / BAR_FULL_BITS).min(1.0) * BAR_WIDTH as f64).round() as usize;
+    write!(
+        out,
+        "{}: {} [{}{}] {}",
+        label,
+        "*".repeat(password.chars().count()),
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        if password.is_empty() { "" } else { strength.level.label() }
+    )?;
+    out.flush()
+}
+
+/// Asks whether a weak master password should be used anyway.
+// 询问是否仍要使用较弱的主密码。
+fn confirm_weak(level: StrengthLevel, entropy_bits: f64) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut stderr = io::stderr();
+    write!(
+        stderr,
+        "This master password is {} (about {:.0} bits). Use it anyway? [y/N] ",
+        level.label(),
+        entropy_bits
+    )?;
+    stderr.flush()?;
+    let answer = {
+        let _guard = RawModeGuard::enable()?;
+        loop {
+            if let Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) = event::read()? {
+                break matches!(code, KeyCode::Char('y') | KeyCode::Char('Y'));
+            }
+        }
+    };
+    writeln!(stderr, "{}", if answer { "y" } else { "n" })?;
+    Ok(answer)
+}
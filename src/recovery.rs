@@ -0,0 +1,102 @@
+// --- Recovery codes ---
+// --- 恢复码 ---
+// Derives a set of numeric backup codes for a site, so the recovery codes a site asks you to
+// "store somewhere safe" can be regenerated instead. The `recovery` purpose key is expanded once
+// into one 32-byte sub-seed per code, and code `i` is drawn from sub-seed `i` alone. HKDF output
+// does not depend on how much of it is requested, so asking for more codes later keeps the
+// earlier ones, and the slow hash runs only once for the whole set.
+// 为站点派生一组数字备用码，使站点要求“妥善保存”的恢复码可以重新生成，而无需保存。`recovery` 用途的密钥只扩展一次，
+// 为每个恢复码得到一个 32 字节的子种子，第 `i` 个恢复码只由第 `i` 个子种子抽取。HKDF 的输出与请求的长度无关，
+// 因此日后请求更多恢复码时，之前的恢复码保持不变，并且整组恢复码只运行一次慢速哈希。
+
+use crate::core::{create_rng_from_seed, derive_purpose_key, AegixPassError, Preset, Purpose, SeedOptions};
+use crate::username::pick;
+
+/// Most codes in one set. / 一组恢复码的最大数量。
+pub const MAX_RECOVERY_CODES: usize = 100;
+/// Fewest digits of a code. / 恢复码的最少位数。
+pub const MIN_RECOVERY_DIGITS: usize = 6;
+/// Most digits of a code. / 恢复码的最多位数。
+pub const MAX_RECOVERY_DIGITS: usize = 20;
+/// Bytes of each code's sub-seed. / 每个恢复码子种子的字节数。
+const SUB_SEED_BYTES: usize = 32;
+
+/// Derives `count` recovery codes of `digits` digits each for a site.
+// 为站点派生 `count` 个、每个 `digits` 位的恢复码。
+pub fn derive_recovery_codes(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    count: usize,
+    digits: usize,
+) -> Result<Vec<String>, AegixPassError> {
+    derive_recovery_codes_with(password_source, distinguish_key, preset, count, digits, &SeedOptions::default())
+}
+
+/// [`derive_recovery_codes`] with extra seed inputs such as a keyfile. The `recovery` purpose key
+/// with the label `recovery` is expanded to `32 * count` bytes; each 32-byte slice seeds the
+/// preset's RNG, which draws the code's digits with the same unbiased range sampling as passwords.
+// 带有额外种子输入（例如密钥文件）的 [`derive_recovery_codes`]。以标签 `recovery` 将 `recovery` 用途的密钥扩展为
+// `32 * count` 字节；每个 32 字节的片段作为预设 RNG 的种子，以与密码相同的无偏范围抽样抽取该恢复码的数字。
+pub fn derive_recovery_codes_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    count: usize,
+    digits: usize,
+    options: &SeedOptions,
+) -> Result<Vec<String>, AegixPassError> {
+    if count == 0 || count > MAX_RECOVERY_CODES || !(MIN_RECOVERY_DIGITS..=MAX_RECOVERY_DIGITS).contains(&digits) {
+        return Err(AegixPassError::InvalidRecoveryCodes(count, digits));
+    }
+    let key = derive_purpose_key(password_source, distinguish_key, preset, Purpose::Recovery, "recovery", SUB_SEED_BYTES * count, options)?;
+    key.chunks_exact(SUB_SEED_BYTES)
+        .map(|chunk| {
+            let mut seed = [0u8; SUB_SEED_BYTES];
+            seed.copy_from_slice(chunk);
+            let mut rng = create_rng_from_seed(seed, &preset.rng_algorithm)?;
+            Ok((0..digits).map(|_| pick(&mut *rng, "0123456789")).collect())
+        })
+        .collect()
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::load_purpose_preset;
+
+    #[test]
+    fn test_recovery_codes() {
+        let preset = load_purpose_preset(2);
+        let codes = derive_recovery_codes("MySecretPassword123!", "example.com", &preset, 10, 8).unwrap();
+        // The first code, pinned for other implementations. / 第一个恢复码，固定下来供其它实现核对。
+        assert_eq!(codes[0], "09988142");
+        assert_eq!(codes.len(), 10);
+        assert!(codes.iter().all(|code| code.len() == 8 && code.bytes().all(|b| b.is_ascii_digit())));
+        let mut unique = codes.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), codes.len());
+
+        // More codes keep the earlier ones. / 更多的恢复码会保留之前的恢复码。
+        let more = derive_recovery_codes("MySecretPassword123!", "example.com", &preset, 12, 8).unwrap();
+        assert_eq!(more[..10], codes[..]);
+        assert_ne!(codes, derive_recovery_codes("MySecretPassword123!", "example.org", &preset, 10, 8).unwrap());
+        assert_ne!(codes, derive_recovery_codes("AnotherPassword!", "example.com", &preset, 10, 8).unwrap());
+    }
+
+    #[test]
+    fn test_recovery_code_limits() {
+        let preset = load_purpose_preset(2);
+        for (count, digits) in [(0, 8), (MAX_RECOVERY_CODES + 1, 8), (10, MIN_RECOVERY_DIGITS - 1), (10, MAX_RECOVERY_DIGITS + 1)] {
+            assert_eq!(
+                derive_recovery_codes("MySecretPassword123!", "example.com", &preset, count, digits),
+                Err(AegixPassError::InvalidRecoveryCodes(count, digits))
+            );
+        }
+        let codes = derive_recovery_codes("MySecretPassword123!", "example.com", &preset, MAX_RECOVERY_CODES, MAX_RECOVERY_DIGITS).unwrap();
+        assert_eq!(codes.len(), MAX_RECOVERY_CODES);
+    }
+}
@@ -0,0 +1,183 @@
+// --- Algorithm registry ---
+// --- 算法注册表 ---
+// Global tables mapping preset algorithm names to their implementations, plus the set of
+// known-good `platformId` values. The tables are built lazily on first use from the built-in
+// algorithms, the official `platformId` and anything registered before then, and are read-only
+// afterwards, so lookups need no locking and no unsafe statics.
+// 将预设中的算法名称映射到其实现的全局表，以及可信 `platformId` 的集合。这些表在首次使用时才根据内置算法、
+// 官方 `platformId` 以及此前注册的内容构建，之后即为只读，因此查找无需加锁，也无需使用 unsafe 静态变量。
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use rand::RngCore;
+use serde_json::Value;
+
+use crate::core::{builtin_hash_functions, builtin_rng_factories, AegixPassError, HashAlgorithm, RngAlgorithm};
+
+/// Turns the generator input into a 32-byte master seed. The second argument is the
+/// preset's salt source ([`crate::Preset::salt_source`]): its `platformId`, domain-tagged when the
+/// preset sets `saltDomainTag`. Built-in slow hashes derive their salt from it.
+// 将生成器输入转换为 32 字节的主种子。第二个参数是预设的盐来源（[`crate::Preset::salt_source`]）：即其 `platformId`，
+// 预设设置了 `saltDomainTag` 时带有域标签。内置的慢哈希用它派生盐。
+pub type HashFunction = fn(&[u8], &str) -> Result<[u8; 32], AegixPassError>;
+
+/// Creates a deterministic RNG from a 32-byte master seed.
+// 由 32 字节的主种子创建确定性 RNG。
+pub type RngFactory = fn([u8; 32]) -> Box<dyn RngCore>;
+
+/// Transforms a finished password; listed by name in a preset's `postProcessors`.
+// 对生成完毕的密码进行变换；在预设的 `postProcessors` 中按名称引用。
+pub type PostProcessor = fn(String) -> Result<String, AegixPassError>;
+
+#[derive(Default)]
+struct Registry {
+    hashes: HashMap<String, HashFunction>,
+    rngs: HashMap<String, RngFactory>,
+    post_processors: HashMap<String, PostProcessor>,
+    platform_ids: HashSet<String>,
+}
+
+enum Registration {
+    Hash(String, HashFunction),
+    Rng(String, RngFactory),
+    PostProcessor(String, PostProcessor),
+    PlatformId(String),
+}
+
+/// The `platformId` of the official AegixPass presets. / AegixPass 官方预设的 `platformId`。
+pub const OFFICIAL_PLATFORM_ID: &str = "aegixpass.takuron.com";
+
+/// Registrations waiting for the registry to be built; `None` once it has been frozen.
+// 等待注册表构建的注册项；注册表冻结后为 `None`。
+static PENDING: Mutex<Option<Vec<Registration>>> = Mutex::new(Some(Vec::new()));
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(|| {
+        let mut registry = Registry::default();
+        for (name, function) in builtin_hash_functions() {
+            registry.hashes.insert(name.to_string(), function);
+        }
+        for (name, factory) in builtin_rng_factories() {
+            registry.rngs.insert(name.to_string(), factory);
+        }
+        registry.platform_ids.insert(OFFICIAL_PLATFORM_ID.to_string());
+        let pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner).take();
+        for registration in pending.unwrap_or_default() {
+            match registration {
+                Registration::Hash(name, function) => {
+                    registry.hashes.insert(name, function);
+                }
+                Registration::Rng(name, factory) => {
+                    registry.rngs.insert(name, factory);
+                }
+                Registration::PostProcessor(name, processor) => {
+                    registry.post_processors.insert(name, processor);
+                }
+                Registration::PlatformId(platform_id) => {
+                    registry.platform_ids.insert(platform_id);
+                }
+            }
+        }
+        registry
+    })
+}
+
+fn register(registration: Registration) -> Result<(), AegixPassError> {
+    let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+    match pending.as_mut() {
+        Some(registrations) => {
+            registrations.push(registration);
+            Ok(())
+        }
+        None => Err(AegixPassError::RegistryError(
+            "algorithms must be registered before the first password is generated".to_string(),
+        )),
+    }
+}
+
+/// Registers a custom hash algorithm that presets can select with `"hashAlgorithm": name`.
+/// Must be called before the first password or key is derived; built-in names are reserved.
+// 注册一个自定义哈希算法，预设可通过 `"hashAlgorithm": name` 选用。
+// 必须在首次派生密码或密钥之前调用；内置算法的名称是保留的。
+pub fn register_hash(name: &str, function: HashFunction) -> Result<(), AegixPassError> {
+    let parsed: Result<HashAlgorithm, _> = serde_json::from_value(Value::String(name.to_string()));
+    if !matches!(parsed, Ok(HashAlgorithm::Custom(_))) {
+        return Err(reserved(name));
+    }
+    register(Registration::Hash(name.to_string(), function))
+}
+
+/// Registers a custom RNG that presets can select with `"rngAlgorithm": name`.
+/// Must be called before the first password is generated; built-in names are reserved.
+// 注册一个自定义 RNG，预设可通过 `"rngAlgorithm": name` 选用。
+// 必须在首次生成密码之前调用；内置算法的名称是保留的。
+pub fn register_rng(name: &str, factory: RngFactory) -> Result<(), AegixPassError> {
+    let parsed: Result<RngAlgorithm, _> = serde_json::from_value(Value::String(name.to_string()));
+    if !matches!(parsed, Ok(RngAlgorithm::Custom(_))) {
+        return Err(reserved(name));
+    }
+    register(Registration::Rng(name.to_string(), factory))
+}
+
+/// Registers a post-processor that presets can list in `"postProcessors"`.
+/// Must be called before the first password is generated.
+// 注册一个后处理器，预设可在 `"postProcessors"` 中列出。必须在首次生成密码之前调用。
+pub fn register_post_processor(name: &str, processor: PostProcessor) -> Result<(), AegixPassError> {
+    register(Registration::PostProcessor(name.to_string(), processor))
+}
+
+/// Marks a `platformId` as known-good, so presets using it generate without a warning.
+/// Must be called before the first password is generated.
+// 将某个 `platformId` 标记为可信，使用它的预设在生成时不会产生警告。必须在首次生成密码之前调用。
+pub fn register_platform_id(platform_id: &str) -> Result<(), AegixPassError> {
+    register(Registration::PlatformId(platform_id.to_string()))
+}
+
+fn reserved(name: &str) -> AegixPassError {
+    AegixPassError::RegistryError(format!("'{}' is a built-in algorithm name", name))
+}
+
+/// Looks up a hash algorithm by its preset name, freezing the registry on first use.
+// 按预设名称查找哈希算法；首次调用时冻结注册表。
+pub fn hash_function(name: &str) -> Option<HashFunction> {
+    registry().hashes.get(name).copied()
+}
+
+/// Looks up an RNG by its preset name, freezing the registry on first use.
+// 按预设名称查找 RNG；首次调用时冻结注册表。
+pub fn rng_factory(name: &str) -> Option<RngFactory> {
+    registry().rngs.get(name).copied()
+}
+
+/// Looks up a post-processor by name, freezing the registry on first use.
+// 按名称查找后处理器；首次调用时冻结注册表。
+pub fn post_processor(name: &str) -> Option<PostProcessor> {
+    registry().post_processors.get(name).copied()
+}
+
+/// Whether a `platformId` is known-good, freezing the registry on first use.
+// 判断某个 `platformId` 是否可信；首次调用时冻结注册表。
+pub fn is_known_platform_id(platform_id: &str) -> bool {
+    registry().platform_ids.contains(platform_id)
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_builtin_names_are_reserved() {
+        assert!(register_hash("sha256", |_, _| Ok([0; 32])).is_err());
+        assert!(register_rng("chaCha20", |seed| Box::new(rand_chacha::ChaCha20Rng::from_seed(seed))).is_err());
+        // Lookups freeze the registry, after which registration is refused.
+        // 查找会冻结注册表，此后的注册将被拒绝。
+        assert!(hash_function("sha256").is_some());
+        assert!(is_known_platform_id(OFFICIAL_PLATFORM_ID));
+        assert!(register_hash("custom", |_, _| Ok([0; 32])).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+// --- Remote presets ---
+// --- 远程预设 ---
+// Fetches a centrally distributed preset over HTTPS. A preset decides every derived password,
+// so the caller must pin the expected fingerprint (see `Preset::fingerprint`) and check it
+// before use; this module only refuses plain HTTP and oversized responses.
+// 通过 HTTPS 获取集中分发的预设。预设决定了所有派生密码，因此调用者必须固定预期的指纹
+// （参见 `Preset::fingerprint`）并在使用前进行校验；本模块只负责拒绝明文 HTTP 与过大的响应。
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+
+/// Largest preset document accepted (1 MiB). / 可接受的最大预设文档 (1 MiB)。
+pub const MAX_PRESET_BYTES: u64 = 1024 * 1024;
+
+/// Downloads the preset JSON at an `https://` URL.
+// 下载位于 `https://` URL 的预设 JSON。
+pub fn fetch_preset_json(url: &str) -> Result<String, AegixPassError> {
+    if !url.starts_with("https://") {
+        return Err(IntegrationError::RemotePreset(format!("refusing to fetch '{}' without HTTPS", url)).into());
+    }
+    let remote_error = |e: ureq::Error| IntegrationError::RemotePreset(format!("{}: {}", url, e));
+    let mut response = ureq::get(url).call().map_err(remote_error)?;
+    response.body_mut().with_config().limit(MAX_PRESET_BYTES).read_to_string().map_err(|e| remote_error(e).into())
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_http_is_refused() {
+        assert!(matches!(
+            fetch_preset_json("http://example.com/preset.json"),
+            Err(AegixPassError::Integration(IntegrationError::RemotePreset(_)))
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+// --- Date-based rotation ---
+// --- 基于日期的轮换 ---
+// A preset with a `rotation` policy mixes the current rotation epoch (e.g. `2026-Q4` for a
+// quarterly policy) into the seed, so every password changes automatically when a new period
+// begins. The epoch is computed from the UTC date, or pinned with `SeedOptions::epoch` to
+// derive the password of another period.
+// 设置了 `rotation` 策略的预设会将当前的轮换周期（例如季度策略下的 `2026-Q4`）混入种子，
+// 使所有密码在新周期开始时自动更换。周期根据 UTC 日期计算，也可以通过 `SeedOptions::epoch` 固定，以派生其它周期的密码。
+
+use serde::Deserialize;
+
+/// How often passwords derived with a preset change. / 使用某预设派生的密码多久更换一次。
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RotationPolicy {
+    /// Epochs like `2026-10`. / 周期形如 `2026-10`。
+    Monthly,
+    /// Epochs like `2026-Q4`. / 周期形如 `2026-Q4`。
+    Quarterly,
+    /// Epochs like `2026`. / 周期形如 `2026`。
+    Yearly,
+}
+
+impl RotationPolicy {
+    /// The name of the policy as written in preset files. / 策略在预设文件中的名称。
+    pub fn name(&self) -> &'static str {
+        match self {
+            RotationPolicy::Monthly => "monthly",
+            RotationPolicy::Quarterly => "quarterly",
+            RotationPolicy::Yearly => "yearly",
+        }
+    }
+
+    /// An example epoch, for error messages. / 用于错误信息的周期示例。
+    pub fn example(&self) -> &'static str {
+        match self {
+            RotationPolicy::Monthly => "2026-10",
+            RotationPolicy::Quarterly => "2026-Q4",
+            RotationPolicy::Yearly => "2026",
+        }
+    }
+
+    /// The epoch containing the given Unix time (UTC). / 包含给定 Unix 时间（UTC）的周期。
+    pub fn epoch_at(&self, unix_secs: u64) -> String {
+        let (year, month, _) = civil_date(unix_secs / 86_400);
+        match self {
+            RotationPolicy::Monthly => format!("{:04}-{:02}", year, month),
+            RotationPolicy::Quarterly => format!("{:04}-Q{}", year, (month - 1) / 3 + 1),
+            RotationPolicy::Yearly => format!("{:04}", year),
+        }
+    }
+
+    /// The Unix time (UTC midnight) at which the epoch containing `unix_secs` began.
+    // 包含 `unix_secs` 的周期开始时的 Unix 时间（UTC 零点）。
+    pub fn epoch_start_at(&self, unix_secs: u64) -> u64 {
+        let (year, month, _) = civil_date(unix_secs / 86_400);
+        let first_month = match self {
+            RotationPolicy::Monthly => month,
+            RotationPolicy::Quarterly => (month - 1) / 3 * 3 + 1,
+            RotationPolicy::Yearly => 1,
+        };
+        days_from_civil(year, first_month, 1) * 86_400
+    }
+
+    /// The epoch containing the current date. / 包含当前日期的周期。
+    pub fn current_epoch(&self) -> String {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        self.epoch_at(now)
+    }
+
+    /// Whether `epoch` is written the way this policy writes its epochs.
+    // `epoch` 的写法是否与该策略的周期格式一致。
+    pub fn is_valid_epoch(&self, epoch: &str) -> bool {
+        let in_range = |s: &str, low: u32, high: u32| s.bytes().all(|b| b.is_ascii_digit()) && s.parse::<u32>().is_ok_and(|n| (low..=high).contains(&n));
+        let (Some(year), Some(rest)) = (epoch.get(..4), epoch.get(4..)) else {
+            return false;
+        };
+        if !year.bytes().all(|b| b.is_ascii_digit()) {
+            return false;
+        }
+        match self {
+            RotationPolicy::Monthly => rest.strip_prefix('-').is_some_and(|m| m.len() == 2 && in_range(m, 1, 12)),
+            RotationPolicy::Quarterly => rest.strip_prefix("-Q").is_some_and(|q| q.len() == 1 && in_range(q, 1, 4)),
+            RotationPolicy::Yearly => rest.is_empty(),
+        }
+    }
+}
+
+/// The UTC date of a Unix time as `YYYY-MM-DD`. / Unix 时间对应的 UTC 日期，格式为 `YYYY-MM-DD`。
+pub fn date_at(unix_secs: u64) -> String {
+    let (year, month, day) = civil_date(unix_secs / 86_400);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Whether `date` is a calendar date written as `YYYY-MM-DD`. / `date` 是否为以 `YYYY-MM-DD` 书写的日历日期。
+pub fn is_valid_date(date: &str) -> bool {
+    let bytes = date.as_bytes();
+    let digits = |range: std::ops::Range<usize>| date.get(range).filter(|s| s.bytes().all(|b| b.is_ascii_digit())).and_then(|s| s.parse::<u64>().ok());
+    let (Some(year), Some(month), Some(day)) = (digits(0..4), digits(5..7), digits(8..10)) else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    bytes.len() == 10 && bytes[4] == b'-' && bytes[7] == b'-' && (1..=12).contains(&month) && (1..=days_in_month).contains(&day)
+}
+
+/// The proleptic Gregorian year, month and day of a day count since 1970-01-01.
+// 自 1970-01-01 起的天数所对应的公历年份、月份与日期。
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    // Howard Hinnant's civil-from-days, shifted so that years start in March.
+    // Howard Hinnant 的 civil-from-days 算法，将年份的起点移到三月。
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// The day count since 1970-01-01 of a proleptic Gregorian date; the inverse of [`civil_date`].
+// 公历日期自 1970-01-01 起的天数；即 [`civil_date`] 的逆运算。
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = year - u64::from(month <= 2);
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epochs() {
+        // 2026-10-16T00:00:00Z, 2024-02-29T12:00:00Z and 1999-12-31T23:59:59Z.
+        assert_eq!(RotationPolicy::Monthly.epoch_at(1_792_108_800), "2026-10");
+        assert_eq!(RotationPolicy::Quarterly.epoch_at(1_792_108_800), "2026-Q4");
+        assert_eq!(RotationPolicy::Yearly.epoch_at(1_792_108_800), "2026");
+        assert_eq!(RotationPolicy::Quarterly.epoch_at(1_709_208_000), "2024-Q1");
+        assert_eq!(RotationPolicy::Monthly.epoch_at(946_684_799), "1999-12");
+
+        assert!(RotationPolicy::Quarterly.is_valid_epoch("2026-Q4"));
+        assert!(!RotationPolicy::Quarterly.is_valid_epoch("2026-Q5"));
+        assert!(RotationPolicy::Monthly.is_valid_epoch("2026-01"));
+        assert!(!RotationPolicy::Monthly.is_valid_epoch("2026-1"));
+        assert!(!RotationPolicy::Yearly.is_valid_epoch("26"));
+        assert!(!RotationPolicy::Yearly.is_valid_epoch("2026-Q4"));
+        assert!(!RotationPolicy::Yearly.is_valid_epoch("20é6"));
+
+        assert_eq!(date_at(RotationPolicy::Monthly.epoch_start_at(1_792_108_800)), "2026-10-01");
+        assert_eq!(date_at(RotationPolicy::Quarterly.epoch_start_at(1_709_208_000)), "2024-01-01");
+        assert_eq!(date_at(RotationPolicy::Yearly.epoch_start_at(946_684_799)), "1999-01-01");
+        assert_eq!(date_at(RotationPolicy::Monthly.epoch_start_at(1_709_208_000)), "2024-02-01");
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(date_at(1_792_108_800), "2026-10-16");
+        assert_eq!(date_at(1_709_208_000), "2024-02-29");
+        assert_eq!(date_at(946_684_799), "1999-12-31");
+        assert!(is_valid_date("2024-02-29"));
+        assert!(!is_valid_date("2026-02-29"));
+        assert!(!is_valid_date("2026-13-01"));
+        assert!(!is_valid_date("2026-1-01"));
+        assert!(!is_valid_date("2026-01-01T00"));
+        assert!(!is_valid_date("2026/01/01"));
+    }
+}
@@ -0,0 +1,203 @@
+// --- Sandboxed derivation ---
+// --- 沙箱派生 ---
+// An API for servers that derive on behalf of many users, e.g. a multi-tenant password
+// service. Every call is bounded: presets are parsed under strict limits, the KDF cost of a
+// call is capped, and a `Sandbox` shared between threads admits only as much Argon2/scrypt
+// memory at once as its budget allows, so one tenant's expensive preset cannot starve the
+// others. Calls touch neither the filesystem (no KDF cache, no `/proc/meminfo`) nor the
+// environment, and run only built-in algorithms, never code registered in the process.
+// 面向代表众多用户进行派生的服务器（例如多租户密码服务）的 API。每次调用都有上限：预设在严格的限制下解析，
+// 单次调用的 KDF 成本有上限，并且在线程间共享的 `Sandbox` 同一时间只接纳其预算允许的 Argon2/scrypt 内存，
+// 因此某个租户的昂贵预设无法拖垮其它租户。调用既不访问文件系统（不使用 KDF 缓存，不读取 `/proc/meminfo`），
+// 也不访问环境变量，并且只运行内置算法，绝不运行进程中注册的代码。
+
+use std::sync::{Mutex, PoisonError};
+
+use crate::core::{generate_password, AegixPassError, HashAlgorithm, Host, Preset, PresetLimits, RngAlgorithm, SeedOptions, SCRYPT_MEMORY_KIB};
+
+/// The limits a [`Sandbox`] enforces. / [`Sandbox`] 强制执行的限制。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SandboxLimits {
+    /// Limits on the preset itself. / 对预设本身的限制。
+    pub preset: PresetLimits,
+    /// Maximum size of the preset JSON, master password and distinguish key together, in bytes.
+    // 预设 JSON、主密码与区分密钥合计的最大字节数。
+    pub max_input_bytes: usize,
+    /// Maximum KDF work of one call, in KiB of memory times passes over it, which bounds its time.
+    // 单次调用的最大 KDF 工作量，以内存 KiB 数乘以遍历次数计，从而限制其耗时。
+    pub max_kdf_work_kib: u64,
+    /// Maximum KDF memory of all calls running at once, in KiB. / 同时运行的所有调用的最大 KDF 内存（KiB）。
+    pub max_concurrent_memory_kib: u64,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            preset: PresetLimits {
+                max_length: 256,
+                max_charset_chars: 1024,
+                max_argon2_memory_kib: 64 * 1024,
+                max_argon2_iterations: 8,
+            },
+            max_input_bytes: 64 * 1024,
+            // Four passes over 64 MiB; the standard profile needs 19 MiB × 2.
+            // 对 64 MiB 遍历四次；标准参数需要 19 MiB × 2。
+            max_kdf_work_kib: 4 * 64 * 1024,
+            max_concurrent_memory_kib: 256 * 1024,
+        }
+    }
+}
+
+/// Derives passwords for untrusted callers within [`SandboxLimits`]. Share one between threads.
+// 在 [`SandboxLimits`] 之内为不受信任的调用者派生密码。应在线程间共享同一个实例。
+#[derive(Debug)]
+pub struct Sandbox {
+    limits: SandboxLimits,
+    in_flight_kib: Mutex<u64>,
+}
+
+/// Gives a call's memory back to the budget when it ends. / 调用结束时将其内存归还给预算。
+struct Admission<'a> {
+    in_flight_kib: &'a Mutex<u64>,
+    memory_kib: u64,
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        *self.in_flight_kib.lock().unwrap_or_else(PoisonError::into_inner) -= self.memory_kib;
+    }
+}
+
+impl Sandbox {
+    pub fn new(limits: SandboxLimits) -> Self {
+        Self { limits, in_flight_kib: Mutex::new(0) }
+    }
+
+    /// The limits this sandbox enforces. / 此沙箱强制执行的限制。
+    pub fn limits(&self) -> &SandboxLimits {
+        &self.limits
+    }
+
+    /// Derives the password for a preset given as JSON. / 为以 JSON 形式给出的预设派生密码。
+    pub fn generate(&self, preset_json: &str, password_source: &str, distinguish_key: &str) -> Result<String, AegixPassError> {
+        self.generate_with(preset_json, password_source, distinguish_key, &SeedOptions::default())
+    }
+
+    /// [`Sandbox::generate`] with extra seed inputs such as a keyfile. / 带有额外种子输入（例如密钥文件）的 [`Sandbox::generate`]。
+    pub fn generate_with(
+        &self,
+        preset_json: &str,
+        password_source: &str,
+        distinguish_key: &str,
+        options: &SeedOptions,
+    ) -> Result<String, AegixPassError> {
+        let input_bytes = preset_json.len().saturating_add(password_source.len()).saturating_add(distinguish_key.len());
+        if input_bytes > self.limits.max_input_bytes {
+            return Err(AegixPassError::SandboxLimitExceeded {
+                what: "input size (bytes)",
+                value: input_bytes as u64,
+                limit: self.limits.max_input_bytes as u64,
+            });
+        }
+        let preset = Preset::from_json_with_limits(preset_json, &self.limits.preset)?;
+        preset.validate()?;
+        check_builtin_only(&preset)?;
+        let (memory_kib, work_kib) = kdf_cost(&preset);
+        if work_kib > self.limits.max_kdf_work_kib {
+            return Err(AegixPassError::SandboxLimitExceeded {
+                what: "KDF work (KiB × passes)",
+                value: work_kib,
+                limit: self.limits.max_kdf_work_kib,
+            });
+        }
+        let _admission = self.admit(memory_kib)?;
+        generate_password(password_source, distinguish_key, &preset, options, Host::Sandbox)
+    }
+
+    /// Reserves a call's KDF memory, refusing rather than waiting when the budget is spent.
+    // 为一次调用预留 KDF 内存；预算用尽时直接拒绝，而不是等待。
+    fn admit(&self, memory_kib: u64) -> Result<Admission<'_>, AegixPassError> {
+        let mut in_flight_kib = self.in_flight_kib.lock().unwrap_or_else(PoisonError::into_inner);
+        if in_flight_kib.saturating_add(memory_kib) > self.limits.max_concurrent_memory_kib {
+            return Err(AegixPassError::SandboxBusy { in_flight_kib: *in_flight_kib, limit_kib: self.limits.max_concurrent_memory_kib });
+        }
+        *in_flight_kib += memory_kib;
+        Ok(Admission { in_flight_kib: &self.in_flight_kib, memory_kib })
+    }
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new(SandboxLimits::default())
+    }
+}
+
+/// Refuses registered algorithms and post-processors: they are arbitrary code whose cost the
+/// sandbox cannot bound.
+// 拒绝已注册的算法与后处理器：它们是任意代码，沙箱无法限制其成本。
+fn check_builtin_only(preset: &Preset) -> Result<(), AegixPassError> {
+    let custom = match (&preset.hash_algorithm, &preset.rng_algorithm) {
+        (HashAlgorithm::Custom(name), _) | (_, RngAlgorithm::Custom(name)) => Some(name.as_str()),
+        _ => preset.post_processors.first().map(String::as_str),
+    };
+    match custom {
+        Some(name) => Err(AegixPassError::SandboxRejected(format!("'{}' is not a built-in algorithm", name))),
+        None => Ok(()),
+    }
+}
+
+/// The KDF memory of a preset and its work, in KiB and KiB × passes. Fast hashes count as free.
+// 预设的 KDF 内存及其工作量，单位分别为 KiB 与 KiB × 遍历次数。快速哈希视为零成本。
+fn kdf_cost(preset: &Preset) -> (u64, u64) {
+    match preset.hash_algorithm {
+        HashAlgorithm::Argon2id => {
+            let settings = preset.argon2.unwrap_or_default();
+            (settings.memory_kib as u64, settings.memory_kib as u64 * settings.iterations as u64)
+        }
+        HashAlgorithm::Scrypt => (SCRYPT_MEMORY_KIB, SCRYPT_MEMORY_KIB),
+        _ => (0, 0),
+    }
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aegixpass_generator;
+
+    const PRESET: &str = r#"{"name": "t", "version": 1, "hashAlgorithm": "sha256", "rngAlgorithm": "chaCha20",
+        "shuffleAlgorithm": "fisherYates", "length": 16, "platformId": "aegixpass.takuron.com",
+        "charsets": ["0123456789", "abcdefghijklmnopqrstuvwxyz", "ABCDEFGHIJKLMNOPQRSTUVWXYZ", "!@#$%^&*()_+-="]}"#;
+
+    #[test]
+    fn test_sandbox_matches_the_plain_generator() {
+        let preset = Preset::from_json(PRESET).unwrap();
+        assert_eq!(Sandbox::default().generate(PRESET, "password", "example.com"), aegixpass_generator("password", "example.com", &preset));
+    }
+
+    #[test]
+    fn test_sandbox_limits() {
+        let sandbox = Sandbox::default();
+        let long = PRESET.replace("\"length\": 16", "\"length\": 1000");
+        assert!(matches!(sandbox.generate(&long, "password", "example.com"), Err(AegixPassError::PresetLimitExceeded { .. })));
+        let costly = PRESET.replace("\"sha256\"", "\"argon2id\", \"argon2\": {\"memoryKib\": 65536, \"iterations\": 8}");
+        assert!(matches!(
+            sandbox.generate(&costly, "password", "example.com"),
+            Err(AegixPassError::SandboxLimitExceeded { what: "KDF work (KiB × passes)", .. })
+        ));
+        assert!(matches!(sandbox.generate(PRESET, &"x".repeat(70_000), "example.com"), Err(AegixPassError::SandboxLimitExceeded { .. })));
+        let custom = PRESET.replace("\"sha256\"", "\"myHash\"");
+        assert!(matches!(sandbox.generate(&custom, "password", "example.com"), Err(AegixPassError::SandboxRejected(_))));
+    }
+
+    #[test]
+    fn test_memory_budget_admission() {
+        let sandbox = Sandbox::new(SandboxLimits { max_concurrent_memory_kib: 40_000, ..SandboxLimits::default() });
+        let first = sandbox.admit(30_000).unwrap();
+        assert_eq!(sandbox.admit(20_000).err(), Some(AegixPassError::SandboxBusy { in_flight_kib: 30_000, limit_kib: 40_000 }));
+        drop(first);
+        assert!(sandbox.admit(20_000).is_ok());
+        assert_eq!(*sandbox.in_flight_kib.lock().unwrap(), 0);
+    }
+}
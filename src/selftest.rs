@@ -0,0 +1,69 @@
+// --- Self-test ---
+// --- 自检 ---
+// Known-answer tests for every hash and RNG algorithm compiled into this build. A build whose
+// output drifted (a miscompiled dependency, a platform quirk) would otherwise derive different
+// passwords without any visible error.
+// 针对本次构建所包含的每种哈希与 RNG 算法的已知答案测试。否则，输出发生漂移的构建
+// （依赖被错误编译、平台差异等）会在没有任何可见错误的情况下派生出不同的密码。
+
+use crate::core::{aegixpass_generator, AegixPassError, Preset};
+
+const PASSWORD_SOURCE: &str = "AegixPass self-test";
+const DISTINGUISH_KEY: &str = "selftest.example";
+
+/// (hashAlgorithm, rngAlgorithm, expected password) for a 20-character, four-charset preset.
+// 针对 20 位、四个字符集的预设的（哈希算法，RNG 算法，期望密码）。
+const VECTORS: &[(&str, &str, &str)] = &[
+    ("sha256", "chaCha20", ")6lrCobvoW98zLL!)6^r"),
+    #[cfg(feature = "blake3")]
+    ("blake3", "chaCha20", "_1dQ7=T)fky_0pB45J8d"),
+    #[cfg(all(feature = "sha3", feature = "hc128"))]
+    ("sha3_256", "hc128", "05=baEPz0v^ZhTsa6I=A"),
+    #[cfg(feature = "blake2")]
+    ("blake2b", "chaCha20", "x#ncWe8-=wm!N)dAJ#$u"),
+    #[cfg(feature = "xoshiro")]
+    ("sha256", "xoshiro256StarStar", "3ZDlL)4iG)Zf32Uo$#68"),
+    #[cfg(feature = "argon2")]
+    ("argon2id", "chaCha20", "2TDR3uRj7&x7bn8^ZG(q"),
+    #[cfg(all(feature = "scrypt", feature = "hc128"))]
+    ("scrypt", "hc128", "Yb9#5bJFxc=sY9by5O)U"),
+];
+
+/// Runs the known-answer tests, returning the names of the algorithm pairs that passed.
+// 运行已知答案测试，返回通过测试的算法组合名称。
+pub fn self_test() -> Result<Vec<String>, AegixPassError> {
+    let mut passed = Vec::with_capacity(VECTORS.len());
+    for &(hash, rng, expected) in VECTORS {
+        let preset = Preset::from_json(&format!(
+            r#"{{
+              "name": "AegixPass - Self-test",
+              "version": 1,
+              "hashAlgorithm": "{}",
+              "rngAlgorithm": "{}",
+              "shuffleAlgorithm": "fisherYates",
+              "length": 20,
+              "platformId": "aegixpass.takuron.com",
+              "charsets": ["0123456789", "abcdefghijklmnopqrstuvwxyz", "ABCDEFGHIJKLMNOPQRSTUVWXYZ", "!@#$%^&*()_+-="]
+            }}"#,
+            hash, rng
+        ))?;
+        let name = format!("{}/{}", hash, rng);
+        if aegixpass_generator(PASSWORD_SOURCE, DISTINGUISH_KEY, &preset)? != expected {
+            return Err(AegixPassError::SelfTestFailed(name));
+        }
+        passed.push(name);
+    }
+    Ok(passed)
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        assert_eq!(self_test().unwrap().len(), VECTORS.len());
+    }
+}
@@ -0,0 +1,90 @@
+// --- User settings ---
+// --- 用户设置 ---
+// `settings.json` in the config directory holds preferences of the program itself, as opposed
+// to presets, which decide what is derived. A missing file means the defaults.
+// 配置目录中的 `settings.json` 保存程序自身的偏好设置；与之相对，预设决定派生的内容。文件不存在时使用默认值。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+
+/// Current `settings.json` format version. / 当前 `settings.json` 的格式版本。
+pub const SETTINGS_VERSION: u32 = 1;
+/// File name of the settings in the config directory. / 设置在配置目录中的文件名。
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// How the master password is read when there is no terminal to prompt on, e.g. when invoked
+/// from a launcher or a browser host.
+// 没有可供提示的终端时（例如由启动器或浏览器宿主调用）如何读取主密码。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PromptFallback {
+    /// The first line of stdin, or pinentry when stdin is empty. / 标准输入的第一行；标准输入为空时使用 pinentry。
+    #[default]
+    Auto,
+    /// Always the first line of stdin. / 始终读取标准输入的第一行。
+    Stdin,
+    /// Always a pinentry dialog. / 始终使用 pinentry 对话框。
+    Pinentry,
+}
+
+/// The contents of a `settings.json` file. / `settings.json` 文件的内容。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    pub version: u32,
+    #[serde(rename = "promptFallback", default)]
+    pub prompt_fallback: PromptFallback,
+    /// The pinentry program to run, a name on `PATH` or a full path.
+    // 要运行的 pinentry 程序，可以是 `PATH` 中的名称或完整路径。
+    #[serde(rename = "pinentryProgram", default = "default_pinentry_program")]
+    pub pinentry_program: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { version: SETTINGS_VERSION, prompt_fallback: PromptFallback::default(), pinentry_program: default_pinentry_program() }
+    }
+}
+
+fn default_pinentry_program() -> String {
+    "pinentry".to_string()
+}
+
+impl Settings {
+    /// Loads the settings; a missing file means the defaults.
+    // 加载设置；文件不存在时使用默认值。
+    pub fn load(path: &Path) -> Result<Self, AegixPassError> {
+        let settings_error = |e: &dyn std::fmt::Display| IntegrationError::Settings(format!("{}: {}", path.display(), e));
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(settings_error(&e).into()),
+        };
+        let settings: Self = serde_json::from_str(&json).map_err(|e| settings_error(&e))?;
+        if settings.version != SETTINGS_VERSION {
+            return Err(settings_error(&format!("unsupported version {}", settings.version)).into());
+        }
+        Ok(settings)
+    }
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_fill_missing_fields() {
+        let settings: Settings = serde_json::from_str(r#"{"version": 1}"#).unwrap();
+        assert_eq!(settings, Settings::default());
+        let settings: Settings =
+            serde_json::from_str(r#"{"version": 1, "promptFallback": "pinentry", "pinentryProgram": "pinentry-mac"}"#).unwrap();
+        assert_eq!(settings.prompt_fallback, PromptFallback::Pinentry);
+        assert_eq!(settings.pinentry_program, "pinentry-mac");
+        assert_eq!(Settings::load(Path::new("/nonexistent/aegixpass/settings.json")), Ok(Settings::default()));
+    }
+}
@@ -0,0 +1,202 @@
+// --- Output sinks ---
+// --- 输出目标 ---
+// Where a derived secret goes once it is rendered: standard output, the clipboard, the OS
+// keychain, a file or a callback of the embedding program. Each sink names the `Destination` the
+// preset's output policy judges it by, so a TUI or an FFI host that delivers through
+// [`deliver`] enforces the same policy as the command line, and never writes the secret into an
+// error message.
+// 渲染后的派生秘密的去向：标准输出、剪贴板、操作系统钥匙串、文件或嵌入程序的回调。每个输出目标都指明预设输出策略用来评判它的
+// `Destination`，因此通过 [`deliver`] 投递的 TUI 或 FFI 宿主与命令行程序执行相同的策略，并且从不把秘密写进错误信息。
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::core::{AegixPassError, Destination, OutputPolicy};
+use crate::integration::IntegrationError;
+use crate::output::{encode_text, TextEncoding};
+
+/// Somewhere a rendered secret can be delivered. / 可以投递渲染后秘密的地方。
+pub trait OutputSink {
+    /// The destination the output policy judges this sink by. / 输出策略评判该输出目标时所依据的目的地。
+    fn destination(&self) -> Destination;
+
+    /// Hands over the secret. / 交出秘密。
+    fn deliver(&mut self, secret: &str) -> Result<(), AegixPassError>;
+
+    /// Called once the caller is done with the sink, e.g. to clear the clipboard again.
+    // 调用方用完输出目标后调用一次，例如再次清空剪贴板。
+    fn finish(&mut self) -> Result<(), AegixPassError> {
+        Ok(())
+    }
+}
+
+/// Checks the output policy, then delivers the secret and finishes the sink.
+// 检查输出策略，然后投递秘密并结束输出目标。
+pub fn deliver(sink: &mut dyn OutputSink, policy: &OutputPolicy, secret: &str) -> Result<(), AegixPassError> {
+    policy.check(sink.destination())?;
+    sink.deliver(secret)?;
+    sink.finish()
+}
+
+/// Standard output, encoded as a file or pipe expects it. / 标准输出，按文件或管道所期望的方式编码。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StdoutSink {
+    pub encoding: TextEncoding,
+    /// Prefix a byte order mark. / 加上字节顺序标记前缀。
+    pub bom: bool,
+    /// Turn every `\n` into `\r\n`. / 将每个 `\n` 转换为 `\r\n`。
+    pub crlf: bool,
+}
+
+impl Default for StdoutSink {
+    fn default() -> Self {
+        Self { encoding: TextEncoding::Utf8, bom: false, crlf: false }
+    }
+}
+
+impl OutputSink for StdoutSink {
+    fn destination(&self) -> Destination {
+        Destination::Stdout
+    }
+
+    fn deliver(&mut self, secret: &str) -> Result<(), AegixPassError> {
+        let bytes = encode_text(secret, self.encoding, self.bom, self.crlf);
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&bytes).and_then(|_| stdout.flush()).map_err(|e| IntegrationError::OutputSink(format!("standard output: {}", e)).into())
+    }
+}
+
+/// The system clipboard (see [`crate::clipboard`]). A trailing line ending is not copied, so
+/// pasting does not submit a form early.
+// 系统剪贴板（参见 [`crate::clipboard`]）。末尾的换行符不会被复制，因此粘贴时不会提前提交表单。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClipboardSink {
+    /// Clears the clipboard this long after copying; [`OutputSink::finish`] waits for it.
+    // 复制后经过这段时间清空剪贴板；[`OutputSink::finish`] 会等待这段时间。
+    pub clear_after: Option<Duration>,
+}
+
+impl OutputSink for ClipboardSink {
+    fn destination(&self) -> Destination {
+        Destination::Clipboard
+    }
+
+    fn deliver(&mut self, secret: &str) -> Result<(), AegixPassError> {
+        crate::clipboard::copy(secret.trim_end_matches(['\r', '\n']))
+    }
+
+    fn finish(&mut self) -> Result<(), AegixPassError> {
+        if let Some(delay) = self.clear_after {
+            std::thread::sleep(delay);
+            crate::clipboard::copy("")?;
+        }
+        Ok(())
+    }
+}
+
+/// An OS keychain entry, written through the Secret Service's `secret-tool`, which reads the
+/// secret from standard input. macOS's `security` only takes a new password on its command line,
+/// where other users could see it, so it is not used.
+// 操作系统钥匙串条目，通过 Secret Service 的 `secret-tool` 写入，它从标准输入读取秘密。macOS 的 `security`
+// 只能在命令行上接收新密码，其他用户可能看到它，因此不予使用。
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeychainSink {
+    pub service: String,
+    pub account: String,
+}
+
+impl OutputSink for KeychainSink {
+    fn destination(&self) -> Destination {
+        Destination::Export
+    }
+
+    fn deliver(&mut self, secret: &str) -> Result<(), AegixPassError> {
+        if !cfg!(unix) || cfg!(target_os = "macos") {
+            return Err(IntegrationError::OutputSink("writing to the keychain needs the Secret Service".to_string()).into());
+        }
+        let keychain_error = |e: &dyn std::fmt::Display| IntegrationError::OutputSink(format!("secret-tool: {}", e));
+        let label = format!("AegixPass {}/{}", self.service, self.account);
+        let mut child = std::process::Command::new("secret-tool")
+            .args(["store", "--label", &label, "service", &self.service, "account", &self.account])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|e| keychain_error(&e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(secret.trim_end_matches(['\r', '\n']).as_bytes()).map_err(|e| keychain_error(&e))?;
+        }
+        let status = child.wait().map_err(|e| keychain_error(&e))?;
+        if !status.success() {
+            return Err(keychain_error(&status).into());
+        }
+        Ok(())
+    }
+}
+
+/// A file, replaced atomically with owner-only permissions (see [`crate::write_guard::write`]).
+// 一个文件，以仅所有者可读写的权限原子地替换（参见 [`crate::write_guard::write`]）。
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSink {
+    pub path: PathBuf,
+}
+
+impl OutputSink for FileSink {
+    fn destination(&self) -> Destination {
+        Destination::Export
+    }
+
+    fn deliver(&mut self, secret: &str) -> Result<(), AegixPassError> {
+        crate::write_guard::write(&self.path, secret).map_err(|e| IntegrationError::OutputSink(format!("{}: {}", self.path.display(), e)).into())
+    }
+}
+
+/// Hands the secret to a function of the embedding program, judged as the given destination.
+// 将秘密交给嵌入程序的函数，并按给定的目的地进行评判。
+pub struct CallbackSink<F: FnMut(&str) -> Result<(), AegixPassError>> {
+    pub destination: Destination,
+    pub callback: F,
+}
+
+impl<F: FnMut(&str) -> Result<(), AegixPassError>> OutputSink for CallbackSink<F> {
+    fn destination(&self) -> Destination {
+        self.destination
+    }
+
+    fn deliver(&mut self, secret: &str) -> Result<(), AegixPassError> {
+        (self.callback)(secret)
+    }
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deliver_checks_the_policy() {
+        let mut received = Vec::new();
+        let mut sink = CallbackSink {
+            destination: Destination::Stdout,
+            callback: |secret: &str| {
+                received.push(secret.to_string());
+                Ok(())
+            },
+        };
+        assert_eq!(deliver(&mut sink, &OutputPolicy::default(), "s3cret"), Ok(()));
+        let no_stdout = OutputPolicy { no_stdout: true, ..OutputPolicy::default() };
+        assert!(deliver(&mut sink, &no_stdout, "s3cret").is_err());
+        assert_eq!(received, vec!["s3cret"]);
+    }
+
+    #[test]
+    fn test_refused_sink_writes_nothing() {
+        let path = std::env::temp_dir().join(format!("aegixpass-sink-{}.txt", std::process::id()));
+        let mut sink = FileSink { path: path.clone() };
+        let no_export = OutputPolicy { no_export: true, ..OutputPolicy::default() };
+        assert_eq!(deliver(&mut sink, &no_export, "s3cret"), Err(AegixPassError::OutputNotAllowed(Destination::Export.name())));
+        assert!(!path.exists());
+    }
+}
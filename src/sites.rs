@@ -0,0 +1,334 @@
+// --- Site list ---
+// --- 站点列表 ---
+// `sites.json` records which distinguish keys (and usernames) a user has, so batch and rotation
+// features know what to regenerate. It never contains passwords: every password is re-derived.
+// `sites.json` 记录用户拥有哪些区分密钥（及用户名），以便批量与轮换功能知道需要重新生成哪些密码。
+// 它从不包含密码：每个密码都是重新派生的。
+
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+use crate::rotation::RotationPolicy;
+
+/// Current `sites.json` format version. / 当前 `sites.json` 的格式版本。
+pub const SITES_VERSION: u32 = 1;
+
+/// One site: the distinguish key to derive with and, optionally, the account name.
+// 一个站点：用于派生的区分密钥，以及可选的账户名。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Site {
+    #[serde(rename = "distinguishKey")]
+    pub distinguish_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Whether the account has been switched to its derived password. Imported sites start
+    /// out `false`, still using the password stored in the old manager.
+    // 该账户是否已切换为派生密码。导入的站点初始为 `false`，即仍在使用旧密码管理器中保存的密码。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub migrated: bool,
+    /// The page to log in at, when it differs from the bare distinguish key.
+    // 登录页面地址（当它与单纯的区分密钥不同时）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Free-form, non-secret notes (e.g. "security questions in the safe").
+    // 自由填写的非机密备注（例如“安全问题答案在保险箱里”）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// The most days a password may stay in use, for workplaces that mandate periodic changes.
+    /// Derived passwords only change with the preset's `rotation`, so `status` measures their
+    /// age from the start of the current rotation epoch.
+    // 密码最多可以使用的天数，适用于强制定期更换密码的工作单位。派生密码只随预设的 `rotation` 而更换，
+    // 因此 `status` 从当前轮换周期的开始计算其使用天数。
+    #[serde(rename = "maxAgeDays", default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+}
+
+/// A site with `maxAgeDays` and the age of its derived password, from [`SitesFile::aging`].
+// 设置了 `maxAgeDays` 的站点及其派生密码的使用天数，由 [`SitesFile::aging`] 给出。
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteAge<'a> {
+    pub site: &'a Site,
+    pub max_age_days: u32,
+    /// Days since the current rotation epoch began; `None` when the preset has no rotation, so
+    /// the password never changes. / 自当前轮换周期开始以来的天数；预设没有轮换时为 `None`，即密码从不更换。
+    pub age_days: Option<u64>,
+}
+
+impl SiteAge<'_> {
+    /// Whether the password is older than the site allows; one that never changes always is.
+    // 密码是否已超过站点允许的使用天数；从不更换的密码总是超期。
+    pub fn overdue(&self) -> bool {
+        self.age_days.is_none_or(|age| age > u64::from(self.max_age_days))
+    }
+}
+
+impl Site {
+    /// A site for `distinguish_key`, not yet migrated. / 尚未迁移的 `distinguish_key` 站点。
+    pub fn new(distinguish_key: impl Into<String>, username: Option<String>) -> Self {
+        Self { distinguish_key: distinguish_key.into(), username, migrated: false, url: None, notes: None, max_age_days: None }
+    }
+
+    /// Whether the distinguish key, username, URL or notes contain `query`, ignoring case.
+    // 区分密钥、用户名、URL 或备注中是否包含 `query`（不区分大小写）。
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [Some(&self.distinguish_key), self.username.as_ref(), self.url.as_ref(), self.notes.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|field| field.to_lowercase().contains(&query))
+    }
+
+    /// `distinguishKey (username)`, for listings. / 用于列表显示的 `distinguishKey (username)`。
+    pub fn display_name(&self) -> String {
+        match &self.username {
+            Some(username) => format!("{} ({})", self.distinguish_key, username),
+            None => self.distinguish_key.clone(),
+        }
+    }
+}
+
+/// The contents of a `sites.json` file.
+// `sites.json` 文件的内容。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SitesFile {
+    pub version: u32,
+    pub sites: Vec<Site>,
+}
+
+impl Default for SitesFile {
+    fn default() -> Self {
+        Self { version: SITES_VERSION, sites: Vec::new() }
+    }
+}
+
+impl SitesFile {
+    /// Loads a site list; a missing file is an empty list.
+    // 加载站点列表；文件不存在时视为空列表。
+    pub fn load(path: &Path) -> Result<Self, AegixPassError> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(IntegrationError::Sites(format!("{}: {}", path.display(), e)).into()),
+        };
+        let sites: Self = serde_json::from_str(&json)
+            .map_err(|e| IntegrationError::Sites(format!("{}: {}", path.display(), e)))?;
+        if sites.version != SITES_VERSION {
+            return Err(IntegrationError::Sites(format!(
+                "{}: unsupported version {}",
+                path.display(),
+                sites.version
+            )).into());
+        }
+        Ok(sites)
+    }
+
+    /// Writes the site list as pretty-printed JSON.
+    // 将站点列表写为格式化的 JSON。
+    pub fn save(&self, path: &Path) -> Result<(), AegixPassError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| IntegrationError::Sites(e.to_string()))?;
+        crate::write_guard::write(path, json + "\n").map_err(|e| IntegrationError::Sites(format!("{}: {}", path.display(), e)).into())
+    }
+
+    /// Loads, modifies and saves a site list while holding its lock, creating the parent
+    /// directory if needed, so concurrent updates from other processes are not lost. Nothing is
+    /// saved when `modify` fails.
+    // 在持有锁的情况下加载、修改并保存站点列表（必要时创建父目录），避免其它进程的并发更新丢失。
+    // `modify` 失败时不保存任何内容。
+    pub fn update<R>(
+        path: &Path,
+        modify: impl FnOnce(&mut Self) -> Result<R, AegixPassError>,
+    ) -> Result<R, AegixPassError> {
+        let sites_error = |e: std::io::Error| IntegrationError::Sites(format!("{}: {}", path.display(), e));
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty() && !dir.is_dir()) {
+            crate::write_guard::create_dir_all(dir).map_err(sites_error)?;
+        }
+        let _lock = crate::write_guard::lock(path).map_err(sites_error)?;
+        let mut sites = Self::load(path)?;
+        let result = modify(&mut sites)?;
+        sites.save(path)?;
+        Ok(result)
+    }
+
+    /// Adds a site unless the same key and username are already listed; returns whether it was added.
+    // 添加站点，除非已存在相同的密钥和用户名；返回是否添加成功。
+    pub fn add(&mut self, site: Site) -> bool {
+        let listed = self
+            .sites
+            .iter()
+            .any(|s| s.distinguish_key == site.distinguish_key && s.username == site.username);
+        if listed {
+            return false;
+        }
+        self.sites.push(site);
+        true
+    }
+
+    /// The sites matching `query` (see [`Site::matches`]). / 匹配 `query` 的站点（参见 [`Site::matches`]）。
+    pub fn find(&self, query: &str) -> Vec<&Site> {
+        self.sites.iter().filter(|site| site.matches(query)).collect()
+    }
+
+    /// The sites with a `maxAgeDays`, with the age their derived passwords have at `unix_secs`
+    /// under the given rotation policy. / 设置了 `maxAgeDays` 的站点，以及在给定轮换策略下其派生密码在 `unix_secs` 时的使用天数。
+    pub fn aging(&self, rotation: Option<RotationPolicy>, unix_secs: u64) -> Vec<SiteAge<'_>> {
+        let age_days = rotation.map(|policy| (unix_secs - policy.epoch_start_at(unix_secs)) / 86_400);
+        self.sites
+            .iter()
+            .filter_map(|site| site.max_age_days.map(|max_age_days| SiteAge { site, max_age_days, age_days }))
+            .collect()
+    }
+
+    /// Marks every site with this distinguish key as migrated; returns how many were listed.
+    // 将具有该区分密钥的所有站点标记为已迁移；返回列出的站点数量。
+    pub fn mark_migrated(&mut self, distinguish_key: &str) -> usize {
+        let mut count = 0;
+        for site in self.sites.iter_mut().filter(|s| s.distinguish_key == distinguish_key) {
+            site.migrated = true;
+            count += 1;
+        }
+        count
+    }
+}
+
+/// Canonicalizes a URL (or bare host) into a distinguish key: the lowercase host name without
+/// scheme, credentials, port, path or a leading `www.`.
+// 将 URL（或裸主机名）规范化为区分密钥：不含协议、凭据、端口、路径及开头 `www.` 的小写主机名。
+pub fn canonical_distinguish_key(url: &str) -> Option<String> {
+    let rest = url.trim();
+    let rest = rest.split_once("://").map_or(rest, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = host.split(':').next().unwrap_or_default().trim_end_matches('.').to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// Reads a Bitwarden, Chrome or Firefox CSV export and returns its sites. The format is detected
+/// from the header; password columns are never read.
+// 读取 Bitwarden、Chrome 或 Firefox 的 CSV 导出文件并返回其中的站点。格式由表头识别；从不读取密码列。
+pub fn import_csv(reader: impl Read) -> Result<Vec<Site>, AegixPassError> {
+    let import_error = |e: csv::Error| IntegrationError::Import(e.to_string());
+    let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv.headers().map_err(import_error)?.clone();
+    let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.trim()));
+
+    // Bitwarden uses login_uri / login_username; Chrome and Firefox use url / username.
+    // Bitwarden 使用 login_uri / login_username；Chrome 与 Firefox 使用 url / username。
+    let url_column = column(&["login_uri", "url"]).ok_or_else(|| {
+        IntegrationError::Import("unrecognized export: no 'login_uri' or 'url' column".to_string())
+    })?;
+    let username_column = column(&["login_username", "username"]);
+
+    let mut sites = Vec::new();
+    for record in csv.records() {
+        let record = record.map_err(import_error)?;
+        // Bitwarden may list several URIs separated by commas; the first one names the site.
+        // Bitwarden 可能列出多个以逗号分隔的 URI；以第一个作为站点名称。
+        let url = record.get(url_column).unwrap_or_default().split(',').next().unwrap_or_default();
+        let Some(distinguish_key) = canonical_distinguish_key(url) else {
+            continue;
+        };
+        let username = username_column
+            .and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+        let mut site = Site::new(distinguish_key, username);
+        // Keep the login page only when it says more than the distinguish key.
+        // 仅当登录页面比区分密钥包含更多信息时才保留它。
+        let url = url.trim();
+        site.url = url
+            .split_once("://")
+            .filter(|(_, rest)| rest.trim_end_matches('/').contains('/'))
+            .map(|_| url.to_string());
+        if !sites.iter().any(|s: &Site| s.distinguish_key == site.distinguish_key && s.username == site.username) {
+            sites.push(site);
+        }
+    }
+    Ok(sites)
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_distinguish_key() {
+        assert_eq!(canonical_distinguish_key("https://www.Example.com:8443/login?x=1").as_deref(), Some("example.com"));
+        assert_eq!(canonical_distinguish_key("https://user@accounts.example.org/").as_deref(), Some("accounts.example.org"));
+        assert_eq!(canonical_distinguish_key("example.net").as_deref(), Some("example.net"));
+        assert_eq!(canonical_distinguish_key("androidapp://com.example.app").as_deref(), Some("com.example.app"));
+        assert_eq!(canonical_distinguish_key("  "), None);
+    }
+
+    #[test]
+    fn test_import_bitwarden_and_chrome() {
+        let bitwarden = "folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp\n\
+            ,,login,GitHub,,,0,https://github.com/login,alice,hunter2,\n\
+            ,,note,Secret note,text,,0,,,,\n";
+        let sites = import_csv(bitwarden.as_bytes()).unwrap();
+        let mut github = Site::new("github.com", Some("alice".to_string()));
+        github.url = Some("https://github.com/login".to_string());
+        assert_eq!(sites, vec![github]);
+
+        let chrome = "name,url,username,password,note\n\
+            example.com,https://www.example.com/,bob,pw,\n\
+            example.com,https://example.com/account,bob,pw,\n";
+        let sites = import_csv(chrome.as_bytes()).unwrap();
+        assert_eq!(sites, vec![Site::new("example.com", Some("bob".to_string()))]);
+
+        assert!(import_csv("a,b\n1,2\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_mark_migrated() {
+        let mut sites = SitesFile::default();
+        assert!(sites.add(Site::new("github.com", Some("alice".to_string()))));
+        assert!(!sites.add(Site::new("github.com", Some("alice".to_string()))));
+        assert!(sites.add(Site::new("example.com", None)));
+        assert_eq!(sites.mark_migrated("github.com"), 1);
+        assert!(sites.sites[0].migrated && !sites.sites[1].migrated);
+        // Re-importing a migrated site does not reset it. / 重新导入已迁移的站点不会将其重置。
+        assert!(!sites.add(Site::new("github.com", Some("alice".to_string()))));
+        assert!(sites.sites[0].migrated);
+    }
+
+    #[test]
+    fn test_find() {
+        let json = r#"{"version": 1, "sites": [
+            {"distinguishKey": "github.com", "username": "alice", "notes": "Recovery codes in the safe"},
+            {"distinguishKey": "example.com", "url": "https://example.com/account/login"}
+        ]}"#;
+        let sites: SitesFile = serde_json::from_str(json).unwrap();
+        let keys = |query| sites.find(query).iter().map(|s| s.distinguish_key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys("ALICE"), vec!["github.com"]);
+        assert_eq!(keys("safe"), vec!["github.com"]);
+        assert_eq!(keys("account"), vec!["example.com"]);
+        assert_eq!(keys(".com"), vec!["github.com", "example.com"]);
+        assert!(keys("gitlab").is_empty());
+    }
+
+    #[test]
+    fn test_aging() {
+        let json = r#"{"version": 1, "sites": [
+            {"distinguishKey": "intranet.example", "maxAgeDays": 30},
+            {"distinguishKey": "github.com"},
+            {"distinguishKey": "vpn.example", "maxAgeDays": 7}
+        ]}"#;
+        let sites: SitesFile = serde_json::from_str(json).unwrap();
+        // 2026-10-16T00:00:00Z, 15 days into 2026-Q4. / 2026-10-16T00:00:00Z，即 2026-Q4 的第 15 天。
+        let aging = sites.aging(Some(RotationPolicy::Quarterly), 1_792_108_800);
+        assert_eq!(aging.iter().map(|age| (age.site.distinguish_key.as_str(), age.age_days)).collect::<Vec<_>>(),
+            vec![("intranet.example", Some(15)), ("vpn.example", Some(15))]);
+        assert_eq!(aging.iter().map(SiteAge::overdue).collect::<Vec<_>>(), vec![false, true]);
+        // Without rotation the password never changes. / 没有轮换时密码从不更换。
+        assert!(sites.aging(None, 1_792_108_800).iter().all(|age| age.age_days.is_none() && age.overdue()));
+        assert!(serde_json::to_string(&sites).unwrap().contains(r#""maxAgeDays":30"#));
+    }
+}
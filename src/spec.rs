@@ -0,0 +1,188 @@
+// --- Specification ---
+// --- 规范 ---
+// Renders the algorithm specification from the constants, encoders and functions derivation
+// itself uses: the limits, the HKDF labels, the hash input and charset encodings (produced by
+// the real encoders on an example) and a test vector computed on the spot. A spec rendered by
+// one build therefore always describes that build.
+// 使用派生本身所用的常量、编码器与函数渲染算法规范：各项限制、HKDF 标签、哈希输入与字符集编码（由真实的编码器对示例生成），
+// 以及当场计算的测试向量。因此，由某个构建渲染出的规范总是准确描述该构建。
+
+use std::fmt::Write;
+
+use crate::core::{
+    aegixpass_generator, master_input, AegixPassError, Charset, HashAlgorithm, Purpose, RngAlgorithm, SeedOptions, ShuffleAlgorithm,
+    AMBIGUOUS_CHARS, CHARSET_ALIASES, CHUNK_SIZE, MAX_PIN_DIGITS, MIN_PIN_DIGITS, PIN_DIGITS, HASH_INPUT_PREFIX, KEYFILE_TAG, KEY_INFO, PURPOSE_KEY_INFO, RESERVED_KEY_LABELS, ANSWER_LABEL_PREFIX, MAX_CHARSET_CHARS, MAX_PASSWORD_LENGTH,
+    MAX_PATTERN_CHARS, PATTERN_CLASSES, GRAPHEME_PLACEHOLDER_BASE,
+    MAX_REPAIR_SWAPS_PER_CHAR, MAX_SALT_BYTES, MAX_SEED_BYTES, MIN_SALT_BYTES, OUTPUT_INFO, SALT_DOMAIN_TAG, SEED_INFO, SUBKEY_INFO, SUPPORTED_VERSIONS,
+};
+use crate::demo::{DEMO_DISTINGUISH_KEY, DEMO_PASSWORD, DEMO_TIER};
+use crate::recovery::{MAX_RECOVERY_CODES, MAX_RECOVERY_DIGITS, MIN_RECOVERY_DIGITS};
+use crate::output::VERIFIER_SALT_LABEL;
+use crate::registry;
+use crate::answer::{ANSWER_SYLLABLES, ANSWER_WORDS};
+use crate::username::{USERNAME_CONSONANTS, USERNAME_DIGITS, USERNAME_SYLLABLES, USERNAME_VOWELS, USERNAME_WORDS};
+
+/// Renders the specification as Markdown. / 以 Markdown 格式渲染规范。
+pub fn markdown() -> Result<String, AegixPassError> {
+    let preset = DEMO_TIER.preset()?;
+    let hash_input = master_input(DEMO_PASSWORD, DEMO_DISTINGUISH_KEY, &preset, &SeedOptions::default())?;
+    let password = aegixpass_generator(DEMO_PASSWORD, DEMO_DISTINGUISH_KEY, &preset)?;
+    let charsets = [
+        Charset::from("abc"),
+        Charset { chars: "0123456789".to_string(), min: 2, max: Some(4), weight: Some(3) },
+    ];
+    let charsets_json = serde_json::to_string(&charsets).map_err(|e| AegixPassError::PresetParseError(e.to_string()))?;
+    let classes: Vec<String> = PATTERN_CLASSES.iter().map(|(placeholder, chars)| format!("`{}` = `{}`", placeholder, chars)).collect();
+    let versions: Vec<String> = SUPPORTED_VERSIONS.iter().map(u32::to_string).collect();
+    let purposes: Vec<String> =
+        [Purpose::Password, Purpose::Username, Purpose::Totp, Purpose::Ssh, Purpose::Key, Purpose::Passphrase, Purpose::Answer, Purpose::Recovery].iter().map(|p| format!("`{}`", p.name())).collect();
+
+    // `write!` into a `String` cannot fail. / 向 `String` 中 `write!` 不会失败。
+    let mut out = String::new();
+    let _ = writeln!(out, "# AegixPass algorithm specification (version {})\n", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "Generated by `aegixpass spec` from the constants and encoders of this build.\n");
+
+    let _ = writeln!(out, "## Limits\n");
+    let _ = writeln!(out, "- Supported preset versions: {}", versions.join(", "));
+    let _ = writeln!(out, "- Maximum `length`: {}", MAX_PASSWORD_LENGTH);
+    let _ = writeln!(out, "- Maximum characters over all charsets: {}", MAX_CHARSET_CHARS);
+    let _ = writeln!(out, "- `seedBytes`: 32 (default), 64 or {}", MAX_SEED_BYTES);
+    let _ = writeln!(out, "- Seed bytes per charset group: {}, so at most `seedBytes / {}` groups", CHUNK_SIZE, CHUNK_SIZE);
+    let aliases: Vec<String> = CHARSET_ALIASES.iter().map(|(alias, chars)| format!("`{}` = `{}`", alias, chars)).collect();
+    let _ = writeln!(out, "- Charset aliases, expanded when the preset is parsed: {}", aliases.join(", "));
+    let _ = writeln!(out, "- Characters removed by `excludeAmbiguous`: `{}`", AMBIGUOUS_CHARS);
+    let _ = writeln!(out, "- `pin`: {} to {} digits; the charsets become `[\"{}\"]` and `length` the digit count", MIN_PIN_DIGITS, MAX_PIN_DIGITS, PIN_DIGITS);
+    let _ = writeln!(out, "- `graphemes`: multi-code-point clusters become placeholders U+{:X} + 2i, in order of first appearance", GRAPHEME_PLACEHOLDER_BASE);
+    let _ = writeln!(out, "- `pattern`: at most {} characters; placeholders {}, `X` = the concatenated charsets; `\\` escapes\n", MAX_PATTERN_CHARS, classes.join(", "));
+
+    let _ = writeln!(out, "## Algorithms\n");
+    let _ = writeln!(out, "| kind | name | in this build |\n| --- | --- | --- |");
+    for hash in HashAlgorithm::BUILT_IN {
+        let _ = writeln!(out, "| hashAlgorithm | `{}` | {} |", hash.name(), yes_no(registry::hash_function(hash.name()).is_some()));
+    }
+    for rng in RngAlgorithm::BUILT_IN {
+        let _ = writeln!(out, "| rngAlgorithm | `{}` | {} |", rng.name(), yes_no(registry::rng_factory(rng.name()).is_some()));
+    }
+    for shuffle in [ShuffleAlgorithm::FisherYates, ShuffleAlgorithm::Sattolo] {
+        let _ = writeln!(out, "| shuffleAlgorithm | `{}` | yes |", shuffle.name());
+    }
+
+    let _ = writeln!(out, "\n## Field encodings\n");
+    let _ = writeln!(out, "The hash input is UTF-8 text, fields joined by `:`:\n");
+    let _ = writeln!(out, "    {}{{version}}:{{platformId}}:{{length}}:{{master password}}:{{distinguish key}}:{{charsets JSON}}\n", HASH_INPUT_PREFIX);
+    let _ = writeln!(out, "With `normalizeInputs`, the master password and distinguish key are converted to Unicode NFC first.");
+    let _ = writeln!(out, "The input is then followed, only when present, by `:pepper={{hex SHA-256 of the pepper}}`, `:counter={{n}}` (n ≠ 0), `:epoch={{epoch}}` and `:pattern={{pattern}}`, in that order.");
+    let _ = writeln!(out, "The charsets are compact JSON. A group with `min` 1 and neither `max` nor `weight` is a plain string;");
+    let _ = writeln!(out, "any other group is an object with `chars`, `min`, then `max` and `weight` when set. For example:\n");
+    let _ = writeln!(out, "    {}\n", charsets_json);
+    let _ = writeln!(out, "With the test vector's inputs the hash input is:\n");
+    let _ = writeln!(out, "    {}\n", hash_input);
+    let _ = writeln!(out, "## Seeds\n");
+    let _ = writeln!(out, "- Master seed: the 32-byte output of `hashAlgorithm` over the hash input. `argon2id` and `scrypt` are salted with");
+    let _ = writeln!(out, "  SHA-256(`platformId`), or SHA-256(`{}:{{platformId}}`) when the preset sets `saltDomainTag`.", SALT_DOMAIN_TAG);
+    let _ = writeln!(out, "  A preset `salt` ({} to {} bytes as hex) is used byte for byte instead.", MIN_SALT_BYTES, MAX_SALT_BYTES);
+    let _ = writeln!(out, "- Keyfile: the master seed becomes HMAC-SHA256(key = master seed, `{}` ‖ SHA-256(keyfile)).", KEYFILE_TAG);
+    let _ = writeln!(out, "- Purpose seed: the master seed for version 1; from version 2 on, HKDF-SHA256(master seed,");
+    let _ = writeln!(out, "  info = `{}{{purpose}}`), purposes {}. Passwords use the `password` purpose seed.", SUBKEY_INFO, purposes.join(", "));
+    let _ = writeln!(out, "- Labelled passwords: HKDF-SHA256(password purpose seed, info = `{}{{label}}`).", OUTPUT_INFO);
+    let reserved: Vec<String> = RESERVED_KEY_LABELS.iter().map(|label| format!("`{}`", label)).collect();
+    let _ = writeln!(
+        out,
+        "- Key material: HKDF-SHA256(purpose seed, info = `{}{{label}}`). Callers may not use the labels {} or `{}…`.",
+        KEY_INFO,
+        reserved.join(", "),
+        ANSWER_LABEL_PREFIX
+    );
+    let _ = writeln!(out, "  On version 1, usernames, answers and recovery codes use info = `{}{{label}}` instead.", PURPOSE_KEY_INFO);
+    let _ = writeln!(
+        out,
+        "- Usernames: the `username` purpose seed expanded as key material with label `username` seeds the RNG, which draws {} syllables",
+        USERNAME_SYLLABLES
+    );
+    let _ = writeln!(
+        out,
+        "  (one of `{}`, then one of `{}`) or {} capitalized wordlist words, then {} digits.",
+        USERNAME_CONSONANTS, USERNAME_VOWELS, USERNAME_WORDS, USERNAME_DIGITS
+    );
+    let _ = writeln!(
+        out,
+        "- Answers: the `answer` purpose seed expanded as key material with label `answer:{{question}}` seeds the RNG, which draws {} words",
+        ANSWER_WORDS
+    );
+    let _ = writeln!(
+        out,
+        "  of {} username syllables, joined by spaces; the question is lowercased and its runs of letters and digits joined by single spaces.",
+        ANSWER_SYLLABLES
+    );
+    let _ = writeln!(out, "- Recovery codes: the `recovery` purpose seed expanded as key material with label `recovery` to 32 bytes per code; code i's slice");
+    let _ = writeln!(
+        out,
+        "  seeds the RNG, which draws {} to {} digits. At most {} codes.",
+        MIN_RECOVERY_DIGITS, MAX_RECOVERY_DIGITS, MAX_RECOVERY_CODES
+    );
+    let _ = writeln!(
+        out,
+        "- Verifier salts: the first 16 bytes of SHA-256(key ‖ `AegixPass_{{format}}`), the key being key material with label `{}`.",
+        VERIFIER_SALT_LABEL
+    );
+    let _ = writeln!(out, "- Stage C bytes: the seed itself when `seedBytes` is 32, else HKDF-SHA256(seed, info = `{}`).\n", SEED_INFO);
+
+    let _ = writeln!(out, "## Stage ordering\n");
+    let _ = writeln!(out, "1. A: validate the preset; `pin`, `excludeAmbiguous`, `excludeChars` and `noAdjacentGroup` rewrite the charsets before anything else.");
+    let _ = writeln!(out, "2. B: hash the input into the master seed, then the password purpose seed.");
+    let _ = writeln!(out, "3. C: for group g, read bytes [{0}·g, {0}·g + {0}) of the stage C bytes as a little-endian `u32` and take it", CHUNK_SIZE);
+    let _ = writeln!(out, "   modulo the group's character count. Seed the RNG with the password purpose seed. Groups with `min` m > 1 then draw m − 1");
+    let _ = writeln!(out, "   characters each from the RNG, in group order.");
+    let _ = writeln!(out, "4. D: fill up to `length`: uniformly over the concatenated charsets; over the groups still below `max` when a");
+    let _ = writeln!(out, "   group has a `max` or `noRepeat` is set; or one group by weight, then one of its characters, when a group has a `weight`.");
+    let _ = writeln!(out, "5. E: shuffle, then repair layout rules (`noSequential`, `noAdjacentGroup`, `firstChar`, `lastChar`).");
+    let _ = writeln!(out, "   With `graphemes`, C to E see each cluster as its placeholder, and F turns placeholders back first.");
+    let _ = writeln!(out, "   With a `pattern`, C to E instead seed the RNG the same way and draw one index per placeholder, left to right.");
+    let _ = writeln!(out, "6. F: join the characters and run the `postProcessors` in order.\n");
+
+    let _ = writeln!(out, "## RNG consumption\n");
+    let _ = writeln!(out, "- Every random index below n is drawn as: take `v = next_u32()`; reject and redraw while");
+    let _ = writeln!(out, "  `v ≥ {} − ({} mod n)`; return `v mod n`.", u32::MAX, u32::MAX);
+    let _ = writeln!(out, "- Stage C's first character per group uses no RNG output.");
+    let _ = writeln!(out, "- Stage D draws one index per character, two (group, then character) for weighted presets.");
+    let _ = writeln!(out, "- With `noRepeat`, a draw of a character already in the password is discarded and redrawn the same way.");
+    let _ = writeln!(out, "- Fisher-Yates: for i from len − 1 down to 1, draw j below i + 1 and swap i and j. Sattolo: draw j below i.");
+    let _ = writeln!(out, "- `noAdjacentGroup` layout, before the repair: settle the last position first with `lastChar`, then positions");
+    let _ = writeln!(out, "  0, 1, … in order. The open groups differ from the previous position's, have a character left (of the class");
+    let _ = writeln!(out, "  at an end with `firstChar`/`lastChar`), and leave the rest arrangeable: no group needs more than half,");
+    let _ = writeln!(out, "  rounded up, of the positions it may use. Draw one index below the open groups' characters left and walk them");
+    let _ = writeln!(out, "  in order to pick one; it gives its next character in shuffled order (its first of the class at an end).");
+    let _ = writeln!(out, "- Layout repair: while a rule is broken at k, draw j below len and swap k and j, at most");
+    let _ = writeln!(out, "  {} · len times. Rules are checked in the order `noSequential`, `noAdjacentGroup`, `firstChar` (k = 0),", MAX_REPAIR_SWAPS_PER_CHAR);
+    let _ = writeln!(out, "  `lastChar` (k = len − 1). With `noAdjacentGroup`, j is drawn among the positions of k's group instead.");
+    let _ = writeln!(out, "- Builds with the `rng-accounting` feature count the words each stage consumes (`aegixpass_rng_accounting`).\n");
+
+    let _ = writeln!(out, "## Test vector\n");
+    let _ = writeln!(out, "Preset: the `{}` tier (`aegixpass setup --tier {}`).\n", DEMO_TIER.name(), DEMO_TIER.name());
+    let _ = writeln!(out, "- Master password: `{}`", DEMO_PASSWORD);
+    let _ = writeln!(out, "- Distinguish key: `{}`", DEMO_DISTINGUISH_KEY);
+    let _ = writeln!(out, "- Password: `{}`\n", password);
+    let _ = writeln!(out, "`aegixpass demo` prints every intermediate value of this derivation.");
+    Ok(out)
+}
+
+fn yes_no(available: bool) -> &'static str {
+    if available { "yes" } else { "no (feature disabled)" }
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_uses_the_real_encoders() {
+        let spec = markdown().unwrap();
+        assert!(spec.contains(r#"    ["abc",{"chars":"0123456789","min":2,"max":4,"weight":3}]"#));
+        assert!(spec.contains("    AegixPass_V1:aegixpass.takuron.com:16:AegixPass demo:demo.example:[\"0123456789\""));
+        assert!(spec.contains("- Password: `#jMiT^Y$Yp$5aRtF`"));
+        assert!(spec.contains("| hashAlgorithm | `sha256` | yes |"));
+        assert!(spec.contains("SHA-256(`AegixPass-salt-v2:{platformId}`)"));
+    }
+}
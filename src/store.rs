@@ -0,0 +1,183 @@
+// --- Escrow store ---
+// --- 托管存储 ---
+// Some passwords cannot be derived: an administrator set them, or the site refuses changes. The
+// store keeps those few in `store.json`, each encrypted with ChaCha20-Poly1305 under a key derived
+// from the master password with the preset's KDF, so one master password unlocks both derived and
+// stored credentials. The file holds no key; without the master password it reveals only the
+// entry names.
+// 有些密码无法派生：它们由管理员设定，或者网站拒绝修改。托管存储将这些少量密码保存在 `store.json` 中，
+// 每一条都以 ChaCha20-Poly1305 加密，密钥由主密码经预设的 KDF 派生，因此一个主密码即可解锁派生的与保存的凭据。
+// 文件中不含任何密钥；没有主密码时，它只会泄露条目名称。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{derive_purpose_key, AegixPassError, Preset, Purpose, SeedOptions};
+use crate::integration::IntegrationError;
+
+/// File name of the store in the config directory. / 托管存储在配置目录中的文件名。
+pub const STORE_FILE: &str = "store.json";
+/// Distinguish key the store key is derived for. / 派生存储密钥所用的区分密钥。
+pub const STORE_DISTINGUISH_KEY: &str = "aegixpass.store";
+/// Key material label of the store key. / 存储密钥的密钥材料标签。
+pub const STORE_KEY_LABEL: &str = "store";
+/// Prefix of each entry's associated data, followed by its name. / 每个条目关联数据的前缀，其后为条目名称。
+const ENTRY_AAD: &str = "AegixPass_Store:";
+const STORE_VERSION: u32 = 1;
+
+/// The key that encrypts every entry of a store. / 加密托管存储中每个条目的密钥。
+pub struct StoreKey([u8; 32]);
+
+impl StoreKey {
+    /// Derives the store key; a different master password or preset gives a different key.
+    // 派生存储密钥；主密码或预设不同，密钥也不同。
+    pub fn derive(password_source: &str, preset: &Preset, options: &SeedOptions) -> Result<Self, AegixPassError> {
+        let key = derive_purpose_key(password_source, STORE_DISTINGUISH_KEY, preset, Purpose::Key, STORE_KEY_LABEL, 32, options)?;
+        let key: [u8; 32] = key.try_into().map_err(|key: Vec<u8>| AegixPassError::InvalidKeyLength(key.len(), 32))?;
+        Ok(Self(key))
+    }
+}
+
+/// One encrypted entry. / 一个加密的条目。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StoredSecret {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The contents of a `store.json` file. / `store.json` 文件的内容。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreFile {
+    pub version: u32,
+    entries: BTreeMap<String, StoredSecret>,
+}
+
+impl Default for StoreFile {
+    fn default() -> Self {
+        Self { version: STORE_VERSION, entries: BTreeMap::new() }
+    }
+}
+
+impl StoreFile {
+    /// Loads a store; a missing file is an empty store. / 加载托管存储；文件不存在时视为空存储。
+    pub fn load(path: &Path) -> Result<Self, AegixPassError> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(IntegrationError::Store(format!("{}: {}", path.display(), e)).into()),
+        };
+        let store: Self = serde_json::from_str(&json).map_err(|e| IntegrationError::Store(format!("{}: {}", path.display(), e)))?;
+        if store.version != STORE_VERSION {
+            return Err(IntegrationError::Store(format!("{}: unsupported version {}", path.display(), store.version)).into());
+        }
+        Ok(store)
+    }
+
+    /// Loads, modifies and saves a store while holding its lock, creating the parent directory
+    /// if needed. Nothing is saved when `modify` fails.
+    // 在持有锁的情况下加载、修改并保存托管存储（必要时创建父目录）。`modify` 失败时不保存任何内容。
+    pub fn update<R>(path: &Path, modify: impl FnOnce(&mut Self) -> Result<R, AegixPassError>) -> Result<R, AegixPassError> {
+        let store_error = |e: std::io::Error| IntegrationError::Store(format!("{}: {}", path.display(), e));
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty() && !dir.is_dir()) {
+            crate::write_guard::create_dir_all(dir).map_err(store_error)?;
+        }
+        let _lock = crate::write_guard::lock(path).map_err(store_error)?;
+        let mut store = Self::load(path)?;
+        let result = modify(&mut store)?;
+        let json = serde_json::to_string_pretty(&store).map_err(|e| IntegrationError::Store(e.to_string()))?;
+        crate::write_guard::write(path, json + "\n").map_err(store_error)?;
+        Ok(result)
+    }
+
+    /// The names of the entries, in order. / 按顺序列出的条目名称。
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Whether an entry of this name exists; needs no key. / 是否存在该名称的条目；无需密钥。
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Encrypts and stores a secret, replacing any entry of the same name. The key is first
+    /// checked against an existing entry, so a mistyped master password cannot leave entries
+    /// encrypted under different keys.
+    // 加密并保存一个秘密，替换同名的已有条目。会先用已有条目检查密钥，因此输错的主密码不会导致条目以不同的密钥加密。
+    pub fn set(&mut self, key: &StoreKey, name: &str, secret: &str) -> Result<(), AegixPassError> {
+        if name.is_empty() {
+            return Err(IntegrationError::Store("the entry name is empty".to_string()).into());
+        }
+        if let Some(existing) = self.entries.keys().next() {
+            self.get(key, existing)?;
+        }
+        let mut nonce = [0u8; 12];
+        rand::rng().fill_bytes(&mut nonce);
+        let aad = format!("{}{}", ENTRY_AAD, name);
+        let ciphertext = ChaCha20Poly1305::new(&key.0.into())
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: secret.as_bytes(), aad: aad.as_bytes() })
+            .map_err(|e| IntegrationError::Store(e.to_string()))?;
+        self.entries.insert(name.to_string(), StoredSecret { nonce: BASE64.encode(nonce), ciphertext: BASE64.encode(ciphertext) });
+        Ok(())
+    }
+
+    /// Decrypts a stored secret. / 解密一个保存的秘密。
+    pub fn get(&self, key: &StoreKey, name: &str) -> Result<String, AegixPassError> {
+        let entry = self.entries.get(name).ok_or_else(|| IntegrationError::Store(format!("no entry named '{}'", name)))?;
+        let damaged = || IntegrationError::Store(format!("entry '{}' is damaged", name));
+        let nonce = BASE64.decode(&entry.nonce).ok().filter(|nonce| nonce.len() == 12).ok_or_else(damaged)?;
+        let ciphertext = BASE64.decode(&entry.ciphertext).map_err(|_| damaged())?;
+        // The name is authenticated, so entries cannot be swapped. / 名称经过认证，因此条目无法被互换。
+        let aad = format!("{}{}", ENTRY_AAD, name);
+        let plaintext = ChaCha20Poly1305::new(&key.0.into())
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: aad.as_bytes() })
+            .map_err(|_| IntegrationError::StoreWrongKey)?;
+        String::from_utf8(plaintext).map_err(|_| damaged().into())
+    }
+
+    /// Removes an entry; returns whether it existed. / 删除一个条目；返回它是否存在。
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::PresetTier;
+
+    #[test]
+    fn test_store_round_trip() {
+        let preset = PresetTier::Fast.preset().unwrap();
+        let key = StoreKey::derive("password", &preset, &SeedOptions::default()).unwrap();
+        let mut store = StoreFile::default();
+        store.set(&key, "intranet", "set-by-admin!").unwrap();
+        store.set(&key, "router", "admin").unwrap();
+        assert_eq!(store.get(&key, "intranet"), Ok("set-by-admin!".to_string()));
+        assert_eq!(store.names().collect::<Vec<_>>(), ["intranet", "router"]);
+        assert!(store.contains("router") && !store.contains("mail"));
+
+        // A wrong master password can neither read nor add entries. / 错误的主密码既不能读取也不能添加条目。
+        let wrong = StoreKey::derive("passw0rd", &preset, &SeedOptions::default()).unwrap();
+        assert_eq!(store.get(&wrong, "intranet"), Err(IntegrationError::StoreWrongKey.into()));
+        assert_eq!(store.set(&wrong, "mail", "secret"), Err(IntegrationError::StoreWrongKey.into()));
+
+        // Swapped ciphertexts fail authentication. / 互换的密文无法通过认证。
+        let mut swapped = store.clone();
+        let intranet = swapped.entries["intranet"].clone();
+        swapped.entries.insert("router".to_string(), intranet);
+        assert_eq!(swapped.get(&key, "router"), Err(IntegrationError::StoreWrongKey.into()));
+
+        assert!(store.remove("router"));
+        assert!(!store.remove("router"));
+        assert!(matches!(store.get(&key, "router"), Err(AegixPassError::Integration(IntegrationError::Store(_)))));
+    }
+}
@@ -0,0 +1,138 @@
+// --- Usernames ---
+// --- 用户名 ---
+// Derives a username for a site from the same inputs as its password, so each site can get a
+// handle of its own that cannot be linked to the others and never has to be stored. The handle
+// comes from the `username` purpose key, so it reveals nothing about the password, and is either
+// pronounceable (consonant-vowel syllables) or made of words from the preset's passphrase
+// wordlist, followed by two digits for sites that insist on them.
+// 从与密码相同的输入为站点派生用户名，使每个站点都能拥有一个独立的、无法与其它站点关联、且无需保存的名称。
+// 该名称来自 `username` 用途的密钥，因此不会透露密码的任何信息；它或者是可读的（辅音-元音音节），或者由预设口令短语词表中的单词组成，
+// 末尾再加上两位数字，以满足坚持要求数字的站点。
+
+use rand::RngCore;
+
+use crate::core::{create_rng_from_seed, derive_purpose_key, secure_random_range_u32, AegixPassError, Preset, Purpose, SeedOptions};
+use crate::passphrase::Wordlist;
+
+/// Consonants that start a syllable of a pronounceable username. / 可读用户名中每个音节开头的辅音。
+pub const USERNAME_CONSONANTS: &str = "bdfghjklmnprstvz";
+/// Vowels that end a syllable of a pronounceable username. / 可读用户名中每个音节结尾的元音。
+pub const USERNAME_VOWELS: &str = "aeiou";
+/// Syllables of a pronounceable username. / 可读用户名的音节数。
+pub const USERNAME_SYLLABLES: usize = 4;
+/// Words of a word-based username. / 基于单词的用户名所含的单词数。
+pub const USERNAME_WORDS: usize = 2;
+/// Digits at the end of every username. / 每个用户名末尾的数字位数。
+pub const USERNAME_DIGITS: usize = 2;
+
+/// What a derived username is made of. / 派生用户名的组成方式。
+#[derive(Debug, Clone, Copy)]
+pub enum UsernameStyle<'a> {
+    /// Consonant-vowel syllables, e.g. `kasumodi47`. / 辅音-元音音节，例如 `kasumodi47`。
+    Pronounceable,
+    /// Words from a wordlist, each capitalized, e.g. `AmberFalcon47`. / 取自词表的单词，每个首字母大写，例如 `AmberFalcon47`。
+    Words(&'a Wordlist),
+}
+
+impl UsernameStyle<'_> {
+    /// How many bits of the derivation a username of this style keeps. / 该方式的用户名保留了派生结果的多少比特。
+    pub fn entropy_bits(&self) -> f64 {
+        let letters = match self {
+            UsernameStyle::Pronounceable => {
+                USERNAME_SYLLABLES as f64 * ((USERNAME_CONSONANTS.len() * USERNAME_VOWELS.len()) as f64).log2()
+            }
+            UsernameStyle::Words(wordlist) => USERNAME_WORDS as f64 * wordlist.bits_per_word(),
+        };
+        letters + USERNAME_DIGITS as f64 * 10f64.log2()
+    }
+}
+
+/// Derives the username for a site. / 为站点派生用户名。
+pub fn derive_username(password_source: &str, distinguish_key: &str, preset: &Preset, style: UsernameStyle) -> Result<String, AegixPassError> {
+    derive_username_with(password_source, distinguish_key, preset, style, &SeedOptions::default())
+}
+
+/// [`derive_username`] with extra seed inputs such as a keyfile. Every part is drawn with the
+/// preset's RNG, seeded from the `username` purpose key, using the same unbiased range sampling
+/// as passwords.
+// 带有额外种子输入（例如密钥文件）的 [`derive_username`]。每个部分都由预设的 RNG 抽取，该 RNG 以 `username`
+// 用途的密钥为种子，并使用与密码相同的无偏范围抽样。
+pub fn derive_username_with(
+    password_source: &str,
+    distinguish_key: &str,
+    preset: &Preset,
+    style: UsernameStyle,
+    options: &SeedOptions,
+) -> Result<String, AegixPassError> {
+    let key = derive_purpose_key(password_source, distinguish_key, preset, Purpose::Username, "username", 32, options)?;
+    let seed: [u8; 32] = key.try_into().map_err(|key: Vec<u8>| AegixPassError::InvalidKeyLength(key.len(), 32))?;
+    let mut rng = create_rng_from_seed(seed, &preset.rng_algorithm)?;
+
+    let mut username = String::new();
+    match style {
+        UsernameStyle::Pronounceable => {
+            for _ in 0..USERNAME_SYLLABLES {
+                username.push(pick(&mut *rng, USERNAME_CONSONANTS));
+                username.push(pick(&mut *rng, USERNAME_VOWELS));
+            }
+        }
+        UsernameStyle::Words(wordlist) => {
+            for _ in 0..USERNAME_WORDS {
+                // The file size limit keeps the word count within `u32`. / 文件大小限制保证单词数在 `u32` 范围内。
+                let mut chars = wordlist.word(secure_random_range_u32(&mut *rng, wordlist.len() as u32) as usize).chars();
+                username.extend(chars.next().into_iter().flat_map(char::to_uppercase).chain(chars));
+            }
+        }
+    }
+    for _ in 0..USERNAME_DIGITS {
+        username.push(pick(&mut *rng, "0123456789"));
+    }
+    Ok(username)
+}
+
+/// Draws one character of `chars`. / 从 `chars` 中抽取一个字符。
+pub(crate) fn pick(rng: &mut dyn RngCore, chars: &str) -> char {
+    let chars: Vec<char> = chars.chars().collect();
+    chars[secure_random_range_u32(rng, chars.len() as u32) as usize]
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{aegixpass_generator, load_purpose_preset};
+    use crate::passphrase::sha256_hex;
+
+    #[test]
+    fn test_pronounceable_username() {
+        let preset = load_purpose_preset(2);
+        let username = derive_username("MySecretPassword123!", "example.com", &preset, UsernameStyle::Pronounceable).unwrap();
+        // A known answer, so other implementations can check theirs. / 已知答案，供其它实现核对。
+        assert_eq!(username, "tebikami81");
+        assert_eq!(username, derive_username("MySecretPassword123!", "example.com", &preset, UsernameStyle::Pronounceable).unwrap());
+        assert_eq!(username.len(), 2 * USERNAME_SYLLABLES + USERNAME_DIGITS);
+        let (letters, digits) = username.split_at(2 * USERNAME_SYLLABLES);
+        for (i, c) in letters.chars().enumerate() {
+            assert!(if i % 2 == 0 { USERNAME_CONSONANTS } else { USERNAME_VOWELS }.contains(c), "{}", username);
+        }
+        assert!(digits.bytes().all(|b| b.is_ascii_digit()));
+
+        assert_ne!(username, derive_username("MySecretPassword123!", "example.org", &preset, UsernameStyle::Pronounceable).unwrap());
+        assert_ne!(username, derive_username("AnotherPassword!", "example.com", &preset, UsernameStyle::Pronounceable).unwrap());
+        assert!(!aegixpass_generator("MySecretPassword123!", "example.com", &preset).unwrap().contains(letters));
+        assert_eq!(derive_username("", "example.com", &preset, UsernameStyle::Pronounceable), Err(AegixPassError::InputEmpty));
+    }
+
+    #[test]
+    fn test_word_username() {
+        let file: String = (0..2000).map(|n| format!("word{}\n", n)).collect();
+        let wordlist = Wordlist::from_bytes(file.as_bytes(), &sha256_hex(file.as_bytes())).unwrap();
+        let username = derive_username("MySecretPassword123!", "example.com", &load_purpose_preset(2), UsernameStyle::Words(&wordlist)).unwrap();
+        assert_eq!(username.matches("Word").count(), USERNAME_WORDS);
+        assert!(username.starts_with("Word"));
+        assert!(username[username.len() - USERNAME_DIGITS..].bytes().all(|b| b.is_ascii_digit()));
+        let expected_bits = USERNAME_WORDS as f64 * 2000f64.log2() + USERNAME_DIGITS as f64 * 10f64.log2();
+        assert!((UsernameStyle::Words(&wordlist).entropy_bits() - expected_bits).abs() < 1e-9);
+    }
+}
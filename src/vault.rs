@@ -0,0 +1,163 @@
+// --- HashiCorp Vault integration (feature "vault") ---
+// --- HashiCorp Vault 集成 (需启用 "vault" 特性) ---
+// Writes a derived value into a Vault KV version 2 secrets engine using the
+// ambient `VAULT_ADDR` / `VAULT_TOKEN` environment variables, the same way the
+// official `vault` CLI does.
+// 使用环境变量 `VAULT_ADDR` / `VAULT_TOKEN`（与官方 `vault` 命令行一致），
+// 将派生出的值写入 Vault 的 KV v2 秘密引擎。
+
+use serde_json::{Map, Value};
+
+use crate::core::AegixPassError;
+use crate::integration::IntegrationError;
+
+/// Default Vault address used when `VAULT_ADDR` is not set.
+// 未设置 `VAULT_ADDR` 时使用的默认 Vault 地址。
+const DEFAULT_VAULT_ADDR: &str = "http://127.0.0.1:8200";
+
+/// A parsed `mount/path#field` target inside a KV v2 secrets engine.
+// 解析后的 KV v2 写入目标，格式为 `mount/path#field`。
+#[derive(Debug, PartialEq)]
+pub struct VaultTarget {
+    pub mount: String,
+    pub path: String,
+    pub field: String,
+}
+
+impl VaultTarget {
+    /// Parses a target such as `secret/apps/example#password`.
+    /// The first path segment is the KV mount, the rest is the secret path.
+    // 解析形如 `secret/apps/example#password` 的目标。
+    // 第一个路径段是 KV 挂载点，其余部分是秘密路径。
+    pub fn parse(target: &str) -> Result<Self, AegixPassError> {
+        let invalid = || {
+            IntegrationError::Vault(format!(
+                "Invalid Vault target '{}'; expected the form 'mount/path#field'.",
+                target
+            ))
+        };
+
+        let (full_path, field) = target.split_once('#').ok_or_else(invalid)?;
+        let (mount, path) = full_path.trim_matches('/').split_once('/').ok_or_else(invalid)?;
+        let path = path.trim_matches('/');
+        if mount.is_empty() || path.is_empty() || field.is_empty() {
+            return Err(invalid().into());
+        }
+
+        Ok(Self {
+            mount: mount.to_string(),
+            path: path.to_string(),
+            field: field.to_string(),
+        })
+    }
+
+    /// The sibling field a derivation stamp is stored in, e.g. `password_stamp`.
+    // 存放派生记录的同级字段，例如 `password_stamp`。
+    pub fn stamp_field(&self) -> String {
+        format!("{}_stamp", self.field)
+    }
+}
+
+/// Writes `value` into the given field of a KV v2 secret, preserving the other fields
+/// already stored at that path. A `stamp` recording how the value was derived goes into
+/// [`VaultTarget::stamp_field`] next to it. The write is a check-and-set against the version that
+/// was read, so a concurrent writer's fields are never silently dropped.
+// 将 `value` 写入 KV v2 秘密的指定字段，并保留该路径下已存在的其它字段。记录该值派生方式的 `stamp`
+// 写入与其相邻的 [`VaultTarget::stamp_field`]。写入以读到的版本做 check-and-set，因此不会悄悄丢掉并发写入者的字段。
+pub fn write_secret(target: &VaultTarget, value: &str, stamp: Option<&str>) -> Result<(), AegixPassError> {
+    let addr = std::env::var("VAULT_ADDR").unwrap_or_else(|_| DEFAULT_VAULT_ADDR.to_string());
+    let token = std::env::var("VAULT_TOKEN")
+        .map_err(|_| IntegrationError::Vault("VAULT_TOKEN is not set.".to_string()))?;
+    let namespace = std::env::var("VAULT_NAMESPACE").ok();
+
+    let url = format!(
+        "{}/v1/{}/data/{}",
+        addr.trim_end_matches('/'),
+        target.mount,
+        target.path
+    );
+    let vault_error = |e: ureq::Error| IntegrationError::Vault(e.to_string());
+
+    // 1. Read the current version so that a write does not drop sibling fields.
+    // 1. 先读取当前版本，避免写入时丢失同一秘密下的其它字段。
+    let mut get = ureq::get(&url).header("X-Vault-Token", &token);
+    if let Some(ns) = &namespace {
+        get = get.header("X-Vault-Namespace", ns);
+    }
+    let mut response = get.config().http_status_as_error(false).build().call().map_err(vault_error)?;
+    let body: Value = match response.status().as_u16() {
+        200 => response.body_mut().read_json().map_err(vault_error)?,
+        // A missing secret is fine; it will be created. If only its latest version was deleted,
+        // the 404 still carries that version's metadata.
+        // 秘密不存在时直接创建即可。若只是最新版本被删除，404 响应中仍带有该版本的元数据。
+        404 => response.body_mut().read_json().unwrap_or(Value::Null),
+        status => return Err(vault_error(ureq::Error::StatusCode(status)).into()),
+    };
+    let (mut data, version) = current_secret(&body);
+
+    // 2. Merge the field and write the new version, only if no one wrote in between.
+    // 2. 合并字段并写入新版本，前提是期间没有其他人写入。
+    data.insert(target.field.clone(), Value::String(value.to_string()));
+    if let Some(stamp) = stamp {
+        data.insert(target.stamp_field(), Value::String(stamp.to_string()));
+    }
+    let mut post = ureq::post(&url).header("X-Vault-Token", &token);
+    if let Some(ns) = &namespace {
+        post = post.header("X-Vault-Namespace", ns);
+    }
+    post.send_json(serde_json::json!({ "options": { "cas": version }, "data": data }))
+        .map_err(|e| match e {
+            ureq::Error::StatusCode(400) => IntegrationError::Vault(
+                "Vault rejected the write (HTTP 400), most likely because the secret changed after it was read; run again.".to_string(),
+            ),
+            e => vault_error(e),
+        })?;
+
+    Ok(())
+}
+
+/// The fields and version of a KV v2 read response; a secret that does not exist is version 0.
+// KV v2 读取响应中的字段与版本；不存在的秘密版本为 0。
+fn current_secret(body: &Value) -> (Map<String, Value>, u64) {
+    let data = body.pointer("/data/data").and_then(Value::as_object).cloned().unwrap_or_default();
+    let version = body.pointer("/data/metadata/version").and_then(Value::as_u64).unwrap_or(0);
+    (data, version)
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let target = VaultTarget::parse("secret/apps/example#password").unwrap();
+        assert_eq!(
+            target,
+            VaultTarget {
+                mount: "secret".to_string(),
+                path: "apps/example".to_string(),
+                field: "password".to_string(),
+            }
+        );
+        assert_eq!(target.stamp_field(), "password_stamp");
+    }
+
+    #[test]
+    fn test_parse_target_rejects_malformed() {
+        assert!(VaultTarget::parse("secret/apps/example").is_err());
+        assert!(VaultTarget::parse("secret#password").is_err());
+        assert!(VaultTarget::parse("secret/apps#").is_err());
+    }
+
+    #[test]
+    fn test_current_secret_reads_the_version() {
+        let body = serde_json::json!({ "data": { "data": { "user": "admin" }, "metadata": { "version": 3 } } });
+        assert_eq!(current_secret(&body), (Map::from_iter([("user".to_string(), Value::from("admin"))]), 3));
+        // The latest version was deleted. / 最新版本已被删除。
+        let deleted = serde_json::json!({ "data": { "data": null, "metadata": { "version": 4 } } });
+        assert_eq!(current_secret(&deleted), (Map::new(), 4));
+        assert_eq!(current_secret(&Value::Null), (Map::new(), 0));
+    }
+}
@@ -0,0 +1,137 @@
+// --- Write guard ---
+// --- 写入守卫 ---
+// Every file the tool writes goes through this module. In ephemeral mode (`--ephemeral`) it
+// refuses all writes, so nothing (state, caches, site lists, presets) can persist on a shared
+// or untrusted machine, even through a code path that forgot to check the flag itself.
+// Writes are atomic (a temporary file renamed over the target) and owner-only (0600 on Unix),
+// and `lock` serializes read-modify-write updates between concurrent processes, so a file is
+// never left half-written or loses an update.
+// 程序写入的每个文件都要经过本模块。在临时模式 (`--ephemeral`) 下，它会拒绝所有写入，
+// 因此在共享或不可信的机器上不会留下任何内容（状态、缓存、站点列表、预设），即使某条代码路径忘记自行检查该标志。
+// 写入是原子的（先写临时文件再重命名覆盖目标），且仅所有者可读写（Unix 上为 0600）；
+// `lock` 使并发进程之间的“读取-修改-写入”更新串行化，因此文件不会被写坏，也不会丢失更新。
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static EPHEMERAL: AtomicBool = AtomicBool::new(false);
+
+/// Turns ephemeral mode on or off for the whole process.
+// 为整个进程开启或关闭临时模式。
+pub fn set_ephemeral(ephemeral: bool) {
+    EPHEMERAL.store(ephemeral, Ordering::SeqCst);
+}
+
+/// Whether ephemeral mode is on. / 是否处于临时模式。
+pub fn is_ephemeral() -> bool {
+    EPHEMERAL.load(Ordering::SeqCst)
+}
+
+fn check(path: &Path) -> io::Result<()> {
+    if is_ephemeral() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("refusing to write '{}' in ephemeral mode", path.display()),
+        ));
+    }
+    Ok(())
+}
+
+/// Atomically replaces a file with owner-only permissions, unless ephemeral mode is on.
+// 以仅所有者可读写的权限原子地替换文件，除非处于临时模式。
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    check(path)?;
+    let temp = sibling(path, &format!("tmp.{}", std::process::id()));
+    let result = (|| {
+        let mut file = private_options().write(true).create(true).truncate(true).open(&temp)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        std::fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// An exclusive lock on a file, released when dropped. / 文件上的独占锁，在析构时释放。
+#[derive(Debug)]
+pub struct FileLock {
+    _file: File,
+}
+
+/// Blocks until this process holds the exclusive lock for `path`, kept in a `.lock` file next
+/// to it. Hold it across a load-modify-save sequence so concurrent updates are not lost.
+// 阻塞直到本进程持有 `path` 的独占锁（保存在其旁边的 `.lock` 文件中）。
+// 在“加载-修改-保存”的整个过程中持有该锁，可避免并发更新丢失。
+pub fn lock(path: &Path) -> io::Result<FileLock> {
+    check(path)?;
+    let file = private_options().read(true).write(true).create(true).truncate(false).open(sibling(path, "lock"))?;
+    file.lock()?;
+    Ok(FileLock { _file: file })
+}
+
+/// `path` with an extra extension, e.g. `sites.json.lock`. / 附加扩展名后的 `path`，例如 `sites.json.lock`。
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// Options that create files as owner-only (0600) on Unix. / 在 Unix 上以仅所有者可读写 (0600) 创建文件的选项。
+fn private_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+}
+
+/// Removes a file, unless ephemeral mode is on. / 删除文件，除非处于临时模式。
+pub fn remove_file(path: &Path) -> io::Result<()> {
+    check(path)?;
+    std::fs::remove_file(path)
+}
+
+/// Creates a directory and its parents, unless ephemeral mode is on.
+// 创建目录及其父目录，除非处于临时模式。
+pub fn create_dir_all(path: &Path) -> io::Result<()> {
+    check(path)?;
+    std::fs::create_dir_all(path)
+}
+
+// --- Unit Test Module ---
+// --- 单元测试模块 ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ephemeral_mode_blocks_writes() {
+        let path = std::env::temp_dir().join(format!("aegixpass-write-guard-{}", std::process::id()));
+        set_ephemeral(true);
+        let result = write(&path, "x");
+        set_ephemeral(false);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_atomic_private_write() {
+        let path = std::env::temp_dir().join(format!("aegixpass-atomic-write-{}", std::process::id()));
+        let _lock = lock(&path).unwrap();
+        write(&path, "first").unwrap();
+        write(&path, "second").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert!(!sibling(&path, &format!("tmp.{}", std::process::id())).exists());
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(sibling(&path, "lock"));
+    }
+}
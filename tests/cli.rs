@@ -0,0 +1,216 @@
+// --- Command-line integration tests ---
+// --- 命令行集成测试 ---
+// Each test runs the built binary with the hidden `--test-mode DIR` flag: DIR stands in for the
+// config and cache directories, prompts are answered from stdin, and `--clip` writes
+// DIR/clipboard.txt, so nothing touches the real home directory, terminal or clipboard.
+// 每个测试都以隐藏的 `--test-mode DIR` 参数运行构建出的程序：DIR 代替配置与缓存目录，提示从标准输入回答，`--clip`
+// 写入 DIR/clipboard.txt，因此不会触及真实的主目录、终端或剪贴板。
+
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+/// The `default.json` shipped with the repository. / 仓库自带的 `default.json`。
+fn default_preset() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("default.json")
+}
+
+/// The binary in test mode with a fresh temporary directory and the default preset.
+// 使用新的临时目录与默认预设、处于测试模式的程序。
+fn aegixpass() -> (Command, TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("aegixpass").unwrap();
+    cmd.arg("--test-mode").arg(dir.path()).arg("-c").arg(default_preset());
+    (cmd, dir)
+}
+
+// `default.json` uses Argon2id, so tests that derive with it need the `argon2` feature.
+// `default.json` 使用 Argon2id，因此用它派生的测试需要 `argon2` 特性。
+#[test]
+#[cfg(feature = "argon2")]
+fn test_generates_the_known_password() {
+    let (mut cmd, _dir) = aegixpass();
+    cmd.args(["pw", "example.com"]).assert().success().stdout("oMMm&m3iRa%iFk8B\n");
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_prompt_is_answered_from_stdin() {
+    let (mut cmd, _dir) = aegixpass();
+    cmd.args(["-", "example.com"]).write_stdin("pw\n").assert().success().stdout("oMMm&m3iRa%iFk8B\n");
+}
+
+#[test]
+fn test_missing_answer_fails() {
+    let (mut cmd, _dir) = aegixpass();
+    cmd.args(["-", "example.com"]).write_stdin("").assert().failure().stderr(predicate::str::contains("no answer on stdin"));
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_clip_writes_the_fake_clipboard() {
+    let (mut cmd, dir) = aegixpass();
+    cmd.args(["--clip", "pw", "example.com"]).assert().success().stdout("");
+    assert_eq!(std::fs::read_to_string(dir.path().join("clipboard.txt")).unwrap(), "oMMm&m3iRa%iFk8B");
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_answer_prompts_by_default() {
+    let (mut cmd, _dir) = aegixpass();
+    cmd.args(["answer", "example.com", "Mother's maiden name?"]).write_stdin("pw\n").assert().success().stdout("jolefa febopi mizola\n");
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_answer_follows_the_seed_inputs() {
+    let (mut cmd, _dir) = aegixpass();
+    let output = cmd.args(["answer", "example.com", "Mother's maiden name?", "pw", "--counter", "1"]).assert().success().get_output().stdout.clone();
+    assert_ne!(output, b"jolefa febopi mizola\n");
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_recovery_codes() {
+    let (mut cmd, _dir) = aegixpass();
+    let output = cmd.args(["recovery-codes", "example.com", "pw", "--count", "3", "--digits", "6"]).assert().success().get_output().stdout.clone();
+    let codes = String::from_utf8(output).unwrap();
+    assert_eq!(codes.lines().count(), 3);
+    assert!(codes.lines().all(|code| code.len() == 6 && code.bytes().all(|b| b.is_ascii_digit())));
+
+    let (mut rotated, _dir) = aegixpass();
+    let output = rotated.args(["recovery-codes", "example.com", "pw", "--count", "3", "--digits", "6", "--counter", "1"]).assert().success().get_output().stdout.clone();
+    assert_ne!(String::from_utf8(output).unwrap(), codes, "The seed inputs reach the codes");
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_recovery_codes_out_of_range() {
+    let (mut cmd, _dir) = aegixpass();
+    cmd.args(["recovery-codes", "example.com", "pw", "--digits", "4"]).assert().failure().stderr(predicate::str::contains("Hint: ask for 1 to 100 codes"));
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_store_roundtrip() {
+    let (mut cmd, dir) = aegixpass();
+    // The master password comes from the argument, the secret from stdin.
+    // 主密码来自参数，秘密来自标准输入。
+    cmd.args(["store", "set", "intranet.example", "pw"]).write_stdin("hunter2\n").assert().success();
+    assert!(dir.path().join("store.json").exists());
+
+    let mut get = Command::cargo_bin("aegixpass").unwrap();
+    get.arg("--test-mode").arg(dir.path()).arg("-c").arg(default_preset());
+    get.args(["get", "intranet.example", "-"]).write_stdin("pw\n").assert().success().stdout("hunter2\n");
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_store_key_follows_the_seed_inputs() {
+    let (mut set, dir) = aegixpass();
+    set.args(["store", "set", "intranet.example", "pw", "--counter", "1"]).write_stdin("hunter2\n").assert().success();
+    let get = |counter: &str| {
+        let mut get = Command::cargo_bin("aegixpass").unwrap();
+        get.arg("--test-mode").arg(dir.path()).arg("-c").arg(default_preset());
+        get.args(["store", "get", "intranet.example", "pw", "--counter", counter]).assert()
+    };
+    get("1").success().stdout("hunter2\n");
+    get("0").failure();
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_get_derives_with_the_seed_inputs() {
+    let (mut get, _dir) = aegixpass();
+    let derived = get.args(["get", "example.com", "pw", "--counter", "1"]).assert().success().get_output().stdout.clone();
+    let (mut generate, _dir) = aegixpass();
+    generate.args(["pw", "example.com", "--counter", "1"]).assert().success().stdout(String::from_utf8(derived.clone()).unwrap());
+    let (mut original, _dir) = aegixpass();
+    assert_ne!(original.args(["get", "example.com", "pw"]).assert().success().get_output().stdout, derived);
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_dotenv_entries_carry_their_stamp() {
+    let (mut cmd, _dir) = aegixpass();
+    cmd.args(["--output", "dotenv", "--version-of-output", "--counter", "2", "--label-suffix", "db", "--label-suffix", "api", "pw", "example.com"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let lines: Vec<String> = String::from_utf8(output).unwrap().lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("# aegixpass-stamp: ") && lines[0].ends_with(" counter=2 label=db"));
+    assert!(lines[1].starts_with("EXAMPLE_COM_DB='"));
+    assert!(lines[2].ends_with(" counter=2 label=api"));
+    assert!(lines[3].starts_with("EXAMPLE_COM_API='"));
+}
+
+#[test]
+fn test_find_and_status_read_the_site_list() {
+    let (mut cmd, dir) = aegixpass();
+    let sites = r#"{"version": 1, "sites": [
+        {"distinguishKey": "example.com", "username": "alice", "maxAgeDays": 90},
+        {"distinguishKey": "mail.example.org", "notes": "work mail"}
+    ]}"#;
+    std::fs::write(dir.path().join("sites.json"), sites).unwrap();
+    cmd.args(["find", "WORK"]).assert().success().stdout(predicate::str::contains("mail.example.org").and(predicate::str::contains("example.com\n").not()));
+
+    let mut status = Command::cargo_bin("aegixpass").unwrap();
+    status.arg("--test-mode").arg(dir.path()).arg("-c").arg(default_preset());
+    status.arg("status").assert().success().stdout(predicate::str::contains("example.com"));
+}
+
+#[test]
+#[cfg(feature = "argon2")]
+fn test_rekey_takes_the_seed_inputs() {
+    let (mut cmd, dir) = aegixpass();
+    std::fs::write(dir.path().join("sites.json"), r#"{"version": 1, "sites": [{"distinguishKey": "example.com"}]}"#).unwrap();
+    let keyfile = dir.path().join("key.bin");
+    std::fs::write(&keyfile, "usb stick").unwrap();
+    let output = cmd.args(["rekey", "pw", "new pw", "--output", "json", "--counter", "1", "--keyfile"]).arg(&keyfile).output().unwrap();
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    // The checklist holds what generate derives with the same inputs. / 核对清单与 generate 使用相同输入派生的结果一致。
+    for (password, column) in [("pw", "oldPassword"), ("new pw", "newPassword")] {
+        let mut generate = Command::cargo_bin("aegixpass").unwrap();
+        generate.arg("--test-mode").arg(dir.path()).arg("-c").arg(default_preset());
+        let expected = generate.args([password, "example.com", "--counter", "1", "--keyfile"]).arg(&keyfile).output().unwrap();
+        assert_eq!(rows[0][column].as_str().unwrap(), String::from_utf8(expected.stdout).unwrap().trim_end());
+    }
+    assert_ne!(rows[0]["oldPassword"], "oMMm&m3iRa%iFk8B");
+}
+
+#[test]
+fn test_invalid_preset_reports_error_and_hint() {
+    let (_, dir) = aegixpass();
+    let preset = dir.path().join("broken.json");
+    let json = std::fs::read_to_string(default_preset()).unwrap().replace("\"length\": 16", "\"length\": 2");
+    std::fs::write(&preset, json).unwrap();
+    let mut cmd = Command::cargo_bin("aegixpass").unwrap();
+    cmd.arg("--test-mode").arg(dir.path()).arg("-c").arg(&preset);
+    cmd.args(["pw", "example.com"]).assert().failure().stderr(predicate::str::contains("Error").and(predicate::str::contains("Hint")));
+}
+
+#[test]
+fn test_weak_preset_warns_once_for_several_passwords() {
+    let (_, dir) = aegixpass();
+    let preset = dir.path().join("weak.json");
+    let json = std::fs::read_to_string(default_preset()).unwrap().replace("\"length\": 16", "\"length\": 4").replace("argon2id", "sha256");
+    std::fs::write(&preset, json).unwrap();
+    let mut cmd = Command::cargo_bin("aegixpass").unwrap();
+    cmd.arg("--test-mode").arg(dir.path()).arg("-c").arg(&preset);
+    let output = cmd.args(["--count", "3", "--output", "dotenv", "pw", "example.com"]).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stderr).unwrap().matches("Warning: this preset derives weak passwords").count(), 1);
+}
+
+#[test]
+#[cfg(feature = "vault")]
+fn test_vault_write_takes_only_the_bare_password() {
+    let (mut cmd, _dir) = aegixpass();
+    cmd.args(["--vault-write", "secret/apps/example#password", "--output", "dotenv", "pw", "example.com"]);
+    cmd.assert().failure().stderr(predicate::str::contains("bare password"));
+    let (mut cmd, _dir) = aegixpass();
+    cmd.args(["--vault-write", "secret/apps/example#password", "--label-suffix", "db", "pw", "example.com"]);
+    cmd.assert().failure().stderr(predicate::str::contains("cannot be used with"));
+}